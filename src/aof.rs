@@ -0,0 +1,652 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::comando::crear_comando_handler_sin_cliente;
+use crate::comando_info::ComandoInfo;
+use crate::config::Config;
+
+use std::fs::{rename, File, OpenOptions};
+use std::io::{BufRead, BufReader, Error, Result, Write};
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Politica con la que el archivo AOF se sincroniza con el disco
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoliticaFsync {
+    /// Se sincroniza luego de registrar cada comando
+    Siempre,
+    /// Se sincroniza a lo sumo una vez por segundo
+    CadaSegundo,
+    /// Se deja que el sistema operativo decida cuando sincronizar
+    Nunca,
+}
+
+impl PoliticaFsync {
+    /// Obtiene la politica a partir del valor de `appendfsync` en la configuracion
+    pub fn desde(valor: &str) -> Option<Self> {
+        match valor.to_lowercase().as_str() {
+            "always" => Some(PoliticaFsync::Siempre),
+            "everysec" => Some(PoliticaFsync::CadaSegundo),
+            "no" => Some(PoliticaFsync::Nunca),
+            _ => None,
+        }
+    }
+}
+
+/// Archivo donde se registran, en formato RESP, los comandos que modifican la base de datos
+#[derive(Debug)]
+pub struct ArchivoAof {
+    ruta: String,
+    archivo: File,
+    politica: PoliticaFsync,
+    sin_sincronizar: bool,
+    /// Indica que fallo la ultima sincronizacion en segundo plano, hasta que alguna funcione
+    sincronizacion_fallida: bool,
+    reescritura: Option<Vec<u8>>,
+}
+
+impl ArchivoAof {
+    /// Abre el archivo AOF en modo append, creandolo si no existe
+    ///
+    /// # Argumentos
+    ///
+    /// * `ruta` - ruta del archivo AOF
+    /// * `politica` - politica de sincronizacion con el disco
+    pub fn abrir(ruta: String, politica: PoliticaFsync) -> Result<Self> {
+        let archivo = OpenOptions::new().create(true).append(true).open(&ruta)?;
+        Ok(ArchivoAof {
+            ruta,
+            archivo,
+            politica,
+            sin_sincronizar: false,
+            sincronizacion_fallida: false,
+            reescritura: None,
+        })
+    }
+
+    /// Registra el comando al final del archivo. Si hay una reescritura en curso tambien se guarda
+    /// para agregarlo al archivo reescrito
+    pub fn registrar(&mut self, argumentos: &[Vec<u8>]) -> Result<()> {
+        self.escribir(codificar_comando(argumentos))
+    }
+
+    /// Registra los comandos de una transaccion entre MULTI y EXEC con una sola escritura, asi al
+    /// reproducir el archivo se aplican todos o ninguno
+    pub fn registrar_transaccion(&mut self, comandos: &[Vec<Vec<u8>>]) -> Result<()> {
+        let mut transaccion = codificar_comando(&[b"MULTI".to_vec()]);
+        for comando in comandos {
+            transaccion.extend(codificar_comando(comando));
+        }
+        transaccion.extend(codificar_comando(&[b"EXEC".to_vec()]));
+        self.escribir(transaccion)
+    }
+
+    /// Escribe los comandos ya codificados. Devuelve error si falla la escritura, la sincronizacion
+    /// o la ultima sincronizacion en segundo plano, porque en ese caso no se sabe si llegaron al disco
+    fn escribir(&mut self, comandos: Vec<u8>) -> Result<()> {
+        if let Some(pendientes) = &mut self.reescritura {
+            pendientes.extend_from_slice(&comandos);
+        }
+        self.archivo.write_all(&comandos)?;
+        match self.politica {
+            PoliticaFsync::Siempre => self.archivo.sync_data(),
+            _ => {
+                self.sin_sincronizar = true;
+                if self.sincronizacion_fallida {
+                    return Err(Error::other("background fsync failed"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Sincroniza con el disco las escrituras pendientes si la politica es everysec
+    pub fn sincronizar(&mut self) -> Result<()> {
+        if self.politica == PoliticaFsync::CadaSegundo && self.sin_sincronizar {
+            if let Err(error) = self.archivo.sync_data() {
+                self.sincronizacion_fallida = true;
+                return Err(error);
+            }
+            self.sincronizacion_fallida = false;
+            self.sin_sincronizar = false;
+        }
+        Ok(())
+    }
+
+    pub fn cambiar_politica(&mut self, politica: PoliticaFsync) {
+        self.politica = politica;
+    }
+
+    /// Comienza a guardar los comandos registrados hasta que termine la reescritura.
+    /// Devuelve false si ya habia una reescritura en curso
+    fn iniciar_reescritura(&mut self) -> bool {
+        if self.reescritura.is_some() {
+            return false;
+        }
+        self.reescritura = Some(vec![]);
+        true
+    }
+
+    /// Reemplaza el archivo por el archivo temporal reescrito, agregandole los comandos
+    /// registrados mientras se escribia
+    fn finalizar_reescritura(&mut self, temporal: &str) -> Result<()> {
+        let pendientes = self.reescritura.take().unwrap_or_default();
+        let mut archivo_temporal = OpenOptions::new().append(true).open(temporal)?;
+        archivo_temporal.write_all(&pendientes)?;
+        archivo_temporal.sync_data()?;
+        rename(temporal, &self.ruta)?;
+        self.archivo = OpenOptions::new().append(true).open(&self.ruta)?;
+        Ok(())
+    }
+
+    fn cancelar_reescritura(&mut self) {
+        self.reescritura = None;
+    }
+}
+
+/// Mensajes que recibe el SincronizadorAof
+pub enum MensajeAof {
+    /// Detiene el hilo del sincronizador
+    Cerrar,
+}
+
+/// Entidad que corre en un hilo y sincroniza el archivo AOF con el disco una vez por segundo
+pub struct SincronizadorAof {
+    aof: Arc<Mutex<ArchivoAof>>,
+    receptor: Receiver<MensajeAof>,
+}
+
+impl SincronizadorAof {
+    pub fn new(aof: Arc<Mutex<ArchivoAof>>, receptor: Receiver<MensajeAof>) -> Self {
+        SincronizadorAof { aof, receptor }
+    }
+
+    /// Sincroniza el archivo cada un segundo hasta recibir el mensaje de cierre
+    pub fn sincronizar(&mut self) {
+        while let Err(RecvTimeoutError::Timeout) =
+            self.receptor.recv_timeout(Duration::from_secs(1))
+        {
+            match self.aof.lock() {
+                Ok(mut aof) => aof.sincronizar().ok(),
+                Err(_) => break,
+            };
+        }
+    }
+}
+
+/// Codifica los argumentos de un comando como un array RESP de bulk strings
+pub fn codificar_comando(argumentos: &[Vec<u8>]) -> Vec<u8> {
+    let mut codificado = format!("*{}\r\n", argumentos.len()).into_bytes();
+    for argumento in argumentos {
+        codificado.extend(format!("${}\r\n", argumento.len()).into_bytes());
+        codificado.extend_from_slice(argumento);
+        codificado.extend_from_slice(b"\r\n");
+    }
+    codificado
+}
+
+/// Lee todos los comandos registrados en el archivo AOF. Si el ultimo comando quedo escrito
+/// de forma incompleta se descarta
+pub fn leer_comandos(ruta: &str) -> Vec<Vec<Vec<u8>>> {
+    let archivo = match File::open(ruta) {
+        Ok(a) => a,
+        Err(_) => return vec![],
+    };
+    let mut lector = BufReader::new(archivo);
+    let mut comandos = vec![];
+    while let Some(comando) = leer_comando(&mut lector) {
+        comandos.push(comando);
+    }
+    comandos
+}
+
+fn leer_comando<R: BufRead>(lector: &mut R) -> Option<Vec<Vec<u8>>> {
+    let cantidad = leer_longitud(lector, '*')?;
+    let mut argumentos = Vec::with_capacity(cantidad);
+    for _ in 0..cantidad {
+        let longitud = leer_longitud(lector, '$')?;
+        let mut argumento = vec![0; longitud + 2];
+        lector.read_exact(&mut argumento).ok()?;
+        argumento.truncate(longitud);
+        argumentos.push(argumento);
+    }
+    Some(argumentos)
+}
+
+fn leer_longitud<R: BufRead>(lector: &mut R, prefijo: char) -> Option<usize> {
+    let mut linea = String::new();
+    if lector.read_line(&mut linea).ok()? == 0 {
+        return None;
+    }
+    linea.trim_end().strip_prefix(prefijo)?.parse().ok()
+}
+
+/// Reconstruye la base de datos ejecutando los comandos registrados en el archivo AOF con los manejadores de comandos.
+/// Los comandos de una transaccion se ejecutan recien al leer su EXEC, y si el archivo termina antes se descartan
+pub fn reproducir(ruta: &str, bdd: Arc<Mutex<BaseDeDatos>>, config: Arc<Mutex<Config>>) {
+    let mut transaccion: Option<Vec<Vec<Vec<u8>>>> = None;
+    for argumentos in leer_comandos(ruta) {
+        if argumentos.is_empty() {
+            continue;
+        }
+        match argumentos[0].to_ascii_uppercase().as_slice() {
+            b"MULTI" => transaccion = Some(vec![]),
+            b"EXEC" => {
+                for argumentos in transaccion.take().unwrap_or_default() {
+                    reproducir_comando(argumentos, &bdd, &config);
+                }
+            }
+            _ => match &mut transaccion {
+                Some(comandos) => comandos.push(argumentos),
+                None => reproducir_comando(argumentos, &bdd, &config),
+            },
+        }
+    }
+}
+
+fn reproducir_comando(
+    argumentos: Vec<Vec<u8>>,
+    bdd: &Arc<Mutex<BaseDeDatos>>,
+    config: &Arc<Mutex<Config>>,
+) {
+    let handler =
+        crear_comando_handler_sin_cliente(ComandoInfo::new(argumentos), Arc::clone(config));
+    handler.ejecutar(Arc::clone(bdd));
+}
+
+/// Genera los comandos minimos que reconstruyen el contenido actual de la base de datos
+pub fn comandos_para_reconstruir(bdd: &BaseDeDatos) -> Vec<Vec<Vec<u8>>> {
+    let mut comandos = vec![];
+    for clave in bdd.claves(b"*") {
+        let mut comando = match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Str(valor)) => vec![b"SET".to_vec(), clave.clone(), valor.clone()],
+            Some(TipoRedis::Lista(lista)) if !lista.is_empty() => {
+                vec![b"RPUSH".to_vec(), clave.clone()]
+            }
+            Some(TipoRedis::Set(set)) if !set.is_empty() => vec![b"SADD".to_vec(), clave.clone()],
+            Some(TipoRedis::Hash(hash)) if !hash.is_empty() => {
+                vec![b"HSET".to_vec(), clave.clone()]
+            }
+            Some(TipoRedis::ZSet(conjunto)) if !conjunto.is_empty() => {
+                vec![b"ZADD".to_vec(), clave.clone()]
+            }
+            _ => continue,
+        };
+        match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Lista(lista)) => comando.extend(lista.iter().cloned()),
+            Some(TipoRedis::Set(set)) => comando.extend(set.iter().cloned()),
+            Some(TipoRedis::Hash(hash)) => {
+                for (campo, valor) in hash.iter() {
+                    comando.push(campo.clone());
+                    comando.push(valor.clone());
+                }
+            }
+            Some(TipoRedis::ZSet(conjunto)) => {
+                for (miembro, puntaje) in conjunto.elementos() {
+                    comando.push(puntaje.to_string().into_bytes());
+                    comando.push(miembro);
+                }
+            }
+            _ => (),
+        }
+        comandos.push(comando);
+
+        if let Some(Some(vencimiento)) = bdd.obtener_vencimiento(&clave) {
+            comandos.push(pexpireat(clave, vencimiento));
+        }
+    }
+    comandos
+}
+
+/// Obtiene los comandos a registrar en el AOF por un comando ya ejecutado. Las expiraciones se
+/// registran como PEXPIREAT con el momento absoluto en que vence la clave, asi al reproducir el
+/// archivo luego de un reinicio no se vuelve a contar el tiempo desde cero. Los comandos con efectos al azar se
+/// registran a partir de la respuesta que devolvieron
+pub fn comandos_a_registrar(
+    argumentos: &[Vec<u8>],
+    bdd: &BaseDeDatos,
+    resultado: &ResultadoRedis,
+) -> Vec<Vec<Vec<u8>>> {
+    let nombre = argumentos[0].to_ascii_uppercase();
+    let clave = match argumentos.get(1) {
+        Some(clave) => clave.clone(),
+        None => return vec![argumentos.to_vec()],
+    };
+    let vencimiento = bdd.obtener_vencimiento(&clave);
+    match (nombre.as_slice(), vencimiento) {
+        (b"EXPIRE" | b"PEXPIRE" | b"EXPIREAT" | b"PEXPIREAT", Some(Some(v))) => {
+            vec![pexpireat(clave, v)]
+        }
+        (b"EXPIRE" | b"PEXPIRE" | b"EXPIREAT" | b"PEXPIREAT", _) => {
+            vec![vec![b"DEL".to_vec(), clave]]
+        }
+        (b"SET" | b"SETEX" | b"PSETEX", Some(Some(v))) => {
+            vec![argumentos.to_vec(), pexpireat(clave, v)]
+        }
+        (b"GETEX", Some(Some(v))) => vec![pexpireat(clave, v)],
+        (b"GETEX", Some(None)) => vec![vec![b"PERSIST".to_vec(), clave]],
+        (b"GETEX", None) => vec![vec![b"DEL".to_vec(), clave]],
+        // Se registra el resultado para que la reconstruccion no dependa del redondeo de cada suma
+        (b"INCRBYFLOAT", _) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Str(valor)) => vec![vec![
+                b"SET".to_vec(),
+                clave,
+                valor.clone(),
+                b"KEEPTTL".to_vec(),
+            ]],
+            _ => vec![argumentos.to_vec()],
+        },
+        (b"SPOP", _) => {
+            let mut srem = vec![b"SREM".to_vec(), clave];
+            match resultado {
+                ResultadoRedis::BulkStr(miembro) => srem.push(miembro.clone()),
+                ResultadoRedis::Set(miembros) => {
+                    for miembro in miembros {
+                        if let ResultadoRedis::BulkStr(miembro) = miembro {
+                            srem.push(miembro.clone());
+                        }
+                    }
+                }
+                _ => (),
+            }
+            vec![srem]
+        }
+        _ => vec![argumentos.to_vec()],
+    }
+}
+
+fn pexpireat(clave: Vec<u8>, vencimiento: u64) -> Vec<Vec<u8>> {
+    vec![
+        b"PEXPIREAT".to_vec(),
+        clave,
+        vencimiento.to_string().into_bytes(),
+    ]
+}
+
+/// Escribe los comandos en un archivo nuevo, reemplazando su contenido anterior
+fn escribir_archivo(ruta: &str, comandos: &[Vec<Vec<u8>>]) -> Result<()> {
+    let mut archivo = File::create(ruta)?;
+    for comando in comandos {
+        archivo.write_all(&codificar_comando(comando))?;
+    }
+    archivo.sync_data()
+}
+
+/// Reescribe en segundo plano el archivo AOF a partir del contenido actual de la base de datos.
+/// Los comandos registrados mientras se reescribe se agregan al final del archivo nuevo
+///
+/// # Argumentos
+///
+/// * `bdd` - base de datos de la que se toma el contenido, debe estar tomada por el llamador
+/// * `ruta` - archivo a escribir si no hay un archivo AOF activo
+pub fn reescribir_en_segundo_plano(bdd: &BaseDeDatos, ruta: String) -> ResultadoRedis {
+    let comandos = comandos_para_reconstruir(bdd);
+    let aof = match bdd.aof() {
+        Some(aof) => aof,
+        None => {
+            thread::spawn(move || if escribir_archivo(&ruta, &comandos).is_err() {});
+            return ResultadoRedis::StrSimple(
+                "Background append only file rewriting started".to_string(),
+            );
+        }
+    };
+
+    let temporal = match aof.lock() {
+        Ok(mut archivo) => {
+            if !archivo.iniciar_reescritura() {
+                return ResultadoRedis::Error(
+                    "ERR Background append only file rewriting already in progress".to_string(),
+                );
+            }
+            format!("temp-rewriteaof-{}.aof", marca_de_tiempo())
+        }
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the AOF file".to_string()),
+    };
+
+    thread::spawn(move || {
+        let escrito = escribir_archivo(&temporal, &comandos);
+        if let Ok(mut archivo) = aof.lock() {
+            let finalizado = escrito.and_then(|_| archivo.finalizar_reescritura(&temporal));
+            if finalizado.is_err() {
+                archivo.cancelar_reescritura();
+                if std::fs::remove_file(&temporal).is_err() {}
+            }
+        }
+    });
+    ResultadoRedis::StrSimple("Background append only file rewriting started".to_string())
+}
+
+fn marca_de_tiempo() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::remove_file;
+
+    fn argumentos(comando: &[&str]) -> Vec<Vec<u8>> {
+        comando.iter().map(|a| a.as_bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn un_comando_se_codifica_como_un_array_resp_de_bulk_strings() {
+        assert_eq!(
+            b"*3\r\n$3\r\nSET\r\n$5\r\nclave\r\n$0\r\n\r\n".to_vec(),
+            codificar_comando(&argumentos(&["SET", "clave", ""]))
+        );
+    }
+
+    #[test]
+    fn los_comandos_registrados_se_leen_y_un_comando_incompleto_se_descarta() {
+        let ruta = "test_aof_lectura.aof";
+        let mut aof = ArchivoAof::abrir(ruta.to_string(), PoliticaFsync::Siempre).unwrap();
+        aof.registrar(&argumentos(&["SET", "clave", "va\r\nlor"]))
+            .unwrap();
+        aof.registrar(&argumentos(&["DEL", "otra"])).unwrap();
+        aof.archivo.write_all(b"*2\r\n$3\r\nDEL").unwrap();
+
+        let comandos = leer_comandos(ruta);
+        remove_file(ruta).unwrap();
+
+        assert_eq!(
+            vec![
+                argumentos(&["SET", "clave", "va\r\nlor"]),
+                argumentos(&["DEL", "otra"])
+            ],
+            comandos
+        );
+    }
+
+    #[test]
+    fn reproducir_el_archivo_reconstruye_la_base_de_datos() {
+        let ruta = "test_aof_reproducir.aof";
+        let mut aof = ArchivoAof::abrir(ruta.to_string(), PoliticaFsync::Nunca).unwrap();
+        aof.registrar(&argumentos(&["SET", "clave", "valor"]))
+            .unwrap();
+        aof.registrar(&argumentos(&["RPUSH", "lista", "a", "b"]))
+            .unwrap();
+        aof.registrar(&argumentos(&["DEL", "clave"])).unwrap();
+
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        reproducir(ruta, Arc::clone(&bdd), Arc::new(Mutex::new(Config::new())));
+        remove_file(ruta).unwrap();
+
+        let bdd = bdd.lock().unwrap();
+        assert_eq!(None, bdd.obtener_valor("clave"));
+        assert_eq!(
+            Some(&TipoRedis::Lista(argumentos(&["a", "b"]).into())),
+            bdd.obtener_valor("lista")
+        );
+    }
+
+    #[test]
+    fn una_transaccion_se_registra_entre_multi_y_exec_y_solo_se_reproduce_completa() {
+        let ruta = "test_aof_transaccion.aof";
+        let mut aof = ArchivoAof::abrir(ruta.to_string(), PoliticaFsync::Nunca).unwrap();
+        aof.registrar_transaccion(&[
+            argumentos(&["SET", "a", "1"]),
+            argumentos(&["SET", "b", "2"]),
+        ])
+        .unwrap();
+        aof.registrar(&argumentos(&["MULTI"])).unwrap();
+        aof.registrar(&argumentos(&["SET", "c", "3"])).unwrap();
+
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        reproducir(ruta, Arc::clone(&bdd), Arc::new(Mutex::new(Config::new())));
+        let comandos = leer_comandos(ruta);
+        remove_file(ruta).unwrap();
+
+        assert_eq!(argumentos(&["MULTI"]), comandos[0]);
+        assert_eq!(argumentos(&["EXEC"]), comandos[3]);
+        let bdd = bdd.lock().unwrap();
+        assert_eq!(Some(&TipoRedis::Str("1".into())), bdd.obtener_valor("a"));
+        assert_eq!(Some(&TipoRedis::Str("2".into())), bdd.obtener_valor("b"));
+        assert_eq!(None, bdd.obtener_valor("c"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn si_falla_la_escritura_del_aof_se_informa_el_error() {
+        let aof = ArchivoAof::abrir("/dev/full".to_string(), PoliticaFsync::Nunca).unwrap();
+        let mut bdd = BaseDeDatos::new();
+        bdd.set_aof(Arc::new(Mutex::new(aof)));
+
+        bdd.registrar_comando(&argumentos(&["SET", "clave", "valor"]));
+
+        match bdd.tomar_error_aof() {
+            Some(ResultadoRedis::Error(error)) => assert!(error.starts_with("MISCONF")),
+            otro => panic!("se esperaba un error y se obtuvo {:?}", otro),
+        }
+        assert_eq!(None, bdd.tomar_error_aof());
+    }
+
+    #[test]
+    fn las_expiraciones_relativas_se_registran_como_pexpireat() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor_con_vencimiento("clave", 4102444800000, TipoRedis::Str("valor".into()));
+
+        assert_eq!(
+            vec![argumentos(&["PEXPIREAT", "clave", "4102444800000"])],
+            comandos_a_registrar(
+                &argumentos(&["EXPIRE", "clave", "100"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+        assert_eq!(
+            vec![
+                argumentos(&["SET", "clave", "valor", "EX", "100"]),
+                argumentos(&["PEXPIREAT", "clave", "4102444800000"])
+            ],
+            comandos_a_registrar(
+                &argumentos(&["SET", "clave", "valor", "EX", "100"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+        assert_eq!(
+            vec![argumentos(&["DEL", "otra"])],
+            comandos_a_registrar(
+                &argumentos(&["EXPIREAT", "otra", "1"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+    }
+
+    #[test]
+    fn incrbyfloat_se_registra_como_set_del_resultado() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("clave", TipoRedis::Str("10.6".into()));
+
+        assert_eq!(
+            vec![argumentos(&["SET", "clave", "10.6", "KEEPTTL"])],
+            comandos_a_registrar(
+                &argumentos(&["INCRBYFLOAT", "clave", "0.1"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+    }
+
+    #[test]
+    fn getex_se_registra_segun_como_quedo_la_clave() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor_con_vencimiento("clave", 4102444800000, TipoRedis::Str("valor".into()));
+        bdd.guardar_valor("persistente", TipoRedis::Str("valor".into()));
+
+        assert_eq!(
+            vec![argumentos(&["PEXPIREAT", "clave", "4102444800000"])],
+            comandos_a_registrar(
+                &argumentos(&["GETEX", "clave", "EX", "100"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+        assert_eq!(
+            vec![argumentos(&["PERSIST", "persistente"])],
+            comandos_a_registrar(
+                &argumentos(&["GETEX", "persistente", "PERSIST"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+        assert_eq!(
+            vec![argumentos(&["DEL", "otra"])],
+            comandos_a_registrar(
+                &argumentos(&["GETEX", "otra", "PXAT", "1"]),
+                &bdd,
+                &ResultadoRedis::Vacio
+            )
+        );
+    }
+
+    #[test]
+    fn spop_se_registra_como_srem_de_los_miembros_elegidos() {
+        let bdd = BaseDeDatos::new();
+        let elegidos = ResultadoRedis::Set(vec![
+            ResultadoRedis::BulkStr("a".into()),
+            ResultadoRedis::BulkStr("c".into()),
+        ]);
+
+        assert_eq!(
+            vec![argumentos(&["SREM", "set", "a", "c"])],
+            comandos_a_registrar(&argumentos(&["SPOP", "set", "2"]), &bdd, &elegidos)
+        );
+        assert_eq!(
+            vec![argumentos(&["SREM", "set", "b"])],
+            comandos_a_registrar(
+                &argumentos(&["SPOP", "set"]),
+                &bdd,
+                &ResultadoRedis::BulkStr("b".into())
+            )
+        );
+    }
+
+    #[test]
+    fn la_reescritura_genera_los_comandos_del_contenido_actual() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("clave", TipoRedis::Str("valor".into()));
+        bdd.guardar_valor_con_vencimiento(
+            "lista".to_string(),
+            4102444800000,
+            TipoRedis::Lista(argumentos(&["a", "b"]).into()),
+        );
+
+        let mut comandos = comandos_para_reconstruir(&bdd);
+        comandos.sort();
+
+        assert_eq!(
+            vec![
+                argumentos(&["PEXPIREAT", "lista", "4102444800000"]),
+                argumentos(&["RPUSH", "lista", "a", "b"]),
+                argumentos(&["SET", "clave", "valor"]),
+            ],
+            comandos
+        );
+    }
+}
@@ -1,127 +1,293 @@
+use crate::aof::ArchivoAof;
+use crate::bloqueo::{Bloqueos, Espera};
+use crate::notificacion::{ClaseEvento, Notificaciones};
 use crate::observer::{Observable, Observer};
+use crate::registro_pubsub::RegistroPubSub;
 
-use crate::canal::Canal;
+use crate::conjunto_ordenado::ConjuntoOrdenado;
+use crate::cursor::{ConjuntoEscaneable, IndiceEscaneo, TablaEscaneable};
+use crate::patron::coincide;
 use crate::valor::Valor;
 
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, PartialEq)]
 
-/// Los posibles resultados que puede devolver un comando
+/// Los posibles resultados que puede devolver un comando. Los tipos propios de RESP3 se
+/// envian tal cual a las conexiones que lo negociaron con HELLO, y al resto se les envia
+/// su equivalente en RESP2
 pub enum ResultadoRedis {
     StrSimple(String),
-    BulkStr(String),
+    BulkStr(Vec<u8>),
     Int(isize),
     Vector(Vec<ResultadoRedis>),
     Nil,
     Error(String),
     Vacio,
+    /// Pares clave-valor, en RESP2 se envian como un vector con cada clave seguida de su valor
+    Map(Vec<(ResultadoRedis, ResultadoRedis)>),
+    /// Coleccion sin orden ni repetidos, en RESP2 se envia como un vector
+    Set(Vec<ResultadoRedis>),
+    /// Numero de punto flotante, en RESP2 se envia como un bulk string
+    Double(f64),
+    /// En RESP2 se envia como el entero 1 o 0
+    Boolean(bool),
+    /// Entero de precision arbitraria, en RESP2 se envia como un bulk string
+    BigNumber(String),
+    /// Texto que debe mostrarse sin escapar junto con su formato (`txt` o `mkd`), en RESP2 se envia como un bulk string
+    Verbatim(String, String),
+    /// Mensaje que el servidor envia sin que el cliente lo pida, como los de pub/sub. En RESP2 se envia como un vector
+    Push(Vec<ResultadoRedis>),
+    /// Ausencia de un valor agregado, como la de un EXEC abortado. En RESP2 se envia como un vector nulo
+    Null,
+}
+
+impl ResultadoRedis {
+    /// Arma un mapa a partir de una lista de claves seguidas cada una de su valor
+    pub fn mapa(elementos: Vec<ResultadoRedis>) -> ResultadoRedis {
+        let mut pares = Vec::with_capacity(elementos.len() / 2);
+        let mut elementos = elementos.into_iter();
+        while let (Some(clave), Some(valor)) = (elementos.next(), elementos.next()) {
+            pares.push((clave, valor));
+        }
+        ResultadoRedis::Map(pares)
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 /// Los posibles tipos de datos que maneja el servidor redis
 pub enum TipoRedis {
-    Str(String),
-    Lista(Vec<String>),
-    Set(HashSet<String>),
-    Canal(Canal),
+    Str(Vec<u8>),
+    /// Las listas se guardan en una cola doble para agregar y sacar de ambos extremos en tiempo constante
+    Lista(VecDeque<Vec<u8>>),
+    /// Los sets y los hashes se guardan ordenados por su posicion en el recorrido de SSCAN y HSCAN
+    Set(ConjuntoEscaneable),
+    Hash(TablaEscaneable<Vec<u8>>),
+    ZSet(ConjuntoOrdenado),
 }
-/// Base de datos donde se almacenan todos los elementos almacenados
+/// Base de datos donde se almacenan todos los elementos almacenados.
+/// Tanto las claves como los valores son cadenas binarias
 pub struct BaseDeDatos {
-    hashmap: HashMap<String, Valor>,
+    hashmap: TablaEscaneable<Valor>,
     observadores: Vec<Box<dyn Observer + Send>>,
+    /// Version de cada clave que existe y fue modificada. Al eliminarse una clave se quita su entrada
+    versiones: HashMap<Vec<u8>, u64>,
+    ultima_version: u64,
+    /// Version asignada en la ultima eliminacion, que es la que tienen las claves que no existen
+    version_eliminadas: u64,
+    aof: Option<Arc<Mutex<ArchivoAof>>>,
+    /// Comandos de la transaccion en curso, que se registran en el AOF todos juntos
+    transaccion_aof: Option<Vec<Vec<Vec<u8>>>>,
+    /// Error del ultimo registro en el AOF que fallo y todavia no se informo
+    error_aof: Option<String>,
+    bloqueos: Bloqueos,
+    pubsub: RegistroPubSub,
+    notificaciones: Notificaciones,
+    /// Posiciones de las claves con expiracion, que son las unicas que revisa la expiracion activa
+    volatiles: IndiceEscaneo,
+    cursor_expiracion: u64,
 }
 
 impl BaseDeDatos {
     /// Devuelve el valor que corresponde a la clave enviada por parametro
-    pub fn obtener_valor(&self, clave: &str) -> Option<&TipoRedis> {
-        match self.hashmap.get(clave) {
+    pub fn obtener_valor<K: AsRef<[u8]>>(&self, clave: K) -> Option<&TipoRedis> {
+        match self.hashmap.get(clave.as_ref()) {
             Some(v) => v.get(),
             None => None,
         }
     }
-    /// Devuelve el tiempo de expiracion de una clave almacenada en la base de datos
-    pub fn obtener_expiracion(&self, clave: &str) -> isize {
-        match self.hashmap.get(clave) {
-            Some(v) => v.obtener_expiracion(),
-            None => -2,
+    /// Devuelve el momento en que expira la clave, en milisegundos desde el epoch de Unix.
+    /// Devuelve ninguno si la clave no existe y un vencimiento vacio si la clave no expira
+    pub fn obtener_vencimiento<K: AsRef<[u8]>>(&self, clave: K) -> Option<Option<u64>> {
+        match self.hashmap.get(clave.as_ref()) {
+            Some(v) if !v.expiro() => Some(v.vencimiento()),
+            _ => None,
         }
     }
-    /// Guarda una valor con un tiempo de expiracion enviado por parametro
-    pub fn guardar_valor_con_expiracion(
+    /// Guarda una valor que expira en el momento indicado, en milisegundos desde el epoch de Unix
+    pub fn guardar_valor_con_vencimiento<K: Into<Vec<u8>>>(
         &mut self,
-        clave: String,
-        expiracion: u64,
+        clave: K,
+        vencimiento: u64,
         valor: TipoRedis,
     ) {
-        self.hashmap
-            .insert(clave, Valor::expirable(valor, expiracion));
-        self.notificar_observadores(self.hashmap.clone());
+        let clave = clave.into();
+        self.modificar_version(&clave);
+        self.insertar(clave, Valor::con_vencimiento(valor, vencimiento));
+        self.notificar_observadores();
     }
-    /// Dada una clave con expiracion almacenada en la base de datos, actualiza su 'tiempo de vida' con el parametro 'expiracion'
+    /// Dada una clave almacenada en la base de datos, cambia el momento en que expira
     /// # Arguments
     ///
     /// * `self` - Referencia a la bases de datos
     /// * `clave` - Clave con la que se identifica un elemento almacenado en la base de datos
-    /// * `expiracion` - Nuevo valor de expiracion de la clave
+    /// * `vencimiento` - Momento en que expira la clave, en milisegundos desde el epoch de Unix
     ///
-    pub fn actualizar_valor_con_expiracion(&mut self, clave: String, expiracion: u64) -> usize {
-        match self.hashmap.get_mut(&clave) {
-            Some(v) => {
-                v.actualizar_expiracion(expiracion);
+    /// La version de la clave solo cambia si existe
+    pub fn actualizar_vencimiento<K: AsRef<[u8]>>(&mut self, clave: K, vencimiento: u64) -> usize {
+        match self.hashmap.get_mut(clave.as_ref()) {
+            Some(v) if !v.expiro() => {
+                v.cambiar_vencimiento(vencimiento);
+                self.volatiles.insertar(clave.as_ref());
+                self.modificar_version(clave.as_ref());
+                self.notificar_observadores();
                 1
             }
-            None => 0,
+            _ => 0,
         }
     }
 
-    pub fn actualizar_valor_sin_expiracion(&mut self, clave: String) -> usize {
-        match self.hashmap.get_mut(&clave) {
-            Some(v) => {
+    /// Quita la expiracion de la clave. Devuelve 0 sin cambiar la version si la clave no existe o no expira
+    pub fn actualizar_valor_sin_expiracion<K: AsRef<[u8]>>(&mut self, clave: K) -> usize {
+        match self.hashmap.get_mut(clave.as_ref()) {
+            Some(v) if !v.expiro() && v.vencimiento().is_some() => {
                 v.hacer_persistente();
+                self.volatiles.eliminar(clave.as_ref());
+                self.modificar_version(clave.as_ref());
                 1
             }
-            None => 0,
+            _ => 0,
         }
     }
 
-    pub fn guardar_valor(&mut self, clave: String, valor: TipoRedis) {
-        self.hashmap.insert(clave, Valor::no_expirable(valor));
+    /// Devuelve el entero almacenado en la clave si contiene un string con forma de entero
+    pub fn obtener_entero<K: AsRef<[u8]>>(&self, clave: K) -> Option<i64> {
+        self.hashmap.get(clave.as_ref()).and_then(|v| v.entero())
+    }
+    /// Guarda el entero como string en la clave, conservando su expiracion si ya existia
+    pub fn guardar_entero<K: Into<Vec<u8>>>(&mut self, clave: K, entero: i64) {
+        let clave = clave.into();
+        let vencimiento = self.obtener_vencimiento(&clave).flatten();
+        self.modificar_version(&clave);
+        self.insertar(clave, Valor::desde_entero(entero, vencimiento));
+        self.notificar_observadores();
+    }
+    /// Reemplaza el valor de la clave conservando su expiracion si ya existia
+    pub fn reemplazar_valor<K: Into<Vec<u8>>>(&mut self, clave: K, valor: TipoRedis) {
+        let clave = clave.into();
+        let vencimiento = self.obtener_vencimiento(&clave).flatten();
+        self.modificar_version(&clave);
+        self.insertar(clave, Valor::nuevo(valor, vencimiento));
+        self.notificar_observadores();
+    }
 
-        self.notificar_observadores(self.hashmap.clone());
+    /// Aplica la modificacion al valor de la clave sin copiarlo, conservando su expiracion. La
+    /// modificacion devuelve ninguno si el valor no es del tipo que espera, y entonces la clave no cambia
+    fn modificar_valor<R>(
+        &mut self,
+        clave: &[u8],
+        modificacion: impl FnOnce(&mut TipoRedis) -> Option<R>,
+    ) -> Option<R> {
+        let resultado = self.hashmap.get_mut(clave)?.modificar(modificacion)?;
+        self.modificar_version(clave);
+        self.notificar_observadores();
+        Some(resultado)
     }
 
-    pub fn guardar_valores(&mut self, parametros: Vec<String>) {
-        let mut index = 0;
-        while index != parametros.len() - 1 {
-            let clave = &parametros[index];
-            let valor = &parametros[index + 1];
+    /// Aplica la modificacion al string de la clave sin copiarlo, conservando su expiracion. Devuelve ninguno si
+    /// la clave no contiene un string
+    pub fn modificar_string<K: AsRef<[u8]>, R>(
+        &mut self,
+        clave: K,
+        modificacion: impl FnOnce(&mut Vec<u8>) -> R,
+    ) -> Option<R> {
+        self.modificar_valor(clave.as_ref(), |valor| match valor {
+            TipoRedis::Str(string) => Some(modificacion(string)),
+            _ => None,
+        })
+    }
 
-            self.hashmap.insert(
-                clave.to_string(),
-                Valor::no_expirable(TipoRedis::Str(valor.to_string())),
-            );
+    /// Aplica la modificacion a la lista de la clave sin copiarla, conservando su expiracion. Devuelve ninguno si
+    /// la clave no contiene una lista. Si la lista queda vacia quien la modifica debe eliminar la clave
+    pub fn modificar_lista<K: AsRef<[u8]>, R>(
+        &mut self,
+        clave: K,
+        modificacion: impl FnOnce(&mut VecDeque<Vec<u8>>) -> R,
+    ) -> Option<R> {
+        self.modificar_valor(clave.as_ref(), |valor| match valor {
+            TipoRedis::Lista(lista) => Some(modificacion(lista)),
+            _ => None,
+        })
+    }
 
-            index += 1;
+    /// Aplica la modificacion al sorted set de la clave sin copiarlo, conservando su expiracion. Devuelve ninguno
+    /// si la clave no contiene un sorted set. Si queda vacio quien lo modifica debe eliminar la clave
+    pub fn modificar_zset<K: AsRef<[u8]>, R>(
+        &mut self,
+        clave: K,
+        modificacion: impl FnOnce(&mut ConjuntoOrdenado) -> R,
+    ) -> Option<R> {
+        self.modificar_valor(clave.as_ref(), |valor| match valor {
+            TipoRedis::ZSet(conjunto) => Some(modificacion(conjunto)),
+            _ => None,
+        })
+    }
+
+    /// Aplica la modificacion al set de la clave sin copiarlo, conservando su expiracion. Devuelve ninguno si
+    /// la clave no contiene un set. Si el set queda vacio quien lo modifica debe eliminar la clave
+    pub fn modificar_set<K: AsRef<[u8]>, R>(
+        &mut self,
+        clave: K,
+        modificacion: impl FnOnce(&mut ConjuntoEscaneable) -> R,
+    ) -> Option<R> {
+        self.modificar_valor(clave.as_ref(), |valor| match valor {
+            TipoRedis::Set(set) => Some(modificacion(set)),
+            _ => None,
+        })
+    }
+
+    /// Aplica la modificacion al hash de la clave sin copiarlo, conservando su expiracion. Devuelve ninguno si
+    /// la clave no contiene un hash. Si el hash queda vacio quien lo modifica debe eliminar la clave
+    pub fn modificar_hash<K: AsRef<[u8]>, R>(
+        &mut self,
+        clave: K,
+        modificacion: impl FnOnce(&mut TablaEscaneable<Vec<u8>>) -> R,
+    ) -> Option<R> {
+        self.modificar_valor(clave.as_ref(), |valor| match valor {
+            TipoRedis::Hash(hash) => Some(modificacion(hash)),
+            _ => None,
+        })
+    }
+
+    pub fn guardar_valor<K: Into<Vec<u8>>>(&mut self, clave: K, valor: TipoRedis) {
+        let clave = clave.into();
+        self.modificar_version(&clave);
+        self.insertar(clave, Valor::no_expirable(valor));
+
+        self.notificar_observadores();
+    }
+
+    pub fn guardar_valores(&mut self, parametros: Vec<Vec<u8>>) {
+        for par in parametros.chunks_exact(2) {
+            let (clave, valor) = (&par[0], &par[1]);
+
+            self.modificar_version(clave);
+            self.insertar(
+                clave.to_vec(),
+                Valor::no_expirable(TipoRedis::Str(valor.to_vec())),
+            );
         }
-        self.notificar_observadores(self.hashmap.clone());
+        self.notificar_observadores();
     }
 
-    pub fn existe_clave(&mut self, clave: &str) -> bool {
-        match self.hashmap.get(clave) {
+    pub fn existe_clave<K: AsRef<[u8]>>(&mut self, clave: K) -> bool {
+        match self.hashmap.get(clave.as_ref()) {
             Some(v) => !v.expiro(),
             None => false,
         }
     }
 
-    pub fn eliminar_clave(&mut self, clave: &str) -> usize {
-        let valor = match self.hashmap.remove(clave) {
-            Some(_) => 1,
+    /// Elimina la clave y devuelve 1 si existia. Si no existia no cambia su version, para no abortar los WATCH
+    pub fn eliminar_clave<K: AsRef<[u8]>>(&mut self, clave: K) -> usize {
+        match self.quitar(clave.as_ref()) {
+            Some(_) => {
+                self.notificar_observadores();
+                1
+            }
             None => 0,
-        };
-        self.notificar_observadores(self.hashmap.clone());
-        valor
+        }
     }
     /// Dado un valor ya almacenado en la base de datos, lo copia en una nueva clave
     /// # Arguments
@@ -130,18 +296,18 @@ impl BaseDeDatos {
     /// * `clave_actual` - Clave almacenada en la base de datos
     /// * `clave_nueva` - Nuevo clave
     ///
-    pub fn copiar_valor(&mut self, clave_actual: &str, clave_nueva: &str) -> Option<()> {
+    pub fn copiar_valor(&mut self, clave_actual: &[u8], clave_nueva: &[u8]) -> Option<()> {
         let valor = match self.obtener_valor(clave_actual) {
             None => return None,
             Some(valor) => valor.clone(),
         };
 
-        self.guardar_valor(clave_nueva.to_string(), valor);
+        self.guardar_valor(clave_nueva, valor);
         Some(())
     }
 
-    pub fn actualizar_ultimo_acceso(&mut self, clave: String) -> isize {
-        match self.hashmap.get_mut(&clave) {
+    pub fn actualizar_ultimo_acceso<K: AsRef<[u8]>>(&mut self, clave: K) -> isize {
+        match self.hashmap.get_mut(clave.as_ref()) {
             Some(v) => {
                 v.actualizar_ultimo_acceso();
                 1
@@ -149,98 +315,289 @@ impl BaseDeDatos {
             None => 0,
         }
     }
-    /// Devuelve todas las claves que matchean con un patron
+    /// Devuelve todas las claves que coinciden con un patron glob
     /// # Arguments
     ///
     /// * `self` - Referencia a la bases de datos
-    /// * `re` - Patron de referencia
+    /// * `patron` - Patron de referencia, por ejemplo `user:*`
     ///
-    pub fn claves(&self, re: &str) -> Vec<String> {
-        let regex = match Regex::new(re) {
-            Ok(r) => r,
-            Err(_) => return Vec::new(),
-        };
-
+    pub fn claves(&self, patron: &[u8]) -> Vec<Vec<u8>> {
         self.hashmap
-            .keys()
-            .cloned()
-            .into_iter()
-            .filter(|c| regex.is_match(c))
+            .iter()
+            .filter(|(c, v)| !v.expiro() && coincide(patron, c))
+            .map(|(c, _)| c.clone())
             .collect()
     }
+    /// Devuelve las claves que siguen al cursor junto con sus valores, sin contar las que expiraron,
+    /// y el cursor desde el que continua el recorrido
+    pub fn escanear(&self, cursor: u64, cantidad: usize) -> (u64, Vec<(&[u8], &TipoRedis)>) {
+        let (cursor, entradas) = self.hashmap.escanear(cursor, cantidad);
+        let claves = entradas
+            .into_iter()
+            .filter_map(|(clave, valor)| Some((clave.as_slice(), valor.get()?)))
+            .collect();
+        (cursor, claves)
+    }
     /// Dado un elemento de tipo string, lo actulaliza con un nuevo valor
-    pub fn intercambiar_valor(
+    pub fn intercambiar_valor<K: Into<Vec<u8>>>(
         &mut self,
-        clave: String,
+        clave: K,
         valor_nuevo: TipoRedis,
     ) -> Option<TipoRedis> {
+        let clave = clave.into();
         let valor = match self.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(_)) => return Some(TipoRedis::Lista(vec![])),
-            Some(TipoRedis::Set(_)) => return Some(TipoRedis::Set(HashSet::new())),
-            Some(TipoRedis::Str(valor)) => Some(TipoRedis::Str(valor.to_string())),
-            Some(TipoRedis::Canal(_)) => None,
+            Some(TipoRedis::Lista(_)) => return Some(TipoRedis::Lista(VecDeque::new())),
+            Some(TipoRedis::Set(_)) => return Some(TipoRedis::Set(ConjuntoEscaneable::default())),
+            Some(TipoRedis::Hash(_)) => return Some(TipoRedis::Hash(TablaEscaneable::default())),
+            Some(TipoRedis::ZSet(_)) => return Some(TipoRedis::ZSet(ConjuntoOrdenado::new())),
+            Some(TipoRedis::Str(valor)) => Some(TipoRedis::Str(valor.clone())),
             None => None,
         };
 
-        self.hashmap.insert(clave, Valor::no_expirable(valor_nuevo));
+        self.modificar_version(&clave);
+        self.insertar(clave, Valor::no_expirable(valor_nuevo));
         valor
     }
-    /// Devuelve una lista con todos los canales activos de la base de datos
-    pub fn canales_activos(&self, re: &str) -> Vec<String> {
-        let mut canales: Vec<String> = Vec::new();
-        let claves = self.claves(re);
-        for clave in &claves {
-            let canal = match self.obtener_valor(clave) {
-                Some(TipoRedis::Canal(c)) => c,
-                _ => continue,
-            };
-            if canal.es_activo() {
-                canales.push(clave.to_string());
-            }
-        }
-        canales
-    }
-
     pub fn borrar_claves(&mut self) {
-        self.hashmap = HashMap::new();
+        if !self.hashmap.is_empty() {
+            self.ultima_version += 1;
+            self.version_eliminadas = self.ultima_version;
+        }
+        self.versiones.clear();
+        self.hashmap.clear();
+        self.volatiles.limpiar();
 
-        self.notificar_observadores(self.hashmap.clone());
+        self.notificar_observadores();
     }
 
+    /// Devuelve la cantidad de claves, sin contar las que expiraron y todavia no se eliminaron
     pub fn cantidad_claves(&self) -> usize {
-        self.hashmap.len()
+        self.hashmap.values().filter(|v| !v.expiro()).count()
     }
 
     pub fn info(&self) -> Vec<String> {
         let mut info = vec!["# Database".to_string(), "".to_string()];
 
-        info.push(format!("cantidad de claves:{}", self.hashmap.len()));
+        info.push(format!("cantidad de claves:{}", self.cantidad_claves()));
         info.push(format!("capacidad:{}", self.hashmap.capacity()));
 
         info
     }
 
+    /// Devuelve la version de la clave, que cambia cada vez que la clave es modificada.
+    /// Una clave que nunca fue modificada tiene version 0, y todas las que no existen comparten la
+    /// version de la ultima eliminacion. Asi una clave borrada y vuelta a crear nunca repite version
+    pub fn version<K: AsRef<[u8]>>(&self, clave: K) -> u64 {
+        let clave = clave.as_ref();
+        match self.versiones.get(clave) {
+            Some(version) => *version,
+            None if self.hashmap.contains_key(clave) => 0,
+            None => self.version_eliminadas,
+        }
+    }
+
+    /// Devuelve la ultima version asignada, cambia cada vez que se modifica alguna clave
+    pub fn ultima_version(&self) -> u64 {
+        self.ultima_version
+    }
+
+    /// Clientes bloqueados a la espera de elementos en alguna lista
+    pub fn bloqueos(&mut self) -> &mut Bloqueos {
+        &mut self.bloqueos
+    }
+
+    /// Canales y patrones con clientes suscritos, que no forman parte de las claves
+    pub fn pubsub(&mut self) -> &mut RegistroPubSub {
+        &mut self.pubsub
+    }
+
+    /// Cambia las notificaciones de eventos sobre claves que se publican
+    pub fn set_notificaciones(&mut self, notificaciones: Notificaciones) {
+        self.notificaciones = notificaciones;
+    }
+
+    /// Publica el evento sobre la clave en los canales de keyspace y keyevent, si su clase esta habilitada
+    pub fn notificar(&mut self, clase: ClaseEvento, evento: &str, clave: &[u8]) {
+        for (canal, mensaje) in self.notificaciones.mensajes(clase, evento, clave) {
+            self.pubsub.publicar(&canal, &mensaje);
+        }
+    }
+
+    /// Elimina las claves indicadas que ya expiraron, notificando el evento expired por cada una.
+    /// Cada eliminacion se registra en el AOF como un DEL. Devuelve cuantas claves elimino
+    pub fn eliminar_expiradas(&mut self, claves: &[Vec<u8>]) -> usize {
+        let mut eliminadas = 0;
+        for clave in claves {
+            if self.hashmap.get(clave).is_some_and(|v| v.expiro()) {
+                self.quitar(clave);
+                self.registrar_comando(&[b"DEL".to_vec(), clave.clone()]);
+                self.notificar(ClaseEvento::Expirado, "expired", clave);
+                eliminadas += 1;
+            }
+        }
+        if eliminadas > 0 {
+            self.notificar_observadores();
+        }
+        eliminadas
+    }
+
+    /// Revisa hasta `muestra` claves con expiracion, continuando desde donde termino la revision
+    /// anterior, y elimina las que ya expiraron. Devuelve cuantas claves reviso y cuantas elimino
+    pub fn expirar_muestra(&mut self, muestra: usize) -> (usize, usize) {
+        let (siguiente, posiciones) = self.volatiles.escanear(self.cursor_expiracion, muestra);
+        let revisadas: Vec<Vec<u8>> = posiciones
+            .into_iter()
+            .flat_map(|posicion| self.hashmap.en_posicion(posicion))
+            .filter(|(_, valor)| valor.vencimiento().is_some())
+            .map(|(clave, _)| clave.clone())
+            .collect();
+        self.cursor_expiracion = siguiente;
+        let eliminadas = self.eliminar_expiradas(&revisadas);
+        (revisadas.len(), eliminadas)
+    }
+
+    /// Guarda el valor en la clave, manteniendo al dia el indice de claves con expiracion
+    fn insertar(&mut self, clave: Vec<u8>, valor: Valor) {
+        let era_volatil = self
+            .hashmap
+            .get(&clave)
+            .is_some_and(|v| v.vencimiento().is_some());
+        match (era_volatil, valor.vencimiento().is_some()) {
+            (false, true) => self.volatiles.insertar(&clave),
+            (true, false) => self.volatiles.eliminar(&clave),
+            _ => {}
+        }
+        self.hashmap.insert(clave, valor);
+    }
+
+    /// Quita la clave, manteniendo al dia el indice de claves con expiracion. La version de la
+    /// clave pasa a ser la de la ultima eliminacion, asi no se guardan versiones de claves borradas
+    fn quitar(&mut self, clave: &[u8]) -> Option<Valor> {
+        let valor = self.hashmap.remove(clave)?;
+        self.ultima_version += 1;
+        self.version_eliminadas = self.ultima_version;
+        self.versiones.remove(clave);
+        if valor.vencimiento().is_some() {
+            self.volatiles.eliminar(clave);
+        }
+        Some(valor)
+    }
+
+    /// Quita y devuelve el primer cliente bloqueado en una clave que ya tiene una lista con
+    /// elementos, junto con esa clave
+    pub fn siguiente_desbloqueado(&mut self) -> Option<(Espera, Vec<u8>)> {
+        let hashmap = &self.hashmap;
+        self.bloqueos.primera_lista(|clave| {
+            matches!(hashmap.get(clave).and_then(|v| v.get()), Some(TipoRedis::Lista(l)) if !l.is_empty())
+        })
+    }
+
+    /// Asigna el archivo AOF donde se registran los comandos que modifican la base de datos
+    pub fn set_aof(&mut self, aof: Arc<Mutex<ArchivoAof>>) {
+        self.aof = Some(aof);
+    }
+
+    pub fn aof(&self) -> Option<Arc<Mutex<ArchivoAof>>> {
+        self.aof.clone()
+    }
+
+    /// Registra el comando en el archivo AOF, si lo hay. Durante una transaccion solo se acumula,
+    /// para escribirla completa al terminar. Si falla se guarda el error para informarlo al cliente
+    pub fn registrar_comando(&mut self, argumentos: &[Vec<u8>]) {
+        if let Some(transaccion) = &mut self.transaccion_aof {
+            transaccion.push(argumentos.to_vec());
+            return;
+        }
+        self.escribir_en_aof(|aof| aof.registrar(argumentos));
+    }
+
+    /// Empieza a acumular los comandos a registrar en el AOF hasta que termine la transaccion
+    pub fn iniciar_transaccion_aof(&mut self) {
+        self.transaccion_aof = Some(vec![]);
+    }
+
+    /// Registra en el AOF los comandos acumulados desde que empezo la transaccion entre MULTI y EXEC
+    pub fn terminar_transaccion_aof(&mut self) {
+        let comandos = self.transaccion_aof.take().unwrap_or_default();
+        if !comandos.is_empty() {
+            self.escribir_en_aof(|aof| aof.registrar_transaccion(&comandos));
+        }
+    }
+
+    fn escribir_en_aof(&mut self, escribir: impl FnOnce(&mut ArchivoAof) -> io::Result<()>) {
+        let resultado = match &self.aof {
+            Some(aof) => match aof.lock() {
+                Ok(mut aof) => escribir(&mut aof),
+                Err(_) => Err(io::Error::other("the AOF file is not accessible")),
+            },
+            None => Ok(()),
+        };
+        if let Err(error) = resultado {
+            self.error_aof = Some(error.to_string());
+        }
+    }
+
+    /// Devuelve el error del ultimo registro en el AOF que fallo, como respuesta para el cliente,
+    /// y lo olvida
+    pub fn tomar_error_aof(&mut self) -> Option<ResultadoRedis> {
+        self.error_aof.take().map(|error| {
+            ResultadoRedis::Error(format!("MISCONF Errors writing to the AOF file: {}", error))
+        })
+    }
+
+    fn modificar_version(&mut self, clave: &[u8]) {
+        self.ultima_version += 1;
+        self.versiones.insert(clave.to_vec(), self.ultima_version);
+    }
+
     #[allow(dead_code)]
     pub fn new() -> Self {
         BaseDeDatos {
-            hashmap: HashMap::<String, Valor>::new(),
+            hashmap: TablaEscaneable::default(),
             observadores: vec![],
+            versiones: HashMap::new(),
+            ultima_version: 0,
+            version_eliminadas: 0,
+            aof: None,
+            transaccion_aof: None,
+            error_aof: None,
+            bloqueos: Bloqueos::default(),
+            pubsub: RegistroPubSub::new(),
+            notificaciones: Notificaciones::default(),
+            volatiles: IndiceEscaneo::default(),
+            cursor_expiracion: 0,
         }
     }
 
-    pub fn new_con(tabla_persistida: HashMap<String, Valor>) -> Self {
+    pub fn new_con(tabla_persistida: HashMap<Vec<u8>, Valor>) -> Self {
+        let mut volatiles = IndiceEscaneo::default();
+        tabla_persistida
+            .iter()
+            .filter(|(_, valor)| valor.vencimiento().is_some())
+            .for_each(|(clave, _)| volatiles.insertar(clave));
         BaseDeDatos {
-            hashmap: tabla_persistida,
+            hashmap: tabla_persistida.into(),
             observadores: vec![],
+            versiones: HashMap::new(),
+            ultima_version: 0,
+            version_eliminadas: 0,
+            aof: None,
+            transaccion_aof: None,
+            error_aof: None,
+            bloqueos: Bloqueos::default(),
+            pubsub: RegistroPubSub::new(),
+            notificaciones: Notificaciones::default(),
+            volatiles,
+            cursor_expiracion: 0,
         }
     }
 }
 
 impl Observable for BaseDeDatos {
-    fn notificar_observadores(&self, bdd: HashMap<String, Valor>) {
+    fn notificar_observadores(&self) {
         self.observadores
             .iter()
-            .for_each(|o| o.actualizar(bdd.clone()))
+            .for_each(|o| o.actualizar(&self.hashmap))
     }
 
     fn agregar_observador(&mut self, o: Box<dyn Observer + Send>) {
@@ -251,22 +608,109 @@ impl Observable for BaseDeDatos {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::valor::ahora_ms;
     use std::thread;
     use std::time::Duration;
 
     #[test]
     fn base_de_datos_devuelve_una_copia_de_un_elemento_almacenado() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
 
         let valor = data_base.obtener_valor("clave");
-        assert_eq!(&TipoRedis::Str("valor".to_string()), valor.unwrap());
+        assert_eq!(&TipoRedis::Str("valor".into()), valor.unwrap());
+    }
+
+    #[test]
+    fn expirar_muestra_solo_revisa_las_claves_con_expiracion() {
+        let mut data_base = BaseDeDatos::new();
+        for i in 0..1000 {
+            data_base.guardar_valor(format!("persistente{}", i), TipoRedis::Str("v".into()));
+        }
+        for clave in ["vencida1", "vencida2", "pisada", "borrada"] {
+            data_base.guardar_valor_con_vencimiento(clave, 0, TipoRedis::Str("v".into()));
+        }
+        let vencimiento = crate::valor::ahora_ms() + 100_000;
+        data_base.guardar_valor_con_vencimiento(
+            "persistida",
+            vencimiento,
+            TipoRedis::Str("v".into()),
+        );
+        data_base.actualizar_valor_sin_expiracion("persistida");
+        data_base.guardar_valor("pisada", TipoRedis::Str("v".into()));
+        data_base.eliminar_clave("borrada");
+
+        assert_eq!((2, 2), data_base.expirar_muestra(20));
+        assert_eq!((0, 0), data_base.expirar_muestra(20));
+        assert_eq!(1002, data_base.cantidad_claves());
+    }
+
+    #[test]
+    fn las_operaciones_sobre_claves_inexistentes_no_cambian_la_version() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("persistente", TipoRedis::Str("v".into()));
+        data_base.guardar_valor_con_vencimiento("vencida", 0, TipoRedis::Str("v".into()));
+        let version = data_base.ultima_version();
+
+        assert_eq!(0, data_base.eliminar_clave("inexistente"));
+        assert_eq!(0, data_base.actualizar_vencimiento("inexistente", 1));
+        assert_eq!(0, data_base.actualizar_vencimiento("vencida", u64::MAX));
+        assert_eq!(0, data_base.actualizar_valor_sin_expiracion("inexistente"));
+        assert_eq!(0, data_base.actualizar_valor_sin_expiracion("persistente"));
+        assert_eq!(version, data_base.ultima_version());
+        assert_eq!(None, data_base.obtener_valor("vencida"));
+
+        assert_eq!(1, data_base.eliminar_clave("persistente"));
+        assert_ne!(version, data_base.ultima_version());
+    }
+
+    #[test]
+    fn eliminar_expiradas_notifica_el_evento_expired() {
+        use crate::prueba::cliente_de_prueba;
+        use std::io::Read;
+
+        let (cliente, mut conexion) = cliente_de_prueba(1);
+
+        let mut data_base = BaseDeDatos::new();
+        data_base.set_notificaciones(Notificaciones::desde("Ex").unwrap());
+        data_base
+            .pubsub()
+            .suscribir(b"__keyevent@0__:expired".to_vec(), cliente);
+        data_base.guardar_valor_con_vencimiento("clave", 0, TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("otra", TipoRedis::Str("valor".into()));
+
+        data_base.eliminar_expiradas(&[b"clave".to_vec(), b"otra".to_vec()]);
+        assert!(!data_base.existe_clave("clave"));
+        assert!(data_base.existe_clave("otra"));
+
+        let esperado = "*3\r\n$7\r\nmessage\r\n$22\r\n__keyevent@0__:expired\r\n$5\r\nclave\r\n";
+        let mut recibido = String::new();
+        let mut buffer = [0; 256];
+        while !recibido.ends_with(esperado) {
+            let leidos = conexion.read(&mut buffer).unwrap();
+            recibido.push_str(&String::from_utf8_lossy(&buffer[..leidos]));
+        }
+    }
+
+    #[test]
+    fn claves_devuelve_las_que_coinciden_con_el_patron_glob() {
+        let mut data_base = BaseDeDatos::new();
+        for clave in ["user:1", "user:2", "user", "users", "otra"] {
+            data_base.guardar_valor(clave, TipoRedis::Str("valor".into()));
+        }
+
+        let mut claves = data_base.claves(b"user:*");
+        claves.sort();
+        assert_eq!(vec![b"user:1".to_vec(), b"user:2".to_vec()], claves);
+        assert_eq!(5, data_base.claves(b"*").len());
+        assert_eq!(vec![b"users".to_vec()], data_base.claves(b"user?"));
+        assert!(data_base.claves(b"user:[^12]").is_empty());
     }
 
     #[test]
     fn base_de_datos_elimina_valor_almacenado() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
 
         assert!(data_base.existe_clave("clave"));
 
@@ -279,13 +723,56 @@ mod tests {
     fn si_se_guarda_una_clave_que_expira_en_1_segundo_cuando_se_la_quiere_recuperar_no_se_encuentra(
     ) {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor_con_expiracion(
+        data_base.guardar_valor_con_vencimiento(
             "clave".to_string(),
-            1,
-            TipoRedis::Str("valor".to_string()),
+            ahora_ms() + 1000,
+            TipoRedis::Str("valor".into()),
         );
 
         thread::sleep(Duration::from_secs(2));
         assert_eq!(None, data_base.obtener_valor("clave"));
     }
+
+    #[test]
+    fn modificar_una_clave_cambia_su_version_y_no_la_de_las_demas() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        let version_clave = data_base.version("clave");
+        let version_otra = data_base.version("otra");
+
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("nuevo".into()));
+        assert_ne!(version_clave, data_base.version("clave"));
+        assert_eq!(version_otra, data_base.version("otra"));
+
+        let version_clave = data_base.version("clave");
+        data_base.eliminar_clave("clave");
+        assert_ne!(version_clave, data_base.version("clave"));
+    }
+
+    #[test]
+    fn las_claves_eliminadas_o_expiradas_no_conservan_su_version() {
+        let mut data_base = BaseDeDatos::new();
+        for i in 0..100 {
+            data_base.guardar_valor(format!("clave{}", i), TipoRedis::Str("valor".into()));
+            data_base.eliminar_clave(format!("clave{}", i));
+        }
+        data_base.guardar_valor_con_vencimiento("volatil", 1, TipoRedis::Str("valor".into()));
+        data_base.eliminar_expiradas(&[b"volatil".to_vec()]);
+
+        assert!(data_base.versiones.is_empty());
+    }
+
+    #[test]
+    fn una_clave_eliminada_y_vuelta_a_crear_no_repite_version() {
+        let mut data_base = BaseDeDatos::new();
+        let version_inexistente = data_base.version("clave");
+
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        let version_creada = data_base.version("clave");
+        data_base.eliminar_clave("clave");
+        assert_ne!(version_inexistente, data_base.version("clave"));
+
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        assert_ne!(version_creada, data_base.version("clave"));
+    }
 }
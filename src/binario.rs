@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+/// Interpreta una cadena binaria como texto y la parsea al tipo pedido.
+/// Devuelve None si la cadena no es UTF-8 valido o no se puede parsear
+pub fn parsear<T: FromStr>(bytes: &[u8]) -> Option<T> {
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
+
+/// Parsea un entero de 64 bits con las mismas reglas que Redis: solo digitos con un signo menos
+/// opcional, sin espacios, sin signo mas y sin ceros a la izquierda. Asi el entero vuelve a
+/// escribirse exactamente igual que la cadena original
+pub fn parsear_entero(bytes: &[u8]) -> Option<i64> {
+    let digitos = bytes.strip_prefix(b"-").unwrap_or(bytes);
+    let valido = match digitos {
+        [b'0'] => bytes.len() == 1,
+        [primero, resto @ ..] => {
+            (b'1'..=b'9').contains(primero) && resto.iter().all(u8::is_ascii_digit)
+        }
+        [] => false,
+    };
+    if valido {
+        parsear(bytes)
+    } else {
+        None
+    }
+}
+
+/// Representacion en texto de una cadena binaria, reemplazando los bytes que no son UTF-8 valido
+pub fn a_texto(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsear_devuelve_none_si_la_cadena_no_es_utf8_o_no_es_un_numero() {
+        assert_eq!(Some(-12), parsear::<i64>(b"-12"));
+        assert_eq!(None, parsear::<i64>(b"12a"));
+        assert_eq!(None, parsear::<i64>(&[0x31, 0xff]));
+    }
+
+    #[test]
+    fn parsear_entero_solo_acepta_la_forma_canonica() {
+        assert_eq!(Some(0), parsear_entero(b"0"));
+        assert_eq!(Some(-42), parsear_entero(b"-42"));
+        assert_eq!(Some(i64::MAX), parsear_entero(b"9223372036854775807"));
+        assert_eq!(None, parsear_entero(b"9223372036854775808"));
+        assert_eq!(None, parsear_entero(b"007"));
+        assert_eq!(None, parsear_entero(b"+1"));
+        assert_eq!(None, parsear_entero(b"-0"));
+        assert_eq!(None, parsear_entero(b" 1"));
+        assert_eq!(None, parsear_entero(b""));
+    }
+}
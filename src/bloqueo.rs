@@ -0,0 +1,292 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::binario::parsear;
+use crate::cliente::Cliente;
+use crate::comando::ejecutar_comando;
+use crate::comando_info::ComandoInfo;
+use crate::config::Config;
+
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cada cuanto un cliente bloqueado revisa si la conexion se cerro mientras espera
+const INTERVALO_CONEXION: Duration = Duration::from_millis(100);
+
+/// Cliente bloqueado hasta que alguna de sus claves tenga elementos para sacar
+pub struct Espera {
+    id: u64,
+    comando: ComandoInfo,
+    claves: Vec<Vec<u8>>,
+    cliente: Cliente,
+    tx: Sender<ResultadoRedis>,
+}
+
+/// Clientes bloqueados, en el orden en que se bloquearon. Vive dentro de la base de datos
+/// para que solo se modifique con su lock tomado, igual que las claves que esperan
+#[derive(Default)]
+pub struct Bloqueos {
+    esperas: Vec<Espera>,
+    siguiente_id: u64,
+}
+
+impl Bloqueos {
+    /// Registra al cliente como bloqueado en las claves indicadas. Devuelve el id de la
+    /// espera y el canal por el que recibira la respuesta cuando sea atendido
+    pub fn bloquear(
+        &mut self,
+        comando: ComandoInfo,
+        claves: Vec<Vec<u8>>,
+        cliente: Cliente,
+    ) -> (u64, Receiver<ResultadoRedis>) {
+        let (tx, rx) = channel();
+        let id = self.siguiente_id;
+        self.siguiente_id += 1;
+        self.esperas.push(Espera {
+            id,
+            comando,
+            claves,
+            cliente,
+            tx,
+        });
+        (id, rx)
+    }
+
+    /// Quita la espera indicada. Devuelve falso si ya no estaba porque fue atendida
+    pub fn desbloquear(&mut self, id: u64) -> bool {
+        let cantidad = self.esperas.len();
+        self.esperas.retain(|e| e.id != id);
+        self.esperas.len() != cantidad
+    }
+
+    /// Quita y devuelve la primera espera, en orden de llegada, con alguna clave que cumpla
+    /// el predicado, junto con esa clave
+    pub fn primera_lista(
+        &mut self,
+        esta_lista: impl Fn(&[u8]) -> bool,
+    ) -> Option<(Espera, Vec<u8>)> {
+        self.esperas
+            .iter()
+            .enumerate()
+            .find_map(|(i, espera)| {
+                espera
+                    .claves
+                    .iter()
+                    .find(|c| esta_lista(c))
+                    .map(|c| (i, c.clone()))
+            })
+            .map(|(i, clave)| (self.esperas.remove(i), clave))
+    }
+}
+
+/// Se encarga de detectar si el comando puede bloquear al cliente hasta que haya elementos
+pub fn es_comando_bloqueante(comando: &str) -> bool {
+    let comandos = ["BLPOP", "BRPOP", "BLMOVE", "BLMPOP"];
+    comandos.contains(&comando)
+}
+
+/// Parsea un timeout en segundos, que puede tener decimales. Cero indica esperar sin limite
+pub fn parsear_timeout(timeout: &[u8]) -> Result<Option<Duration>, ResultadoRedis> {
+    match parsear::<f64>(timeout) {
+        Some(t) if t < 0.0 => Err(ResultadoRedis::Error("ERR timeout is negative".to_string())),
+        Some(0.0) => Ok(None),
+        Some(t) if t.is_finite() => Duration::try_from_secs_f64(t)
+            .map(Some)
+            .map_err(|_| ResultadoRedis::Error("ERR timeout is out of range".to_string())),
+        _ => Err(ResultadoRedis::Error(
+            "ERR timeout is not a float or out of range".to_string(),
+        )),
+    }
+}
+
+/// Obtiene las claves en las que espera un comando bloqueante ya validado y su timeout
+pub fn claves_y_timeout(comando: &ComandoInfo) -> (Vec<Vec<u8>>, Option<Duration>) {
+    let argumentos = comando.argumentos();
+    let (claves, timeout) = match comando.get_nombre().as_str() {
+        "BLMOVE" => (argumentos[1..2].to_vec(), argumentos.get(5)),
+        "BLMPOP" => {
+            let cantidad = parsear::<usize>(&argumentos[2]).unwrap_or(0);
+            (
+                argumentos.iter().skip(3).take(cantidad).cloned().collect(),
+                argumentos.get(1),
+            )
+        }
+        _ => (
+            argumentos[1..argumentos.len() - 1].to_vec(),
+            argumentos.last(),
+        ),
+    };
+    let timeout = timeout.and_then(|t| parsear_timeout(t).ok()).flatten();
+    (claves, timeout)
+}
+
+/// Reescribe el comando bloqueante para que solo opere sobre la clave que lo desbloqueo.
+/// Asi se registra en el AOF exactamente la operacion que se hizo
+fn reescribir(comando: &ComandoInfo, clave: &[u8]) -> ComandoInfo {
+    let mut argumentos = comando.argumentos();
+    match comando.get_nombre().as_str() {
+        "BLMOVE" => (),
+        "BLMPOP" => {
+            let cantidad = parsear::<usize>(&argumentos[2]).unwrap_or(0);
+            argumentos.splice(2..3 + cantidad, [b"1".to_vec(), clave.to_vec()]);
+        }
+        _ => {
+            let timeout = argumentos.len() - 1;
+            argumentos.splice(1..timeout, [clave.to_vec()]);
+        }
+    }
+    ComandoInfo::new(argumentos)
+}
+
+/// Atiende a los clientes bloqueados cuyas claves ya tienen elementos, en orden de llegada.
+/// Se ejecuta con la base de datos tomada luego de cada comando que la modifica, por lo que
+/// ningun otro cliente puede sacar los elementos antes que los que estaban esperando
+pub fn atender_bloqueados(bdd: &mut BaseDeDatos, config: &Arc<Mutex<Config>>) {
+    while let Some((espera, clave)) = bdd.siguiente_desbloqueado() {
+        let comando = reescribir(&espera.comando, &clave);
+        let resultado = ejecutar_comando(comando, espera.cliente.clone(), bdd, Arc::clone(config));
+        espera.tx.send(resultado).ok();
+    }
+}
+
+/// Espera, sin tomar la base de datos, a que el cliente sea atendido o venza el timeout.
+/// Si vence o el cliente se desconecta se quita la espera y se devuelve un vector nulo
+pub fn esperar_desbloqueo(
+    id: u64,
+    rx: Receiver<ResultadoRedis>,
+    timeout: Option<Duration>,
+    cliente: &Cliente,
+    bdd: &Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    let limite = timeout.and_then(|t| Instant::now().checked_add(t));
+    loop {
+        let intervalo = match limite {
+            Some(l) => l
+                .saturating_duration_since(Instant::now())
+                .min(INTERVALO_CONEXION),
+            None => INTERVALO_CONEXION,
+        };
+        match rx.recv_timeout(intervalo) {
+            Ok(resultado) => return resultado,
+            Err(RecvTimeoutError::Disconnected) => return ResultadoRedis::Null,
+            Err(RecvTimeoutError::Timeout) => {
+                let vencido = limite.is_some_and(|l| Instant::now() >= l);
+                if !vencido && cliente.conexion_abierta() {
+                    continue;
+                }
+                // Se pudo haber atendido justo antes de tomar el lock, en ese caso la respuesta ya esta en el canal
+                let estaba_bloqueado = match bdd.lock() {
+                    Ok(mut bdd) => bdd.bloqueos().desbloquear(id),
+                    Err(_) => {
+                        return ResultadoRedis::Error("ERR when accessing the database".to_string())
+                    }
+                };
+                if estaba_bloqueado {
+                    return ResultadoRedis::Null;
+                }
+                return rx.try_recv().unwrap_or(ResultadoRedis::Null);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_de_datos::TipoRedis;
+    use crate::prueba::cliente_de_prueba;
+
+    #[test]
+    fn el_timeout_admite_decimales_y_cero_es_sin_limite() {
+        assert_eq!(
+            Ok(Some(Duration::from_millis(1500))),
+            parsear_timeout(b"1.5")
+        );
+        assert_eq!(Ok(None), parsear_timeout(b"0"));
+        assert_eq!(
+            Err(ResultadoRedis::Error("ERR timeout is negative".to_string())),
+            parsear_timeout(b"-1")
+        );
+        assert_eq!(
+            Err(ResultadoRedis::Error(
+                "ERR timeout is not a float or out of range".to_string()
+            )),
+            parsear_timeout(b"uno")
+        );
+    }
+
+    #[test]
+    fn un_timeout_demasiado_grande_es_un_error() {
+        assert_eq!(
+            Err(ResultadoRedis::Error(
+                "ERR timeout is out of range".to_string()
+            )),
+            parsear_timeout(b"1e20")
+        );
+    }
+
+    #[test]
+    fn los_clientes_bloqueados_se_atienden_en_orden_de_llegada() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let config = Arc::new(Mutex::new(Config::new()));
+        let mut bdd = BaseDeDatos::new();
+        let (_, primero) = bdd.bloqueos().bloquear(
+            ComandoInfo::new(vec!["BLPOP", "otra", "lista", "0"]),
+            vec![b"otra".to_vec(), b"lista".to_vec()],
+            cliente.clone(),
+        );
+        let (_, segundo) = bdd.bloqueos().bloquear(
+            ComandoInfo::new(vec!["BRPOP", "lista", "0"]),
+            vec![b"lista".to_vec()],
+            cliente.clone(),
+        );
+
+        let push = ComandoInfo::new(vec!["RPUSH", "lista", "a", "b", "c"]);
+        ejecutar_comando(push, cliente, &mut bdd, config);
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("lista".into()),
+                ResultadoRedis::BulkStr("a".into()),
+            ]),
+            primero.try_recv().unwrap()
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("lista".into()),
+                ResultadoRedis::BulkStr("c".into()),
+            ]),
+            segundo.try_recv().unwrap()
+        );
+        assert_eq!(
+            Some(&TipoRedis::Lista(vec!["b".into()].into())),
+            bdd.obtener_valor("lista")
+        );
+    }
+
+    #[test]
+    fn el_comando_se_reescribe_con_la_clave_que_lo_desbloqueo() {
+        let blpop = ComandoInfo::new(vec!["BLPOP", "a", "b", "c", "0"]);
+        assert_eq!(
+            vec![b"BLPOP".to_vec(), b"b".to_vec(), b"0".to_vec()],
+            reescribir(&blpop, b"b").argumentos()
+        );
+
+        let blmpop = ComandoInfo::new(vec!["BLMPOP", "0", "2", "a", "b", "LEFT", "COUNT", "3"]);
+        assert_eq!(
+            vec![
+                b"BLMPOP".to_vec(),
+                b"0".to_vec(),
+                b"1".to_vec(),
+                b"b".to_vec(),
+                b"LEFT".to_vec(),
+                b"COUNT".to_vec(),
+                b"3".to_vec()
+            ],
+            reescribir(&blmpop, b"b").argumentos()
+        );
+        assert_eq!(
+            (vec![b"a".to_vec(), b"b".to_vec()], None),
+            claves_y_timeout(&blmpop)
+        );
+    }
+}
@@ -1,16 +1,17 @@
 use crate::base_de_datos::ResultadoRedis;
 use crate::cliente::Cliente;
+use crate::patron::coincide;
 use crate::redis_error::RedisError;
 
 /// Representa un canal donde se pueden suscribir clientes y publicar mensajes
 #[derive(Debug, PartialEq, Clone)]
 pub struct Canal {
-    nombre: String,
+    nombre: Vec<u8>,
     suscriptores: Vec<Cliente>,
 }
 
 impl Canal {
-    pub fn new(nombre: String) -> Self {
+    pub fn new(nombre: Vec<u8>) -> Self {
         Canal {
             nombre,
             suscriptores: Vec::new(),
@@ -23,10 +24,10 @@ impl Canal {
         }
     }
 
-    pub fn publicar(&mut self, mensaje: String) -> usize {
+    pub fn publicar(&mut self, mensaje: Vec<u8>) -> usize {
         let mut publicados: usize = 0;
-        let resultado = ResultadoRedis::Vector(vec![
-            ResultadoRedis::BulkStr("message".to_string()),
+        let resultado = ResultadoRedis::Push(vec![
+            ResultadoRedis::BulkStr(b"message".to_vec()),
             ResultadoRedis::BulkStr(self.nombre.clone()),
             ResultadoRedis::BulkStr(mensaje),
         ]);
@@ -39,22 +40,28 @@ impl Canal {
         publicados
     }
 
-    pub fn desuscribirse(&mut self, suscriptor: Cliente) {
-        let index = match self
-            .suscriptores
-            .iter()
-            .position(|x| *x == suscriptor.clone())
-        {
-            Some(i) => i,
-            None => return,
-        };
-
-        self.suscriptores.remove(index);
-        if self.notificar_desubscripcion(suscriptor).is_ok() {}
+    /// Agrega al suscriptor sin notificarlo. Devuelve falso si ya estaba suscrito
+    pub fn agregar(&mut self, suscriptor: Cliente) -> bool {
+        if self.es_suscriptor(&suscriptor) {
+            return false;
+        }
+        self.suscriptores.push(suscriptor);
+        true
+    }
+
+    /// Quita al suscriptor sin notificarlo. Devuelve falso si no estaba suscrito
+    pub fn quitar(&mut self, suscriptor: &Cliente) -> bool {
+        let cantidad = self.suscriptores.len();
+        self.suscriptores.retain(|x| x != suscriptor);
+        self.suscriptores.len() != cantidad
+    }
+
+    pub fn es_suscriptor(&self, cliente: &Cliente) -> bool {
+        self.suscriptores.contains(cliente)
     }
 
     pub fn es_activo(&self) -> bool {
-        self.suscriptores.len() > 1
+        !self.suscriptores.is_empty()
     }
 
     pub fn len(&self) -> usize {
@@ -63,19 +70,72 @@ impl Canal {
 
     fn notificar_suscripcion(&self, mut suscriptor: Cliente) -> Result<(), RedisError> {
         let cant = self.suscriptores.len() + 1;
-        let resultado = ResultadoRedis::Vector(vec![
-            ResultadoRedis::BulkStr("subscribe".to_string()),
+        let resultado = ResultadoRedis::Push(vec![
+            ResultadoRedis::BulkStr(b"subscribe".to_vec()),
             ResultadoRedis::BulkStr(self.nombre.clone()),
             ResultadoRedis::Int(cant as isize),
         ]);
         suscriptor.enviar_resultado(&resultado)
     }
-    fn notificar_desubscripcion(&self, mut suscriptor: Cliente) -> Result<(), RedisError> {
-        let resultado = ResultadoRedis::Vector(vec![
-            ResultadoRedis::BulkStr("unsubscribe".to_string()),
-            ResultadoRedis::BulkStr(self.nombre.clone()),
-            ResultadoRedis::Int(self.suscriptores.len() as isize),
+}
+
+/// Representa un patron glob al que se suscriben clientes para recibir los mensajes publicados
+/// en todos los canales cuyo nombre coincide con el
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatronCanal {
+    patron: Vec<u8>,
+    suscriptores: Vec<Cliente>,
+}
+
+impl PatronCanal {
+    pub fn new(patron: Vec<u8>) -> Self {
+        PatronCanal {
+            patron,
+            suscriptores: Vec::new(),
+        }
+    }
+
+    /// Agrega al suscriptor sin notificarlo. Devuelve falso si ya estaba suscrito
+    pub fn agregar(&mut self, suscriptor: Cliente) -> bool {
+        if self.es_suscriptor(&suscriptor) {
+            return false;
+        }
+        self.suscriptores.push(suscriptor);
+        true
+    }
+
+    /// Quita al suscriptor sin notificarlo. Devuelve falso si no estaba suscrito
+    pub fn quitar(&mut self, suscriptor: &Cliente) -> bool {
+        let cantidad = self.suscriptores.len();
+        self.suscriptores.retain(|x| x != suscriptor);
+        self.suscriptores.len() != cantidad
+    }
+
+    /// Envia el mensaje a los suscriptores si el canal coincide con el patron. Devuelve a cuantos se envio
+    pub fn publicar(&mut self, canal: &[u8], mensaje: &[u8]) -> usize {
+        if !coincide(&self.patron, canal) {
+            return 0;
+        }
+        let mut publicados: usize = 0;
+        let resultado = ResultadoRedis::Push(vec![
+            ResultadoRedis::BulkStr(b"pmessage".to_vec()),
+            ResultadoRedis::BulkStr(self.patron.clone()),
+            ResultadoRedis::BulkStr(canal.to_vec()),
+            ResultadoRedis::BulkStr(mensaje.to_vec()),
         ]);
-        suscriptor.enviar_resultado(&resultado)
+        for suscriptor in &mut self.suscriptores {
+            if suscriptor.enviar_resultado(&resultado).is_ok() {
+                publicados += 1;
+            }
+        }
+        publicados
+    }
+
+    pub fn es_suscriptor(&self, cliente: &Cliente) -> bool {
+        self.suscriptores.contains(cliente)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.suscriptores.is_empty()
     }
 }
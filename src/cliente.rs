@@ -2,7 +2,9 @@ use crate::base_de_datos::ResultadoRedis;
 use crate::cliente_http::ClienteHttp;
 use crate::cliente_redis::ClienteRedis;
 use crate::comando_info::ComandoInfo;
+use crate::parser::Protocolo;
 use crate::redis_error::RedisError;
+use crate::transaccion::Transaccion;
 use std::fmt;
 use std::fmt::Debug;
 
@@ -32,6 +34,9 @@ pub trait TipoCliente: ClienteClone + ClienteDebug {
 
     fn esta_conectado(&self) -> bool;
 
+    /// Predicado que indica, sin bloquearse a esperar datos, si el otro extremo no cerro la conexion
+    fn conexion_abierta(&self) -> bool;
+
     /// enviar el resultado procesandolo en el protocolo especifico
     fn enviar_resultado(&mut self, resultado: &ResultadoRedis) -> Result<(), RedisError>;
 
@@ -42,6 +47,22 @@ pub trait TipoCliente: ClienteClone + ClienteDebug {
 
     /// Predicado que indica si un Cliente puede enviar determinado comando
     fn soporta_comando(&self, comando: &str) -> bool;
+
+    /// Devuelve el estado de la transaccion del Cliente, o ninguno si no soporta transacciones
+    fn obtener_transaccion(&mut self) -> Option<&mut Transaccion>;
+
+    /// Version del protocolo con la que se codifican las respuestas enviadas al Cliente
+    fn obtener_protocolo(&self) -> Protocolo;
+
+    /// Cambia la version del protocolo para las siguientes respuestas. Las copias del
+    /// Cliente, como las suscritas a un canal, tambien pasan a usarla
+    fn cambiar_protocolo(&mut self, protocolo: Protocolo);
+
+    /// Cantidad de canales y patrones a los que esta suscrito el Cliente
+    fn obtener_suscripciones(&self) -> usize;
+
+    /// Registra la cantidad de suscripciones del Cliente. Como el protocolo, se comparte con sus copias
+    fn cambiar_suscripciones(&mut self, cantidad: usize);
 }
 
 pub trait ClienteClone {
@@ -91,21 +112,41 @@ where
 }
 
 /// Crea a un cliente especifico dependiendo de como sea el protocolo que utilice
-/// ya sea HTTP o Redis
+/// ya sea HTTP o Redis, tanto multibulk como inline
 pub fn crear_cliente(id: Token, timeout: u64, stream: TcpStream) -> Box<dyn TipoCliente + Send> {
     let mut buffer = [0; 1024];
-    match stream.peek(&mut buffer) {
-        Ok(_) => (),
+    let leidos = match stream.peek(&mut buffer) {
+        Ok(leidos) => leidos,
         Err(_) => return Box::new(ClienteRedis::new(id, timeout, stream)),
     };
 
-    let mensaje = match String::from_utf8(buffer.to_vec()) {
-        Ok(s) => s,
-        Err(_) => "".to_string(),
-    };
-    if mensaje.contains("HTTP") {
+    if es_pedido_http(&buffer[..leidos]) {
         Box::new(ClienteHttp::new(id, stream))
     } else {
         Box::new(ClienteRedis::new(id, timeout, stream))
     }
 }
+
+/// Predicado que indica si el mensaje comienza con una linea de pedido HTTP, como
+/// `GET /clave HTTP/1.1`. Un comando inline que solo contiene la palabra HTTP no lo es
+fn es_pedido_http(mensaje: &[u8]) -> bool {
+    let primera_linea = match mensaje.split(|b| *b == b'\n').next() {
+        Some(linea) => String::from_utf8_lossy(linea),
+        None => return false,
+    };
+    let partes: Vec<&str> = primera_linea.split_whitespace().collect();
+    partes.len() == 3 && partes[2].starts_with("HTTP/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solo_una_linea_de_pedido_http_se_considera_http() {
+        assert!(es_pedido_http(b"GET /clave HTTP/1.1\r\nHost: x\r\n\r\n"));
+        assert!(!es_pedido_http(b"GET HTTP\r\n"));
+        assert!(!es_pedido_http(b"SET clave HTTP\r\n"));
+        assert!(!es_pedido_http(b"*2\r\n$3\r\nGET\r\n$4\r\nHTTP\r\n"));
+    }
+}
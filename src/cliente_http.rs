@@ -3,7 +3,9 @@ use crate::cliente::{TipoCliente, Token};
 use crate::comando_http::ComandoHttp;
 use crate::comando_info::ComandoInfo;
 use crate::http_parser::{parsear_respuesta, HttpParser};
+use crate::parser::Protocolo;
 use crate::redis_error::RedisError;
+use crate::transaccion::Transaccion;
 use std::fs::{read_to_string, File};
 
 use std::fmt;
@@ -178,6 +180,10 @@ impl TipoCliente for ClienteHttp {
         !self.mando
     }
 
+    fn conexion_abierta(&self) -> bool {
+        !self.mando
+    }
+
     fn enviar_resultado(&mut self, resultado: &ResultadoRedis) -> Result<(), RedisError> {
         self.mando = true;
 
@@ -193,6 +199,20 @@ impl TipoCliente for ClienteHttp {
         self.id
     }
 
+    fn obtener_protocolo(&self) -> Protocolo {
+        Protocolo::Resp2
+    }
+
+    /// Las respuestas HTTP no usan el protocolo redis, por lo que no hay nada que cambiar
+    fn cambiar_protocolo(&mut self, _protocolo: Protocolo) {}
+
+    /// Los clientes HTTP no pueden suscribirse a canales
+    fn obtener_suscripciones(&self) -> usize {
+        0
+    }
+
+    fn cambiar_suscripciones(&mut self, _cantidad: usize) {}
+
     fn soporta_comando(&self, comando: &str) -> bool {
         let comandos = vec![
             "COPY",
@@ -205,6 +225,7 @@ impl TipoCliente for ClienteHttp {
             "TTL",
             "TOUCH",
             "KEYS",
+            "SCAN",
             "SORT",
             "TYPE",
             "LINDEX",
@@ -218,17 +239,81 @@ impl TipoCliente for ClienteHttp {
             "LREM",
             "LSET",
             "LLEN",
+            "LINSERT",
+            "LTRIM",
+            "LPOS",
+            "LMOVE",
+            "RPOPLPUSH",
+            "LMPOP",
             "SADD",
             "SCARD",
             "SISMEMBER",
             "SMEMBERS",
+            "SSCAN",
             "SREM",
+            "SMISMEMBER",
+            "SINTER",
+            "SINTERCARD",
+            "SINTERSTORE",
+            "SUNION",
+            "SUNIONSTORE",
+            "SDIFF",
+            "SDIFFSTORE",
+            "SMOVE",
+            "SPOP",
+            "SRANDMEMBER",
+            "HSET",
+            "HGET",
+            "HMGET",
+            "HDEL",
+            "HEXISTS",
+            "HLEN",
+            "HKEYS",
+            "HVALS",
+            "HGETALL",
+            "HSCAN",
+            "HINCRBY",
+            "HINCRBYFLOAT",
+            "HSETNX",
+            "HSTRLEN",
+            "ZADD",
+            "ZSCORE",
+            "ZINCRBY",
+            "ZRANK",
+            "ZREVRANK",
+            "ZRANGE",
+            "ZRANGEBYSCORE",
+            "ZREM",
+            "ZREMRANGEBYSCORE",
+            "ZREMRANGEBYRANK",
+            "ZCARD",
+            "ZSCAN",
+            "ZCOUNT",
+            "ZPOPMIN",
+            "ZPOPMAX",
+            "ZUNIONSTORE",
+            "ZINTERSTORE",
             "GET",
             "SET",
             "APPEND",
             "STRLEN",
+            "INCR",
+            "DECR",
             "INCRBY",
             "DECRBY",
+            "INCRBYFLOAT",
+            "GETRANGE",
+            "SETRANGE",
+            "LCS",
+            "SETBIT",
+            "GETBIT",
+            "BITCOUNT",
+            "BITPOS",
+            "BITOP",
+            "BITFIELD",
+            "PFADD",
+            "PFCOUNT",
+            "PFMERGE",
             "MGET",
             "MSET",
             "GETSET",
@@ -241,6 +326,10 @@ impl TipoCliente for ClienteHttp {
         ];
         comandos.iter().any(|&c| c == comando)
     }
+
+    fn obtener_transaccion(&mut self) -> Option<&mut Transaccion> {
+        None
+    }
 }
 
 impl Clone for ClienteHttp {
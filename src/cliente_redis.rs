@@ -1,21 +1,26 @@
 use crate::base_de_datos::ResultadoRedis;
 use crate::cliente::{TipoCliente, Token};
 use crate::comando_info::ComandoInfo;
-use crate::parser::{parsear_respuesta, Parser};
+use crate::parser::{parsear_respuesta, Parser, ParserError, Protocolo};
 use crate::redis_error::RedisError;
+use crate::transaccion::Transaccion;
 use std::time::{Duration, Instant};
 
 use std::fmt;
-use std::io::Write;
-use std::net::TcpStream;
+use std::io::{ErrorKind, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::{Arc, Mutex};
 
 /// Representa a un Cliente que envia mensajes utilizando el protocolo redis
 pub struct ClienteRedis {
     id: Token,
-    canales: usize,
+    canales: Arc<Mutex<usize>>,
     timeout: Option<Duration>,
     ultimo_mensaje: Instant,
     socket: Option<TcpStream>,
+    parser: Option<Arc<Mutex<Parser<TcpStream>>>>,
+    protocolo: Arc<Mutex<Protocolo>>,
+    transaccion: Transaccion,
 }
 
 impl ClienteRedis {
@@ -32,12 +37,48 @@ impl ClienteRedis {
             t => Some(Duration::from_secs(t)),
         };
 
+        // El parser se crea una unica vez por conexion, asi los bytes que se leen de mas
+        // (comandos encadenados o un comando a medio llegar) quedan para la siguiente lectura
+        let parser = stream
+            .try_clone()
+            .ok()
+            .map(|lector| Arc::new(Mutex::new(Parser::new(lector))));
+
         ClienteRedis {
             id,
-            canales: 0,
+            canales: Arc::new(Mutex::new(0)),
             timeout: duracion,
             ultimo_mensaje: Instant::now(),
             socket: Some(stream),
+            parser,
+            protocolo: Arc::new(Mutex::new(Protocolo::default())),
+            transaccion: Transaccion::new(),
+        }
+    }
+
+    /// Predicado que indica si el parser ya tiene leidos bytes de comandos sin procesar
+    fn tiene_comandos_pendientes(&self) -> bool {
+        match &self.parser {
+            Some(parser) => match parser.lock() {
+                Ok(parser) => parser.tiene_pendientes(),
+                Err(_) => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Envia los bytes tal cual al Cliente, las respuestas pueden contener cadenas binarias
+    fn enviar_bytes(&mut self, bytes: &[u8]) -> Result<(), RedisError> {
+        self.ultimo_mensaje = Instant::now();
+
+        let socket = match &mut self.socket {
+            None => return Err(RedisError::Coneccion),
+            Some(t) => t,
+        };
+
+        match socket.write_all(bytes) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(RedisError::Coneccion),
         }
     }
 
@@ -62,14 +103,29 @@ impl TipoCliente for ClienteRedis {
     /// * `Ok(Some(c))` - Se obtiene el comando enviado correctamente
     /// * `Err(e)` - Se produjo un error al la hora de obtener el comando
     fn obtener_comando(&mut self) -> Result<Option<ComandoInfo>, RedisError> {
-        let stream = match self.obtener_socket() {
-            Some(s) => s,
+        let mut parser = match &self.parser {
+            Some(p) => match p.lock() {
+                Ok(p) => p,
+                Err(_) => return Err(RedisError::Server),
+            },
             None => return Err(RedisError::Coneccion),
         };
-        let parser = Parser::new(stream);
 
-        match parser.parsear_stream() {
+        let resultado = parser.parsear_stream();
+        drop(parser);
+
+        match resultado {
             Ok(orden) => Ok(Some(orden)),
+            // Como en Redis, se avisa al cliente antes de cerrar la conexion, ya que el resto de
+            // la linea no puede descartarse sin leerla completa
+            Err(ParserError::LineaDemasiadoLarga) => {
+                let error = "ERR Protocol error: too big inline request".to_string();
+                self.enviar_resultado(&ResultadoRedis::Error(error)).ok();
+                if let Some(socket) = &self.socket {
+                    socket.shutdown(Shutdown::Both).ok();
+                }
+                Err(RedisError::Server)
+            }
             Err(_) => Err(RedisError::Server),
         }
     }
@@ -87,6 +143,10 @@ impl TipoCliente for ClienteRedis {
     }
 
     fn envio_informacion(&self) -> bool {
+        if self.tiene_comandos_pendientes() {
+            return true;
+        }
+
         let socket = match &self.socket {
             None => return false,
             Some(t) => t,
@@ -117,23 +177,35 @@ impl TipoCliente for ClienteRedis {
         esta_conectado && !paso_el_timeout
     }
 
-    fn enviar_resultado(&mut self, resultado: &ResultadoRedis) -> Result<(), RedisError> {
-        let mensaje = parsear_respuesta(resultado);
-        self.enviar_mensaje(mensaje)
-    }
-
-    fn enviar_mensaje(&mut self, mensaje: String) -> Result<(), RedisError> {
-        self.ultimo_mensaje = Instant::now();
-
-        let socket = match &mut self.socket {
-            None => return Err(RedisError::Coneccion),
+    fn conexion_abierta(&self) -> bool {
+        let socket = match &self.socket {
+            None => return false,
             Some(t) => t,
         };
 
-        match socket.write(mensaje.as_bytes()) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(RedisError::Coneccion),
+        // El timeout de lectura solo afecta a este hilo, que es el unico que lee del socket,
+        // a diferencia de volverlo no bloqueante, que afectaria a quienes le escriben
+        if socket
+            .set_read_timeout(Some(Duration::from_millis(1)))
+            .is_err()
+        {
+            return false;
         }
+        let abierta = match socket.peek(&mut [0; 1]) {
+            Ok(len) => len > 0,
+            Err(e) => matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut),
+        };
+        socket.set_read_timeout(None).ok();
+        abierta
+    }
+
+    fn enviar_resultado(&mut self, resultado: &ResultadoRedis) -> Result<(), RedisError> {
+        let mensaje = parsear_respuesta(resultado, self.obtener_protocolo());
+        self.enviar_bytes(&mensaje)
+    }
+
+    fn enviar_mensaje(&mut self, mensaje: String) -> Result<(), RedisError> {
+        self.enviar_bytes(mensaje.as_bytes())
     }
     fn obtener_token(&self) -> Token {
         self.id
@@ -142,16 +214,49 @@ impl TipoCliente for ClienteRedis {
     fn soporta_comando(&self, _comando: &str) -> bool {
         true
     }
+
+    fn obtener_transaccion(&mut self) -> Option<&mut Transaccion> {
+        Some(&mut self.transaccion)
+    }
+
+    fn obtener_protocolo(&self) -> Protocolo {
+        match self.protocolo.lock() {
+            Ok(p) => *p,
+            Err(_) => Protocolo::default(),
+        }
+    }
+
+    fn cambiar_protocolo(&mut self, protocolo: Protocolo) {
+        if let Ok(mut p) = self.protocolo.lock() {
+            *p = protocolo;
+        }
+    }
+
+    fn obtener_suscripciones(&self) -> usize {
+        match self.canales.lock() {
+            Ok(c) => *c,
+            Err(_) => 0,
+        }
+    }
+
+    fn cambiar_suscripciones(&mut self, cantidad: usize) {
+        if let Ok(mut c) = self.canales.lock() {
+            *c = cantidad;
+        }
+    }
 }
 
 impl Clone for ClienteRedis {
     fn clone(&self) -> Self {
         ClienteRedis {
             id: self.id,
-            canales: self.canales,
+            canales: Arc::clone(&self.canales),
             timeout: self.timeout,
             ultimo_mensaje: self.ultimo_mensaje,
             socket: self.obtener_socket(),
+            parser: self.parser.clone(),
+            protocolo: Arc::clone(&self.protocolo),
+            transaccion: self.transaccion.clone(),
         }
     }
 }
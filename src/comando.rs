@@ -1,5 +1,11 @@
+use crate::aof::comandos_a_registrar;
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::bloqueo::atender_bloqueados;
 use crate::cliente::Cliente;
+use crate::comando_bit_handler::{es_comando_bit, ComandoBitHandler};
+use crate::comando_conexion_handler::{es_comando_conexion, ComandoConexionHandler};
+use crate::comando_hash_handler::{es_comando_hash, ComandoHashHandler};
+use crate::comando_hll_handler::{es_comando_hll, ComandoHllHandler};
 use crate::comando_info::ComandoInfo;
 use crate::comando_key_handler::{es_comando_key, ComandoKeyHandler};
 use crate::comando_list_handler::{es_comando_list, ComandoListHandler};
@@ -8,8 +14,10 @@ use crate::comando_pubsub_handler::{es_comando_pubsub, ComandoPubSubHandler};
 use crate::comando_server_handler::{es_comando_server, ComandoServerHandler};
 use crate::comando_set_handler::{es_comando_set, ComandoSetHandler};
 use crate::comando_string_handler::{es_comando_string, ComandoStringHandler};
+use crate::comando_zset_handler::{es_comando_zset, ComandoZSetHandler};
 use crate::config::Config;
 
+use std::mem;
 use std::sync::{Arc, Mutex};
 
 /// Interfaz publica que todos los manejadores de comando deben implementar
@@ -25,16 +33,37 @@ pub fn crear_comando_handler(
 ) -> Box<dyn ComandoHandler> {
     if !cliente.soporta_comando(comando.get_nombre().as_str()) {
         Box::new(ComandoNuloHandler::new(comando))
-    } else if es_comando_string(comando.get_nombre().as_str()) {
+    } else if es_comando_pubsub(comando.get_nombre().as_str()) {
+        Box::new(ComandoPubSubHandler::new(comando, cliente))
+    } else if es_comando_conexion(comando.get_nombre().as_str()) {
+        Box::new(ComandoConexionHandler::new(comando, cliente))
+    } else {
+        crear_comando_handler_sin_cliente(comando, config)
+    }
+}
+
+/// Instancia al manejador de un comando que no necesita conocer al cliente que lo envio,
+/// como los comandos que se reproducen desde el archivo AOF
+pub fn crear_comando_handler_sin_cliente(
+    comando: ComandoInfo,
+    config: Arc<Mutex<Config>>,
+) -> Box<dyn ComandoHandler> {
+    if es_comando_string(comando.get_nombre().as_str()) {
         Box::new(ComandoStringHandler::new(comando))
+    } else if es_comando_bit(comando.get_nombre().as_str()) {
+        Box::new(ComandoBitHandler::new(comando))
+    } else if es_comando_hll(comando.get_nombre().as_str()) {
+        Box::new(ComandoHllHandler::new(comando))
     } else if es_comando_set(comando.get_nombre().as_str()) {
         Box::new(ComandoSetHandler::new(comando))
+    } else if es_comando_hash(comando.get_nombre().as_str()) {
+        Box::new(ComandoHashHandler::new(comando))
+    } else if es_comando_zset(comando.get_nombre().as_str()) {
+        Box::new(ComandoZSetHandler::new(comando))
     } else if es_comando_key(comando.get_nombre().as_str()) {
         Box::new(ComandoKeyHandler::new(comando))
     } else if es_comando_list(comando.get_nombre().as_str()) {
         Box::new(ComandoListHandler::new(comando))
-    } else if es_comando_pubsub(comando.get_nombre().as_str()) {
-        Box::new(ComandoPubSubHandler::new(comando, cliente))
     } else if es_comando_server(comando.get_nombre().as_str()) {
         Box::new(ComandoServerHandler::new(comando, config))
     } else {
@@ -42,6 +71,147 @@ pub fn crear_comando_handler(
     }
 }
 
+/// Ejecuta el comando con acceso exclusivo a la base de datos. Si el comando la modifica, se registra en el archivo AOF
+/// y se atiende a los clientes bloqueados en las listas que hayan recibido elementos
+///
+/// # Argumentos
+///
+/// * `comando` - comando a ejecutar
+/// * `cliente` - cliente que envio el comando
+/// * `bdd` - base de datos ya tomada por el llamador
+/// * `config` - la configuracion del servidor
+pub fn ejecutar_comando(
+    comando: ComandoInfo,
+    cliente: Cliente,
+    bdd: &mut BaseDeDatos,
+    config: Arc<Mutex<Config>>,
+) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    let manejador = crear_comando_handler(comando, cliente, Arc::clone(&config));
+    ejecutar_manejador(&argumentos, manejador, bdd, &config)
+}
+
+/// Ejecuta el manejador del comando sobre la base ya tomada, y si modifico alguna clave registra
+/// el comando en el AOF y atiende a los clientes bloqueados
+fn ejecutar_manejador(
+    argumentos: &[Vec<u8>],
+    manejador: Box<dyn ComandoHandler>,
+    bdd: &mut BaseDeDatos,
+    config: &Arc<Mutex<Config>>,
+) -> ResultadoRedis {
+    // Las claves expiradas que usa el comando se eliminan antes de ejecutarlo, asi se notifica
+    // su expiracion. Se revisan todos los argumentos, eliminar una clave expirada no cambia nada
+    bdd.eliminar_expiradas(&argumentos[1..]);
+    if let Ok(config) = config.lock() {
+        bdd.set_notificaciones(config.notify_keyspace_events());
+    }
+    let version = bdd.ultima_version();
+
+    // Los manejadores reciben la base en un mutex y lo toman, pero el llamador ya tiene tomado el
+    // mutex compartido, y volver a tomarlo seria un deadlock. Por eso se les presta la base dentro
+    // de un mutex propio que solo ve este manejador: nunca hay otro hilo esperandolo, asi que no
+    // agrega un orden de locks que respetar. El mutex compartido sigue tomado durante el prestamo
+    let resultado = {
+        let prestamo = Prestamo::new(bdd);
+        manejador.ejecutar(Arc::clone(&prestamo.local))
+    };
+
+    if bdd.ultima_version() != version {
+        for comando in comandos_a_registrar(argumentos, bdd, &resultado) {
+            bdd.registrar_comando(&comando);
+        }
+        atender_bloqueados(bdd, config);
+        // El comando ya se aplico, pero el cliente no puede darlo por guardado si no llego al AOF
+        if let Some(error) = bdd.tomar_error_aof() {
+            return error;
+        }
+    }
+    resultado
+}
+
+/// Base que se saca del lugar del llamador para entregarsela a los manejadores. Al descartarse la
+/// devuelve a su lugar, incluso si el manejador entra en panico, para que la base compartida nunca
+/// quede reemplazada por una vacia
+struct Prestamo<'a> {
+    lugar: &'a mut BaseDeDatos,
+    local: Arc<Mutex<BaseDeDatos>>,
+}
+
+impl<'a> Prestamo<'a> {
+    fn new(lugar: &'a mut BaseDeDatos) -> Self {
+        let local = Arc::new(Mutex::new(mem::replace(lugar, BaseDeDatos::new())));
+        Prestamo { lugar, local }
+    }
+}
+
+impl Drop for Prestamo<'_> {
+    fn drop(&mut self) {
+        let mut local = match self.local.lock() {
+            Ok(b) => b,
+            Err(e) => e.into_inner(),
+        };
+        mem::swap(self.lugar, &mut *local);
+    }
+}
+
+/// Predicado que indica si existe un manejador para el comando
+pub fn es_comando_conocido(comando: &str) -> bool {
+    es_comando_string(comando)
+        || es_comando_bit(comando)
+        || es_comando_hll(comando)
+        || es_comando_set(comando)
+        || es_comando_hash(comando)
+        || es_comando_zset(comando)
+        || es_comando_key(comando)
+        || es_comando_list(comando)
+        || es_comando_pubsub(comando)
+        || es_comando_conexion(comando)
+        || es_comando_server(comando)
+}
+
 /// Interfaz publica de como debe ser un comando redis
 pub type Comando =
     Box<dyn FnOnce(&mut ComandoInfo, Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis + 'static>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_de_datos::TipoRedis;
+    use std::panic::{self, AssertUnwindSafe};
+
+    struct ManejadorQueEntraEnPanico;
+
+    impl ComandoHandler for ManejadorQueEntraEnPanico {
+        fn ejecutar(self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+            let mut bdd = bdd.lock().unwrap();
+            bdd.guardar_valor("otra", TipoRedis::Str("valor".into()));
+            panic!("fallo en el manejador");
+        }
+    }
+
+    #[test]
+    fn la_base_prestada_vuelve_a_su_lugar_aunque_el_manejador_entre_en_panico() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("clave", TipoRedis::Str("valor".into()));
+        let config = Arc::new(Mutex::new(Config::new()));
+
+        let resultado = panic::catch_unwind(AssertUnwindSafe(|| {
+            ejecutar_manejador(
+                &[b"PANICO".to_vec()],
+                Box::new(ManejadorQueEntraEnPanico),
+                &mut bdd,
+                &config,
+            )
+        }));
+
+        assert!(resultado.is_err());
+        assert_eq!(
+            Some(&TipoRedis::Str("valor".into())),
+            bdd.obtener_valor("clave")
+        );
+        assert_eq!(
+            Some(&TipoRedis::Str("valor".into())),
+            bdd.obtener_valor("otra")
+        );
+    }
+}
@@ -0,0 +1,841 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::parsear;
+use crate::comando::{Comando, ComandoHandler};
+use crate::comando_info::ComandoInfo;
+use crate::notificacion::ClaseEvento;
+use std::sync::{Arc, Mutex};
+
+/// Manejador de los comandos que operan bit a bit sobre valores de tipo string
+pub struct ComandoBitHandler {
+    comando: ComandoInfo,
+    a_ejecutar: Comando,
+}
+
+impl ComandoBitHandler {
+    pub fn new(comando: ComandoInfo) -> Self {
+        let a_ejecutar = match comando.get_nombre().as_str() {
+            "GETBIT" => getbit,
+            "BITCOUNT" => bitcount,
+            "BITPOS" => bitpos,
+            "BITOP" => bitop,
+            "BITFIELD" => bitfield,
+            _ => setbit,
+        };
+        ComandoBitHandler {
+            comando,
+            a_ejecutar: Box::new(a_ejecutar),
+        }
+    }
+}
+
+impl ComandoHandler for ComandoBitHandler {
+    fn ejecutar(mut self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        (self.a_ejecutar)(&mut self.comando, bdd)
+    }
+}
+
+/// Se encarga de detectar si el comando corresponde a los que operan sobre bits
+pub fn es_comando_bit(comando: &str) -> bool {
+    let comandos = [
+        "SETBIT", "GETBIT", "BITCOUNT", "BITPOS", "BITOP", "BITFIELD",
+    ];
+    comandos.contains(&comando)
+}
+
+/// Cantidad de bits que puede direccionar un comando, igual que en Redis los strings no pueden superar los 512MB
+const BITS_MAXIMOS: u64 = 512 * 1024 * 1024 * 8;
+
+fn error_tipo() -> ResultadoRedis {
+    ResultadoRedis::Error(
+        "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_offset() -> ResultadoRedis {
+    ResultadoRedis::Error("ERR bit offset is not an integer or out of range".to_string())
+}
+
+fn error_entero() -> ResultadoRedis {
+    ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+}
+
+/// Obtiene el string almacenado en la clave sin copiarlo, ninguno si no existe. Devuelve error si la clave contiene otro tipo
+fn obtener_string<'a>(
+    bdd: &'a BaseDeDatos,
+    clave: &[u8],
+) -> Result<Option<&'a [u8]>, ResultadoRedis> {
+    match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Str(valor)) => Ok(Some(valor)),
+        None => Ok(None),
+        _ => Err(error_tipo()),
+    }
+}
+
+/// Crea un string vacio en la clave si no existe, para poder modificarlo en el lugar. Devuelve error si la clave
+/// contiene otro tipo
+fn crear_si_no_existe(bdd: &mut BaseDeDatos, clave: &[u8]) -> Result<(), ResultadoRedis> {
+    if obtener_string(bdd, clave)?.is_none() {
+        bdd.guardar_valor(clave.to_vec(), TipoRedis::Str(Vec::new()));
+    }
+    Ok(())
+}
+
+/// Parsea la posicion de un bit, que no puede superar el tamaño maximo de un string
+fn parsear_offset(offset: &[u8]) -> Result<u64, ResultadoRedis> {
+    match parsear::<u64>(offset) {
+        Some(offset) if offset < BITS_MAXIMOS => Ok(offset),
+        _ => Err(error_offset()),
+    }
+}
+
+/// Devuelve el bit de la posicion indicada, siendo el bit 0 el mas significativo del primer byte.
+/// Los bits mas alla del final del string valen 0
+fn leer_bit(bytes: &[u8], offset: u64) -> u8 {
+    match bytes.get((offset / 8) as usize) {
+        Some(byte) => (byte >> (7 - offset % 8)) & 1,
+        None => 0,
+    }
+}
+
+/// Cambia el bit de la posicion indicada, agrandando el string con ceros si hace falta
+fn escribir_bit(bytes: &mut Vec<u8>, offset: u64, bit: u8) {
+    let indice = (offset / 8) as usize;
+    if bytes.len() <= indice {
+        bytes.resize(indice + 1, 0);
+    }
+    let mascara = 1 << (7 - offset % 8);
+    if bit == 1 {
+        bytes[indice] |= mascara;
+    } else {
+        bytes[indice] &= !mascara;
+    }
+}
+
+/// Convierte un rango con indices que pueden ser negativos en uno dentro de `[0, largo)`, ambos inclusive.
+/// Devuelve ninguno si el rango queda vacio
+fn normalizar_rango(inicio: i64, fin: i64, largo: i64) -> Option<(u64, u64)> {
+    let normalizar = |indice: i64| {
+        if indice < 0 {
+            (largo + indice).max(0)
+        } else {
+            indice
+        }
+    };
+    let (inicio, fin) = (normalizar(inicio), normalizar(fin).min(largo - 1));
+    if largo == 0 || inicio > fin {
+        None
+    } else {
+        Some((inicio as u64, fin as u64))
+    }
+}
+
+/// Parsea el inicio, el fin y la unidad opcional BYTE o BIT de un rango. Devuelve si los indices son en bits
+fn parsear_rango(argumentos: &[Vec<u8>]) -> Result<(i64, i64, bool), ResultadoRedis> {
+    let (inicio, fin) = match (
+        parsear::<i64>(&argumentos[0]),
+        parsear::<i64>(&argumentos[1]),
+    ) {
+        (Some(inicio), Some(fin)) => (inicio, fin),
+        _ => return Err(error_entero()),
+    };
+    let en_bits = match argumentos.get(2).map(|u| u.to_ascii_uppercase()) {
+        None => false,
+        Some(unidad) if unidad == b"BYTE" => false,
+        Some(unidad) if unidad == b"BIT" => true,
+        Some(_) => return Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    };
+    Ok((inicio, fin, en_bits))
+}
+
+/// Cambia el bit de la posicion indicada del string almacenado en la clave y devuelve el valor que tenia.
+/// Si la clave no existe o el string es mas corto se agranda con ceros
+fn setbit(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'setbit' command".to_string(),
+        );
+    }
+    let clave = &argumentos[1];
+    let offset = match parsear_offset(&argumentos[2]) {
+        Ok(offset) => offset,
+        Err(error) => return error,
+    };
+    let bit = match argumentos[3].as_slice() {
+        b"0" => 0,
+        b"1" => 1,
+        _ => return ResultadoRedis::Error("ERR bit is not an integer or out of range".to_string()),
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    if let Err(error) = crear_si_no_existe(&mut bdd, clave) {
+        return error;
+    }
+    let anterior = bdd.modificar_string(clave, |valor| {
+        let anterior = leer_bit(valor, offset);
+        escribir_bit(valor, offset, bit);
+        anterior
+    });
+    bdd.notificar(ClaseEvento::Cadena, "setbit", clave);
+    ResultadoRedis::Int(anterior.unwrap_or(0) as isize)
+}
+
+/// Devuelve el bit de la posicion indicada del string almacenado en la clave, 0 si esta mas alla del final
+fn getbit(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'getbit' command".to_string(),
+        );
+    }
+    let offset = match parsear_offset(&argumentos[2]) {
+        Ok(offset) => offset,
+        Err(error) => return error,
+    };
+    match bdd.lock() {
+        Ok(bdd) => match obtener_string(&bdd, &argumentos[1]) {
+            Ok(valor) => ResultadoRedis::Int(leer_bit(valor.unwrap_or_default(), offset) as isize),
+            Err(error) => error,
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+/// Cuenta los bits en 1 del string almacenado en la clave. Opcionalmente se cuentan solo los de un rango de bytes,
+/// o de bits si se indica BIT, con indices negativos contados desde el final
+fn bitcount(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    let rango = match argumentos.len() {
+        2 => None,
+        4 | 5 => match parsear_rango(&argumentos[2..]) {
+            Ok(rango) => Some(rango),
+            Err(error) => return error,
+        },
+        3 => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'bitcount' command".to_string(),
+            )
+        }
+    };
+
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let valor = match obtener_string(&bdd, &argumentos[1]) {
+        Ok(valor) => valor.unwrap_or_default(),
+        Err(error) => return error,
+    };
+    let cantidad = match rango {
+        None => valor.iter().map(|b| b.count_ones() as u64).sum(),
+        Some((inicio, fin, true)) => match normalizar_rango(inicio, fin, valor.len() as i64 * 8) {
+            Some((inicio, fin)) => (inicio..=fin).map(|i| leer_bit(valor, i) as u64).sum(),
+            None => 0,
+        },
+        Some((inicio, fin, false)) => match normalizar_rango(inicio, fin, valor.len() as i64) {
+            Some((inicio, fin)) => valor[inicio as usize..=fin as usize]
+                .iter()
+                .map(|b| b.count_ones() as u64)
+                .sum(),
+            None => 0,
+        },
+    };
+    ResultadoRedis::Int(cantidad as isize)
+}
+
+/// Devuelve la posicion del primer bit en 1 o en 0 del string almacenado en la clave, opcionalmente dentro de un
+/// rango de bytes o de bits. Si se busca un 0 sin indicar el fin del rango, el string se considera completado con ceros
+fn bitpos(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if !(3..=6).contains(&argumentos.len()) {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'bitpos' command".to_string(),
+        );
+    }
+    let buscado = match argumentos[2].as_slice() {
+        b"0" => 0,
+        b"1" => 1,
+        _ => return ResultadoRedis::Error("ERR The bit argument must be 1 or 0.".to_string()),
+    };
+    let mut limites = argumentos[3..].to_vec();
+    let fin_indicado = limites.len() > 1;
+    if limites.is_empty() {
+        limites.push(b"0".to_vec());
+    }
+    if limites.len() == 1 {
+        limites.push(b"-1".to_vec());
+    }
+    let (inicio, fin, en_bits) = match parsear_rango(&limites) {
+        Ok(rango) => rango,
+        Err(error) => return error,
+    };
+
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let valor = match obtener_string(&bdd, &argumentos[1]) {
+        Ok(Some(valor)) => valor,
+        Ok(None) => return ResultadoRedis::Int(if buscado == 1 { -1 } else { 0 }),
+        Err(error) => return error,
+    };
+    let rango_en_bits = if en_bits {
+        normalizar_rango(inicio, fin, valor.len() as i64 * 8)
+    } else {
+        normalizar_rango(inicio, fin, valor.len() as i64).map(|(i, f)| (i * 8, f * 8 + 7))
+    };
+    let (inicio, fin) = match rango_en_bits {
+        Some(rango) => rango,
+        None => return ResultadoRedis::Int(-1),
+    };
+    match (inicio..=fin).find(|&i| leer_bit(valor, i) == buscado) {
+        Some(posicion) => ResultadoRedis::Int(posicion as isize),
+        None if buscado == 0 && !fin_indicado => ResultadoRedis::Int(fin as isize + 1),
+        None => ResultadoRedis::Int(-1),
+    }
+}
+
+/// Operacion de BITOP entre los strings de origen
+enum OperacionBit {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+/// Aplica la operacion bit a bit entre los strings de las claves de origen y guarda el resultado en la clave destino.
+/// Los strings mas cortos se completan con ceros. Devuelve el largo del resultado, si es vacio se elimina el destino
+fn bitop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'bitop' command".to_string(),
+        );
+    }
+    let operacion = match argumentos[1].to_ascii_uppercase().as_slice() {
+        b"AND" => OperacionBit::And,
+        b"OR" => OperacionBit::Or,
+        b"XOR" => OperacionBit::Xor,
+        b"NOT" => OperacionBit::Not,
+        _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+    };
+    let (destino, origenes) = (&argumentos[2], &argumentos[3..]);
+    if matches!(operacion, OperacionBit::Not) && origenes.len() > 1 {
+        return ResultadoRedis::Error(
+            "ERR BITOP NOT must be called with a single source key.".to_string(),
+        );
+    }
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let mut valores = Vec::with_capacity(origenes.len());
+    for origen in origenes {
+        match obtener_string(&bdd, origen) {
+            Ok(valor) => valores.push(valor.unwrap_or_default()),
+            Err(error) => return error,
+        }
+    }
+    let largo = valores.iter().map(|v| v.len()).max().unwrap_or(0);
+    let resultado: Vec<u8> = (0..largo)
+        .map(|i| {
+            let mut bytes = valores.iter().map(|v| v.get(i).copied().unwrap_or(0));
+            let primero = bytes.next().unwrap_or(0);
+            match operacion {
+                OperacionBit::And => bytes.fold(primero, |a, b| a & b),
+                OperacionBit::Or => bytes.fold(primero, |a, b| a | b),
+                OperacionBit::Xor => bytes.fold(primero, |a, b| a ^ b),
+                OperacionBit::Not => !primero,
+            }
+        })
+        .collect();
+
+    if resultado.is_empty() {
+        if bdd.eliminar_clave(destino) > 0 {
+            bdd.notificar(ClaseEvento::Generico, "del", destino);
+        }
+    } else {
+        bdd.guardar_valor(destino.clone(), TipoRedis::Str(resultado));
+        bdd.notificar(ClaseEvento::Cadena, "set", destino);
+    }
+    ResultadoRedis::Int(largo as isize)
+}
+
+/// Que hacer cuando SET o INCRBY de BITFIELD se salen del rango del tipo del campo
+#[derive(Clone, Copy)]
+enum Desborde {
+    /// Se da la vuelta, como en la aritmetica de enteros de ancho fijo
+    Envolver,
+    /// Se queda en el minimo o el maximo del tipo
+    Saturar,
+    /// No se modifica el campo y se devuelve nil
+    Fallar,
+}
+
+/// Tipo de un campo de BITFIELD, como `i8` o `u16`
+#[derive(Clone, Copy)]
+struct TipoCampo {
+    con_signo: bool,
+    bits: u32,
+}
+
+impl TipoCampo {
+    /// Parsea el tipo, los enteros con signo pueden tener hasta 64 bits y los sin signo hasta 63
+    fn parsear(tipo: &[u8]) -> Result<Self, ResultadoRedis> {
+        let con_signo = match tipo.first().map(|c| c.to_ascii_lowercase()) {
+            Some(b'i') => Some(true),
+            Some(b'u') => Some(false),
+            _ => None,
+        };
+        let bits = parsear::<u32>(&tipo[1.min(tipo.len())..]);
+        match (con_signo, bits) {
+            (Some(true), Some(bits @ 1..=64)) | (Some(false), Some(bits @ 1..=63)) => {
+                Ok(TipoCampo { con_signo: con_signo == Some(true), bits })
+            }
+            _ => Err(ResultadoRedis::Error(
+                "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+                    .to_string(),
+            )),
+        }
+    }
+
+    fn minimo(&self) -> i128 {
+        if self.con_signo {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn maximo(&self) -> i128 {
+        if self.con_signo {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+
+    /// Ajusta el valor al rango del tipo segun la politica de desborde, o devuelve ninguno si hay que fallar
+    fn ajustar(&self, valor: i128, desborde: Desborde) -> Option<i64> {
+        if (self.minimo()..=self.maximo()).contains(&valor) {
+            return Some(valor as i64);
+        }
+        match desborde {
+            Desborde::Fallar => None,
+            Desborde::Saturar => Some(valor.clamp(self.minimo(), self.maximo()) as i64),
+            Desborde::Envolver => {
+                let modulo = 1i128 << self.bits;
+                Some(((valor - self.minimo()).rem_euclid(modulo) + self.minimo()) as i64)
+            }
+        }
+    }
+
+    /// Lee el campo que empieza en el bit indicado, con el bit mas significativo primero
+    fn leer(&self, bytes: &[u8], offset: u64) -> i64 {
+        let crudo = (0..self.bits as u64).fold(0u64, |acc, i| {
+            (acc << 1) | leer_bit(bytes, offset + i) as u64
+        });
+        if self.con_signo && self.bits < 64 && crudo >> (self.bits - 1) & 1 == 1 {
+            (crudo | (u64::MAX << self.bits)) as i64
+        } else {
+            crudo as i64
+        }
+    }
+
+    /// Escribe el valor en el campo que empieza en el bit indicado, agrandando el string si hace falta
+    fn escribir(&self, bytes: &mut Vec<u8>, offset: u64, valor: i64) {
+        for i in 0..self.bits as u64 {
+            let bit = (valor as u64 >> (self.bits as u64 - 1 - i)) & 1;
+            escribir_bit(bytes, offset + i, bit as u8);
+        }
+    }
+}
+
+/// Subcomando de BITFIELD ya validado
+enum OperacionCampo {
+    Get(TipoCampo, u64),
+    Set(TipoCampo, u64, i64, Desborde),
+    IncrBy(TipoCampo, u64, i64, Desborde),
+}
+
+/// Parsea el offset de un campo, que con el prefijo `#` se multiplica por el ancho del tipo
+fn parsear_offset_campo(offset: &[u8], tipo: &TipoCampo) -> Result<u64, ResultadoRedis> {
+    let offset = match offset.strip_prefix(b"#") {
+        Some(indice) => parsear::<u64>(indice).and_then(|i| i.checked_mul(tipo.bits as u64)),
+        None => parsear::<u64>(offset),
+    };
+    match offset {
+        Some(offset) if offset.saturating_add(tipo.bits as u64) <= BITS_MAXIMOS => Ok(offset),
+        _ => Err(error_offset()),
+    }
+}
+
+/// Parsea todos los subcomandos de BITFIELD. OVERFLOW cambia la politica de los SET e INCRBY que le siguen
+fn parsear_operaciones(argumentos: &[Vec<u8>]) -> Result<Vec<OperacionCampo>, ResultadoRedis> {
+    let error_de_sintaxis = || ResultadoRedis::Error("ERR syntax error".to_string());
+    let mut operaciones = Vec::new();
+    let mut desborde = Desborde::Envolver;
+    let mut i = 0;
+    while i < argumentos.len() {
+        let subcomando = argumentos[i].to_ascii_uppercase();
+        if subcomando == b"OVERFLOW" {
+            desborde = match argumentos.get(i + 1).map(|d| d.to_ascii_uppercase()) {
+                Some(d) if d == b"WRAP" => Desborde::Envolver,
+                Some(d) if d == b"SAT" => Desborde::Saturar,
+                Some(d) if d == b"FAIL" => Desborde::Fallar,
+                Some(_) => {
+                    return Err(ResultadoRedis::Error(
+                        "ERR Invalid OVERFLOW type specified".to_string(),
+                    ))
+                }
+                None => return Err(error_de_sintaxis()),
+            };
+            i += 2;
+            continue;
+        }
+
+        let con_valor = subcomando == b"SET" || subcomando == b"INCRBY";
+        let largo = if con_valor { 4 } else { 3 };
+        if !(con_valor || subcomando == b"GET") || i + largo > argumentos.len() {
+            return Err(error_de_sintaxis());
+        }
+        let tipo = TipoCampo::parsear(&argumentos[i + 1])?;
+        let offset = parsear_offset_campo(&argumentos[i + 2], &tipo)?;
+        let operacion = if con_valor {
+            let valor = parsear::<i64>(&argumentos[i + 3]).ok_or_else(error_entero)?;
+            if subcomando == b"SET" {
+                OperacionCampo::Set(tipo, offset, valor, desborde)
+            } else {
+                OperacionCampo::IncrBy(tipo, offset, valor, desborde)
+            }
+        } else {
+            OperacionCampo::Get(tipo, offset)
+        };
+        operaciones.push(operacion);
+        i += largo;
+    }
+    Ok(operaciones)
+}
+
+/// Trata al string almacenado en la clave como un arreglo de enteros de ancho arbitrario. Con GET se lee un campo,
+/// con SET se escribe devolviendo el valor anterior y con INCRBY se incrementa devolviendo el valor nuevo.
+/// OVERFLOW indica que hacer cuando el resultado no entra en el tipo del campo
+fn bitfield(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'bitfield' command".to_string(),
+        );
+    }
+    let clave = &argumentos[1];
+    let operaciones = match parsear_operaciones(&argumentos[2..]) {
+        Ok(operaciones) => operaciones,
+        Err(error) => return error,
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let escribe = operaciones
+        .iter()
+        .any(|operacion| !matches!(operacion, OperacionCampo::Get(..)));
+    if !escribe {
+        let valor = match obtener_string(&bdd, clave) {
+            Ok(valor) => valor.unwrap_or_default(),
+            Err(error) => return error,
+        };
+        let resultados = operaciones.iter().map(|operacion| match operacion {
+            OperacionCampo::Get(tipo, offset) => {
+                ResultadoRedis::Int(tipo.leer(valor, *offset) as isize)
+            }
+            _ => ResultadoRedis::Nil,
+        });
+        return ResultadoRedis::Vector(resultados.collect());
+    }
+
+    if let Err(error) = crear_si_no_existe(&mut bdd, clave) {
+        return error;
+    }
+    let (resultados, modificado) = bdd
+        .modificar_string(clave, |valor| aplicar_operaciones(operaciones, valor))
+        .unwrap_or_default();
+    if modificado {
+        bdd.notificar(ClaseEvento::Cadena, "setbit", clave);
+    }
+    ResultadoRedis::Vector(resultados)
+}
+
+/// Aplica las operaciones de BITFIELD en orden sobre el string. Devuelve el resultado de cada una y si alguna
+/// lo modifico
+fn aplicar_operaciones(
+    operaciones: Vec<OperacionCampo>,
+    valor: &mut Vec<u8>,
+) -> (Vec<ResultadoRedis>, bool) {
+    let mut modificado = false;
+    let mut resultados = Vec::with_capacity(operaciones.len());
+    for operacion in operaciones {
+        let resultado = match operacion {
+            OperacionCampo::Get(tipo, offset) => Some(tipo.leer(valor, offset)),
+            OperacionCampo::Set(tipo, offset, nuevo, desborde) => {
+                let anterior = tipo.leer(valor, offset);
+                tipo.ajustar(nuevo as i128, desborde).map(|nuevo| {
+                    tipo.escribir(valor, offset, nuevo);
+                    modificado = true;
+                    anterior
+                })
+            }
+            OperacionCampo::IncrBy(tipo, offset, incremento, desborde) => {
+                let anterior = tipo.leer(valor, offset) as i128;
+                tipo.ajustar(anterior + incremento as i128, desborde)
+                    .inspect(|&nuevo| {
+                        tipo.escribir(valor, offset, nuevo);
+                        modificado = true;
+                    })
+            }
+        };
+        resultados.push(match resultado {
+            Some(n) => ResultadoRedis::Int(n as isize),
+            None => ResultadoRedis::Nil,
+        });
+    }
+
+    (resultados, modificado)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ejecutar(argumentos: &[&str], bdd: &Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        Box::new(ComandoBitHandler::new(comando)).ejecutar(Arc::clone(bdd))
+    }
+
+    #[test]
+    fn setbit_agranda_el_string_y_devuelve_el_bit_anterior() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SETBIT", "clave", "7", "1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["SETBIT", "clave", "7", "0"], &bdd)
+        );
+        ejecutar(&["SETBIT", "clave", "17", "1"], &bdd);
+        assert_eq!(
+            Some(&TipoRedis::Str(vec![0, 0, 0b0100_0000])),
+            bdd.lock().unwrap().obtener_valor("clave")
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["GETBIT", "clave", "17"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["GETBIT", "clave", "1000"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR bit is not an integer or out of range".to_string()),
+            ejecutar(&["SETBIT", "clave", "1", "2"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR bit offset is not an integer or out of range".to_string()),
+            ejecutar(&["SETBIT", "clave", "4294967296", "1"], &bdd)
+        );
+    }
+
+    #[test]
+    fn bitcount_cuenta_en_rangos_de_bytes_o_de_bits() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("clave", TipoRedis::Str("foobar".into()));
+
+        assert_eq!(
+            ResultadoRedis::Int(26),
+            ejecutar(&["BITCOUNT", "clave"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            ejecutar(&["BITCOUNT", "clave", "0", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(6),
+            ejecutar(&["BITCOUNT", "clave", "1", "1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(17),
+            ejecutar(&["BITCOUNT", "clave", "5", "30", "BIT"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["BITCOUNT", "otra"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR syntax error".to_string()),
+            ejecutar(&["BITCOUNT", "clave", "0"], &bdd)
+        );
+    }
+
+    #[test]
+    fn bitpos_busca_el_primer_bit_y_completa_con_ceros_si_no_hay_fin() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("clave", TipoRedis::Str(vec![0xff, 0xf0, 0x00]));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("unos", TipoRedis::Str(vec![0xff, 0xff]));
+
+        assert_eq!(
+            ResultadoRedis::Int(12),
+            ejecutar(&["BITPOS", "clave", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(8),
+            ejecutar(&["BITPOS", "clave", "1", "1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(-1),
+            ejecutar(&["BITPOS", "clave", "1", "2", "-1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(7),
+            ejecutar(&["BITPOS", "clave", "1", "7", "15", "BIT"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(16),
+            ejecutar(&["BITPOS", "unos", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(-1),
+            ejecutar(&["BITPOS", "unos", "0", "0", "-1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["BITPOS", "otra", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(-1),
+            ejecutar(&["BITPOS", "otra", "1"], &bdd)
+        );
+    }
+
+    #[test]
+    fn bitop_opera_completando_con_ceros_los_strings_mas_cortos() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("a", TipoRedis::Str(vec![0b1100, 0xff]));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("b", TipoRedis::Str(vec![0b1010]));
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            ejecutar(&["BITOP", "AND", "d", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            Some(&TipoRedis::Str(vec![0b1000, 0])),
+            bdd.lock().unwrap().obtener_valor("d")
+        );
+        ejecutar(&["BITOP", "XOR", "d", "a", "b", "otra"], &bdd);
+        assert_eq!(
+            Some(&TipoRedis::Str(vec![0b0110, 0xff])),
+            bdd.lock().unwrap().obtener_valor("d")
+        );
+        ejecutar(&["BITOP", "NOT", "d", "b"], &bdd);
+        assert_eq!(
+            Some(&TipoRedis::Str(vec![!0b1010])),
+            bdd.lock().unwrap().obtener_valor("d")
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["BITOP", "OR", "d", "otra"], &bdd)
+        );
+        assert!(!bdd.lock().unwrap().existe_clave("d"));
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR BITOP NOT must be called with a single source key.".to_string()
+            ),
+            ejecutar(&["BITOP", "NOT", "d", "a", "b"], &bdd)
+        );
+    }
+
+    #[test]
+    fn bitfield_lee_escribe_e_incrementa_respetando_el_desborde() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(0), ResultadoRedis::Int(-1)]),
+            ejecutar(
+                &["BITFIELD", "clave", "SET", "i8", "#1", "-1", "GET", "i8", "8"],
+                &bdd
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(255), ResultadoRedis::Int(15)]),
+            ejecutar(
+                &["BITFIELD", "clave", "GET", "u8", "8", "GET", "u4", "8"],
+                &bdd
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::Int(0),
+                ResultadoRedis::Int(255),
+                ResultadoRedis::Nil,
+            ]),
+            ejecutar(
+                &[
+                    "BITFIELD", "clave", "INCRBY", "u8", "8", "1", "OVERFLOW", "SAT", "INCRBY",
+                    "u8", "8", "300", "OVERFLOW", "FAIL", "INCRBY", "u8", "8", "1"
+                ],
+                &bdd
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(-128)]),
+            ejecutar(&["BITFIELD", "clave", "INCRBY", "i8", "0", "128"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+                    .to_string()
+            ),
+            ejecutar(&["BITFIELD", "clave", "GET", "u64", "0"], &bdd)
+        );
+    }
+
+    #[test]
+    fn setbit_y_bitfield_modifican_el_string_conservando_la_expiracion() {
+        let mut base = BaseDeDatos::new();
+        let vencimiento = crate::valor::ahora_ms() + 100_000;
+        base.guardar_valor_con_vencimiento("clave", vencimiento, TipoRedis::Str(vec![0]));
+        let bdd = Arc::new(Mutex::new(base));
+
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SETBIT", "clave", "15", "1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(0)]),
+            ejecutar(&["BITFIELD", "clave", "SET", "u8", "0", "7"], &bdd)
+        );
+
+        let base = bdd.lock().unwrap();
+        assert_eq!(
+            Some(&TipoRedis::Str(vec![7, 1])),
+            base.obtener_valor("clave")
+        );
+        assert_eq!(Some(Some(vencimiento)), base.obtener_vencimiento("clave"));
+    }
+}
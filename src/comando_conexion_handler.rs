@@ -0,0 +1,189 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::binario::parsear;
+use crate::cliente::Cliente;
+use crate::comando::ComandoHandler;
+use crate::comando_info::ComandoInfo;
+use crate::comando_pubsub_handler::ComandoConCliente;
+use crate::parser::Protocolo;
+use std::sync::{Arc, Mutex};
+
+/// Manejador de los comandos que modifican el estado de la conexion del cliente
+pub struct ComandoConexionHandler {
+    cliente: Cliente,
+    comando: ComandoInfo,
+    a_ejecutar: ComandoConCliente,
+}
+
+impl ComandoConexionHandler {
+    pub fn new(comando: ComandoInfo, cliente: Cliente) -> Self {
+        let a_ejecutar = match comando.get_nombre().as_str() {
+            "RESET" => reset,
+            _ => hello,
+        };
+        ComandoConexionHandler {
+            cliente,
+            comando,
+            a_ejecutar: Box::new(a_ejecutar),
+        }
+    }
+}
+
+impl ComandoHandler for ComandoConexionHandler {
+    fn ejecutar(mut self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        (self.a_ejecutar)(&mut self.comando, self.cliente, bdd)
+    }
+}
+
+/// Se encarga de detectar si el comando corresponde a los que manejan la conexion
+pub fn es_comando_conexion(comando: &str) -> bool {
+    let comandos = ["HELLO", "RESET"];
+    comandos.contains(&comando)
+}
+
+/// Cambia la version del protocolo de la conexion, si se indica, y devuelve un mapa con los
+/// datos del servidor. La respuesta ya se codifica con la nueva version del protocolo
+fn hello(
+    comando: &mut ComandoInfo,
+    mut cliente: Cliente,
+    _bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    let protocolo = match comando.get_clave() {
+        Some(version) => match parsear::<i64>(&version) {
+            Some(2) => Protocolo::Resp2,
+            Some(3) => Protocolo::Resp3,
+            Some(_) => {
+                return ResultadoRedis::Error("NOPROTO unsupported protocol version".to_string())
+            }
+            None => {
+                return ResultadoRedis::Error(
+                    "ERR Protocol version is not an integer or out of range".to_string(),
+                )
+            }
+        },
+        None => cliente.obtener_protocolo(),
+    };
+    if let Some(opcion) = comando.get_parametro() {
+        return ResultadoRedis::Error(format!(
+            "ERR Syntax error in HELLO option '{}'",
+            String::from_utf8_lossy(&opcion)
+        ));
+    }
+
+    cliente.cambiar_protocolo(protocolo);
+    let version = match protocolo {
+        Protocolo::Resp2 => 2,
+        Protocolo::Resp3 => 3,
+    };
+    let texto = |t: &str| ResultadoRedis::BulkStr(t.into());
+    ResultadoRedis::Map(vec![
+        (texto("server"), texto("redis")),
+        (texto("version"), texto(env!("CARGO_PKG_VERSION"))),
+        (texto("proto"), ResultadoRedis::Int(version)),
+        (
+            texto("id"),
+            ResultadoRedis::Int(cliente.obtener_token() as isize),
+        ),
+        (texto("mode"), texto("standalone")),
+        (texto("role"), texto("master")),
+        (texto("modules"), ResultadoRedis::Vector(vec![])),
+    ])
+}
+
+/// Devuelve la conexion a su estado inicial: la desuscribe de todos los canales y patrones y
+/// vuelve a RESP2. La transaccion en curso la descarta quien recibe el comando, antes de encolarlo
+fn reset(
+    _comando: &mut ComandoInfo,
+    mut cliente: Cliente,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    match bdd.lock() {
+        Ok(mut bdd) => bdd.pubsub().quitar_cliente(cliente.clone()),
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+    cliente.cambiar_protocolo(Protocolo::Resp2);
+    ResultadoRedis::StrSimple("RESET".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::cliente_de_prueba;
+
+    fn ejecutar(argumentos: &[&str], cliente: &Cliente) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        let handler = Box::new(ComandoConexionHandler::new(comando, cliente.clone()));
+        handler.ejecutar(Arc::new(Mutex::new(BaseDeDatos::new())))
+    }
+
+    #[test]
+    fn hello_3_cambia_el_protocolo_de_la_conexion_y_devuelve_el_handshake() {
+        let (cliente, _conexion) = cliente_de_prueba(7);
+
+        let respuesta = ejecutar(&["HELLO", "3"], &cliente);
+        assert_eq!(Protocolo::Resp3, cliente.obtener_protocolo());
+        match respuesta {
+            ResultadoRedis::Map(pares) => {
+                assert!(pares.contains(&(
+                    ResultadoRedis::BulkStr("proto".into()),
+                    ResultadoRedis::Int(3)
+                )));
+                assert!(
+                    pares.contains(&(ResultadoRedis::BulkStr("id".into()), ResultadoRedis::Int(7)))
+                );
+            }
+            otra => panic!("se esperaba un mapa y se obtuvo {:?}", otra),
+        }
+
+        ejecutar(&["HELLO", "2"], &cliente);
+        assert_eq!(Protocolo::Resp2, cliente.obtener_protocolo());
+    }
+
+    #[test]
+    fn hello_sin_version_no_cambia_el_protocolo() {
+        let (cliente, _conexion) = cliente_de_prueba(7);
+
+        ejecutar(&["HELLO"], &cliente);
+        assert_eq!(Protocolo::Resp2, cliente.obtener_protocolo());
+    }
+
+    #[test]
+    fn hello_con_una_version_no_soportada_devuelve_error() {
+        let (cliente, _conexion) = cliente_de_prueba(7);
+
+        assert_eq!(
+            ResultadoRedis::Error("NOPROTO unsupported protocol version".to_string()),
+            ejecutar(&["HELLO", "4"], &cliente)
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR Protocol version is not an integer or out of range".to_string()
+            ),
+            ejecutar(&["HELLO", "tres"], &cliente)
+        );
+        assert_eq!(Protocolo::Resp2, cliente.obtener_protocolo());
+    }
+
+    #[test]
+    fn reset_desuscribe_al_cliente_y_vuelve_a_resp2() {
+        let (cliente, _conexion) = cliente_de_prueba(7);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["HELLO", "3"], &cliente);
+        bdd.lock()
+            .unwrap()
+            .pubsub()
+            .suscribir(b"canal".to_vec(), cliente.clone());
+
+        let comando = ComandoInfo::new(vec!["RESET"]);
+        let handler = Box::new(ComandoConexionHandler::new(comando, cliente.clone()));
+        assert_eq!(
+            ResultadoRedis::StrSimple("RESET".to_string()),
+            handler.ejecutar(Arc::clone(&bdd))
+        );
+        assert_eq!(Protocolo::Resp2, cliente.obtener_protocolo());
+        assert_eq!(0, cliente.obtener_suscripciones());
+        assert_eq!(
+            0,
+            bdd.lock().unwrap().pubsub().cantidad_suscriptores(b"canal")
+        );
+    }
+}
@@ -0,0 +1,1067 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::parsear;
+use crate::comando::{Comando, ComandoHandler};
+use crate::comando_info::ComandoInfo;
+use crate::cursor::{respuesta, Escaneo, TablaEscaneable};
+use crate::notificacion::ClaseEvento;
+use crate::parser::formatear_flotante;
+use std::sync::{Arc, Mutex};
+
+/// Manejador de comandos del tipo hash
+pub struct ComandoHashHandler {
+    comando: ComandoInfo,
+    a_ejecutar: Comando,
+}
+
+impl ComandoHashHandler {
+    pub fn new(comando: ComandoInfo) -> Self {
+        let a_ejecutar = match comando.get_nombre().as_str() {
+            "HGET" => hget,
+            "HMGET" => hmget,
+            "HDEL" => hdel,
+            "HEXISTS" => hexists,
+            "HLEN" => hlen,
+            "HKEYS" => hkeys,
+            "HVALS" => hvals,
+            "HGETALL" => hgetall,
+            "HSCAN" => hscan,
+            "HINCRBY" => hincrby,
+            "HINCRBYFLOAT" => hincrbyfloat,
+            "HSETNX" => hsetnx,
+            "HSTRLEN" => hstrlen,
+            _ => hset,
+        };
+        ComandoHashHandler {
+            comando,
+            a_ejecutar: Box::new(a_ejecutar),
+        }
+    }
+}
+
+impl ComandoHandler for ComandoHashHandler {
+    fn ejecutar(mut self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        (self.a_ejecutar)(&mut self.comando, bdd)
+    }
+}
+/// Se encarga de detectar si el comando corresponde a los implementados del tipo hash
+pub fn es_comando_hash(comando: &str) -> bool {
+    let comandos = vec![
+        "HSET",
+        "HGET",
+        "HMGET",
+        "HDEL",
+        "HEXISTS",
+        "HLEN",
+        "HKEYS",
+        "HVALS",
+        "HGETALL",
+        "HSCAN",
+        "HINCRBY",
+        "HINCRBYFLOAT",
+        "HSETNX",
+        "HSTRLEN",
+    ];
+    comandos.contains(&comando)
+}
+/// Setea los campos indicados con sus respectivos valores en el hash almacenado en la clave. Si la clave no existe, se crea un hash vacío. Retorna la cantidad de campos agregados
+fn hset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hset' command".to_string(),
+            )
+        }
+    };
+
+    let mut pares = vec![];
+    while let Some(campo) = comando.get_parametro() {
+        match comando.get_parametro() {
+            Some(valor) => pares.push((campo, valor)),
+            None => {
+                return ResultadoRedis::Error(
+                    "ERR wrong number of arguments for 'hset' command".to_string(),
+                )
+            }
+        }
+    }
+    if pares.is_empty() {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'hset' command".to_string(),
+        );
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let agregados = modificar_o_crear(&mut bdd, &clave, |hash| {
+                pares
+                    .into_iter()
+                    .map(|(campo, valor)| hash.insert(campo, valor))
+                    .filter(Option::is_none)
+                    .count()
+            });
+            match agregados {
+                Ok(agregados) => {
+                    bdd.notificar(ClaseEvento::Hash, "hset", &clave);
+                    ResultadoRedis::Int(agregados as isize)
+                }
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+fn error_tipo() -> ResultadoRedis {
+    ResultadoRedis::Error(
+        "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+/// Aplica la modificacion al hash de la clave en el lugar. Si la clave no existe arma el hash nuevo y
+/// lo guarda una sola vez con sus campos, de modo que nunca queda un hash vacio en la base de datos.
+/// Devuelve el error de tipo si la clave contiene otro tipo de valor
+fn modificar_o_crear<R>(
+    bdd: &mut BaseDeDatos,
+    clave: &[u8],
+    modificacion: impl FnOnce(&mut TablaEscaneable<Vec<u8>>) -> R,
+) -> Result<R, ResultadoRedis> {
+    match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Hash(_)) => bdd
+            .modificar_hash(clave, modificacion)
+            .ok_or_else(error_tipo),
+        None => {
+            let mut hash = TablaEscaneable::default();
+            let resultado = modificacion(&mut hash);
+            if !hash.is_empty() {
+                bdd.guardar_valor(clave, TipoRedis::Hash(hash));
+            }
+            Ok(resultado)
+        }
+        _ => Err(error_tipo()),
+    }
+}
+/// Setea el campo con el valor indicado solamente si el campo todavía no existe en el hash. Retorna 1 si el campo fue seteado y 0 en caso contrario
+fn hsetnx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, campo, valor) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(f), Some(v)) => (c, f, v),
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hsetnx' command".to_string(),
+            )
+        }
+    };
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            if let Some(TipoRedis::Hash(hash)) = bdd.obtener_valor(&clave) {
+                if hash.contains_key(&campo) {
+                    return ResultadoRedis::Int(0);
+                }
+            }
+            match modificar_o_crear(&mut bdd, &clave, |hash| hash.insert(campo, valor)) {
+                Ok(_) => {
+                    bdd.notificar(ClaseEvento::Hash, "hset", &clave);
+                    ResultadoRedis::Int(1)
+                }
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna el valor asociado al campo en el hash almacenado en la clave. Si el campo o la clave no existen se retorna nil
+fn hget(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, campo) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(f)) => (c, f),
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hget' command".to_string(),
+            )
+        }
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Hash(hash)) => match hash.get(&campo) {
+                Some(valor) => ResultadoRedis::BulkStr(valor.clone()),
+                None => ResultadoRedis::Nil,
+            },
+            None => ResultadoRedis::Nil,
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna los valores asociados a los campos indicados. Para cada campo que no existe en el hash se retorna nil
+fn hmget(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hmget' command".to_string(),
+            )
+        }
+    };
+
+    let mut campos = vec![];
+    while let Some(campo) = comando.get_parametro() {
+        campos.push(campo);
+    }
+    if campos.is_empty() {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'hmget' command".to_string(),
+        );
+    }
+
+    match bdd.lock() {
+        Ok(bdd) => {
+            let hash = match bdd.obtener_valor(&clave) {
+                Some(TipoRedis::Hash(hash)) => Some(hash),
+                None => None,
+                _ => return error_tipo(),
+            };
+            ResultadoRedis::Vector(
+                campos
+                    .iter()
+                    .map(|campo| match hash.and_then(|h| h.get(campo)) {
+                        Some(valor) => ResultadoRedis::BulkStr(valor.clone()),
+                        None => ResultadoRedis::Nil,
+                    })
+                    .collect(),
+            )
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Elimina los campos indicados del hash almacenado en la clave. Si el hash queda vacío se elimina la clave. Retorna la cantidad de campos eliminados
+fn hdel(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hdel' command".to_string(),
+            )
+        }
+    };
+
+    let mut campos = vec![];
+    while let Some(campo) = comando.get_parametro() {
+        campos.push(campo);
+    }
+    if campos.is_empty() {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'hdel' command".to_string(),
+        );
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            match bdd.obtener_valor(&clave) {
+                Some(TipoRedis::Hash(hash)) if campos.iter().any(|c| hash.contains_key(c)) => {}
+                Some(TipoRedis::Hash(_)) | None => return ResultadoRedis::Int(0),
+                _ => return error_tipo(),
+            }
+            let (eliminados, vacio) = bdd
+                .modificar_hash(&clave, |hash| {
+                    let eliminados = campos.iter().filter(|c| hash.remove(c).is_some()).count();
+                    (eliminados, hash.is_empty())
+                })
+                .unwrap_or((0, false));
+            bdd.notificar(ClaseEvento::Hash, "hdel", &clave);
+            if vacio {
+                bdd.eliminar_clave(&clave);
+                bdd.notificar(ClaseEvento::Generico, "del", &clave);
+            }
+            ResultadoRedis::Int(eliminados as isize)
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna si el campo existe en el hash almacenado en la clave
+fn hexists(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, campo) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(f)) => (c, f),
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hexists' command".to_string(),
+            )
+        }
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Hash(hash)) => {
+                ResultadoRedis::Int(if hash.contains_key(&campo) { 1 } else { 0 })
+            }
+            None => ResultadoRedis::Int(0),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna la cantidad de campos del hash almacenado en la clave
+fn hlen(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hlen' command".to_string(),
+            )
+        }
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Hash(hash)) => ResultadoRedis::Int(hash.len() as isize),
+            None => ResultadoRedis::Int(0),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna el largo del valor asociado al campo en el hash almacenado en la clave. Si el campo o la clave no existen se retorna 0
+fn hstrlen(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, campo) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(f)) => (c, f),
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hstrlen' command".to_string(),
+            )
+        }
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Hash(hash)) => match hash.get(&campo) {
+                Some(valor) => ResultadoRedis::Int(valor.len() as isize),
+                None => ResultadoRedis::Int(0),
+            },
+            None => ResultadoRedis::Int(0),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+/// Aplica la funcion de proyeccion a cada par campo-valor del hash almacenado en la clave
+fn recorrer_hash(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    proyeccion: fn(&[u8], &[u8]) -> Vec<Vec<u8>>,
+) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => {
+            return ResultadoRedis::Error(format!(
+                "ERR wrong number of arguments for '{}' command",
+                comando.get_nombre().to_lowercase()
+            ))
+        }
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::Hash(hash)) => ResultadoRedis::Vector(
+                hash.iter()
+                    .flat_map(|(campo, valor)| proyeccion(campo, valor))
+                    .map(ResultadoRedis::BulkStr)
+                    .collect(),
+            ),
+            None => ResultadoRedis::Vector(vec![]),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna todos los campos del hash almacenado en la clave
+fn hkeys(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    recorrer_hash(comando, bdd, |campo, _| vec![campo.to_vec()])
+}
+/// Retorna todos los valores del hash almacenado en la clave
+fn hvals(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    recorrer_hash(comando, bdd, |_, valor| vec![valor.to_vec()])
+}
+/// Retorna todos los campos y valores del hash almacenado en la clave como un mapa. En RESP2 cada campo es seguido por su valor
+fn hgetall(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    match recorrer_hash(comando, bdd, |campo, valor| {
+        vec![campo.to_vec(), valor.to_vec()]
+    }) {
+        ResultadoRedis::Vector(elementos) => ResultadoRedis::mapa(elementos),
+        otro => otro,
+    }
+}
+
+/// Devuelve de a partes los campos de un hash seguidos de su valor a partir de un cursor,
+/// filtrandolos con MATCH. Con NOVALUES solo devuelve los campos
+fn hscan(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = match comando.get_parametros() {
+        Some(a) if a.len() >= 2 => a,
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'hscan' command".to_string(),
+            )
+        }
+    };
+    let escaneo = match Escaneo::parsear(&argumentos[1..], &["NOVALUES"]) {
+        Ok(e) => e,
+        Err(e) => return ResultadoRedis::Error(e),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&argumentos[0]) {
+            Some(TipoRedis::Hash(hash)) => {
+                let (cursor, campos) = hash.escanear(escaneo.cursor, escaneo.cantidad);
+                let mut elementos = vec![];
+                for (campo, valor) in campos {
+                    if !escaneo.coincide(campo) {
+                        continue;
+                    }
+                    elementos.push(ResultadoRedis::BulkStr(campo.clone()));
+                    if !escaneo.sin_valores {
+                        elementos.push(ResultadoRedis::BulkStr(valor.clone()));
+                    }
+                }
+                respuesta(cursor, elementos)
+            }
+            None => respuesta(0, vec![]),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+/// Operacion numerica sobre el valor actual de un campo y el incremento recibido
+type OperacionSobreCampo = fn(&[u8], &[u8]) -> Result<(Vec<u8>, ResultadoRedis), String>;
+
+/// Dado un campo de un hash que puede ser casteable a un numero y una funcion que opere sobre el, se aplica la funcion y se almacena el resultado.
+/// La funcion devuelve el nuevo valor a almacenar junto con la respuesta a enviar, o el mensaje de error correspondiente
+fn operar_sobre_campo(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    operar: OperacionSobreCampo,
+) -> ResultadoRedis {
+    let (clave, campo, incremento) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(f), Some(i)) => (c, f, i),
+        _ => {
+            return ResultadoRedis::Error(format!(
+                "ERR wrong number of arguments for '{}' command",
+                comando.get_nombre().to_lowercase()
+            ))
+        }
+    };
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let operacion = match bdd.obtener_valor(&clave) {
+                Some(TipoRedis::Hash(hash)) => {
+                    let actual = hash.get(&campo).map_or(&b"0"[..], |v| v.as_slice());
+                    operar(actual, &incremento)
+                }
+                None => operar(b"0", &incremento),
+                _ => return error_tipo(),
+            };
+            let (nuevo, respuesta) = match operacion {
+                Ok(r) => r,
+                Err(e) => return ResultadoRedis::Error(e),
+            };
+            match modificar_o_crear(&mut bdd, &clave, |hash| hash.insert(campo, nuevo)) {
+                Ok(_) => {
+                    let evento = comando.get_nombre().to_lowercase();
+                    bdd.notificar(ClaseEvento::Hash, &evento, &clave);
+                    respuesta
+                }
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Incrementa el número almacenado en el campo del hash en el valor indicado. Si el campo no existe, se setea en 0 antes de realizar la operación
+fn hincrby(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_sobre_campo(comando, bdd, |actual, incremento| {
+        let actual = match parsear::<i64>(actual) {
+            Some(n) => n,
+            None => return Err("ERR hash value is not an integer".to_string()),
+        };
+        let incremento = match parsear::<i64>(incremento) {
+            Some(n) => n,
+            None => return Err("ERR value is not an integer or out of range".to_string()),
+        };
+        match actual.checked_add(incremento) {
+            Some(n) => Ok((n.to_string().into_bytes(), ResultadoRedis::Int(n as isize))),
+            None => Err("ERR increment or decrement would overflow".to_string()),
+        }
+    })
+}
+/// Incrementa el número de punto flotante almacenado en el campo del hash en el valor indicado. Si el campo no existe, se setea en 0 antes de realizar la operación
+fn hincrbyfloat(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_sobre_campo(comando, bdd, |actual, incremento| {
+        let actual = match parsear::<f64>(actual) {
+            Some(n) if n.is_finite() => n,
+            _ => return Err("ERR hash value is not a float".to_string()),
+        };
+        let incremento = match parsear::<f64>(incremento) {
+            Some(n) if n.is_finite() => n,
+            _ => return Err("ERR value is not a valid float".to_string()),
+        };
+        let resultado = actual + incremento;
+        if !resultado.is_finite() {
+            return Err("ERR increment would produce NaN or Infinity".to_string());
+        }
+        let resultado = formatear_flotante(resultado).into_bytes();
+        Ok((resultado.clone(), ResultadoRedis::BulkStr(resultado)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn hash_de_prueba() -> HashMap<Vec<u8>, Vec<u8>> {
+        let mut hash = HashMap::new();
+        hash.insert("nombre".into(), "Juan".into());
+        hash.insert("edad".into(), "20".into());
+        hash
+    }
+
+    #[test]
+    fn hset_cuando_la_clave_no_existe_crea_el_hash_y_devuelve_la_cantidad_de_campos_agregados() {
+        let h = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut comando = ComandoInfo::new(vec![
+            "HSET".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "Juan".to_string(),
+            "edad".to_string(),
+            "20".to_string(),
+        ]);
+
+        assert_eq!(ResultadoRedis::Int(2), hset(&mut comando, Arc::clone(&h)));
+        assert_eq!(
+            h.lock().unwrap().obtener_valor("miHash").unwrap(),
+            &TipoRedis::Hash(hash_de_prueba().into())
+        );
+    }
+
+    #[test]
+    fn las_escrituras_sobre_el_hash_notifican_sus_eventos() {
+        use crate::notificacion::Notificaciones;
+        use crate::prueba::cliente_de_prueba;
+        use std::io::Read;
+
+        let (cliente, mut conexion) = cliente_de_prueba(1);
+        let mut bdd = BaseDeDatos::new();
+        bdd.set_notificaciones(Notificaciones::desde("Khg").unwrap());
+        bdd.pubsub()
+            .suscribir(b"__keyspace@0__:miHash".to_vec(), cliente);
+        let h = Arc::new(Mutex::new(bdd));
+
+        hset(
+            &mut ComandoInfo::new(vec!["HSET", "miHash", "campo", "1"]),
+            Arc::clone(&h),
+        );
+        hincrby(
+            &mut ComandoInfo::new(vec!["HINCRBY", "miHash", "campo", "2"]),
+            Arc::clone(&h),
+        );
+        hdel(
+            &mut ComandoInfo::new(vec!["HDEL", "miHash", "campo"]),
+            Arc::clone(&h),
+        );
+
+        let esperado: String = ["hset", "hincrby", "hdel", "del"]
+            .iter()
+            .map(|evento| {
+                format!(
+                    "*3\r\n$7\r\nmessage\r\n$21\r\n__keyspace@0__:miHash\r\n${}\r\n{}\r\n",
+                    evento.len(),
+                    evento
+                )
+            })
+            .collect();
+        let mut recibido = String::new();
+        let mut buffer = [0; 512];
+        while !recibido.ends_with(&esperado) {
+            let leidos = conexion.read(&mut buffer).unwrap();
+            recibido.push_str(&String::from_utf8_lossy(&buffer[..leidos]));
+        }
+    }
+
+    struct VigilanteDeHashesVacios(Arc<Mutex<bool>>);
+
+    impl crate::observer::Observer for VigilanteDeHashesVacios {
+        fn actualizar(&self, bdd: &TablaEscaneable<crate::valor::Valor>) {
+            let hay_vacios = bdd
+                .values()
+                .any(|v| matches!(v.get(), Some(TipoRedis::Hash(hash)) if hash.is_empty()));
+            if hay_vacios {
+                *self.0.lock().unwrap() = true;
+            }
+        }
+    }
+
+    #[test]
+    fn crear_un_hash_nunca_deja_un_hash_vacio_en_la_base_de_datos() {
+        use crate::observer::Observable;
+        let vio_un_hash_vacio = Arc::new(Mutex::new(false));
+        let mut bdd = BaseDeDatos::new();
+        bdd.agregar_observador(Box::new(VigilanteDeHashesVacios(Arc::clone(
+            &vio_un_hash_vacio,
+        ))));
+        let h = Arc::new(Mutex::new(bdd));
+
+        hset(
+            &mut ComandoInfo::new(vec!["HSET", "a", "campo", "valor"]),
+            Arc::clone(&h),
+        );
+        hsetnx(
+            &mut ComandoInfo::new(vec!["HSETNX", "b", "campo", "valor"]),
+            Arc::clone(&h),
+        );
+        hincrby(
+            &mut ComandoInfo::new(vec!["HINCRBY", "c", "campo", "1"]),
+            Arc::clone(&h),
+        );
+        hincrby(
+            &mut ComandoInfo::new(vec!["HINCRBY", "d", "campo", "uno"]),
+            Arc::clone(&h),
+        );
+
+        assert!(!*vio_un_hash_vacio.lock().unwrap());
+        let bdd = h.lock().unwrap();
+        assert_eq!(
+            3,
+            ["a", "b", "c"]
+                .iter()
+                .filter(|c| bdd.obtener_valor(*c).is_some())
+                .count()
+        );
+        assert_eq!(None, bdd.obtener_valor("d"));
+    }
+
+    #[test]
+    fn hset_cuando_se_pisa_un_campo_existente_este_no_se_cuenta_como_agregado() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+        let mut comando = ComandoInfo::new(vec![
+            "HSET".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "Pedro".to_string(),
+            "apellido".to_string(),
+            "Perez".to_string(),
+        ]);
+
+        assert_eq!(ResultadoRedis::Int(1), hset(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HGET".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("Pedro".into()),
+            hget(&mut comando, h)
+        );
+    }
+
+    #[test]
+    fn hset_con_una_cantidad_impar_de_argumentos_devuelve_error() {
+        let h = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut comando = ComandoInfo::new(vec![
+            "HSET".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+        ]);
+
+        assert_eq!(
+            ResultadoRedis::Error("ERR wrong number of arguments for 'hset' command".to_string()),
+            hset(&mut comando, Arc::clone(&h))
+        );
+        assert_eq!(None, h.lock().unwrap().obtener_valor("miHash"));
+    }
+
+    #[test]
+    fn hset_sobre_una_clave_que_no_es_un_hash_devuelve_error_de_tipo() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("miHash".to_string(), TipoRedis::Str("valor".into()));
+        let mut comando = ComandoInfo::new(vec![
+            "HSET".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "Juan".to_string(),
+        ]);
+
+        assert_eq!(
+            ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ),
+            hset(&mut comando, Arc::new(Mutex::new(bdd)))
+        );
+    }
+
+    #[test]
+    fn hget_de_un_campo_o_clave_inexistente_devuelve_nil() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HGET".to_string(),
+            "miHash".to_string(),
+            "apellido".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Nil, hget(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HGET".to_string(),
+            "otroHash".to_string(),
+            "nombre".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Nil, hget(&mut comando, h));
+    }
+
+    #[test]
+    fn hmget_devuelve_los_valores_en_orden_y_nil_para_los_campos_inexistentes() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let mut comando = ComandoInfo::new(vec![
+            "HMGET".to_string(),
+            "miHash".to_string(),
+            "edad".to_string(),
+            "apellido".to_string(),
+            "nombre".to_string(),
+        ]);
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("20".into()),
+                ResultadoRedis::Nil,
+                ResultadoRedis::BulkStr("Juan".into()),
+            ]),
+            hmget(&mut comando, Arc::new(Mutex::new(bdd)))
+        );
+    }
+
+    #[test]
+    fn hdel_elimina_los_campos_y_borra_la_clave_cuando_el_hash_queda_vacio() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+        let mut comando = ComandoInfo::new(vec![
+            "HDEL".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "apellido".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(1), hdel(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HDEL".to_string(),
+            "miHash".to_string(),
+            "edad".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(1), hdel(&mut comando, Arc::clone(&h)));
+        assert!(!h.lock().unwrap().existe_clave("miHash"));
+    }
+
+    #[test]
+    fn hexists_y_hlen_responden_sobre_los_campos_del_hash() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HEXISTS".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            hexists(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec![
+            "HEXISTS".to_string(),
+            "miHash".to_string(),
+            "apellido".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            hexists(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["HLEN".to_string(), "miHash".to_string()]);
+        assert_eq!(ResultadoRedis::Int(2), hlen(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec!["HLEN".to_string(), "otroHash".to_string()]);
+        assert_eq!(ResultadoRedis::Int(0), hlen(&mut comando, h));
+    }
+
+    #[test]
+    fn hgetall_devuelve_un_mapa_de_cada_campo_a_su_valor() {
+        let mut bdd = BaseDeDatos::new();
+        let mut hash = HashMap::new();
+        hash.insert("nombre".into(), "Juan".into());
+        bdd.guardar_valor("miHash".to_string(), TipoRedis::Hash(hash.into()));
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec!["HGETALL".to_string(), "miHash".to_string()]);
+        assert_eq!(
+            ResultadoRedis::Map(vec![(
+                ResultadoRedis::BulkStr("nombre".into()),
+                ResultadoRedis::BulkStr("Juan".into()),
+            )]),
+            hgetall(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["HKEYS".to_string(), "miHash".to_string()]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("nombre".into())]),
+            hkeys(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["HVALS".to_string(), "miHash".to_string()]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("Juan".into())]),
+            hvals(&mut comando, h)
+        );
+    }
+
+    #[test]
+    fn hsetnx_solo_setea_el_campo_si_no_existia() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HSETNX".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "Pedro".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(0), hsetnx(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HSETNX".to_string(),
+            "miHash".to_string(),
+            "apellido".to_string(),
+            "Perez".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(1), hsetnx(&mut comando, Arc::clone(&h)));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HSTRLEN".to_string(),
+            "miHash".to_string(),
+            "apellido".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(5), hstrlen(&mut comando, h));
+    }
+
+    #[test]
+    fn hincrby_incrementa_el_campo_y_lo_crea_si_no_existe() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBY".to_string(),
+            "miHash".to_string(),
+            "edad".to_string(),
+            "-5".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Int(15),
+            hincrby(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBY".to_string(),
+            "miHash".to_string(),
+            "visitas".to_string(),
+            "3".to_string(),
+        ]);
+        assert_eq!(ResultadoRedis::Int(3), hincrby(&mut comando, h));
+    }
+
+    #[test]
+    fn hincrby_devuelve_error_si_el_campo_no_es_entero_o_hay_overflow() {
+        let mut bdd = BaseDeDatos::new();
+        let mut hash = hash_de_prueba();
+        hash.insert("grande".into(), i64::MAX.to_string().into_bytes());
+        bdd.guardar_valor("miHash".to_string(), TipoRedis::Hash(hash.into()));
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBY".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "1".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR hash value is not an integer".to_string()),
+            hincrby(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBY".to_string(),
+            "miHash".to_string(),
+            "grande".to_string(),
+            "1".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR increment or decrement would overflow".to_string()),
+            hincrby(&mut comando, h)
+        );
+    }
+
+    #[test]
+    fn hincrbyfloat_incrementa_el_campo_con_un_numero_de_punto_flotante() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBYFLOAT".to_string(),
+            "miHash".to_string(),
+            "edad".to_string(),
+            "0.5".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("20.5".into()),
+            hincrbyfloat(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec![
+            "HINCRBYFLOAT".to_string(),
+            "miHash".to_string(),
+            "nombre".to_string(),
+            "0.5".to_string(),
+        ]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR hash value is not a float".to_string()),
+            hincrbyfloat(&mut comando, h)
+        );
+    }
+
+    #[test]
+    fn hscan_devuelve_los_campos_con_sus_valores_o_solo_los_campos_con_novalues() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor(
+            "miHash".to_string(),
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec!["HSCAN", "miHash", "0", "MATCH", "n*"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("0".into()),
+                ResultadoRedis::Vector(vec![
+                    ResultadoRedis::BulkStr("nombre".into()),
+                    ResultadoRedis::BulkStr("Juan".into()),
+                ]),
+            ]),
+            hscan(&mut comando, Arc::clone(&h))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["HSCAN", "miHash", "0", "NOVALUES", "COUNT", "1"]);
+        let campos = match hscan(&mut comando, Arc::clone(&h)) {
+            ResultadoRedis::Vector(mut respuesta) => respuesta.pop(),
+            otro => panic!("respuesta invalida: {:?}", otro),
+        };
+        assert!(matches!(campos, Some(ResultadoRedis::Vector(c)) if c.len() == 1));
+
+        let mut comando = ComandoInfo::new(vec!["HSCAN", "miHash", "0", "TYPE", "hash"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR syntax error".to_string()),
+            hscan(&mut comando, h)
+        );
+    }
+
+    #[test]
+    fn las_escrituras_conservan_la_expiracion_del_hash() {
+        let mut bdd = BaseDeDatos::new();
+        let vencimiento = crate::valor::ahora_ms() + 100_000;
+        bdd.guardar_valor_con_vencimiento(
+            "miHash",
+            vencimiento,
+            TipoRedis::Hash(hash_de_prueba().into()),
+        );
+        let h = Arc::new(Mutex::new(bdd));
+
+        let mut comando = ComandoInfo::new(vec!["HSET", "miHash", "ciudad", "Rosario"]);
+        assert_eq!(ResultadoRedis::Int(1), hset(&mut comando, Arc::clone(&h)));
+        let mut comando = ComandoInfo::new(vec!["HINCRBY", "miHash", "edad", "1"]);
+        assert_eq!(
+            ResultadoRedis::Int(21),
+            hincrby(&mut comando, Arc::clone(&h))
+        );
+        let mut comando = ComandoInfo::new(vec!["HDEL", "miHash", "nombre"]);
+        assert_eq!(ResultadoRedis::Int(1), hdel(&mut comando, Arc::clone(&h)));
+
+        assert!(h
+            .lock()
+            .unwrap()
+            .obtener_vencimiento("miHash")
+            .flatten()
+            .is_some());
+    }
+
+    #[test]
+    fn hdel_sin_campos_devuelve_error_de_argumentos() {
+        let h = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut comando = ComandoInfo::new(vec!["HDEL", "miHash"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR wrong number of arguments for 'hdel' command".to_string()),
+            hdel(&mut comando, h)
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::comando::{Comando, ComandoHandler};
+use crate::comando_info::ComandoInfo;
+use crate::hyperloglog::HyperLogLog;
+use crate::notificacion::ClaseEvento;
+use std::sync::{Arc, Mutex};
+
+/// Manejador de los comandos de HyperLogLog, que se guardan como valores de tipo string
+pub struct ComandoHllHandler {
+    comando: ComandoInfo,
+    a_ejecutar: Comando,
+}
+
+impl ComandoHllHandler {
+    pub fn new(comando: ComandoInfo) -> Self {
+        let a_ejecutar = match comando.get_nombre().as_str() {
+            "PFCOUNT" => pfcount,
+            "PFMERGE" => pfmerge,
+            _ => pfadd,
+        };
+        ComandoHllHandler {
+            comando,
+            a_ejecutar: Box::new(a_ejecutar),
+        }
+    }
+}
+
+impl ComandoHandler for ComandoHllHandler {
+    fn ejecutar(mut self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        (self.a_ejecutar)(&mut self.comando, bdd)
+    }
+}
+
+/// Se encarga de detectar si el comando corresponde a los de HyperLogLog
+pub fn es_comando_hll(comando: &str) -> bool {
+    let comandos = ["PFADD", "PFCOUNT", "PFMERGE"];
+    comandos.contains(&comando)
+}
+
+/// Obtiene el HyperLogLog almacenado en la clave, o ninguno si no existe. Devuelve error si la clave
+/// no contiene un string o si el string no es un HyperLogLog valido
+fn obtener_hll(bdd: &BaseDeDatos, clave: &[u8]) -> Result<Option<HyperLogLog>, ResultadoRedis> {
+    match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Str(valor)) => match HyperLogLog::desde_bytes(valor) {
+            Some(hll) => Ok(Some(hll)),
+            None => Err(ResultadoRedis::Error(
+                "WRONGTYPE Key is not a valid HyperLogLog string value.".to_string(),
+            )),
+        },
+        None => Ok(None),
+        _ => Err(ResultadoRedis::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+        )),
+    }
+}
+
+/// Agrega los elementos al HyperLogLog de la clave, creandolo si no existe. Devuelve 1 si cambio la estimacion
+/// o se creo la clave y 0 si no
+fn pfadd(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'pfadd' command".to_string(),
+        );
+    }
+    let clave = &argumentos[1];
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let (mut hll, mut modificado) = match obtener_hll(&bdd, clave) {
+        Ok(Some(hll)) => (hll, false),
+        Ok(None) => (HyperLogLog::new(), true),
+        Err(error) => return error,
+    };
+    for elemento in &argumentos[2..] {
+        modificado |= hll.agregar(elemento);
+    }
+    if modificado {
+        bdd.reemplazar_valor(clave.clone(), TipoRedis::Str(hll.a_bytes()));
+        bdd.notificar(ClaseEvento::Cadena, "pfadd", clave);
+    }
+    ResultadoRedis::Int(modificado as isize)
+}
+
+/// Devuelve la cantidad estimada de elementos distintos agregados al HyperLogLog de la clave. Con varias claves
+/// estima la cantidad de elementos de la union de todas, sin modificarlas
+fn pfcount(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'pfcount' command".to_string(),
+        );
+    }
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let mut union = HyperLogLog::new();
+    for clave in &argumentos[1..] {
+        match obtener_hll(&bdd, clave) {
+            Ok(Some(hll)) => union.fusionar(&hll),
+            Ok(None) => (),
+            Err(error) => return error,
+        }
+    }
+    ResultadoRedis::Int(union.contar() as isize)
+}
+
+/// Combina los HyperLogLog de las claves de origen en la clave destino, incluyendo el que ya tuviera el destino
+fn pfmerge(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'pfmerge' command".to_string(),
+        );
+    }
+    let destino = &argumentos[1];
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let mut union = HyperLogLog::new();
+    for clave in &argumentos[1..] {
+        match obtener_hll(&bdd, clave) {
+            Ok(Some(hll)) => union.fusionar(&hll),
+            Ok(None) => (),
+            Err(error) => return error,
+        }
+    }
+    union.densificar();
+    bdd.reemplazar_valor(destino.clone(), TipoRedis::Str(union.a_bytes()));
+    bdd.notificar(ClaseEvento::Cadena, "pfadd", destino);
+    ResultadoRedis::StrSimple("OK".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ejecutar(argumentos: &[&str], bdd: &Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        Box::new(ComandoHllHandler::new(comando)).ejecutar(Arc::clone(bdd))
+    }
+
+    #[test]
+    fn pfadd_indica_si_cambio_la_estimacion_y_pfcount_la_devuelve() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["PFADD", "visitas", "a", "b", "c", "d"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["PFADD", "visitas", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            ejecutar(&["PFCOUNT", "visitas"], &bdd)
+        );
+        assert_eq!(ResultadoRedis::Int(1), ejecutar(&["PFADD", "vacio"], &bdd));
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["PFCOUNT", "vacio", "otra"], &bdd)
+        );
+    }
+
+    #[test]
+    fn pfmerge_y_pfcount_con_varias_claves_estiman_la_union() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["PFADD", "lunes", "a", "b", "c"], &bdd);
+        ejecutar(&["PFADD", "martes", "c", "d"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            ejecutar(&["PFCOUNT", "lunes", "martes"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            ejecutar(&["PFMERGE", "semana", "lunes", "martes"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            ejecutar(&["PFCOUNT", "semana"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(3),
+            ejecutar(&["PFCOUNT", "lunes"], &bdd)
+        );
+    }
+
+    #[test]
+    fn los_strings_que_no_son_hyperloglog_devuelven_error() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("texto", TipoRedis::Str("hola".into()));
+
+        assert_eq!(
+            ResultadoRedis::Error(
+                "WRONGTYPE Key is not a valid HyperLogLog string value.".to_string()
+            ),
+            ejecutar(&["PFADD", "texto", "a"], &bdd)
+        );
+    }
+
+    #[test]
+    fn pfcount_acepta_strings_densos_con_registros_fuera_de_rango() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut hll = HyperLogLog::new();
+        hll.densificar();
+        let mut denso = hll.a_bytes();
+        // El primer registro ocupa los 6 bits bajos del primer byte despues de la cabecera
+        denso[16] |= 0x3f;
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("hll", TipoRedis::Str(denso));
+
+        assert!(matches!(
+            ejecutar(&["PFCOUNT", "hll"], &bdd),
+            ResultadoRedis::Int(_)
+        ));
+    }
+}
@@ -67,7 +67,7 @@ mod tests {
 
         assert_eq!("GET".to_string(), comando_http.get_metodo());
         assert_eq!(
-            Some("clave".to_string()),
+            Some(b"clave".to_vec()),
             comando_http.get_comando().unwrap().get_clave()
         );
     }
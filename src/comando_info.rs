@@ -4,7 +4,7 @@ use std::option::Option;
 /// Estructura que encapsula los parametros necesarios para ejecutar un comando
 pub struct ComandoInfo {
     nombre: String,
-    parametros: Vec<String>,
+    parametros: Vec<Vec<u8>>,
     index: usize,
 }
 
@@ -13,17 +13,19 @@ impl ComandoInfo {
     ///
     /// # Argumentos
     ///
-    /// * `comando` - comando redis parseado
-    pub fn new(mut comando_parseado: Vec<String>) -> Self {
+    /// * `comando` - comando redis parseado, cada argumento es una cadena binaria
+    pub fn new<T: Into<Vec<u8>>>(comando_parseado: Vec<T>) -> Self {
+        let mut comando_parseado: Vec<Vec<u8>> =
+            comando_parseado.into_iter().map(|a| a.into()).collect();
         if comando_parseado.len() == 1 {
             return ComandoInfo {
-                nombre: comando_parseado[0].to_uppercase(),
+                nombre: String::from_utf8_lossy(&comando_parseado[0]).to_uppercase(),
                 parametros: vec![],
                 index: 0,
             };
         }
 
-        let nombre = comando_parseado[0].to_uppercase();
+        let nombre = String::from_utf8_lossy(&comando_parseado[0]).to_uppercase();
         comando_parseado.remove(0);
         ComandoInfo {
             nombre,
@@ -38,15 +40,12 @@ impl ComandoInfo {
     }
 
     /// Clave a la que afecta el comando
-    pub fn get_clave(&mut self) -> Option<String> {
+    pub fn get_clave(&mut self) -> Option<Vec<u8>> {
         self.index = 1;
-        self.parametros
-            .get(0)
-            .as_ref()
-            .map(|clave| clave.to_string())
+        self.parametros.get(0).cloned()
     }
     /// Devuelve una lista con todos los parametros del comando
-    pub fn get_parametros(&self) -> Option<Vec<String>> {
+    pub fn get_parametros(&self) -> Option<Vec<Vec<u8>>> {
         if !self.parametros.is_empty() {
             return Some(self.parametros.clone());
         }
@@ -65,21 +64,27 @@ impl ComandoInfo {
     /// > 1
     /// ...
     /// ```
-    pub fn get_parametro(&mut self) -> Option<String> {
+    pub fn get_parametro(&mut self) -> Option<Vec<u8>> {
         if self.index < self.parametros.len() {
             let a_devolver = &self.parametros[self.index];
             self.index += 1;
-            return Some(a_devolver.to_string());
+            return Some(a_devolver.clone());
         };
         None
     }
+    /// Devuelve el nombre y los parametros del comando tal como fue recibido
+    pub fn argumentos(&self) -> Vec<Vec<u8>> {
+        let mut argumentos = vec![self.nombre.clone().into_bytes()];
+        argumentos.extend(self.parametros.iter().cloned());
+        argumentos
+    }
     /// Devuelve un string con una representacion visual del comando
     pub fn descripcion(&self) -> String {
         let mut descripcion = self.nombre.clone();
 
         for param in self.parametros.iter() {
             descripcion += " ";
-            descripcion += param;
+            descripcion += &String::from_utf8_lossy(param);
         }
         descripcion
     }
@@ -103,12 +108,12 @@ mod tests {
 
         let mut comando_info = ComandoInfo::new(parametros);
 
-        assert_eq!(Some("clave".to_string()), comando_info.get_clave());
+        assert_eq!(Some(b"clave".to_vec()), comando_info.get_clave());
 
-        assert_eq!(Some("arg1".to_string()), comando_info.get_parametro());
-        assert_eq!(Some("arg2".to_string()), comando_info.get_parametro());
-        assert_eq!(Some("arg3".to_string()), comando_info.get_parametro());
-        assert_eq!(Some("arg4".to_string()), comando_info.get_parametro());
+        assert_eq!(Some(b"arg1".to_vec()), comando_info.get_parametro());
+        assert_eq!(Some(b"arg2".to_vec()), comando_info.get_parametro());
+        assert_eq!(Some(b"arg3".to_vec()), comando_info.get_parametro());
+        assert_eq!(Some(b"arg4".to_vec()), comando_info.get_parametro());
         assert_eq!(None, comando_info.get_parametro());
     }
 
@@ -144,11 +149,11 @@ mod tests {
 
         assert_eq!(
             Some(vec![
-                "clave1".to_string(),
-                "clave2".to_string(),
-                "clave3".to_string(),
-                "clave4".to_string(),
-                "clave5".to_string(),
+                b"clave1".to_vec(),
+                b"clave2".to_vec(),
+                b"clave3".to_vec(),
+                b"clave4".to_vec(),
+                b"clave5".to_vec(),
             ]),
             comando_info.get_parametros()
         );
@@ -1,9 +1,12 @@
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::parsear;
 use crate::comando::{Comando, ComandoHandler};
 use crate::comando_info::ComandoInfo;
+use crate::cursor::{respuesta, Escaneo};
+use crate::notificacion::ClaseEvento;
+use crate::valor::ahora_ms;
 use std::iter::FromIterator;
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
 /// Manejador de comando del tipo key
 pub struct ComandoKeyHandler {
     comando: ComandoInfo,
@@ -18,11 +21,17 @@ impl ComandoKeyHandler {
             "EXISTS" => exists,
             "RENAME" => rename,
             "EXPIRE" => expire,
+            "PEXPIRE" => pexpire,
             "EXPIREAT" => expireat,
+            "PEXPIREAT" => pexpireat,
             "PERSIST" => persist,
             "TTL" => ttl,
+            "PTTL" => pttl,
+            "EXPIRETIME" => expiretime,
+            "PEXPIRETIME" => pexpiretime,
             "TOUCH" => touch,
             "KEYS" => keys,
+            "SCAN" => scan,
             "SORT" => sort,
             _ => tipo,
         };
@@ -41,8 +50,24 @@ impl ComandoHandler for ComandoKeyHandler {
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo key
 pub fn es_comando_key(comando: &str) -> bool {
     let comandos = vec![
-        "COPY", "DEL", "EXISTS", "RENAME", "EXPIRE", "EXPIREAT", "PERSIST", "TTL", "TOUCH", "KEYS",
-        "SORT", "TYPE",
+        "COPY",
+        "DEL",
+        "EXISTS",
+        "RENAME",
+        "EXPIRE",
+        "PEXPIRE",
+        "EXPIREAT",
+        "PEXPIREAT",
+        "PERSIST",
+        "TTL",
+        "PTTL",
+        "EXPIRETIME",
+        "PEXPIRETIME",
+        "TOUCH",
+        "KEYS",
+        "SCAN",
+        "SORT",
+        "TYPE",
     ];
     comandos.iter().any(|&c| c == comando)
 }
@@ -68,7 +93,10 @@ fn copy(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
     };
     match bdd.lock() {
         Ok(mut bdd) => match bdd.copiar_valor(&clave, &parametro) {
-            Some(_) => ResultadoRedis::Int(1),
+            Some(_) => {
+                bdd.notificar(ClaseEvento::Generico, "copy_to", &parametro);
+                ResultadoRedis::Int(1)
+            }
             None => ResultadoRedis::Int(0),
         },
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
@@ -84,18 +112,30 @@ fn rename(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
             )
         }
     };
-    let clon = Arc::clone(&bdd);
-    match copy(comando, bdd) {
-        ResultadoRedis::Error(_) => ResultadoRedis::Error("ERR no such key".to_string()),
-        _ => {
-            let vector = vec!["rename".to_string(), clave];
-            let mut comando = ComandoInfo::new(vector);
-            del(&mut comando, clon);
-            ResultadoRedis::StrSimple("Ok".to_string())
+    let destino = match comando.get_parametro() {
+        Some(p) => p,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'rename' command".to_string(),
+            )
         }
+    };
+    match bdd.lock() {
+        Ok(mut bdd) => match bdd.copiar_valor(&clave, &destino) {
+            Some(_) => {
+                if clave != destino {
+                    bdd.eliminar_clave(&clave);
+                }
+                bdd.notificar(ClaseEvento::Generico, "rename_from", &clave);
+                bdd.notificar(ClaseEvento::Generico, "rename_to", &destino);
+                ResultadoRedis::StrSimple("Ok".to_string())
+            }
+            None => ResultadoRedis::Error("ERR no such key".to_string()),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
-/// Retorna un string que representa el tipo de valor almacenado en una clave. Los tipos que puede retornar son: string, list, set (no consideramos los tipos de datos que no se implementan en el proyecto)
+/// Retorna un string que representa el tipo de valor almacenado en una clave. Los tipos que puede retornar son: string, list, set, hash, zset (no consideramos los tipos de datos que no se implementan en el proyecto)
 fn tipo(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let clave = match comando.get_clave() {
         Some(c) => c,
@@ -107,19 +147,28 @@ fn tipo(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
     };
     match bdd.lock() {
         Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Str(_)) => ResultadoRedis::BulkStr("string".to_string()),
-            Some(TipoRedis::Lista(_)) => ResultadoRedis::BulkStr("lista".to_string()),
-            Some(TipoRedis::Set(_)) => ResultadoRedis::BulkStr("set".to_string()),
-            _ => ResultadoRedis::BulkStr("none".to_string()),
+            Some(valor) => ResultadoRedis::BulkStr(nombre_tipo(valor).into()),
+            None => ResultadoRedis::BulkStr(b"none".to_vec()),
         },
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
 
+/// Nombre con el que TYPE y SCAN identifican el tipo de un valor
+fn nombre_tipo(valor: &TipoRedis) -> &'static str {
+    match valor {
+        TipoRedis::Str(_) => "string",
+        TipoRedis::Lista(_) => "lista",
+        TipoRedis::Set(_) => "set",
+        TipoRedis::Hash(_) => "hash",
+        TipoRedis::ZSet(_) => "zset",
+    }
+}
+
 fn recorrer_y_ejecutar(
     comando: &mut ComandoInfo,
     base_de_datos: Arc<Mutex<BaseDeDatos>>,
-    funcion: Box<dyn Fn(MutexGuard<BaseDeDatos>, &str)>,
+    funcion: Box<dyn Fn(MutexGuard<BaseDeDatos>, &[u8])>,
 ) -> ResultadoRedis {
     let mut claves_eliminadas = 0;
 
@@ -144,6 +193,7 @@ fn del(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedi
         bdd,
         Box::new(move |mut bdd, clave| {
             bdd.eliminar_clave(clave);
+            bdd.notificar(ClaseEvento::Generico, "del", clave);
         }),
     )
 }
@@ -153,77 +203,71 @@ fn exists(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
 }
 /// Configura un tiempo de expiración sobre una clave (la clave se dice que es volátil). Luego de ese tiempo de expiración, la clave es automáticamente eliminada
 fn expire(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
-    let clave = match comando.get_clave() {
-        Some(c) => c,
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'expire' command".to_string(),
-            )
-        }
-    };
-
-    let parametro: u64 = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(t) => t,
-            Err(_) => {
-                return ResultadoRedis::Error(
-                    "ERR value is not an integer or out of range".to_string(),
-                )
-            }
-        },
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'expire' command".to_string(),
-            )
-        }
-    };
-    match bdd.lock() {
-        Ok(mut bdd) => {
-            ResultadoRedis::Int(bdd.actualizar_valor_con_expiracion(clave, parametro) as isize)
-        }
-        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    }
+    fijar_vencimiento(comando, bdd, |segundos| {
+        segundos.checked_mul(1000)?.checked_add(ahora_ms() as i64)
+    })
+}
+/// Igual que EXPIRE pero el tiempo de expiración se indica en milisegundos
+fn pexpire(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    fijar_vencimiento(comando, bdd, |milisegundos| {
+        milisegundos.checked_add(ahora_ms() as i64)
+    })
 }
 /// Tiene el mismo efecto que EXPIRE, pero en lugar de indicar el número de segundos que representa el TTL (time to live), toma el tiempo absoluto en el timestamp de Unix (segundos desde el 1ro de enero de 1970)
 fn expireat(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
-    let clave = match comando.get_clave() {
-        Some(c) => c,
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'expireat' command".to_string(),
-            )
+    fijar_vencimiento(comando, bdd, |segundos| segundos.checked_mul(1000))
+}
+/// Igual que EXPIREAT pero el timestamp de Unix se indica en milisegundos
+fn pexpireat(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    fijar_vencimiento(comando, bdd, Some)
+}
+
+/// Fija el momento en que expira la clave a partir del parametro del comando, que `vencimiento`
+/// convierte en milisegundos desde el epoch de Unix. Si ese momento ya paso la clave se elimina
+fn fijar_vencimiento(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    vencimiento: fn(i64) -> Option<i64>,
+) -> ResultadoRedis {
+    let nombre = comando.get_nombre().to_lowercase();
+    let (clave, parametro) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(p)) => (c, p),
+        _ => {
+            return ResultadoRedis::Error(format!(
+                "ERR wrong number of arguments for '{}' command",
+                nombre
+            ))
         }
     };
-
-    let parametro: u64 = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(t) => t,
-            Err(_) => {
-                return ResultadoRedis::Error(
-                    "ERR value is not an integer or out of range".to_string(),
-                )
+    let vencimiento = match parsear::<i64>(&parametro) {
+        Some(t) => match vencimiento(t) {
+            Some(v) => v,
+            None => {
+                return ResultadoRedis::Error(format!(
+                    "ERR invalid expire time in '{}' command",
+                    nombre
+                ))
             }
         },
         None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'expireat' command".to_string(),
-            )
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
         }
     };
 
-    let tiempo_desde_epoch = UNIX_EPOCH + Duration::from_secs(parametro);
-    let tiempo_a_esperar = match tiempo_desde_epoch.duration_since(SystemTime::now()) {
-        Ok(d) => d,
-        Err(_) => {
-            return ResultadoRedis::Error(
-                "TimeError the time since epoch has already happened".to_string(),
-            )
-        }
-    };
     match bdd.lock() {
-        Ok(mut bdd) => ResultadoRedis::Int(
-            bdd.actualizar_valor_con_expiracion(clave, tiempo_a_esperar.as_secs()) as isize,
-        ),
+        Ok(mut bdd) => {
+            if !bdd.existe_clave(&clave) {
+                return ResultadoRedis::Int(0);
+            }
+            if vencimiento <= ahora_ms() as i64 {
+                bdd.eliminar_clave(&clave);
+                bdd.notificar(ClaseEvento::Generico, "del", &clave);
+            } else {
+                bdd.actualizar_vencimiento(&clave, vencimiento as u64);
+                bdd.notificar(ClaseEvento::Generico, "expire", &clave);
+            }
+            ResultadoRedis::Int(1)
+        }
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
@@ -238,23 +282,67 @@ fn persist(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultado
         }
     };
     match bdd.lock() {
-        Ok(mut bdd) => ResultadoRedis::Int(bdd.actualizar_valor_sin_expiracion(clave) as isize),
+        Ok(mut bdd) => {
+            let actualizada = bdd.actualizar_valor_sin_expiracion(clave.clone());
+            if actualizada > 0 {
+                bdd.notificar(ClaseEvento::Generico, "persist", &clave);
+            }
+            ResultadoRedis::Int(actualizada as isize)
+        }
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
 /// Retorna el tiempo que le queda a una clave para que se cumpla su timeout. Permite a un cliente Redis conocer cuántos segundos le quedan a una clave como parte del dataset
 fn ttl(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    responder_vencimiento(comando, bdd, true, false)
+}
+/// Igual que TTL pero el tiempo restante se devuelve en milisegundos
+fn pttl(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    responder_vencimiento(comando, bdd, true, true)
+}
+/// Retorna el momento en que expira la clave como timestamp de Unix en segundos
+fn expiretime(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    responder_vencimiento(comando, bdd, false, false)
+}
+/// Retorna el momento en que expira la clave como timestamp de Unix en milisegundos
+fn pexpiretime(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    responder_vencimiento(comando, bdd, false, true)
+}
+
+/// Responde el vencimiento de la clave, como tiempo restante o como momento absoluto, en
+/// segundos o milisegundos. Devuelve -2 si la clave no existe y -1 si no expira
+fn responder_vencimiento(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    restante: bool,
+    en_milisegundos: bool,
+) -> ResultadoRedis {
     let clave = match comando.get_clave() {
         Some(c) => c,
         None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'ttl' command".to_string(),
-            )
+            return ResultadoRedis::Error(format!(
+                "ERR wrong number of arguments for '{}' command",
+                comando.get_nombre().to_lowercase()
+            ))
         }
     };
-    match bdd.lock() {
-        Ok(bdd) => ResultadoRedis::Int(bdd.obtener_expiracion(&clave) as isize),
-        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    let vencimiento = match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_vencimiento(&clave) {
+            Some(Some(v)) => v,
+            Some(None) => return ResultadoRedis::Int(-1),
+            None => return ResultadoRedis::Int(-2),
+        },
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let milisegundos = if restante {
+        vencimiento.saturating_sub(ahora_ms())
+    } else {
+        vencimiento
+    };
+    if en_milisegundos {
+        ResultadoRedis::Int(milisegundos as isize)
+    } else {
+        ResultadoRedis::Int(((milisegundos + 500) / 1000) as isize)
     }
 }
 /// Actualiza el valor de último acceso a la clave
@@ -281,7 +369,7 @@ fn keys(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
         }
     };
 
-    let vector: Vec<String> = match bdd.lock() {
+    let vector: Vec<Vec<u8>> = match bdd.lock() {
         Ok(bdd) => bdd.claves(&re),
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
@@ -289,16 +377,48 @@ fn keys(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
     ResultadoRedis::Vector(
         vector
             .iter()
-            .map(|v| ResultadoRedis::BulkStr(v.to_string()))
+            .map(|v| ResultadoRedis::BulkStr(v.clone()))
             .collect(),
     )
 }
 
-fn es_parseable(num: &str) -> bool {
-    num.parse::<i32>().is_ok()
+/// Devuelve de a partes las claves de la base a partir de un cursor, filtrandolas por patron
+/// con MATCH y por tipo con TYPE. Cada llamada devuelve el cursor con el que seguir, o 0 al terminar
+fn scan(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = match comando.get_parametros() {
+        Some(a) => a,
+        None => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'scan' command".to_string(),
+            )
+        }
+    };
+    let escaneo = match Escaneo::parsear(&argumentos, &["TYPE"]) {
+        Ok(e) => e,
+        Err(e) => return ResultadoRedis::Error(e),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => {
+            let (cursor, claves) = bdd.escanear(escaneo.cursor, escaneo.cantidad);
+            let claves = claves
+                .into_iter()
+                .filter(|(clave, valor)| {
+                    escaneo.coincide(clave) && escaneo.es_del_tipo(nombre_tipo(valor))
+                })
+                .map(|(clave, _)| ResultadoRedis::BulkStr(clave.to_vec()))
+                .collect();
+            respuesta(cursor, claves)
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+fn es_parseable(num: &[u8]) -> bool {
+    parsear::<i32>(num).is_some()
 }
 
-fn tiene_solo_valores_numericos(valores: Vec<String>) -> bool {
+fn tiene_solo_valores_numericos(valores: Vec<Vec<u8>>) -> bool {
     for valor in valores.iter() {
         if !es_parseable(valor) {
             return false;
@@ -307,20 +427,14 @@ fn tiene_solo_valores_numericos(valores: Vec<String>) -> bool {
     true
 }
 
-fn selecionar_rango(parametros: Vec<String>, valores: Vec<String>) -> Option<Vec<String>> {
+fn selecionar_rango(parametros: Vec<Vec<u8>>, valores: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
     let rango = parametros
         .into_iter()
         .filter(|i| es_parseable(i))
         .collect::<Vec<_>>();
 
-    let offset = match rango[0].parse::<i32>() {
-        Ok(num) => num,
-        Err(_) => return None,
-    };
-    let count = match rango[1].parse::<i32>() {
-        Ok(num) => num,
-        Err(_) => return None,
-    };
+    let offset = parsear::<i32>(rango.first()?)?;
+    let count = parsear::<i32>(rango.get(1)?)?;
     let mut index_min = 0;
     let mut index_max = valores.len() - 1;
 
@@ -339,11 +453,11 @@ fn selecionar_rango(parametros: Vec<String>, valores: Vec<String>) -> Option<Vec
 }
 /// Se encarga de ordenar la lista/set en funcion de los mismos elementos
 fn sort_elemento_con_pesos_interno(
-    mut valores: Vec<String>,
-    parametros: Vec<String>,
+    mut valores: Vec<Vec<u8>>,
+    parametros: Vec<Vec<u8>>,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    if !parametros.contains(&"ALPHA".to_string()) {
+    if !parametros.contains(&b"ALPHA".to_vec()) {
         match tiene_solo_valores_numericos(valores.clone()) {
             true => {}
             false => {
@@ -357,147 +471,152 @@ fn sort_elemento_con_pesos_interno(
     sort_configuracion_lista_ordenada(parametros, valores, bdd)
 }
 
-fn obetener_tupla_valor_peso(
-    valores: Vec<String>,
-    pesos: Vec<String>,
-    bdd: Arc<Mutex<BaseDeDatos>>,
-) -> Option<Vec<(String, String)>> {
-    let mut tuplas: Vec<(String, String)> = vec![];
-    for valor in &valores {
-        let mut split_valor = valor.rsplit('_');
-        let indice_valor = split_valor.next();
-
-        for peso in &pesos {
-            let mut indice_peso = peso.rsplit('_');
-            let indice_peso = indice_peso.next();
-
-            if indice_peso == indice_valor {
-                let peso = match bdd.lock() {
-                    Ok(bdd) => match bdd.obtener_valor(peso) {
-                        Some(TipoRedis::Str(peso)) => peso.clone(),
-                        _ => return None,
-                    },
-                    Err(_) => return None,
-                };
-                tuplas.push((valor.clone(), peso.to_string()))
-            }
-        }
+/// Devuelve el argumento que sigue a la ultima aparicion de la opcion indicada
+fn argumento_de<'a>(parametros: &'a [Vec<u8>], opcion: &[u8]) -> Option<&'a Vec<u8>> {
+    let posicion = parametros.iter().rposition(|p| p == opcion)?;
+    parametros.get(posicion + 1)
+}
+
+/// Busca el valor al que apunta un patron de BY o GET para el elemento, como en Redis: el elemento
+/// reemplaza al primer `*` del patron y, si despues sigue `->campo`, se lee ese campo del hash de la
+/// clave resultante. El patron `#` devuelve el propio elemento
+fn buscar_por_patron(bdd: &BaseDeDatos, patron: &[u8], elemento: &[u8]) -> Option<Vec<u8>> {
+    if patron == b"#" {
+        return Some(elemento.to_vec());
+    }
+    let asterisco = patron.iter().position(|&c| c == b'*')?;
+    let (patron_clave, campo) = match patron[asterisco..].windows(2).position(|v| v == b"->") {
+        Some(flecha) => (
+            &patron[..asterisco + flecha],
+            Some(&patron[asterisco + flecha + 2..]),
+        ),
+        None => (patron, None),
+    };
+    let mut clave = patron_clave[..asterisco].to_vec();
+    clave.extend_from_slice(elemento);
+    clave.extend_from_slice(&patron_clave[asterisco + 1..]);
+    match (bdd.obtener_valor(&clave)?, campo) {
+        (TipoRedis::Str(valor), None) => Some(valor.clone()),
+        (TipoRedis::Hash(hash), Some(campo)) => hash.get(campo).cloned(),
+        _ => None,
     }
-    Some(tuplas)
 }
-/// Se encarga de los hiperparametros del comando sort (DESC/LIMIT/STORE)
+
+/// Se encarga de los hiperparametros del comando sort (DESC/LIMIT/GET/STORE)
 fn sort_configuracion_lista_ordenada(
-    parametros: Vec<String>,
-    mut valores: Vec<String>,
+    parametros: Vec<Vec<u8>>,
+    mut valores: Vec<Vec<u8>>,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    if parametros.contains(&"DESC".to_string()) {
+    if parametros.contains(&b"DESC".to_vec()) {
         valores.reverse();
     }
 
-    if parametros.contains(&"LIMIT".to_string()) {
+    if parametros.contains(&b"LIMIT".to_vec()) {
         valores = match selecionar_rango(parametros.clone(), valores) {
             Some(r) => r,
             None => return ResultadoRedis::Error("ERR syntax error".to_string()),
         };
     }
 
-    if parametros.contains(&"STORE".to_string()) {
-        let clave = match parametros.rsplit(|p| p == &"STORE".to_string()).next() {
-            Some(c) => &c[0],
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    // Cada GET agrega por elemento el valor al que apunta su patron, o nil si no existe
+    let patrones: Vec<&Vec<u8>> = parametros
+        .windows(2)
+        .filter(|par| par[0] == b"GET")
+        .map(|par| &par[1])
+        .collect();
+    let resultado: Vec<Option<Vec<u8>>> = if patrones.is_empty() {
+        valores.into_iter().map(Some).collect()
+    } else {
+        let base: &BaseDeDatos = &bdd;
+        valores
+            .iter()
+            .flat_map(|valor| {
+                patrones
+                    .iter()
+                    .map(move |patron| buscar_por_patron(base, patron, valor))
+            })
+            .collect()
+    };
+
+    if parametros.contains(&b"STORE".to_vec()) {
+        let clave = match argumento_de(&parametros, b"STORE") {
+            Some(c) => c,
             None => return ResultadoRedis::Error("ERR syntax error".to_string()),
         };
-        let tamanio = valores.len();
-        match bdd.lock() {
-            Ok(mut bdd) => {
-                bdd.guardar_valor(clave.to_string(), TipoRedis::Lista(valores));
-                return ResultadoRedis::StrSimple(tamanio.to_string());
-            }
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-        }
+        let tamanio = resultado.len();
+        let lista = resultado.into_iter().map(Option::unwrap_or_default);
+        bdd.guardar_valor(clave.clone(), TipoRedis::Lista(lista.collect()));
+        bdd.notificar(ClaseEvento::Lista, "sortstore", clave);
+        return ResultadoRedis::StrSimple(tamanio.to_string());
     }
     ResultadoRedis::Vector(
-        valores
-            .iter()
-            .map(|x| ResultadoRedis::StrSimple(x.to_string()))
+        resultado
+            .into_iter()
+            .map(|x| match x {
+                Some(x) => ResultadoRedis::BulkStr(x),
+                None => ResultadoRedis::Nil,
+            })
             .collect::<Vec<ResultadoRedis>>(),
     )
 }
-/// Se encarga de ordenar la lista/set en funcion de pesos externos a lista/set tambien almacenados en la base de datos
+/// Se encarga de ordenar la lista/set en funcion de pesos externos a lista/set tambien almacenados en la base de datos.
+/// El peso de cada elemento se busca reemplazandolo en el patron de BY; los que no tienen peso valen 0 y los empates se
+/// ordenan por el elemento. Si el patron no tiene `*` los elementos no se ordenan
 fn sort_elemento_con_pesos_externos(
-    valores: Vec<String>,
-    parametros: Vec<String>,
+    mut valores: Vec<Vec<u8>>,
+    parametros: Vec<Vec<u8>>,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    let patron_pesos = match parametros.rsplit(|p| p == &"BY".to_string()).next() {
-        Some(c) => &c[0],
-        None => return ResultadoRedis::Error("ERR syntax error".to_string()),
-    };
-    let pesos = match bdd.lock() {
-        Ok(bdd) => bdd.claves(patron_pesos),
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-
-    if pesos.len() != valores.len() {
-        return ResultadoRedis::Vector(
-            valores
-                .iter()
-                .map(|x| ResultadoRedis::StrSimple(x.to_string()))
-                .collect::<Vec<ResultadoRedis>>(),
-        );
-    }
-    let mut tuplas = match obetener_tupla_valor_peso(valores, pesos, bdd.clone()) {
-        Some(t) => t,
+    let patron_pesos = match argumento_de(&parametros, b"BY") {
+        Some(p) => p,
         None => return ResultadoRedis::Error("ERR syntax error".to_string()),
     };
 
-    tuplas.sort_by(|a, b| a.1.cmp(&b.1));
-
-    if parametros.contains(&"GET".to_string()) {
-        let patron_obj = match parametros.rsplit(|p| p == &"GET".to_string()).next() {
-            Some(c) => &c[0],
-            None => return ResultadoRedis::Error("ERR syntax error".to_string()),
-        };
-
-        let objetos = match bdd.lock() {
-            Ok(bdd) => bdd.claves(patron_obj),
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-        };
-        let mut resultado: Vec<ResultadoRedis> = vec![];
-        let mut pusheado = false;
-        for valor in &tuplas {
-            for objeto in &objetos {
-                if objeto.contains(&valor.0) {
-                    match bdd.lock() {
-                        Ok(bdd) => {
-                            if let Some(TipoRedis::Str(valor)) = bdd.obtener_valor(objeto) {
-                                pusheado = true;
-                                resultado.push(ResultadoRedis::StrSimple(valor.to_string()))
-                            }
-                        }
-                        Err(_) => {
+    if patron_pesos.contains(&b'*') {
+        let alfabetico = parametros.contains(&b"ALPHA".to_vec());
+        let mut tuplas = Vec::with_capacity(valores.len());
+        {
+            let bdd = match bdd.lock() {
+                Ok(bdd) => bdd,
+                Err(_) => {
+                    return ResultadoRedis::Error("ERR when accessing the database".to_string())
+                }
+            };
+            for valor in valores {
+                let peso = buscar_por_patron(&bdd, patron_pesos, &valor);
+                let puntaje = match (&peso, alfabetico) {
+                    (Some(peso), false) => match parsear::<f64>(peso) {
+                        Some(puntaje) if !puntaje.is_nan() => puntaje,
+                        _ => {
                             return ResultadoRedis::Error(
-                                "ERR when accessing the database".to_string(),
+                                "ERR One or more scores can't be converted into double".to_string(),
                             )
                         }
-                    }
-                }
-            }
-            if !pusheado {
-                resultado.push(ResultadoRedis::Nil);
+                    },
+                    _ => 0.0,
+                };
+                tuplas.push((valor, peso, puntaje));
             }
         }
-        return ResultadoRedis::Vector(resultado);
+        tuplas.sort_by(|a, b| {
+            let orden = if alfabetico {
+                a.1.cmp(&b.1)
+            } else {
+                a.2.total_cmp(&b.2)
+            };
+            orden.then_with(|| a.0.cmp(&b.0))
+        });
+        valores = tuplas.into_iter().map(|(valor, _, _)| valor).collect();
     }
 
-    let resultado = tuplas
-        .iter()
-        .map(|x| x.0.to_string())
-        .collect::<Vec<String>>();
-
-    sort_configuracion_lista_ordenada(parametros, resultado, bdd)
+    sort_configuracion_lista_ordenada(parametros, valores, bdd)
 }
-/// Retorna los elementos contenidos en la lista o set, ordenados por la clave
+/// Retorna los elementos contenidos en la lista, set o sorted set, ordenados por la clave
 fn sort(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let parametros = match comando.get_parametros() {
         Some(p) => p,
@@ -509,11 +628,13 @@ fn sort(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
     };
     let valores = match bdd.lock() {
         Ok(bdd) => match bdd.obtener_valor(&parametros[0]) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            Some(TipoRedis::Set(set)) => Vec::from_iter(set)
-                .iter()
-                .map(|x| x.to_string())
-                .collect::<Vec<String>>(),
+            Some(TipoRedis::Lista(lista)) => lista.iter().cloned().collect(),
+            Some(TipoRedis::Set(set)) => Vec::from_iter(set.iter().cloned()),
+            Some(TipoRedis::ZSet(conjunto)) => conjunto
+                .elementos()
+                .into_iter()
+                .map(|(miembro, _)| miembro)
+                .collect::<Vec<Vec<u8>>>(),
             None => return ResultadoRedis::Vector(vec![]),
             _ => {
                 return ResultadoRedis::Error(
@@ -528,7 +649,7 @@ fn sort(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
         return ResultadoRedis::Vector(vec![]);
     }
 
-    if parametros.contains(&"BY".to_string()) {
+    if parametros.contains(&b"BY".to_vec()) {
         sort_elemento_con_pesos_externos(valores, parametros, bdd)
     } else {
         sort_elemento_con_pesos_interno(valores, parametros, bdd)
@@ -539,14 +660,14 @@ fn sort(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
 mod tests {
     use super::*;
     use crate::base_de_datos::TipoRedis;
-    use std::collections::HashSet;
+    use std::collections::VecDeque;
     use std::thread;
     use std::time::Duration;
 
     #[test]
     fn copy_copia_el_valor_de_una_clave_en_otra() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
 
         let ptr_arc = Arc::new(Mutex::new(data_base));
         let arc_clone = Arc::clone(&ptr_arc);
@@ -565,7 +686,7 @@ mod tests {
                 .unwrap()
                 .obtener_valor("otra_clave")
                 .unwrap(),
-            &TipoRedis::Str("valor".to_string())
+            &TipoRedis::Str("valor".into())
         );
     }
 
@@ -586,11 +707,11 @@ mod tests {
     #[test]
     fn del_elimina_las_claves_guardadas_en_la_base_de_datos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".into()));
 
         let comando = vec![
             "del".to_string(),
@@ -609,11 +730,11 @@ mod tests {
     #[test]
     fn del_trata_de_elimina_las_claves_que_no_estan_guardadas_en_la_base_de_datos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".into()));
 
         let comando = vec![
             "del".to_string(),
@@ -632,11 +753,11 @@ mod tests {
     #[test]
     fn del_elimina_las_claves_repetidas_que_de_la_base_de_datos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".into()));
 
         let comando = vec![
             "del".to_string(),
@@ -655,11 +776,11 @@ mod tests {
     #[test]
     fn existis_chequea_las_claves_guardadas_en_la_base_de_datos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".into()));
 
         let comando = vec![
             "del".to_string(),
@@ -678,11 +799,11 @@ mod tests {
     #[test]
     fn existis_chequea_las_claves_repetidas_que_de_la_base_de_datos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("1".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("2".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("3".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("4".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("5".to_string(), TipoRedis::Str("valor".into()));
 
         let comando = vec![
             "del".to_string(),
@@ -701,7 +822,7 @@ mod tests {
     #[test]
     fn rename_cambia_modifica_la_clave_pedida() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
 
         let ptr_arc = Arc::new(Mutex::new(data_base));
         let arc_clone = Arc::clone(&ptr_arc);
@@ -721,7 +842,7 @@ mod tests {
                 .unwrap()
                 .obtener_valor("otra_clave")
                 .unwrap(),
-            &TipoRedis::Str("valor".to_string())
+            &TipoRedis::Str("valor".into())
         );
         assert!(!arc_clone.lock().unwrap().existe_clave("clave"));
     }
@@ -729,9 +850,9 @@ mod tests {
     #[test]
     fn tipo_devuelve_el_tipo_del_valor_almacenado_con_esa_clave() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("string".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("lista".to_string(), TipoRedis::Lista(Vec::new()));
-        data_base.guardar_valor("set".to_string(), TipoRedis::Set(HashSet::new()));
+        data_base.guardar_valor("string".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("lista".to_string(), TipoRedis::Lista(VecDeque::new()));
+        data_base.guardar_valor("set".to_string(), TipoRedis::Set(Default::default()));
 
         let ptr1 = Arc::new(Mutex::new(data_base));
         let ptr2 = Arc::clone(&ptr1);
@@ -750,19 +871,19 @@ mod tests {
 
         assert_eq!(
             tipo(&mut comando_info1, ptr1),
-            ResultadoRedis::BulkStr("string".to_string())
+            ResultadoRedis::BulkStr("string".into())
         );
         assert_eq!(
             tipo(&mut comando_info2, ptr2),
-            ResultadoRedis::BulkStr("lista".to_string())
+            ResultadoRedis::BulkStr("lista".into())
         );
         assert_eq!(
             tipo(&mut comando_info3, ptr3),
-            ResultadoRedis::BulkStr("set".to_string())
+            ResultadoRedis::BulkStr("set".into())
         );
         assert_eq!(
             tipo(&mut comando_info4, ptr4),
-            ResultadoRedis::BulkStr("none".to_string())
+            ResultadoRedis::BulkStr("none".into())
         );
     }
 
@@ -770,7 +891,7 @@ mod tests {
     fn expire_cuando_se_crea_una_clave_no_expirable_y_se_la_pasa_a_volatil_esta_expira_correctamente(
     ) {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -789,12 +910,81 @@ mod tests {
         assert!(!ptr.lock().unwrap().existe_clave("clave"));
     }
 
+    #[test]
+    fn pexpire_fija_un_vencimiento_absoluto_que_devuelven_pttl_y_expiretime() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("persistente".to_string(), TipoRedis::Str("valor".into()));
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["PEXPIRE", "clave", "5000"]);
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            pexpire(&mut comando, Arc::clone(&ptr))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["TTL", "clave"]);
+        assert_eq!(ResultadoRedis::Int(5), ttl(&mut comando, Arc::clone(&ptr)));
+        let mut comando = ComandoInfo::new(vec!["PTTL", "clave"]);
+        match pttl(&mut comando, Arc::clone(&ptr)) {
+            ResultadoRedis::Int(ms) => assert!(ms > 4000 && ms <= 5000),
+            otro => panic!("se esperaba un entero y se obtuvo {:?}", otro),
+        }
+        let vencimiento = ptr
+            .lock()
+            .unwrap()
+            .obtener_vencimiento("clave")
+            .unwrap()
+            .unwrap();
+        let mut comando = ComandoInfo::new(vec!["PEXPIRETIME", "clave"]);
+        assert_eq!(
+            ResultadoRedis::Int(vencimiento as isize),
+            pexpiretime(&mut comando, Arc::clone(&ptr))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["EXPIRETIME", "persistente"]);
+        assert_eq!(
+            ResultadoRedis::Int(-1),
+            expiretime(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["PTTL", "no_existe"]);
+        assert_eq!(
+            ResultadoRedis::Int(-2),
+            pttl(&mut comando, Arc::clone(&ptr))
+        );
+    }
+
+    #[test]
+    fn expireat_con_un_momento_pasado_elimina_la_clave() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["EXPIREAT", "clave", "1000"]);
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            expireat(&mut comando, Arc::clone(&ptr))
+        );
+        assert!(!ptr.lock().unwrap().existe_clave("clave"));
+
+        let mut comando = ComandoInfo::new(vec!["EXPIREAT", "clave", "1000"]);
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            expireat(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["EXPIRE", "clave", "9223372036854775807"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR invalid expire time in 'expire' command".to_string()),
+            expire(&mut comando, ptr)
+        );
+    }
+
     #[test]
     fn keys_si_se_ingresa_la_siguiente_re_el_resultado_es_el_correcto() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("hello".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("hallo".to_string(), TipoRedis::Str("valor".to_string()));
-        data_base.guardar_valor("hillo".to_string(), TipoRedis::Str("valor".to_string()));
+        data_base.guardar_valor("hello".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("hallo".to_string(), TipoRedis::Str("valor".into()));
+        data_base.guardar_valor("hillo".to_string(), TipoRedis::Str("valor".into()));
 
         let mut comando = ComandoInfo::new(vec!["keys".to_string(), "h[ae]llo".to_string()]);
 
@@ -802,8 +992,55 @@ mod tests {
             ResultadoRedis::Vector(v) => v,
             _ => Vec::new(),
         };
-        assert!(valor.contains(&ResultadoRedis::BulkStr("hallo".to_string())));
-        assert!(valor.contains(&ResultadoRedis::BulkStr("hello".to_string())));
+        assert!(valor.contains(&ResultadoRedis::BulkStr("hallo".into())));
+        assert!(valor.contains(&ResultadoRedis::BulkStr("hello".into())));
+    }
+
+    #[test]
+    fn scan_recorre_todas_las_claves_filtrando_por_patron_y_tipo() {
+        let mut data_base = BaseDeDatos::new();
+        for i in 0..50 {
+            data_base.guardar_valor(format!("user:{}", i), TipoRedis::Str("valor".into()));
+            data_base.guardar_valor(format!("otra:{}", i), TipoRedis::Str("valor".into()));
+        }
+        data_base.guardar_valor("user:set", TipoRedis::Set(Default::default()));
+        let bdd = Arc::new(Mutex::new(data_base));
+
+        let mut claves = vec![];
+        let mut cursor = "0".to_string();
+        loop {
+            let mut comando = ComandoInfo::new(vec![
+                "SCAN", &cursor, "MATCH", "user:*", "COUNT", "7", "TYPE", "string",
+            ]);
+            match scan(&mut comando, Arc::clone(&bdd)) {
+                ResultadoRedis::Vector(mut respuesta) => {
+                    if let Some(ResultadoRedis::Vector(pagina)) = respuesta.pop() {
+                        claves.extend(pagina);
+                    }
+                    cursor = match respuesta.pop() {
+                        Some(ResultadoRedis::BulkStr(c)) => String::from_utf8(c).unwrap(),
+                        otro => panic!("cursor invalido: {:?}", otro),
+                    };
+                }
+                otro => panic!("respuesta invalida: {:?}", otro),
+            }
+            if cursor == "0" {
+                break;
+            }
+        }
+
+        assert_eq!(50, claves.len());
+        assert!(
+            (0..50)
+                .all(|i| claves
+                    .contains(&ResultadoRedis::BulkStr(format!("user:{}", i).into_bytes())))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["SCAN", "abc"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR invalid cursor".to_string()),
+            scan(&mut comando, bdd)
+        );
     }
 
     #[test]
@@ -811,22 +1048,17 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-            ]),
+            TipoRedis::Lista(vec!["5".into(), "3".into(), "4".into(), "2".into()].into()),
         );
         let mut comando = ComandoInfo::new(vec!["sort".to_string(), "mylist".to_string()]);
         let valor = sort(&mut comando, Arc::new(Mutex::new(data_base)));
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("2".to_string()),
-                ResultadoRedis::StrSimple("3".to_string()),
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("5".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into())
             ])
         );
     }
@@ -836,13 +1068,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "a".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "a".into(), "4".into(), "2".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -853,11 +1081,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("2".to_string()),
-                ResultadoRedis::StrSimple("3".to_string()),
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("5".to_string()),
-                ResultadoRedis::StrSimple("a".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("a".into())
             ])
         );
     }
@@ -867,13 +1095,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "a".to_string(),
-                "c".to_string(),
-                "d".to_string(),
-                "z".to_string(),
-                "b".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["a".into(), "c".into(), "d".into(), "z".into(), "b".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec!["sort".to_string(), "mylist".to_string()]);
         let valor = sort(&mut comando, Arc::new(Mutex::new(data_base)));
@@ -890,13 +1114,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "a".to_string(),
-                "c".to_string(),
-                "d".to_string(),
-                "z".to_string(),
-                "b".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["a".into(), "c".into(), "d".into(), "z".into(), "b".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -907,11 +1127,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("a".to_string()),
-                ResultadoRedis::StrSimple("b".to_string()),
-                ResultadoRedis::StrSimple("c".to_string()),
-                ResultadoRedis::StrSimple("d".to_string()),
-                ResultadoRedis::StrSimple("z".to_string())
+                ResultadoRedis::BulkStr("a".into()),
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::BulkStr("c".into()),
+                ResultadoRedis::BulkStr("d".into()),
+                ResultadoRedis::BulkStr("z".into())
             ])
         );
     }
@@ -922,12 +1142,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-            ]),
+            TipoRedis::Lista(vec!["5".into(), "3".into(), "4".into(), "2".into()].into()),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -938,10 +1153,10 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("5".to_string()),
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("3".to_string()),
-                ResultadoRedis::StrSimple("2".to_string())
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("2".into())
             ])
         );
     }
@@ -951,12 +1166,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-            ]),
+            TipoRedis::Lista(vec!["5".into(), "3".into(), "4".into(), "2".into()].into()),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -969,8 +1179,8 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("2".to_string()),
-                ResultadoRedis::StrSimple("3".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into())
             ])
         );
     }
@@ -980,13 +1190,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-                "6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -999,11 +1205,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("2".to_string()),
-                ResultadoRedis::StrSimple("3".to_string()),
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("5".to_string()),
-                ResultadoRedis::StrSimple("6".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("6".into())
             ])
         );
     }
@@ -1013,13 +1219,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-                "6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1032,8 +1234,8 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("5".to_string())
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into())
             ])
         );
     }
@@ -1043,13 +1245,9 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "5".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-                "2".to_string(),
-                "6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1062,11 +1260,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("2".to_string()),
-                ResultadoRedis::StrSimple("3".to_string()),
-                ResultadoRedis::StrSimple("4".to_string()),
-                ResultadoRedis::StrSimple("5".to_string()),
-                ResultadoRedis::StrSimple("6".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("6".into())
             ])
         );
     }
@@ -1088,20 +1286,16 @@ mod tests {
     #[test]
     fn sort_ordena_los_elementos_en_una_lista_con_pesos_externos() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".to_string()));
-        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".to_string()));
-        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".to_string()));
-        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".to_string()));
-        data_base.guardar_valor("peso_6".to_string(), TipoRedis::Str("6".to_string()));
+        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".into()));
+        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".into()));
+        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".into()));
+        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".into()));
+        data_base.guardar_valor("peso_6".to_string(), TipoRedis::Str("6".into()));
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "objeto_5".to_string(),
-                "objeto_3".to_string(),
-                "objeto_4".to_string(),
-                "objeto_2".to_string(),
-                "objeto_6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1113,31 +1307,27 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("objeto_2".to_string()),
-                ResultadoRedis::StrSimple("objeto_3".to_string()),
-                ResultadoRedis::StrSimple("objeto_4".to_string()),
-                ResultadoRedis::StrSimple("objeto_5".to_string()),
-                ResultadoRedis::StrSimple("objeto_6".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("6".into())
             ])
         );
     }
 
     #[test]
-    fn sort_ordena_los_elementos_en_una_lista_con_pesos_externos_faltantes() {
+    fn sort_ordena_los_elementos_en_una_lista_con_pesos_externos_faltantes_que_valen_cero() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".to_string()));
-        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".to_string()));
-        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".to_string()));
-        data_base.guardar_valor("peso_6".to_string(), TipoRedis::Str("6".to_string()));
+        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".into()));
+        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".into()));
+        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".into()));
+        data_base.guardar_valor("peso_6".to_string(), TipoRedis::Str("6".into()));
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "objeto_5".to_string(),
-                "objeto_3".to_string(),
-                "objeto_4".to_string(),
-                "objeto_2".to_string(),
-                "objeto_6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1149,27 +1339,23 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("objeto_5".to_string()),
-                ResultadoRedis::StrSimple("objeto_3".to_string()),
-                ResultadoRedis::StrSimple("objeto_4".to_string()),
-                ResultadoRedis::StrSimple("objeto_2".to_string()),
-                ResultadoRedis::StrSimple("objeto_6".to_string())
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("6".into())
             ])
         );
     }
 
     #[test]
-    fn sort_ordena_los_elementos_en_una_lista_con_todos_pesos_externos_faltantes() {
+    fn sort_ordena_por_el_elemento_si_faltan_todos_los_pesos_externos() {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "objeto_5".to_string(),
-                "objeto_3".to_string(),
-                "objeto_4".to_string(),
-                "objeto_2".to_string(),
-                "objeto_6".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["5".into(), "3".into(), "4".into(), "2".into(), "6".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1181,11 +1367,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("objeto_5".to_string()),
-                ResultadoRedis::StrSimple("objeto_3".to_string()),
-                ResultadoRedis::StrSimple("objeto_4".to_string()),
-                ResultadoRedis::StrSimple("objeto_2".to_string()),
-                ResultadoRedis::StrSimple("objeto_6".to_string()),
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into()),
+                ResultadoRedis::BulkStr("5".into()),
+                ResultadoRedis::BulkStr("6".into()),
             ])
         );
     }
@@ -1193,36 +1379,23 @@ mod tests {
     #[test]
     fn sort_ordena_los_elementos_y_devuelve_los_objetos_que_representan_las_ids() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("peso_1".to_string(), TipoRedis::Str("1".to_string()));
-        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".to_string()));
-        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".to_string()));
-        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".to_string()));
-        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".to_string()));
-
-        data_base.guardar_valor(
-            "objeto_1".to_string(),
-            TipoRedis::Str("primero".to_string()),
-        );
-        data_base.guardar_valor(
-            "objeto_2".to_string(),
-            TipoRedis::Str("segundo".to_string()),
-        );
-        data_base.guardar_valor(
-            "objeto_3".to_string(),
-            TipoRedis::Str("tercero".to_string()),
-        );
-        data_base.guardar_valor("objeto_4".to_string(), TipoRedis::Str("cuarto".to_string()));
-        data_base.guardar_valor("objeto_5".to_string(), TipoRedis::Str("quinto".to_string()));
+        data_base.guardar_valor("peso_1".to_string(), TipoRedis::Str("1".into()));
+        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".into()));
+        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".into()));
+        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".into()));
+        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".into()));
+
+        data_base.guardar_valor("objeto_1".to_string(), TipoRedis::Str("primero".into()));
+        data_base.guardar_valor("objeto_2".to_string(), TipoRedis::Str("segundo".into()));
+        data_base.guardar_valor("objeto_3".to_string(), TipoRedis::Str("tercero".into()));
+        data_base.guardar_valor("objeto_4".to_string(), TipoRedis::Str("cuarto".into()));
+        data_base.guardar_valor("objeto_5".to_string(), TipoRedis::Str("quinto".into()));
 
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "objeto_2".to_string(),
-                "objeto_5".to_string(),
-                "objeto_4".to_string(),
-                "objeto_3".to_string(),
-                "objeto_1".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["2".into(), "5".into(), "4".into(), "3".into(), "1".into()].into(),
+            ),
         );
         let mut comando = ComandoInfo::new(vec![
             "sort".to_string(),
@@ -1236,11 +1409,11 @@ mod tests {
         assert_eq!(
             valor,
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::StrSimple("primero".to_string()),
-                ResultadoRedis::StrSimple("segundo".to_string()),
-                ResultadoRedis::StrSimple("tercero".to_string()),
-                ResultadoRedis::StrSimple("cuarto".to_string()),
-                ResultadoRedis::StrSimple("quinto".to_string())
+                ResultadoRedis::BulkStr("primero".into()),
+                ResultadoRedis::BulkStr("segundo".into()),
+                ResultadoRedis::BulkStr("tercero".into()),
+                ResultadoRedis::BulkStr("cuarto".into()),
+                ResultadoRedis::BulkStr("quinto".into())
             ])
         );
     }
@@ -1248,21 +1421,17 @@ mod tests {
     #[test]
     fn sort_ordena_por_pesos_externos_los_elementos_los_guarda_con_la_clave_dada() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("peso_1".to_string(), TipoRedis::Str("1".to_string()));
-        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".to_string()));
-        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".to_string()));
-        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".to_string()));
-        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".to_string()));
+        data_base.guardar_valor("peso_1".to_string(), TipoRedis::Str("1".into()));
+        data_base.guardar_valor("peso_2".to_string(), TipoRedis::Str("2".into()));
+        data_base.guardar_valor("peso_3".to_string(), TipoRedis::Str("3".into()));
+        data_base.guardar_valor("peso_4".to_string(), TipoRedis::Str("4".into()));
+        data_base.guardar_valor("peso_5".to_string(), TipoRedis::Str("5".into()));
 
         data_base.guardar_valor(
             "mylist".to_string(),
-            TipoRedis::Lista(vec![
-                "objeto_2".to_string(),
-                "objeto_5".to_string(),
-                "objeto_4".to_string(),
-                "objeto_3".to_string(),
-                "objeto_1".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec!["2".into(), "5".into(), "4".into(), "3".into(), "1".into()].into(),
+            ),
         );
         let ptr = Arc::new(Mutex::new(data_base));
         let ptr_clone = Arc::clone(&ptr);
@@ -1279,14 +1448,58 @@ mod tests {
             sort(&mut comando, ptr)
         );
         assert_eq!(
-            Some(&TipoRedis::Lista(vec![
-                "objeto_1".to_string(),
-                "objeto_2".to_string(),
-                "objeto_3".to_string(),
-                "objeto_4".to_string(),
-                "objeto_5".to_string()
-            ])),
+            Some(&TipoRedis::Lista(
+                vec!["1".into(), "2".into(), "3".into(), "4".into(), "5".into()].into()
+            )),
             ptr_clone.lock().unwrap().obtener_valor("ordenados")
         );
     }
+
+    #[test]
+    fn sort_sustituye_el_elemento_en_los_patrones_y_lee_campos_de_hashes() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("peso_a", TipoRedis::Str("10".into()));
+        data_base.guardar_valor("peso_b", TipoRedis::Str("9".into()));
+        for (clave, nombre) in [("usuario:a", "ana"), ("usuario:b", "beto")] {
+            let hash = vec![(b"nombre".to_vec(), nombre.as_bytes().to_vec())];
+            data_base.guardar_valor(clave, TipoRedis::Hash(hash.into_iter().collect()));
+        }
+        data_base.guardar_valor(
+            "mylist",
+            TipoRedis::Lista(vec!["a".into(), "b".into(), "c".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec![
+            "sort",
+            "mylist",
+            "BY",
+            "peso_*",
+            "GET",
+            "#",
+            "GET",
+            "usuario:*->nombre",
+        ]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("c".into()),
+                ResultadoRedis::Nil,
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::BulkStr("beto".into()),
+                ResultadoRedis::BulkStr("a".into()),
+                ResultadoRedis::BulkStr("ana".into()),
+            ]),
+            sort(&mut comando, Arc::clone(&ptr))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["sort", "mylist", "BY", "nosort", "DESC"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("c".into()),
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::BulkStr("a".into()),
+            ]),
+            sort(&mut comando, ptr)
+        );
+    }
 }
@@ -1,6 +1,10 @@
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::{parsear, parsear_entero};
+use crate::bloqueo::parsear_timeout;
 use crate::comando::{Comando, ComandoHandler};
 use crate::comando_info::ComandoInfo;
+use crate::notificacion::ClaseEvento;
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
 pub struct ComandoListHandler {
@@ -21,6 +25,16 @@ impl ComandoListHandler {
             "LRANGE" => lrange,
             "LREM" => lrem,
             "LSET" => lset,
+            "BLPOP" => blpop,
+            "BRPOP" => brpop,
+            "BLMOVE" => blmove,
+            "BLMPOP" => blmpop,
+            "LINSERT" => linsert,
+            "LTRIM" => ltrim,
+            "LPOS" => lpos,
+            "LMOVE" => lmove,
+            "RPOPLPUSH" => rpoplpush,
+            "LMPOP" => lmpop,
             _ => llen,
         };
         ComandoListHandler {
@@ -38,8 +52,27 @@ impl ComandoHandler for ComandoListHandler {
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo lista
 pub fn es_comando_list(comando: &str) -> bool {
     let comandos = vec![
-        "LINDEX", "LPOP", "RPOP", "LPUSH", "LPUSHX", "RPUSH", "RPUSHX", "LRANGE", "LREM", "LSET",
+        "LINDEX",
+        "LPOP",
+        "RPOP",
+        "LPUSH",
+        "LPUSHX",
+        "RPUSH",
+        "RPUSHX",
+        "LRANGE",
+        "LREM",
+        "LSET",
         "LLEN",
+        "BLPOP",
+        "BRPOP",
+        "BLMOVE",
+        "BLMPOP",
+        "LINSERT",
+        "LTRIM",
+        "LPOS",
+        "LMOVE",
+        "RPOPLPUSH",
+        "LMPOP",
     ];
     comandos.iter().any(|&c| c == comando)
 }
@@ -62,21 +95,22 @@ pub fn lindex(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Result
             )
         }
     };
-    let lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
+    let lista = match bdd.obtener_valor(&clave) {
+        Some(TipoRedis::Lista(lista)) => lista,
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
 
-    let indice: i32 = match parametro.parse() {
-        Ok(v) => v,
-        Err(_) => {
+    let indice: i32 = match parsear(&parametro) {
+        Some(v) => v,
+        None => {
             return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
         }
     };
@@ -87,7 +121,7 @@ pub fn lindex(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Result
     } else if 0 > indice && tamanio + indice >= 0 {
         ResultadoRedis::BulkStr(lista[(tamanio + indice) as usize].clone())
     } else {
-        ResultadoRedis::BulkStr("nil".to_string())
+        ResultadoRedis::BulkStr(b"nil".to_vec())
     }
 }
 /// Retorna el largo dela lista almacenada en la clave. Si la clave no existe, se interpreta como lista vacía, retornando 0. Se retorna error si el valor almacenado en la clave no es una lista
@@ -123,91 +157,98 @@ pub fn rpop(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>) -
     pop(comando, base_de_datos, true)
 }
 
+/// Saca elementos de un extremo de la lista. Sin cantidad devuelve un unico elemento o nil, y con cantidad devuelve
+/// un vector, que es nulo si la clave no existe
 fn pop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>, reversed: bool) -> ResultadoRedis {
-    let clave = match comando.get_clave() {
-        Some(c) => c,
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'pop' command".to_string(),
-            )
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 || argumentos.len() > 3 {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            comando.get_nombre().to_lowercase()
+        ));
+    }
+    let clave = &argumentos[1];
+    let cantidad = match argumentos.get(2).map(|c| parsear_entero(c)) {
+        None => None,
+        Some(Some(cantidad)) if cantidad >= 0 => Some(cantidad as usize),
+        Some(Some(_)) => {
+            return ResultadoRedis::Error("ERR value is out of range, must be positive".to_string())
+        }
+        Some(None) => {
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
         }
     };
-    let indice = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(i) => i,
-            Err(_) => {
-                return ResultadoRedis::Error(
-                    "ERR value is not an integer or out of range".to_string(),
-                )
-            }
-        },
-        None => 1,
-    };
-    let mut lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => return ResultadoRedis::Nil,
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
-
-    if reversed {
-        lista.reverse();
-    }
-
-    let mut a_devolver: Vec<_> = lista.drain(0..indice).collect();
-
-    if reversed {
-        lista.reverse();
-        a_devolver.reverse();
-    }
-    match bdd.lock() {
-        Ok(mut bdd) => {
-            if !lista.is_empty() {
-                bdd.guardar_valor(clave, TipoRedis::Lista(lista));
-            } else {
-                bdd.eliminar_clave(&clave);
-            }
+    match (bdd.obtener_valor(clave), cantidad) {
+        (Some(TipoRedis::Lista(_)), Some(0)) => return ResultadoRedis::Vector(vec![]),
+        (Some(TipoRedis::Lista(_)), _) => (),
+        (None, None) => return ResultadoRedis::Nil,
+        (None, Some(_)) => return ResultadoRedis::Null,
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
         }
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
-    if a_devolver.len() == 1 {
-        return ResultadoRedis::BulkStr(a_devolver[0].clone());
+    let mut elementos = sacar_elementos(&mut bdd, clave, cantidad.unwrap_or(1), reversed);
+    match cantidad {
+        Some(_) => {
+            ResultadoRedis::Vector(elementos.into_iter().map(ResultadoRedis::BulkStr).collect())
+        }
+        None => ResultadoRedis::BulkStr(elementos.remove(0)),
     }
-
-    ResultadoRedis::Vector(
-        a_devolver
-            .iter()
-            .map(|el| ResultadoRedis::BulkStr(el.to_string()))
-            .collect(),
-    )
 }
 
+/// Agrega los valores del comando en un extremo de la lista de la clave, sin copiarla. Si la clave no existe se
+/// crea la lista solo si se indica, y si no se retorna 0
 fn push(
-    mut lista: Vec<String>,
-    clave: String,
+    clave: Vec<u8>,
     comando: &mut ComandoInfo,
     bdd: Arc<Mutex<BaseDeDatos>>,
     reversed: bool,
+    crear: bool,
 ) -> ResultadoRedis {
+    let mut valores = vec![];
     while let Some(parametro) = comando.get_parametro() {
-        if !reversed {
-            lista.insert(0, parametro);
-        } else {
-            lista.push(parametro);
-        }
+        valores.push(parametro);
     }
-    let long = lista.len();
-    match bdd.lock() {
-        Ok(mut bdd) => bdd.guardar_valor(clave, TipoRedis::Lista(lista)),
+    if valores.is_empty() {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            comando.get_nombre().to_lowercase()
+        ));
+    }
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    match bdd.obtener_valor(&clave) {
+        Some(TipoRedis::Lista(_)) => (),
+        None if crear => bdd.guardar_valor(clave.clone(), TipoRedis::Lista(VecDeque::new())),
+        None => return ResultadoRedis::Int(0),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
     }
-    ResultadoRedis::Int(long as isize)
+    let long = bdd.modificar_lista(&clave, |lista| {
+        for valor in valores {
+            if reversed {
+                lista.push_back(valor);
+            } else {
+                lista.push_front(valor);
+            }
+        }
+        lista.len()
+    });
+    let evento = if reversed { "rpush" } else { "lpush" };
+    bdd.notificar(ClaseEvento::Lista, evento, &clave);
+    ResultadoRedis::Int(long.unwrap_or(0) as isize)
 }
 /// Inserta todos los valores especificados en el inicio de la lista de la clave especificada. Si no existe la clave, se crea inicialmente como una lista vacía para luego aplicar las operaciones. Se retorna error si la clave almacena un elemento que no es una lista
 pub fn lpush(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -219,20 +260,7 @@ pub fn lpush(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resulta
             )
         }
     };
-    let lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => Vec::new(),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-
-    push(lista, clave, comando, bdd, false)
+    push(clave, comando, bdd, false, true)
 }
 /// Inserta los valores especificados al inicio de lalista, solamente si la clave existe y almacena una lista. A diferencia de LPUSH, no se realiza operación si la clave no existe
 pub fn lpushx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -244,19 +272,7 @@ pub fn lpushx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Result
             )
         }
     };
-    let lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => return ResultadoRedis::Int(0),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-    push(lista, clave, comando, bdd, false)
+    push(clave, comando, bdd, false, false)
 }
 /// Inserta todos los valores especificados al final de la lista indicada en la clave. Si la clave no existe, se crear como una lista vacía antes de realizar la operación. Se retorna error si el elemento contenido no es una lista
 pub fn rpush(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -268,19 +284,7 @@ pub fn rpush(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resulta
             )
         }
     };
-    let lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => Vec::new(),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-    push(lista, clave, comando, bdd, true)
+    push(clave, comando, bdd, true, true)
 }
 /// Inserta los valores especificados al final de la lista almacenada en la clave indicada, solamente si la clave contiene una lista. En caso contrario, no se realiza ninguna operación
 pub fn rpushx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -292,19 +296,7 @@ pub fn rpushx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Result
             )
         }
     };
-    let lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => return ResultadoRedis::Int(0),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-    push(lista, clave, comando, bdd, true)
+    push(clave, comando, bdd, true, false)
 }
 /// Retorna los elementos especificados de la lista almacenada en la clave indicada. Los inicios y fin de rango se consideran con el 0 como primer elemento de la lista. Estos valores pueden ser negativos, indicando que corresponde al final de la lista: -1 es el último elemento
 pub fn lrange(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -317,9 +309,9 @@ pub fn lrange(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>)
         }
     };
     let inicio: i32 = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(i) => i,
-            Err(_) => {
+        Some(p) => match parsear(&p) {
+            Some(i) => i,
+            None => {
                 return ResultadoRedis::Error(
                     "ERR value is not an integer or out of range".to_string(),
                 )
@@ -332,9 +324,9 @@ pub fn lrange(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>)
         }
     };
     let fin: i32 = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(i) => i,
-            Err(_) => {
+        Some(p) => match parsear(&p) {
+            Some(i) => i,
+            None => {
                 return ResultadoRedis::Error(
                     "ERR value is not an integer or out of range".to_string(),
                 )
@@ -347,18 +339,19 @@ pub fn lrange(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>)
         }
     };
 
-    let lista = match base_de_datos.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => return ResultadoRedis::Vector(vec![]),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
+    let bdd = match base_de_datos.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
+    let lista = match bdd.obtener_valor(&clave) {
+        Some(TipoRedis::Lista(lista)) => lista,
+        None => return ResultadoRedis::Vector(vec![]),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
     let (a, b) = match obtener_intervalo(inicio, fin, lista.len() as i32) {
         Some((a, b)) => (a, b),
         None => return ResultadoRedis::Vector(vec![]),
@@ -366,12 +359,10 @@ pub fn lrange(comando: &mut ComandoInfo, base_de_datos: Arc<Mutex<BaseDeDatos>>)
     if b < a {
         return ResultadoRedis::Vector(vec![]);
     }
-    let a_devolver: Vec<_> = lista[a..(b + 1)].to_vec();
-
     ResultadoRedis::Vector(
-        a_devolver
-            .iter()
-            .map(|el| ResultadoRedis::BulkStr(el.to_string()))
+        lista
+            .range(a..(b + 1))
+            .map(|el| ResultadoRedis::BulkStr(el.clone()))
             .collect(),
     )
 }
@@ -434,9 +425,9 @@ pub fn lrem(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
         }
     };
     let indice: i32 = match comando.get_parametro() {
-        Some(p) => match p.parse() {
-            Ok(i) => i,
-            Err(_) => {
+        Some(p) => match parsear(&p) {
+            Some(i) => i,
+            None => {
                 return ResultadoRedis::Error(
                     "ERR value is not an integer or out of range".to_string(),
                 )
@@ -457,47 +448,48 @@ pub fn lrem(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
         }
     };
 
-    let mut lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => return ResultadoRedis::Int(0),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
-
-    let mut i = indice;
-    if indice < 0 {
-        lista.reverse();
-        i = indice.abs();
-    }
-    let mut cant_eliminada = 0;
-    let mut lista_filtrada: Vec<String> = Vec::new();
-    let iter = lista.iter();
-    for valor in iter {
-        if !(valor.eq(&a_eliminar) && cant_eliminada < i) {
-            lista_filtrada.push(valor.clone());
-        } else {
-            cant_eliminada += 1;
+    let coincidencias = match bdd.obtener_valor(&clave) {
+        Some(TipoRedis::Lista(lista)) => lista.iter().filter(|e| **e == a_eliminar).count(),
+        None => return ResultadoRedis::Int(0),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
         }
-    }
-    if indice < 0 {
-        lista_filtrada.reverse();
+    };
+    let cant_eliminada = match indice {
+        0 => coincidencias,
+        i => coincidencias.min(i.unsigned_abs() as usize),
+    };
+    if cant_eliminada == 0 {
+        return ResultadoRedis::Int(0);
     }
 
-    match bdd.lock() {
-        Ok(mut bdd) => {
-            if !lista_filtrada.is_empty() {
-                bdd.guardar_valor(clave, TipoRedis::Lista(lista_filtrada));
-            } else {
-                bdd.eliminar_clave(&clave);
+    // Con un indice negativo se conservan las primeras coincidencias y se eliminan las ultimas
+    let (desde, hasta) = if indice < 0 {
+        (coincidencias - cant_eliminada, coincidencias)
+    } else {
+        (0, cant_eliminada)
+    };
+    let vacia = bdd.modificar_lista(&clave, |lista| {
+        let mut vistas = 0;
+        lista.retain(|valor| {
+            if *valor != a_eliminar {
+                return true;
             }
-        }
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+            vistas += 1;
+            !(desde < vistas && vistas <= hasta)
+        });
+        lista.is_empty()
+    });
+    bdd.notificar(ClaseEvento::Lista, "lrem", &clave);
+    if vacia == Some(true) {
+        bdd.eliminar_clave(&clave);
+        bdd.notificar(ClaseEvento::Generico, "del", &clave);
     }
     ResultadoRedis::Int(cant_eliminada as isize)
 }
@@ -521,9 +513,9 @@ pub fn lset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
         }
     };
 
-    let indice: i32 = match parametro.parse() {
-        Ok(v) => v,
-        Err(_) => {
+    let indice: i32 = match parsear(&parametro) {
+        Some(v) => v,
+        None => {
             return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
         }
     };
@@ -537,34 +529,505 @@ pub fn lset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
         }
     };
 
-    let mut lista = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Lista(lista)) => lista.clone(),
-            None => Vec::new(),
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let tamanio = match bdd.obtener_valor(&clave) {
+        Some(TipoRedis::Lista(lista)) => lista.len() as i64,
+        None => 0,
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let posicion = if indice < 0 {
+        tamanio + indice as i64
+    } else {
+        indice as i64
+    };
+    if posicion < 0 || posicion >= tamanio {
+        return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string());
+    }
+
+    bdd.modificar_lista(&clave, |lista| lista[posicion as usize] = parametro);
+    bdd.notificar(ClaseEvento::Lista, "lset", &clave);
+    ResultadoRedis::StrSimple("OK".to_string())
+}
+
+/// Version bloqueante de LPOP. Saca el primer elemento de la primera lista no vacia entre las claves indicadas y devuelve la clave junto con el elemento. Si ninguna tiene elementos devuelve un vector nulo y el cliente queda bloqueado hasta que alguna los tenga o venza el timeout
+pub fn blpop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    pop_bloqueante(comando, bdd, false)
+}
+/// Version bloqueante de RPOP. Saca el ultimo elemento de la primera lista no vacia entre las claves indicadas y devuelve la clave junto con el elemento. Si ninguna tiene elementos devuelve un vector nulo y el cliente queda bloqueado hasta que alguna los tenga o venza el timeout
+pub fn brpop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    pop_bloqueante(comando, bdd, true)
+}
+
+fn pop_bloqueante(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    desde_derecha: bool,
+) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            comando.get_nombre().to_lowercase()
+        ));
+    }
+    if let Err(error) = parsear_timeout(&argumentos[argumentos.len() - 1]) {
+        return error;
+    }
+    let claves = &argumentos[1..argumentos.len() - 1];
+
+    match bdd.lock() {
+        Ok(mut bdd) => match primera_lista_con_elementos(&bdd, claves) {
+            Ok(Some(clave)) => {
+                let elementos = sacar_elementos(&mut bdd, &clave, 1, desde_derecha);
+                ResultadoRedis::Vector(
+                    std::iter::once(clave)
+                        .chain(elementos)
+                        .map(ResultadoRedis::BulkStr)
+                        .collect(),
+                )
+            }
+            Ok(None) => ResultadoRedis::Null,
+            Err(error) => error,
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Version bloqueante de LMOVE. Saca un elemento del extremo indicado de la lista origen y lo agrega en el extremo indicado de la lista destino, devolviendo el elemento. Si el origen no tiene elementos devuelve un vector nulo y el cliente queda bloqueado hasta que los tenga o venza el timeout
+pub fn blmove(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 6 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'blmove' command".to_string(),
+        );
+    }
+    let (origen, destino) = (&argumentos[1], &argumentos[2]);
+    let (desde_derecha, hacia_derecha) =
+        match (es_derecha(&argumentos[3]), es_derecha(&argumentos[4])) {
+            (Some(desde), Some(hacia)) => (desde, hacia),
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        };
+    if let Err(error) = parsear_timeout(&argumentos[5]) {
+        return error;
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            match mover_elemento(&mut bdd, origen, destino, desde_derecha, hacia_derecha) {
+                Ok(Some(elemento)) => ResultadoRedis::BulkStr(elemento),
+                Ok(None) => ResultadoRedis::Null,
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Version bloqueante de LMPOP. Saca hasta COUNT elementos, uno por defecto, del extremo indicado de la primera lista no vacia entre las claves y devuelve la clave junto con los elementos. Si ninguna tiene elementos devuelve un vector nulo y el cliente queda bloqueado hasta que alguna los tenga o venza el timeout
+pub fn blmpop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 5 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'blmpop' command".to_string(),
+        );
+    }
+    if let Err(error) = parsear_timeout(&argumentos[1]) {
+        return error;
+    }
+    match parsear_lmpop(&argumentos[2..]) {
+        Ok((claves, desde_derecha, cantidad)) => match bdd.lock() {
+            Ok(mut bdd) => sacar_de_la_primera_lista(&mut bdd, claves, cantidad, desde_derecha),
+            Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+        },
+        Err(error) => error,
+    }
+}
+
+/// Interpreta los argumentos de LMPOP y BLMPOP a partir de la cantidad de claves. Devuelve las claves, si se
+/// saca desde la derecha y la cantidad de elementos a sacar
+fn parsear_lmpop(argumentos: &[Vec<u8>]) -> Result<(&[Vec<u8>], bool, usize), ResultadoRedis> {
+    let cantidad_claves = match parsear::<usize>(&argumentos[0]) {
+        Some(n) if n > 0 => n,
+        _ => {
+            return Err(ResultadoRedis::Error(
+                "ERR numkeys should be greater than 0".to_string(),
+            ))
+        }
+    };
+    let (claves, opciones) = match argumentos.get(1..1 + cantidad_claves) {
+        Some(claves) => (claves, &argumentos[1 + cantidad_claves..]),
+        None => return Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    };
+    let desde_derecha = match opciones.first().and_then(|d| es_derecha(d)) {
+        Some(d) => d,
+        None => return Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    };
+    let cantidad = match &opciones[1..] {
+        [] => 1,
+        [opcion, valor] if opcion.eq_ignore_ascii_case(b"COUNT") => match parsear::<usize>(valor) {
+            Some(n) if n > 0 => n,
+            _ => {
+                return Err(ResultadoRedis::Error(
+                    "ERR count should be greater than 0".to_string(),
+                ))
+            }
+        },
+        _ => return Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    };
+    Ok((claves, desde_derecha, cantidad))
+}
+
+/// Saca hasta la cantidad indicada de elementos de la primera lista no vacia entre las claves y devuelve la clave
+/// junto con los elementos, o un vector nulo si ninguna tiene elementos
+fn sacar_de_la_primera_lista(
+    bdd: &mut BaseDeDatos,
+    claves: &[Vec<u8>],
+    cantidad: usize,
+    desde_derecha: bool,
+) -> ResultadoRedis {
+    match primera_lista_con_elementos(bdd, claves) {
+        Ok(Some(clave)) => {
+            let elementos = sacar_elementos(bdd, &clave, cantidad, desde_derecha);
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr(clave),
+                ResultadoRedis::Vector(
+                    elementos.into_iter().map(ResultadoRedis::BulkStr).collect(),
+                ),
+            ])
+        }
+        Ok(None) => ResultadoRedis::Null,
+        Err(error) => error,
+    }
+}
+
+/// Saca un elemento del extremo indicado de la lista origen y lo agrega en el extremo indicado de la lista destino.
+/// Devuelve el elemento movido, o ninguno si el origen no tiene elementos
+fn mover_elemento(
+    bdd: &mut BaseDeDatos,
+    origen: &[u8],
+    destino: &[u8],
+    desde_derecha: bool,
+    hacia_derecha: bool,
+) -> Result<Option<Vec<u8>>, ResultadoRedis> {
+    if primera_lista_con_elementos(bdd, &[origen.to_vec()])?.is_none() {
+        return Ok(None);
+    }
+    match bdd.obtener_valor(destino) {
+        Some(TipoRedis::Lista(_)) | None => (),
+        _ => {
+            return Err(ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ))
+        }
+    }
+
+    let elemento = sacar_elementos(bdd, origen, 1, desde_derecha).remove(0);
+    // El destino se revisa luego de sacar el elemento porque, si es la misma clave que el origen, pudo quedar vacio
+    if bdd.obtener_valor(destino).is_none() {
+        bdd.guardar_valor(destino.to_vec(), TipoRedis::Lista(VecDeque::new()));
+    }
+    bdd.modificar_lista(destino, |lista| {
+        if hacia_derecha {
+            lista.push_back(elemento.clone());
+        } else {
+            lista.push_front(elemento.clone());
+        }
+    });
+    let evento = if hacia_derecha { "rpush" } else { "lpush" };
+    bdd.notificar(ClaseEvento::Lista, evento, destino);
+    Ok(Some(elemento))
+}
+
+/// Saca un elemento del extremo indicado de la lista origen y lo agrega en el extremo indicado de la lista destino, devolviendo el elemento. Si el origen no tiene elementos devuelve nil
+pub fn lmove(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 5 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'lmove' command".to_string(),
+        );
+    }
+    match (es_derecha(&argumentos[3]), es_derecha(&argumentos[4])) {
+        (Some(desde), Some(hacia)) => mover(&argumentos[1], &argumentos[2], desde, hacia, bdd),
+        _ => ResultadoRedis::Error("ERR syntax error".to_string()),
+    }
+}
+/// Saca el ultimo elemento de la lista origen y lo agrega al inicio de la lista destino, devolviendo el elemento. Equivale a LMOVE con RIGHT y LEFT
+pub fn rpoplpush(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'rpoplpush' command".to_string(),
+        );
+    }
+    mover(&argumentos[1], &argumentos[2], true, false, bdd)
+}
+
+fn mover(
+    origen: &[u8],
+    destino: &[u8],
+    desde_derecha: bool,
+    hacia_derecha: bool,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            match mover_elemento(&mut bdd, origen, destino, desde_derecha, hacia_derecha) {
+                Ok(Some(elemento)) => ResultadoRedis::BulkStr(elemento),
+                Ok(None) => ResultadoRedis::Nil,
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Saca hasta COUNT elementos, uno por defecto, del extremo indicado de la primera lista no vacia entre las claves y devuelve la clave junto con los elementos. Si ninguna tiene elementos devuelve un vector nulo
+pub fn lmpop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'lmpop' command".to_string(),
+        );
+    }
+    match parsear_lmpop(&argumentos[1..]) {
+        Ok((claves, desde_derecha, cantidad)) => match bdd.lock() {
+            Ok(mut bdd) => sacar_de_la_primera_lista(&mut bdd, claves, cantidad, desde_derecha),
+            Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+        },
+        Err(error) => error,
+    }
+}
+
+/// Inserta el elemento antes o despues de la primera aparicion del pivote en la lista de la clave. Retorna el nuevo largo de la lista, -1 si no se encontro el pivote o 0 si la clave no existe
+pub fn linsert(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 5 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'linsert' command".to_string(),
+        );
+    }
+    let (clave, pivote, elemento) = (&argumentos[1], &argumentos[3], &argumentos[4]);
+    let despues = if argumentos[2].eq_ignore_ascii_case(b"AFTER") {
+        true
+    } else if argumentos[2].eq_ignore_ascii_case(b"BEFORE") {
+        false
+    } else {
+        return ResultadoRedis::Error("ERR syntax error".to_string());
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let posicion = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Lista(lista)) => lista.iter().position(|e| e == pivote),
+        None => return ResultadoRedis::Int(0),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let posicion = match posicion {
+        Some(posicion) => posicion + despues as usize,
+        None => return ResultadoRedis::Int(-1),
+    };
+    let largo = bdd.modificar_lista(clave, |lista| {
+        lista.insert(posicion, elemento.clone());
+        lista.len()
+    });
+    bdd.notificar(ClaseEvento::Lista, "linsert", clave);
+    ResultadoRedis::Int(largo.unwrap_or(0) as isize)
+}
+
+/// Recorta la lista de la clave para que solo contenga los elementos del rango indicado, con las mismas reglas de indices que LRANGE. Si el rango queda vacio se elimina la clave
+pub fn ltrim(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'ltrim' command".to_string(),
+        );
+    }
+    let clave = &argumentos[1];
+    let (inicio, fin) = match (
+        parsear::<i32>(&argumentos[2]),
+        parsear::<i32>(&argumentos[3]),
+    ) {
+        (Some(inicio), Some(fin)) => (inicio, fin),
+        _ => {
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+        }
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let lista = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Lista(lista)) => lista,
+        None => return ResultadoRedis::StrSimple("OK".to_string()),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let largo = lista.len();
+    match obtener_intervalo(inicio, fin, largo as i32) {
+        Some((a, b)) if a <= b => {
+            if a > 0 || b + 1 < largo {
+                // Se recorta en el lugar, el costo depende solo de la cantidad de elementos descartados
+                bdd.modificar_lista(clave, |lista| {
+                    lista.truncate(b + 1);
+                    lista.drain(..a);
+                });
+                bdd.notificar(ClaseEvento::Lista, "ltrim", clave);
+            }
+        }
+        _ => {
+            bdd.eliminar_clave(clave);
+            bdd.notificar(ClaseEvento::Lista, "ltrim", clave);
+            bdd.notificar(ClaseEvento::Generico, "del", clave);
+        }
+    }
+    ResultadoRedis::StrSimple("OK".to_string())
+}
+
+/// Retorna la posicion del elemento en la lista de la clave. RANK indica cual de las coincidencias devolver, contando desde el final si es negativo; COUNT devuelve esa cantidad de posiciones, todas con 0; y MAXLEN limita cuantos elementos se comparan
+pub fn lpos(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'lpos' command".to_string(),
+        );
+    }
+    let (clave, elemento) = (&argumentos[1], &argumentos[2]);
+    let (mut rango, mut cantidad, mut largo_maximo) = (1, None, 0);
+    let mut opciones = argumentos[3..].iter();
+    while let Some(opcion) = opciones.next() {
+        let valor = match opciones.next().map(|v| parsear_entero(v)) {
+            Some(Some(valor)) => valor,
+            Some(None) => {
+                return ResultadoRedis::Error(
+                    "ERR value is not an integer or out of range".to_string(),
+                )
+            }
+            None => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        };
+        match opcion.to_ascii_uppercase().as_slice() {
+            b"RANK" if valor == 0 || valor == i64::MIN => {
+                return ResultadoRedis::Error("ERR RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the last match".to_string())
+            }
+            b"RANK" => rango = valor,
+            b"COUNT" if valor < 0 => {
+                return ResultadoRedis::Error("ERR COUNT can't be negative".to_string())
+            }
+            b"COUNT" => cantidad = Some(valor as usize),
+            b"MAXLEN" if valor < 0 => {
+                return ResultadoRedis::Error("ERR MAXLEN can't be negative".to_string())
+            }
+            b"MAXLEN" => largo_maximo = valor as usize,
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let vacia = VecDeque::new();
+    let lista = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Lista(lista)) => lista,
+        None => &vacia,
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let revisados = if largo_maximo == 0 {
+        lista.len()
+    } else {
+        largo_maximo.min(lista.len())
+    };
+    let indices: Box<dyn Iterator<Item = usize>> = if rango > 0 {
+        Box::new(0..revisados)
+    } else {
+        Box::new((lista.len() - revisados..lista.len()).rev())
+    };
+    let mut posiciones = indices
+        .filter(|&i| &lista[i] == elemento)
+        .skip(rango.unsigned_abs() as usize - 1)
+        .map(|i| ResultadoRedis::Int(i as isize));
+    match cantidad {
+        None => posiciones.next().unwrap_or(ResultadoRedis::Nil),
+        Some(0) => ResultadoRedis::Vector(posiciones.collect()),
+        Some(cantidad) => ResultadoRedis::Vector(posiciones.take(cantidad).collect()),
+    }
+}
+
+/// Interpreta el extremo de una lista indicado como LEFT o RIGHT. Devuelve verdadero si es el derecho
+fn es_derecha(extremo: &[u8]) -> Option<bool> {
+    if extremo.eq_ignore_ascii_case(b"LEFT") {
+        Some(false)
+    } else if extremo.eq_ignore_ascii_case(b"RIGHT") {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Busca, en el orden indicado, la primera clave que tenga una lista con elementos. Devuelve error si alguna clave revisada no es una lista
+fn primera_lista_con_elementos(
+    bdd: &BaseDeDatos,
+    claves: &[Vec<u8>],
+) -> Result<Option<Vec<u8>>, ResultadoRedis> {
+    for clave in claves {
+        match bdd.obtener_valor(clave) {
+            Some(TipoRedis::Lista(lista)) if !lista.is_empty() => return Ok(Some(clave.clone())),
+            Some(TipoRedis::Lista(_)) | None => (),
             _ => {
-                return ResultadoRedis::Error(
+                return Err(ResultadoRedis::Error(
                     "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
+                ))
             }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-
-    let tamanio = lista.len() as i32;
-
-    if 0 <= indice && indice < tamanio {
-        lista.insert(indice as usize, parametro);
-    } else if 0 > indice && tamanio + indice >= 0 {
-        lista.insert((tamanio + indice) as usize as usize, parametro);
-    } else {
-        return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string());
+        }
     }
+    Ok(None)
+}
 
-    match bdd.lock() {
-        Ok(mut bdd) => bdd.guardar_valor(clave, TipoRedis::Lista(lista)),
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+/// Saca hasta la cantidad indicada de elementos de un extremo de la lista, eliminando la clave si queda vacia.
+/// Notifica el pop y, si corresponde, la eliminacion de la clave
+fn sacar_elementos(
+    bdd: &mut BaseDeDatos,
+    clave: &[u8],
+    cantidad: usize,
+    desde_derecha: bool,
+) -> Vec<Vec<u8>> {
+    let sacados = bdd.modificar_lista(clave, |lista| {
+        let cantidad = cantidad.min(lista.len());
+        let elementos: Vec<Vec<u8>> = if desde_derecha {
+            lista.drain(lista.len() - cantidad..).rev().collect()
+        } else {
+            lista.drain(..cantidad).collect()
+        };
+        (elementos, lista.is_empty())
+    });
+    let (elementos, vacia) = match sacados {
+        Some(sacados) => sacados,
+        None => return vec![],
+    };
+    let evento = if desde_derecha { "rpop" } else { "lpop" };
+    bdd.notificar(ClaseEvento::Lista, evento, clave);
+    if vacia {
+        bdd.eliminar_clave(clave);
+        bdd.notificar(ClaseEvento::Generico, "del", clave);
     }
-    ResultadoRedis::StrSimple("OK".to_string())
+    elementos
 }
 
 #[cfg(test)]
@@ -576,7 +1039,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -587,7 +1050,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("2".to_string()),
+            ResultadoRedis::BulkStr("2".into()),
             lindex(&mut comando, ptr)
         );
     }
@@ -597,7 +1060,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -608,7 +1071,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("2".to_string()),
+            ResultadoRedis::BulkStr("2".into()),
             lindex(&mut comando, ptr)
         );
     }
@@ -618,7 +1081,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -629,7 +1092,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("nil".to_string()),
+            ResultadoRedis::BulkStr("nil".into()),
             lindex(&mut comando, ptr)
         );
     }
@@ -647,7 +1110,7 @@ mod tests {
     #[test]
     fn llen_si_se_llama_llen_a_un_string_se_devuelve_un_error_de_tipo() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("milista".to_string(), TipoRedis::Str("hola".to_string()));
+        data_base.guardar_valor("milista".to_string(), TipoRedis::Str("hola".into()));
 
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -666,12 +1129,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec![
-                "1".to_string(),
-                "2".to_string(),
-                "3".to_string(),
-                "4".to_string(),
-            ]),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into(), "4".into()].into()),
         );
 
         let ptr = Arc::new(Mutex::new(data_base));
@@ -698,14 +1156,14 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["unvalor".to_string()]),
+            TipoRedis::Lista(vec!["unvalor".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["lpop".to_string(), "milista".to_string()]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("unvalor".to_string()),
+            ResultadoRedis::BulkStr("unvalor".into()),
             lpop(&mut comando, Arc::clone(&ptr))
         );
         assert_eq!(ResultadoRedis::Nil, lpop(&mut comando, ptr));
@@ -715,7 +1173,7 @@ mod tests {
     fn lpop_si_se_llama_sobre_un_tipo_distinto_a_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["lpop".to_string(), "clave".to_string()]);
@@ -734,16 +1192,13 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["lpop".to_string(), "milista".to_string()]);
 
-        assert_eq!(
-            ResultadoRedis::BulkStr("1".to_string()),
-            lpop(&mut comando, ptr)
-        );
+        assert_eq!(ResultadoRedis::BulkStr("1".into()), lpop(&mut comando, ptr));
     }
 
     #[test]
@@ -752,7 +1207,7 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -764,8 +1219,8 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("1".to_string()),
-                ResultadoRedis::BulkStr("2".to_string())
+                ResultadoRedis::BulkStr("1".into()),
+                ResultadoRedis::BulkStr("2".into())
             ]),
             lpop(&mut comando, ptr)
         );
@@ -775,7 +1230,7 @@ mod tests {
     fn lpush_si_se_pushea_a_alguna_clave_existente_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -832,7 +1287,7 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec!["c".to_string(), "b".to_string(), "a".to_string()]),
+            TipoRedis::Lista(vec!["c".into(), "b".into(), "a".into()].into()),
             lista,
         );
     }
@@ -842,7 +1297,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["d".to_string()]),
+            TipoRedis::Lista(vec!["d".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -864,12 +1319,7 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "c".to_string(),
-                "b".to_string(),
-                "a".to_string(),
-                "d".to_string()
-            ]),
+            TipoRedis::Lista(vec!["c".into(), "b".into(), "a".into(), "d".into()].into()),
             lista,
         );
     }
@@ -878,7 +1328,7 @@ mod tests {
     fn lpushx_si_se_pushea_a_alguna_clave_existente_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -917,7 +1367,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["d".to_string()]),
+            TipoRedis::Lista(vec!["d".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -942,12 +1392,7 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "c".to_string(),
-                "b".to_string(),
-                "a".to_string(),
-                "d".to_string()
-            ]),
+            TipoRedis::Lista(vec!["c".into(), "b".into(), "a".into(), "d".into()].into()),
             lista,
         );
     }
@@ -956,7 +1401,7 @@ mod tests {
     fn lrange_si_se_lo_llama_sobre_algo_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -999,7 +1444,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["0".to_string(), "1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["0".into(), "1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1012,8 +1457,8 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("1".to_string()),
-                ResultadoRedis::BulkStr("2".to_string())
+                ResultadoRedis::BulkStr("1".into()),
+                ResultadoRedis::BulkStr("2".into())
             ]),
             lrange(&mut comando, ptr)
         );
@@ -1024,7 +1469,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["0".to_string(), "1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["0".into(), "1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1037,9 +1482,9 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("0".to_string()),
-                ResultadoRedis::BulkStr("1".to_string()),
-                ResultadoRedis::BulkStr("2".to_string())
+                ResultadoRedis::BulkStr("0".into()),
+                ResultadoRedis::BulkStr("1".into()),
+                ResultadoRedis::BulkStr("2".into())
             ]),
             lrange(&mut comando, ptr)
         );
@@ -1051,7 +1496,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["0".to_string(), "1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["0".into(), "1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1063,7 +1508,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("0".to_string())]),
+            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("0".into())]),
             lrange(&mut comando, ptr)
         );
     }
@@ -1073,7 +1518,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec!["0".to_string(), "1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["0".into(), "1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1090,7 +1535,7 @@ mod tests {
     #[test]
     fn lrem_si_se_pide_eliminar_de_una_clave_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -1128,13 +1573,16 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec![
-                "hola".to_string(),
-                "que".to_string(),
-                "hola".to_string(),
-                "dame".to_string(),
-                "hola".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec![
+                    "hola".into(),
+                    "que".into(),
+                    "hola".into(),
+                    "dame".into(),
+                    "hola".into(),
+                ]
+                .into(),
+            ),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1148,11 +1596,7 @@ mod tests {
         assert_eq!(ResultadoRedis::Int(2), lrem(&mut comando, Arc::clone(&ptr)));
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "que".to_string(),
-                "dame".to_string(),
-                "hola".to_string()
-            ]),
+            TipoRedis::Lista(vec!["que".into(), "dame".into(), "hola".into()].into()),
             ptr.lock().unwrap().obtener_valor("clave").unwrap().clone()
         );
     }
@@ -1162,14 +1606,17 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "clave".to_string(),
-            TipoRedis::Lista(vec![
-                "hola".to_string(),
-                "que".to_string(),
-                "hola".to_string(),
-                "dame".to_string(),
-                "hola".to_string(),
-                "pepe".to_string(),
-            ]),
+            TipoRedis::Lista(
+                vec![
+                    "hola".into(),
+                    "que".into(),
+                    "hola".into(),
+                    "dame".into(),
+                    "hola".into(),
+                    "pepe".into(),
+                ]
+                .into(),
+            ),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1183,12 +1630,9 @@ mod tests {
         assert_eq!(ResultadoRedis::Int(2), lrem(&mut comando, Arc::clone(&ptr)));
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "hola".to_string(),
-                "que".to_string(),
-                "dame".to_string(),
-                "pepe".to_string()
-            ]),
+            TipoRedis::Lista(
+                vec!["hola".into(), "que".into(), "dame".into(), "pepe".into()].into()
+            ),
             ptr.lock().unwrap().obtener_valor("clave").unwrap().clone()
         );
     }
@@ -1197,7 +1641,7 @@ mod tests {
     fn lset_si_se_lo_llama_sobre_algo_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -1216,10 +1660,13 @@ mod tests {
     }
 
     #[test]
-    fn lset_inserta_en_la_lista_adecuadamente() {
+    fn lset_reemplaza_el_elemento_de_la_posicion() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Lista(vec!["a".to_string()]));
+        data_base.guardar_valor(
+            "clave".to_string(),
+            TipoRedis::Lista(vec!["a".into()].into()),
+        );
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -1235,10 +1682,56 @@ mod tests {
 
         let lista = ptr.lock().unwrap().obtener_valor("clave").unwrap().clone();
 
+        assert_eq!(TipoRedis::Lista(vec!["b".into()].into()), lista);
+    }
+
+    #[test]
+    fn lrem_con_cero_elimina_todas_las_apariciones() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "clave".to_string(),
+            TipoRedis::Lista(vec!["a".into(), "b".into(), "a".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["lrem", "clave", "0", "a"]);
+        assert_eq!(ResultadoRedis::Int(2), lrem(&mut comando, Arc::clone(&ptr)));
+        assert_eq!(
+            TipoRedis::Lista(vec!["b".into()].into()),
+            ptr.lock().unwrap().obtener_valor("clave").unwrap().clone()
+        );
+    }
+
+    #[test]
+    fn lrem_lset_y_linsert_conservan_la_expiracion_de_la_lista() {
+        let mut data_base = BaseDeDatos::new();
+        let vencimiento = crate::valor::ahora_ms() + 100_000;
+        data_base.guardar_valor_con_vencimiento(
+            "clave",
+            vencimiento,
+            TipoRedis::Lista(vec!["a".into(), "b".into(), "c".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["lset", "clave", "-1", "d"]);
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            lset(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["linsert", "clave", "BEFORE", "b", "x"]);
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            linsert(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["lrem", "clave", "1", "a"]);
+        assert_eq!(ResultadoRedis::Int(1), lrem(&mut comando, Arc::clone(&ptr)));
+
+        let bdd = ptr.lock().unwrap();
         assert_eq!(
-            TipoRedis::Lista(vec!["b".to_string(), "a".to_string()]),
-            lista
+            TipoRedis::Lista(vec!["x".into(), "b".into(), "d".into()].into()),
+            bdd.obtener_valor("clave").unwrap().clone()
         );
+        assert_eq!(Some(Some(vencimiento)), bdd.obtener_vencimiento("clave"));
     }
 
     #[test]
@@ -1258,14 +1751,14 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["unvalor".to_string()]),
+            TipoRedis::Lista(vec!["unvalor".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["rpop".to_string(), "milista".to_string()]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("unvalor".to_string()),
+            ResultadoRedis::BulkStr("unvalor".into()),
             rpop(&mut comando, Arc::clone(&ptr))
         );
         assert_eq!(ResultadoRedis::Nil, rpop(&mut comando, ptr));
@@ -1275,7 +1768,7 @@ mod tests {
     fn rpop_si_se_llama_sobre_un_tipo_distinto_a_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["rpop".to_string(), "clave".to_string()]);
@@ -1294,16 +1787,13 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec!["rpop".to_string(), "milista".to_string()]);
 
-        assert_eq!(
-            ResultadoRedis::BulkStr("2".to_string()),
-            rpop(&mut comando, ptr)
-        );
+        assert_eq!(ResultadoRedis::BulkStr("2".into()), rpop(&mut comando, ptr));
     }
 
     #[test]
@@ -1312,7 +1802,7 @@ mod tests {
 
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1324,8 +1814,8 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("2".to_string()),
-                ResultadoRedis::BulkStr("3".to_string())
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("2".into())
             ]),
             rpop(&mut comando, ptr)
         );
@@ -1335,7 +1825,7 @@ mod tests {
     fn rpush_si_se_pushea_a_alguna_clave_existente_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -1392,7 +1882,7 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+            TipoRedis::Lista(vec!["a".into(), "b".into(), "c".into()].into()),
             lista,
         );
     }
@@ -1402,7 +1892,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["d".to_string()]),
+            TipoRedis::Lista(vec!["d".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1424,12 +1914,7 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "d".to_string(),
-                "a".to_string(),
-                "b".to_string(),
-                "c".to_string()
-            ]),
+            TipoRedis::Lista(vec!["d".into(), "a".into(), "b".into(), "c".into()].into()),
             lista,
         );
     }
@@ -1438,7 +1923,7 @@ mod tests {
     fn rpushx_si_se_pushea_a_alguna_clave_existente_que_no_es_una_lista_devuelve_wrong_type() {
         let mut data_base = BaseDeDatos::new();
 
-        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".to_string()));
+        data_base.guardar_valor("clave".to_string(), TipoRedis::Str("".into()));
         let ptr = Arc::new(Mutex::new(data_base));
 
         let mut comando = ComandoInfo::new(vec![
@@ -1477,7 +1962,7 @@ mod tests {
         let mut data_base = BaseDeDatos::new();
         data_base.guardar_valor(
             "milista".to_string(),
-            TipoRedis::Lista(vec!["d".to_string()]),
+            TipoRedis::Lista(vec!["d".into()].into()),
         );
         let ptr = Arc::new(Mutex::new(data_base));
 
@@ -1502,13 +1987,370 @@ mod tests {
             .clone();
 
         assert_eq!(
-            TipoRedis::Lista(vec![
-                "d".to_string(),
-                "a".to_string(),
-                "b".to_string(),
-                "c".to_string()
-            ]),
+            TipoRedis::Lista(vec!["d".into(), "a".into(), "b".into(), "c".into()].into()),
             lista,
         );
     }
+
+    #[test]
+    fn blpop_saca_de_la_primera_lista_con_elementos_y_devuelve_la_clave() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "b".to_string(),
+            TipoRedis::Lista(vec!["1".into(), "2".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["blpop", "a", "b", "0.5"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::BulkStr("1".into()),
+            ]),
+            blpop(&mut comando, Arc::clone(&ptr))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["brpop", "a", "b", "0"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::BulkStr("2".into()),
+            ]),
+            brpop(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(None, ptr.lock().unwrap().obtener_valor("b"));
+
+        let mut comando = ComandoInfo::new(vec!["blpop", "a", "b", "0"]);
+        assert_eq!(ResultadoRedis::Null, blpop(&mut comando, ptr));
+    }
+
+    #[test]
+    fn blpop_con_timeout_invalido_devuelve_error() {
+        let ptr = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        let mut comando = ComandoInfo::new(vec!["blpop", "a", "-1"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR timeout is negative".to_string()),
+            blpop(&mut comando, ptr)
+        );
+    }
+
+    #[test]
+    fn blmove_mueve_el_elemento_entre_los_extremos_indicados() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "origen".to_string(),
+            TipoRedis::Lista(vec!["a".into(), "b".into()].into()),
+        );
+        data_base.guardar_valor(
+            "destino".to_string(),
+            TipoRedis::Lista(vec!["x".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando =
+            ComandoInfo::new(vec!["blmove", "origen", "destino", "RIGHT", "LEFT", "0"]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("b".into()),
+            blmove(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(
+            Some(&TipoRedis::Lista(vec!["b".into(), "x".into()].into())),
+            ptr.lock().unwrap().obtener_valor("destino")
+        );
+
+        let mut comando =
+            ComandoInfo::new(vec!["blmove", "origen", "origen", "LEFT", "RIGHT", "0"]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("a".into()),
+            blmove(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(
+            Some(&TipoRedis::Lista(vec!["a".into()].into())),
+            ptr.lock().unwrap().obtener_valor("origen")
+        );
+    }
+
+    #[test]
+    fn blmpop_saca_la_cantidad_indicada_de_la_primera_lista_con_elementos() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "b".to_string(),
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando =
+            ComandoInfo::new(vec!["blmpop", "0", "2", "a", "b", "RIGHT", "COUNT", "2"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::Vector(vec![
+                    ResultadoRedis::BulkStr("3".into()),
+                    ResultadoRedis::BulkStr("2".into()),
+                ]),
+            ]),
+            blmpop(&mut comando, Arc::clone(&ptr))
+        );
+
+        let mut comando = ComandoInfo::new(vec!["blmpop", "0", "0", "a", "LEFT"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR numkeys should be greater than 0".to_string()),
+            blmpop(&mut comando, ptr)
+        );
+    }
+
+    fn lista(ptr: &Arc<Mutex<BaseDeDatos>>, clave: &str) -> Option<TipoRedis> {
+        ptr.lock().unwrap().obtener_valor(clave).cloned()
+    }
+
+    fn lista_de(elementos: &[&str]) -> Option<TipoRedis> {
+        Some(TipoRedis::Lista(
+            elementos.iter().map(|e| e.as_bytes().to_vec()).collect(),
+        ))
+    }
+
+    #[test]
+    fn pop_con_cantidad_devuelve_un_vector_y_nulo_si_la_clave_no_existe() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "milista",
+            TipoRedis::Lista(vec!["1".into(), "2".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["lpop", "milista", "0"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![]),
+            lpop(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["rpop", "milista", "1"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("2".into())]),
+            rpop(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["lpop", "milista", "5"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::BulkStr("1".into())]),
+            lpop(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["lpop", "milista", "5"]);
+        assert_eq!(ResultadoRedis::Null, lpop(&mut comando, Arc::clone(&ptr)));
+        let mut comando = ComandoInfo::new(vec!["lpop", "milista", "-1"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is out of range, must be positive".to_string()),
+            lpop(&mut comando, ptr)
+        );
+    }
+
+    #[test]
+    fn linsert_inserta_junto_al_pivote_o_indica_que_no_lo_encontro() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "milista",
+            TipoRedis::Lista(vec!["a".into(), "c".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["linsert", "milista", "BEFORE", "c", "b"]);
+        assert_eq!(
+            ResultadoRedis::Int(3),
+            linsert(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["linsert", "milista", "after", "c", "d"]);
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            linsert(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(lista_de(&["a", "b", "c", "d"]), lista(&ptr, "milista"));
+
+        let mut comando = ComandoInfo::new(vec!["linsert", "milista", "BEFORE", "z", "b"]);
+        assert_eq!(
+            ResultadoRedis::Int(-1),
+            linsert(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["linsert", "otra", "BEFORE", "a", "b"]);
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            linsert(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["linsert", "milista", "ENTRE", "a", "b"]);
+        assert_eq!(
+            ResultadoRedis::Error("ERR syntax error".to_string()),
+            linsert(&mut comando, ptr)
+        );
+    }
+
+    #[test]
+    fn ltrim_recorta_la_lista_y_la_elimina_si_el_rango_queda_vacio() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "milista",
+            TipoRedis::Lista(vec!["1".into(), "2".into(), "3".into(), "4".into()].into()),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["ltrim", "milista", "1", "-1"]);
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            ltrim(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(lista_de(&["2", "3", "4"]), lista(&ptr, "milista"));
+
+        let mut comando = ComandoInfo::new(vec!["ltrim", "milista", "-2", "100"]);
+        ltrim(&mut comando, Arc::clone(&ptr));
+        assert_eq!(lista_de(&["3", "4"]), lista(&ptr, "milista"));
+
+        let mut comando = ComandoInfo::new(vec!["ltrim", "milista", "5", "10"]);
+        ltrim(&mut comando, Arc::clone(&ptr));
+        assert_eq!(None, lista(&ptr, "milista"));
+    }
+
+    #[test]
+    fn lpos_respeta_rank_count_y_maxlen() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "milista",
+            TipoRedis::Lista(
+                ["a", "b", "c", "1", "2", "3", "c", "c"]
+                    .iter()
+                    .map(|e| e.as_bytes().to_vec())
+                    .collect(),
+            ),
+        );
+        let ptr = Arc::new(Mutex::new(data_base));
+        let posiciones = |v: &[isize]| {
+            ResultadoRedis::Vector(v.iter().map(|&i| ResultadoRedis::Int(i)).collect())
+        };
+        let ejecutar = |argumentos: Vec<&str>| {
+            let mut comando = ComandoInfo::new(argumentos);
+            lpos(&mut comando, Arc::clone(&ptr))
+        };
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            ejecutar(vec!["lpos", "milista", "c"])
+        );
+        assert_eq!(
+            ResultadoRedis::Int(6),
+            ejecutar(vec!["lpos", "milista", "c", "RANK", "2"])
+        );
+        assert_eq!(
+            ResultadoRedis::Int(6),
+            ejecutar(vec!["lpos", "milista", "c", "RANK", "-2"])
+        );
+        assert_eq!(
+            posiciones(&[2, 6, 7]),
+            ejecutar(vec!["lpos", "milista", "c", "COUNT", "0"])
+        );
+        assert_eq!(
+            posiciones(&[7, 6]),
+            ejecutar(vec!["lpos", "milista", "c", "RANK", "-1", "COUNT", "2"])
+        );
+        assert_eq!(
+            posiciones(&[2]),
+            ejecutar(vec!["lpos", "milista", "c", "COUNT", "0", "MAXLEN", "6"])
+        );
+        assert_eq!(ResultadoRedis::Nil, ejecutar(vec!["lpos", "milista", "z"]));
+        assert_eq!(
+            posiciones(&[]),
+            ejecutar(vec!["lpos", "otra", "z", "COUNT", "1"])
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR COUNT can't be negative".to_string()),
+            ejecutar(vec!["lpos", "milista", "c", "COUNT", "-1"])
+        );
+    }
+
+    #[test]
+    fn lmove_y_rpoplpush_mueven_elementos_y_devuelven_nil_sin_origen() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor(
+            "origen",
+            TipoRedis::Lista(vec!["a".into(), "b".into()].into()),
+        );
+        data_base.guardar_valor("texto", TipoRedis::Str("hola".into()));
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["rpoplpush", "origen", "destino"]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("b".into()),
+            rpoplpush(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["lmove", "origen", "destino", "LEFT", "RIGHT"]);
+        assert_eq!(
+            ResultadoRedis::BulkStr("a".into()),
+            lmove(&mut comando, Arc::clone(&ptr))
+        );
+        assert_eq!(None, lista(&ptr, "origen"));
+        assert_eq!(lista_de(&["b", "a"]), lista(&ptr, "destino"));
+
+        let mut comando = ComandoInfo::new(vec!["lmove", "origen", "destino", "LEFT", "RIGHT"]);
+        assert_eq!(ResultadoRedis::Nil, lmove(&mut comando, Arc::clone(&ptr)));
+        let mut comando = ComandoInfo::new(vec!["lmove", "destino", "texto", "LEFT", "RIGHT"]);
+        assert_eq!(
+            ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ),
+            lmove(&mut comando, ptr)
+        );
+    }
+
+    #[test]
+    fn lmpop_saca_de_la_primera_lista_con_elementos_y_devuelve_nulo_si_no_hay() {
+        let mut data_base = BaseDeDatos::new();
+        data_base.guardar_valor("b", TipoRedis::Lista(vec!["1".into(), "2".into()].into()));
+        let ptr = Arc::new(Mutex::new(data_base));
+
+        let mut comando = ComandoInfo::new(vec!["lmpop", "2", "a", "b", "LEFT", "COUNT", "5"]);
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("b".into()),
+                ResultadoRedis::Vector(vec![
+                    ResultadoRedis::BulkStr("1".into()),
+                    ResultadoRedis::BulkStr("2".into()),
+                ]),
+            ]),
+            lmpop(&mut comando, Arc::clone(&ptr))
+        );
+        let mut comando = ComandoInfo::new(vec!["lmpop", "2", "a", "b", "RIGHT"]);
+        assert_eq!(ResultadoRedis::Null, lmpop(&mut comando, ptr));
+    }
+
+    /// Mide cuantas operaciones por segundo logran LPUSH y LPOP sobre listas de distintos largos. Se ejecuta con
+    /// `cargo test --release benchmark_lpush_lpop -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn benchmark_lpush_lpop_en_listas_grandes() {
+        let operaciones = 10_000;
+        for &largo in &[1_000, 10_000, 100_000, 1_000_000] {
+            let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+            let elementos: Vec<String> = (0..largo).map(|i| i.to_string()).collect();
+            let mut rpush = vec!["RPUSH", "cola"];
+            rpush.extend(elementos.iter().map(|e| e.as_str()));
+            Box::new(ComandoListHandler::new(ComandoInfo::new(rpush))).ejecutar(Arc::clone(&bdd));
+
+            let ejecutar = |argumentos: Vec<&str>| {
+                Box::new(ComandoListHandler::new(ComandoInfo::new(argumentos)))
+                    .ejecutar(Arc::clone(&bdd))
+            };
+            let inicio = std::time::Instant::now();
+            for _ in 0..operaciones {
+                ejecutar(vec!["LPUSH", "cola", "nuevo"]);
+            }
+            let lpush = inicio.elapsed();
+            let inicio = std::time::Instant::now();
+            for _ in 0..operaciones {
+                ejecutar(vec!["LPOP", "cola"]);
+            }
+            let lpop = inicio.elapsed();
+
+            println!(
+                "largo {:>7}: LPUSH {:>10.0} ops/s, LPOP {:>10.0} ops/s",
+                largo,
+                operaciones as f64 / lpush.as_secs_f64(),
+                operaciones as f64 / lpop.as_secs_f64()
+            );
+        }
+    }
 }
@@ -1,8 +1,8 @@
-use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
-use crate::canal::Canal;
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
 use crate::cliente::Cliente;
 use crate::comando::ComandoHandler;
 use crate::comando_info::ComandoInfo;
+use crate::parser::Protocolo;
 use std::sync::{Arc, Mutex};
 
 pub type ComandoConCliente =
@@ -20,6 +20,8 @@ impl ComandoPubSubHandler {
             "UNSUBSCRIBE" => unsubscribe,
             "PUBLISH" => publish,
             "PUBSUB" => pubsub,
+            "PSUBSCRIBE" => psubscribe,
+            "PUNSUBSCRIBE" => punsubscribe,
             _ => subscribe,
         };
         ComandoPubSubHandler {
@@ -37,38 +39,68 @@ impl ComandoHandler for ComandoPubSubHandler {
 }
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo pubsub
 pub fn es_comando_pubsub(comando: &str) -> bool {
-    let comandos = vec!["SUBSCRIBE", "UNSUBSCRIBE", "PUBLISH", "PUBSUB"];
+    let comandos = vec![
+        "SUBSCRIBE",
+        "UNSUBSCRIBE",
+        "PUBLISH",
+        "PUBSUB",
+        "PSUBSCRIBE",
+        "PUNSUBSCRIBE",
+    ];
     comandos.iter().any(|&c| c == comando)
 }
+/// Respuesta a un comando recibido de un cliente suscrito que usa RESP2. En ese modo solo se
+/// admiten los comandos de suscripcion, PING y RESET, y PING responde con un mensaje push.
+/// Devuelve ninguna si el comando se puede ejecutar normalmente
+pub fn responder_en_modo_suscripcion(
+    comando: &ComandoInfo,
+    cliente: &Cliente,
+) -> Option<ResultadoRedis> {
+    // En RESP3 los mensajes push se distinguen de las respuestas, por lo que se admite cualquier comando
+    if cliente.obtener_suscripciones() == 0 || cliente.obtener_protocolo() == Protocolo::Resp3 {
+        return None;
+    }
+    let nombre = comando.get_nombre();
+    match nombre.as_str() {
+        "SUBSCRIBE" | "UNSUBSCRIBE" | "PSUBSCRIBE" | "PUNSUBSCRIBE" | "RESET" => None,
+        "PING" => {
+            let mensaje = comando.argumentos().get(1).cloned().unwrap_or_default();
+            Some(ResultadoRedis::Push(vec![
+                ResultadoRedis::BulkStr(b"pong".to_vec()),
+                ResultadoRedis::BulkStr(mensaje),
+            ]))
+        }
+        _ => Some(ResultadoRedis::Error(format!(
+            "ERR Can't execute '{}': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / RESET are allowed in this context",
+            nombre.to_lowercase()
+        ))),
+    }
+}
 /// Suscribe al cliente al canal especificado
 fn subscribe(
     comando: &mut ComandoInfo,
     cliente: Cliente,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    while let Some(clave) = comando.get_parametro() {
-        let mut canal = match bdd.lock() {
-            Ok(bdd) => match bdd.obtener_valor(&clave) {
-                Some(TipoRedis::Canal(c)) => c.clone(),
-                None => Canal::new(clave.clone()),
-                _ => {
-                    return ResultadoRedis::Error(
-                        "WRONGTYPE Operation against a key holding the wrong kind of value"
-                            .to_string(),
-                    )
-                }
-            },
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-        };
-
-        canal.suscribirse(cliente.clone());
+    let mut canales = vec![];
+    while let Some(canal) = comando.get_parametro() {
+        canales.push(canal);
+    }
+    if canales.is_empty() {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'subscribe' command".to_string(),
+        );
+    }
 
-        match bdd.lock() {
-            Ok(mut bdd) => bdd.guardar_valor(clave, TipoRedis::Canal(canal)),
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            for canal in canales {
+                bdd.pubsub().suscribir(canal, cliente.clone());
+            }
+            ResultadoRedis::Vacio
         }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
-    ResultadoRedis::Vacio
 }
 /// Desuscribe al cliente de los canales indicados, si no se indica ninguno, lo desuscribe de todos
 fn unsubscribe(
@@ -76,28 +108,23 @@ fn unsubscribe(
     cliente: Cliente,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    while let Some(clave) = comando.get_parametro() {
-        let mut canal = match bdd.lock() {
-            Ok(bdd) => match bdd.obtener_valor(&clave) {
-                Some(TipoRedis::Canal(c)) => c.clone(),
-                _ => {
-                    return ResultadoRedis::Error(
-                        "WRONGTYPE Operation against a key holding the wrong kind of value"
-                            .to_string(),
-                    )
-                }
-            },
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-        };
-
-        canal.desuscribirse(cliente.clone());
+    let mut canales = vec![];
+    while let Some(canal) = comando.get_parametro() {
+        canales.push(canal);
+    }
 
-        match bdd.lock() {
-            Ok(mut bdd) => bdd.guardar_valor(clave, TipoRedis::Canal(canal)),
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            if canales.is_empty() {
+                bdd.pubsub().desuscribir_todos(cliente.clone());
+            }
+            for canal in canales {
+                bdd.pubsub().desuscribir(canal, cliente.clone());
+            }
+            ResultadoRedis::Vacio
         }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
-    ResultadoRedis::Vacio
 }
 /// Envía (publica) un mensaje en un canal dado
 fn publish(
@@ -119,18 +146,60 @@ fn publish(
         }
     };
 
-    let mut canal = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Canal(c)) => c.clone(),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
+    match bdd.lock() {
+        Ok(mut bdd) => ResultadoRedis::Int(bdd.pubsub().publicar(&clave, &mensaje) as isize),
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Suscribe al cliente a los patrones indicados. Recibira los mensajes publicados en todos los canales que coincidan con alguno
+fn psubscribe(
+    comando: &mut ComandoInfo,
+    cliente: Cliente,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    let mut patrones = vec![];
+    while let Some(patron) = comando.get_parametro() {
+        patrones.push(patron);
+    }
+    if patrones.is_empty() {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'psubscribe' command".to_string(),
+        );
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            for patron in patrones {
+                bdd.pubsub().suscribir_patron(patron, cliente.clone());
             }
-        },
-        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    };
-    ResultadoRedis::Int(canal.publicar(mensaje) as isize)
+            ResultadoRedis::Vacio
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Desuscribe al cliente de los patrones indicados, si no se indica ninguno, lo desuscribe de todos
+fn punsubscribe(
+    comando: &mut ComandoInfo,
+    cliente: Cliente,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    let mut patrones = vec![];
+    while let Some(patron) = comando.get_parametro() {
+        patrones.push(patron);
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            if patrones.is_empty() {
+                bdd.pubsub().desuscribir_todos_los_patrones(cliente.clone());
+            }
+            for patron in patrones {
+                bdd.pubsub().desuscribir_patron(patron, cliente.clone());
+            }
+            ResultadoRedis::Vacio
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
 }
 /// es un comando de introspección que permite inspeccionar el estado del subsistema Pub / Sub. Está compuesto por subcomandos que se documentan por separado
 fn pubsub(
@@ -147,9 +216,10 @@ fn pubsub(
         }
     };
 
-    match clave.as_str() {
-        "CHANNELS" => channels(comando, _cliente, bdd),
-        "NUMSUB" => numsub(comando, _cliente, bdd),
+    match clave.as_slice() {
+        b"CHANNELS" => channels(comando, _cliente, bdd),
+        b"NUMSUB" => numsub(comando, _cliente, bdd),
+        b"NUMPAT" => numpat(bdd),
         _ => {
             ResultadoRedis::Error("ERR wrong number of arguments for 'pubsub' command".to_string())
         }
@@ -161,22 +231,16 @@ fn channels(
     _cliente: Cliente,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
-    let parametro = match comando.get_parametro() {
-        Some(p) => p,
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'channels' command".to_string(),
-            )
-        }
-    };
-    let canales: Vec<String> = match bdd.lock() {
-        Ok(bdd) => bdd.canales_activos(&parametro),
+    // Sin patron se listan todos los canales activos
+    let patron = comando.get_parametro().unwrap_or_else(|| b"*".to_vec());
+    let canales: Vec<Vec<u8>> = match bdd.lock() {
+        Ok(mut bdd) => bdd.pubsub().canales_activos(&patron),
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
     ResultadoRedis::Vector(
         canales
             .iter()
-            .map(|s| ResultadoRedis::BulkStr(s.to_string()))
+            .map(|s| ResultadoRedis::BulkStr(s.clone()))
             .collect(),
     )
 }
@@ -186,22 +250,172 @@ fn numsub(
     _cliente: Cliente,
     bdd: Arc<Mutex<BaseDeDatos>>,
 ) -> ResultadoRedis {
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
     let mut cantidades = Vec::new();
-    while let Some(clave) = comando.get_parametro() {
-        let canal = match bdd.lock() {
-            Ok(bdd) => match bdd.obtener_valor(&clave) {
-                Some(TipoRedis::Canal(c)) => c.clone(),
-                _ => {
-                    return ResultadoRedis::Error(
-                        "WRONGTYPE Operation against a key holding the wrong kind of value"
-                            .to_string(),
-                    )
-                }
-            },
-            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
-        };
-
-        cantidades.push(canal.len() as isize);
+    while let Some(canal) = comando.get_parametro() {
+        cantidades.push(bdd.pubsub().cantidad_suscriptores(&canal) as isize);
     }
     ResultadoRedis::Vector(cantidades.iter().map(|i| ResultadoRedis::Int(*i)).collect())
 }
+/// Devuelve la cantidad de patrones a los que hay clientes suscritos
+fn numpat(bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    match bdd.lock() {
+        Ok(mut bdd) => ResultadoRedis::Int(bdd.pubsub().cantidad_patrones() as isize),
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_de_datos::TipoRedis;
+    use crate::prueba::cliente_de_prueba;
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    fn ejecutar(
+        argumentos: &[&str],
+        cliente: &Cliente,
+        bdd: &Arc<Mutex<BaseDeDatos>>,
+    ) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        let handler = Box::new(ComandoPubSubHandler::new(comando, cliente.clone()));
+        handler.ejecutar(Arc::clone(bdd))
+    }
+
+    fn leer(conexion: &mut TcpStream) -> String {
+        let mut buffer = [0; 512];
+        let leidos = conexion.read(&mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..leidos]).to_string()
+    }
+
+    #[test]
+    fn publish_envia_pmessage_a_los_patrones_que_coinciden_y_los_cuenta() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let (suscriptor, mut conexion) = cliente_de_prueba(1);
+        let (publicador, _otra) = cliente_de_prueba(2);
+
+        ejecutar(&["PSUBSCRIBE", "orders.*", "users.*"], &suscriptor, &bdd);
+        assert_eq!(
+            "*3\r\n$10\r\npsubscribe\r\n$8\r\norders.*\r\n:1\r\n*3\r\n$10\r\npsubscribe\r\n$7\r\nusers.*\r\n:2\r\n",
+            leer(&mut conexion)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            ejecutar(&["PUBSUB", "NUMPAT"], &publicador, &bdd)
+        );
+
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["PUBLISH", "orders.nueva", "hola"], &publicador, &bdd)
+        );
+        assert_eq!(
+            "*4\r\n$8\r\npmessage\r\n$8\r\norders.*\r\n$12\r\norders.nueva\r\n$4\r\nhola\r\n",
+            leer(&mut conexion)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["PUBLISH", "otro", "hola"], &publicador, &bdd)
+        );
+    }
+
+    #[test]
+    fn punsubscribe_sin_argumentos_quita_todos_los_patrones_del_cliente() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let (suscriptor, _conexion) = cliente_de_prueba(1);
+        let (otro, _otra) = cliente_de_prueba(2);
+
+        ejecutar(&["PSUBSCRIBE", "a*", "b*"], &suscriptor, &bdd);
+        ejecutar(&["PSUBSCRIBE", "b*"], &otro, &bdd);
+        ejecutar(&["PUNSUBSCRIBE"], &suscriptor, &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["PUBSUB", "NUMPAT"], &otro, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["PUBLISH", "bb", "hola"], &otro, &bdd)
+        );
+    }
+
+    #[test]
+    fn los_canales_no_forman_parte_de_las_claves() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let (suscriptor, _conexion) = cliente_de_prueba(1);
+        let (publicador, _otra) = cliente_de_prueba(2);
+
+        ejecutar(&["SUBSCRIBE", "noticias"], &suscriptor, &bdd);
+        assert_eq!(0, bdd.lock().unwrap().cantidad_claves());
+
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("noticias", TipoRedis::Str("valor".into()));
+        bdd.lock().unwrap().borrar_claves();
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["PUBLISH", "noticias", "hola"], &publicador, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(1)]),
+            ejecutar(&["PUBSUB", "NUMSUB", "noticias"], &publicador, &bdd)
+        );
+    }
+
+    #[test]
+    fn unsubscribe_sin_argumentos_quita_todos_los_canales_e_informa_las_suscripciones_restantes() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let (suscriptor, mut conexion) = cliente_de_prueba(1);
+
+        ejecutar(&["SUBSCRIBE", "uno"], &suscriptor, &bdd);
+        ejecutar(&["PSUBSCRIBE", "d*"], &suscriptor, &bdd);
+        leer(&mut conexion);
+        assert_eq!(2, suscriptor.obtener_suscripciones());
+
+        ejecutar(&["UNSUBSCRIBE"], &suscriptor, &bdd);
+        assert_eq!(
+            "*3\r\n$11\r\nunsubscribe\r\n$3\r\nuno\r\n:1\r\n",
+            leer(&mut conexion)
+        );
+        ejecutar(&["UNSUBSCRIBE"], &suscriptor, &bdd);
+        assert_eq!(
+            "*3\r\n$11\r\nunsubscribe\r\n$-1\r\n:1\r\n",
+            leer(&mut conexion)
+        );
+        assert_eq!(1, suscriptor.obtener_suscripciones());
+    }
+
+    #[test]
+    fn un_cliente_suscrito_solo_puede_enviar_comandos_de_suscripcion_ping_y_reset() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let (suscriptor, _conexion) = cliente_de_prueba(1);
+        let comando = |argumentos: &[&str]| ComandoInfo::new(argumentos.to_vec());
+
+        assert_eq!(
+            None,
+            responder_en_modo_suscripcion(&comando(&["GET", "clave"]), &suscriptor)
+        );
+        ejecutar(&["SUBSCRIBE", "uno"], &suscriptor, &bdd);
+
+        assert_eq!(
+            Some(ResultadoRedis::Error(
+                "ERR Can't execute 'get': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / RESET are allowed in this context".to_string()
+            )),
+            responder_en_modo_suscripcion(&comando(&["GET", "clave"]), &suscriptor)
+        );
+        assert_eq!(
+            Some(ResultadoRedis::Push(vec![
+                ResultadoRedis::BulkStr("pong".into()),
+                ResultadoRedis::BulkStr("".into()),
+            ])),
+            responder_en_modo_suscripcion(&comando(&["PING"]), &suscriptor)
+        );
+        assert_eq!(
+            None,
+            responder_en_modo_suscripcion(&comando(&["RESET"]), &suscriptor)
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use crate::aof::reescribir_en_segundo_plano;
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::binario::{a_texto, parsear};
 use crate::comando::ComandoHandler;
 use crate::comando_info::ComandoInfo;
 use crate::config::Config;
+use crate::notificacion::Notificaciones;
 use std::sync::{Arc, Mutex};
 
 pub type ComandoConConfig = Box<
@@ -23,6 +26,7 @@ impl ComandoServerHandler {
             "INFO" => info,
             "MONITOR" => monitor,
             "PING" => ping,
+            "BGREWRITEAOF" => bgrewriteaof,
             _ => flushdb,
         };
         ComandoServerHandler {
@@ -40,7 +44,15 @@ impl ComandoHandler for ComandoServerHandler {
 }
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo server
 pub fn es_comando_server(comando: &str) -> bool {
-    let comandos = vec!["FLUSHDB", "DBSIZE", "CONFIG", "INFO", "MONITOR", "PING"];
+    let comandos = vec![
+        "FLUSHDB",
+        "DBSIZE",
+        "CONFIG",
+        "INFO",
+        "MONITOR",
+        "PING",
+        "BGREWRITEAOF",
+    ];
     comandos.iter().any(|&c| c == comando)
 }
 
@@ -51,6 +63,21 @@ fn ping(
 ) -> ResultadoRedis {
     ResultadoRedis::StrSimple("PONG".to_string())
 }
+/// Reescribe en segundo plano el archivo AOF con los comandos minimos que reconstruyen la base de datos actual
+fn bgrewriteaof(
+    _comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    config: Arc<Mutex<Config>>,
+) -> ResultadoRedis {
+    let ruta = match config.lock() {
+        Ok(c) => c.appendfilename(),
+        Err(_) => return ResultadoRedis::Error("ERR when accessing config".to_string()),
+    };
+    match bdd.lock() {
+        Ok(b) => reescribir_en_segundo_plano(&b, ruta),
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
 /// Borra todas las claves de la base de datos. Este comando nunca falla
 fn flushdb(
     _comando: &mut ComandoInfo,
@@ -90,9 +117,9 @@ fn fconfig(
         }
     };
 
-    match parametro.to_uppercase().as_str() {
-        "GET" => config_get(comando, bdd, config),
-        "SET" => config_set(comando, bdd, config),
+    match parametro.to_ascii_uppercase().as_slice() {
+        b"GET" => config_get(comando, bdd, config),
+        b"SET" => config_set(comando, bdd, config),
         _ => ResultadoRedis::Error("ERR Opcion config not found".to_string()),
     }
 }
@@ -116,10 +143,10 @@ fn config_get(
         Err(_) => return ResultadoRedis::Error("ERR when accessing config".to_string()),
     };
 
-    ResultadoRedis::Vector(
+    ResultadoRedis::mapa(
         valores
-            .iter()
-            .map(|x| ResultadoRedis::BulkStr(x.to_string()))
+            .into_iter()
+            .map(|x| ResultadoRedis::BulkStr(x.into_bytes()))
             .collect(),
     )
 }
@@ -138,8 +165,24 @@ fn config_set(
         }
     };
 
+    if parametro.eq_ignore_ascii_case(b"notify-keyspace-events")
+        && Notificaciones::desde(&a_texto(&valor)).is_none()
+    {
+        return ResultadoRedis::Error(format!(
+            "ERR Invalid argument '{}' for CONFIG SET 'notify-keyspace-events'",
+            a_texto(&valor)
+        ));
+    }
+
+    if parametro.eq_ignore_ascii_case(b"hz") && parsear::<u64>(&valor).is_none() {
+        return ResultadoRedis::Error(format!(
+            "ERR Invalid argument '{}' for CONFIG SET 'hz'",
+            a_texto(&valor)
+        ));
+    }
+
     match config.lock() {
-        Ok(mut c) => c.set(parametro, valor),
+        Ok(mut c) => c.set(a_texto(&parametro), a_texto(&valor)),
         Err(_) => return ResultadoRedis::Error("ERR when accessing config".to_string()),
     };
 
@@ -160,11 +203,7 @@ fn info(
         _ => return ResultadoRedis::Error("ERR when accessing info".to_string()),
     };
 
-    ResultadoRedis::Vector(
-        info.iter()
-            .map(|s| ResultadoRedis::BulkStr(s.to_string()))
-            .collect(),
-    )
+    ResultadoRedis::Verbatim("txt".to_string(), info.join("\r\n"))
 }
 /// Es un comando de depuración que imprime al cliente cada comando procesado por el servidor. Puede ayudar entender qué está sucediendo en la base de datos
 fn monitor(
@@ -1,7 +1,12 @@
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::parsear_entero;
 use crate::comando::{Comando, ComandoHandler};
 use crate::comando_info::ComandoInfo;
+use crate::cursor::{respuesta, ConjuntoEscaneable, Escaneo};
+use crate::notificacion::ClaseEvento;
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
 use std::sync::{Arc, Mutex};
 
 pub struct ComandoSetHandler {
@@ -16,6 +21,18 @@ impl ComandoSetHandler {
             "SCARD" => scard,
             "SISMEMBER" => sismember,
             "SMEMBERS" => smembers,
+            "SSCAN" => sscan,
+            "SMISMEMBER" => smismember,
+            "SINTER" => sinter,
+            "SINTERCARD" => sintercard,
+            "SINTERSTORE" => sinterstore,
+            "SUNION" => sunion,
+            "SUNIONSTORE" => sunionstore,
+            "SDIFF" => sdiff,
+            "SDIFFSTORE" => sdiffstore,
+            "SMOVE" => smove,
+            "SPOP" => spop,
+            "SRANDMEMBER" => srandmember,
             _ => srem,
         };
         ComandoSetHandler {
@@ -32,7 +49,25 @@ impl ComandoHandler for ComandoSetHandler {
 }
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo set
 pub fn es_comando_set(comando: &str) -> bool {
-    let comandos = vec!["SADD", "SCARD", "SISMEMBER", "SMEMBERS", "SREM"];
+    let comandos = vec![
+        "SADD",
+        "SCARD",
+        "SISMEMBER",
+        "SMEMBERS",
+        "SSCAN",
+        "SREM",
+        "SMISMEMBER",
+        "SINTER",
+        "SINTERCARD",
+        "SINTERSTORE",
+        "SUNION",
+        "SUNIONSTORE",
+        "SDIFF",
+        "SDIFFSTORE",
+        "SMOVE",
+        "SPOP",
+        "SRANDMEMBER",
+    ];
     comandos.iter().any(|&c| c == comando)
 }
 ///  Agrega el elemento indicado al set de la clave especificada. Si la clave no existe, crea un set vacío para agregar el valor. Si el valor ya existía en el set, no se realiza agregado. Retorna error si el valor almacenado en la clave no es un set
@@ -45,38 +80,34 @@ fn sadd(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
             )
         }
     };
-    match bdd.lock() {
-        Ok(mut bdd) => {
-            let (a_agregar, cantidad_ingresada) = match bdd.obtener_valor(&clave) {
-                Some(TipoRedis::Set(set)) => aggregar_al_set(comando, &mut set.clone()),
-                None => aggregar_al_set(comando, &mut HashSet::new()),
-                _ => {
-                    return ResultadoRedis::Error(
-                        "WRONGTYPE Operation against a key holding the wrong kind of value"
-                            .to_string(),
-                    )
-                }
-            };
-            bdd.guardar_valor(clave, TipoRedis::Set(a_agregar));
-            ResultadoRedis::Int(cantidad_ingresada as isize)
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let mut agregar = |set: &mut ConjuntoEscaneable| {
+        let mut cantidad_ingresada = 0;
+        while let Some(parametro) = comando.get_parametro() {
+            cantidad_ingresada += set.insert(parametro) as usize;
         }
-        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
-    }
-}
-
-fn aggregar_al_set(
-    comando: &mut ComandoInfo,
-    set: &mut HashSet<String>,
-) -> (HashSet<String>, usize) {
-    let mut cantidad_ingresada = 0;
-
-    while let Some(parametro) = comando.get_parametro() {
-        if !set.contains(&parametro) {
-            set.insert(parametro);
-            cantidad_ingresada += 1;
+        cantidad_ingresada
+    };
+    // Un set nuevo se arma completo antes de guardarlo, para que nunca quede vacio
+    let cantidad_ingresada = match obtener_set(&bdd, &clave) {
+        Ok(Some(_)) => bdd.modificar_set(&clave, agregar).unwrap_or(0),
+        Ok(None) => {
+            let mut set = ConjuntoEscaneable::default();
+            let cantidad_ingresada = agregar(&mut set);
+            if !set.is_empty() {
+                bdd.guardar_valor(clave.clone(), TipoRedis::Set(set));
+            }
+            cantidad_ingresada
         }
+        Err(error) => return error,
+    };
+    if cantidad_ingresada > 0 {
+        bdd.notificar(ClaseEvento::Set, "sadd", &clave);
     }
-    (set.clone(), cantidad_ingresada)
+    ResultadoRedis::Int(cantidad_ingresada as isize)
 }
 /// Retorna la cantidad de elementos del set almacenado en la clave indicada
 fn scard(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -133,6 +164,41 @@ fn sismember(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resulta
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
+/// Devuelve de a partes los miembros de un set a partir de un cursor, filtrandolos con MATCH
+fn sscan(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = match comando.get_parametros() {
+        Some(a) if a.len() >= 2 => a,
+        _ => {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'sscan' command".to_string(),
+            )
+        }
+    };
+    let escaneo = match Escaneo::parsear(&argumentos[1..], &[]) {
+        Ok(e) => e,
+        Err(e) => return ResultadoRedis::Error(e),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&argumentos[0]) {
+            Some(TipoRedis::Set(set)) => {
+                let (cursor, miembros) = set.escanear(escaneo.cursor, escaneo.cantidad);
+                let miembros = miembros
+                    .into_iter()
+                    .filter(|miembro| escaneo.coincide(miembro))
+                    .map(|miembro| ResultadoRedis::BulkStr(miembro.to_vec()))
+                    .collect();
+                respuesta(cursor, miembros)
+            }
+            None => respuesta(0, vec![]),
+            _ => ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
 /// Retorna todos los miembros del set almacenado en la clave indicada
 fn smembers(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let clave = match comando.get_clave() {
@@ -151,9 +217,9 @@ fn smembers(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
                 for valor in set.iter() {
                     vector.push(ResultadoRedis::BulkStr(valor.clone()));
                 }
-                ResultadoRedis::Vector(vector)
+                ResultadoRedis::Set(vector)
             }
-            None => ResultadoRedis::Vector(vec![]),
+            None => ResultadoRedis::Set(vec![]),
             _ => ResultadoRedis::Error(
                 "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
             ),
@@ -161,7 +227,7 @@ fn smembers(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> Resultad
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
-/// Elimina los miembros especificados del set almacenado en la clave indicada. Si la clave no existe, se considera como un set vacío, retornando 0. Retorna error si el valor almacenado en esa clave no es un set
+/// Elimina los miembros especificados del set almacenado en la clave indicada, eliminando la clave si el set queda vacío. Si la clave no existe, se considera como un set vacío, retornando 0. Retorna error si el valor almacenado en esa clave no es un set
 fn srem(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let clave = match comando.get_clave() {
         Some(clave) => clave,
@@ -172,38 +238,443 @@ fn srem(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
         }
     };
 
-    match bdd.lock() {
-        Ok(mut bdd) => {
-            let (a_agregar, cantidad_eliminada) = match bdd.obtener_valor(&clave) {
-                Some(TipoRedis::Set(set)) => eliminar_del_set(comando, &mut set.clone()),
-                None => return ResultadoRedis::Int(0),
-                _ => {
-                    return ResultadoRedis::Error(
-                        "WRONGTYPE Operation against a key holding the wrong kind of value"
-                            .to_string(),
-                    )
-                }
-            };
-            bdd.guardar_valor(clave, TipoRedis::Set(a_agregar));
-            ResultadoRedis::Int(cantidad_eliminada as isize)
+    let mut miembros = vec![];
+    while let Some(parametro) = comando.get_parametro() {
+        miembros.push(parametro);
+    }
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    match obtener_set(&bdd, &clave) {
+        Ok(Some(set)) if miembros.iter().any(|m| set.contains(m)) => {}
+        Ok(_) => return ResultadoRedis::Int(0),
+        Err(error) => return error,
+    }
+    let (cantidad_eliminada, vacio) = bdd
+        .modificar_set(&clave, |set| {
+            let eliminados = miembros.iter().filter(|m| set.remove(m)).count();
+            (eliminados, set.is_empty())
+        })
+        .unwrap_or((0, false));
+    bdd.notificar(ClaseEvento::Set, "srem", &clave);
+    if vacio {
+        bdd.eliminar_clave(&clave);
+        bdd.notificar(ClaseEvento::Generico, "del", &clave);
+    }
+    ResultadoRedis::Int(cantidad_eliminada as isize)
+}
+
+/// Obtiene el set almacenado en la clave, o ninguno si no existe. Devuelve error si la clave no contiene un set
+fn obtener_set<'a>(
+    bdd: &'a BaseDeDatos,
+    clave: &[u8],
+) -> Result<Option<&'a ConjuntoEscaneable>, ResultadoRedis> {
+    match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Set(set)) => Ok(Some(set)),
+        None => Ok(None),
+        _ => Err(ResultadoRedis::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+        )),
+    }
+}
+
+/// Operaciones de conjuntos entre los sets de varias claves
+#[derive(Clone, Copy)]
+enum Operacion {
+    Interseccion,
+    Union,
+    Diferencia,
+}
+
+/// Calcula la operacion entre los sets de las claves, considerando a las claves inexistentes como sets vacios.
+/// Devuelve error si alguna de las claves no contiene un set, aunque otra no exista
+fn operar(
+    bdd: &BaseDeDatos,
+    claves: &[Vec<u8>],
+    operacion: Operacion,
+) -> Result<HashSet<Vec<u8>>, ResultadoRedis> {
+    let mut sets = Vec::with_capacity(claves.len());
+    for clave in claves {
+        sets.push(obtener_set(bdd, clave)?);
+    }
+    let vacio = ConjuntoEscaneable::default();
+    let (primero, resto) = match sets.split_first() {
+        Some((primero, resto)) => (primero.unwrap_or(&vacio), resto),
+        None => return Ok(HashSet::new()),
+    };
+    let resultado = match operacion {
+        Operacion::Interseccion => primero
+            .iter()
+            .filter(|miembro| {
+                resto
+                    .iter()
+                    .all(|set| set.is_some_and(|set| set.contains(miembro)))
+            })
+            .cloned()
+            .collect(),
+        Operacion::Union => {
+            let mut union: HashSet<Vec<u8>> = primero.iter().cloned().collect();
+            for set in resto.iter().flatten() {
+                union.extend(set.iter().cloned());
+            }
+            union
         }
-        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+        Operacion::Diferencia => primero
+            .iter()
+            .filter(|miembro| {
+                !resto
+                    .iter()
+                    .any(|set| set.is_some_and(|set| set.contains(miembro)))
+            })
+            .cloned()
+            .collect(),
+    };
+    Ok(resultado)
+}
+
+fn a_respuesta<'a, I: IntoIterator<Item = &'a Vec<u8>>>(miembros: I) -> Vec<ResultadoRedis> {
+    miembros
+        .into_iter()
+        .map(|miembro| ResultadoRedis::BulkStr(miembro.clone()))
+        .collect()
+}
+
+/// Devuelve el resultado de la operacion entre los sets de las claves indicadas
+fn operar_y_devolver(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    operacion: Operacion,
+) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            comando.get_nombre().to_lowercase()
+        ));
+    }
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    match operar(&bdd, &argumentos[1..], operacion) {
+        Ok(resultado) => ResultadoRedis::Set(a_respuesta(&resultado)),
+        Err(error) => error,
     }
 }
 
-fn eliminar_del_set(
+/// Guarda el resultado de la operacion entre los sets de las claves indicadas en la clave destino, reemplazando
+/// su valor anterior. Si el resultado es vacio se elimina el destino. Retorna la cantidad de elementos guardados
+fn operar_y_guardar(
     comando: &mut ComandoInfo,
-    set: &mut HashSet<String>,
-) -> (HashSet<String>, usize) {
-    let mut cantidad_eliminada = 0;
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    operacion: Operacion,
+) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    let nombre = comando.get_nombre().to_lowercase();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            nombre
+        ));
+    }
+    let destino = &argumentos[1];
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let resultado = match operar(&bdd, &argumentos[2..], operacion) {
+        Ok(resultado) => resultado,
+        Err(error) => return error,
+    };
+    let cantidad = resultado.len();
+    if resultado.is_empty() {
+        if bdd.eliminar_clave(destino) > 0 {
+            bdd.notificar(ClaseEvento::Generico, "del", destino);
+        }
+    } else {
+        bdd.guardar_valor(destino.clone(), TipoRedis::Set(resultado.into()));
+        bdd.notificar(ClaseEvento::Set, &nombre, destino);
+    }
+    ResultadoRedis::Int(cantidad as isize)
+}
 
-    while let Some(parametro) = comando.get_parametro() {
-        if set.contains(&parametro) {
-            set.remove(&parametro);
-            cantidad_eliminada += 1;
+/// Retorna los miembros que pertenecen a todos los sets indicados
+fn sinter(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_devolver(comando, bdd, Operacion::Interseccion)
+}
+/// Retorna los miembros que pertenecen a alguno de los sets indicados
+fn sunion(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_devolver(comando, bdd, Operacion::Union)
+}
+/// Retorna los miembros del primer set que no pertenecen a ninguno de los siguientes
+fn sdiff(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_devolver(comando, bdd, Operacion::Diferencia)
+}
+/// Igual que SINTER, pero guarda el resultado en la clave destino
+fn sinterstore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_guardar(comando, bdd, Operacion::Interseccion)
+}
+/// Igual que SUNION, pero guarda el resultado en la clave destino
+fn sunionstore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_guardar(comando, bdd, Operacion::Union)
+}
+/// Igual que SDIFF, pero guarda el resultado en la clave destino
+fn sdiffstore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    operar_y_guardar(comando, bdd, Operacion::Diferencia)
+}
+
+/// Retorna la cantidad de miembros de la interseccion de los sets, sin armarla. Con LIMIT deja de contar al
+/// alcanzar el limite, y un limite de 0 significa sin limite
+fn sintercard(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'sintercard' command".to_string(),
+        );
+    }
+    let cantidad_claves = match parsear_entero(&argumentos[1]) {
+        Some(cantidad) if cantidad > 0 => cantidad as usize,
+        _ => {
+            return ResultadoRedis::Error("ERR numkeys should be greater than 0".to_string());
+        }
+    };
+    if cantidad_claves > argumentos.len() - 2 {
+        return ResultadoRedis::Error(
+            "ERR Number of keys can't be greater than number of args".to_string(),
+        );
+    }
+    let claves = &argumentos[2..2 + cantidad_claves];
+    let mut limite = 0;
+    let mut opciones = argumentos[2 + cantidad_claves..].iter();
+    while let Some(opcion) = opciones.next() {
+        match (opcion.to_ascii_uppercase().as_slice(), opciones.next()) {
+            (b"LIMIT", Some(valor)) => match parsear_entero(valor) {
+                Some(valor) if valor >= 0 => limite = valor as usize,
+                _ => return ResultadoRedis::Error("ERR LIMIT can't be negative".to_string()),
+            },
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let mut sets = Vec::with_capacity(claves.len());
+    for clave in claves {
+        match obtener_set(&bdd, clave) {
+            Ok(set) => sets.push(set),
+            Err(error) => return error,
+        }
+    }
+    let mut sets = match sets.into_iter().collect::<Option<Vec<_>>>() {
+        Some(sets) => sets,
+        None => return ResultadoRedis::Int(0),
+    };
+    // Se recorre el set mas chico para hacer la menor cantidad de busquedas
+    sets.sort_by_key(|set| set.len());
+    let mut cantidad = 0;
+    for miembro in sets[0].iter() {
+        if sets[1..].iter().all(|set| set.contains(miembro)) {
+            cantidad += 1;
+            if cantidad == limite {
+                break;
+            }
+        }
+    }
+    ResultadoRedis::Int(cantidad as isize)
+}
+
+/// Retorna por cada miembro indicado si pertenece al set de la clave
+fn smismember(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'smismember' command".to_string(),
+        );
+    }
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let set = match obtener_set(&bdd, &argumentos[1]) {
+        Ok(set) => set,
+        Err(error) => return error,
+    };
+    let pertenencias = argumentos[2..]
+        .iter()
+        .map(|miembro| ResultadoRedis::Int(set.is_some_and(|set| set.contains(miembro)) as isize))
+        .collect();
+    ResultadoRedis::Vector(pertenencias)
+}
+
+/// Mueve el miembro del set origen al set destino, creando el destino si no existe y eliminando el origen si
+/// queda vacio. Retorna 1 si se movio y 0 si el miembro no pertenecia al origen
+fn smove(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'smove' command".to_string(),
+        );
+    }
+    let (origen, destino, miembro) = (&argumentos[1], &argumentos[2], &argumentos[3]);
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    match obtener_set(&bdd, origen) {
+        Ok(Some(set)) if set.contains(miembro) => {}
+        Ok(_) => return ResultadoRedis::Int(0),
+        Err(error) => return error,
+    }
+    let destino_existe = match obtener_set(&bdd, destino) {
+        Ok(set) => set.is_some(),
+        Err(error) => return error,
+    };
+    if origen == destino {
+        return ResultadoRedis::Int(1);
+    }
+
+    let vacio = bdd.modificar_set(origen, |set| {
+        set.remove(miembro);
+        set.is_empty()
+    });
+    bdd.notificar(ClaseEvento::Set, "srem", origen);
+    if vacio == Some(true) {
+        bdd.eliminar_clave(origen);
+        bdd.notificar(ClaseEvento::Generico, "del", origen);
+    }
+    let agregado = if destino_existe {
+        bdd.modificar_set(destino, |set| set.insert(miembro.clone())) == Some(true)
+    } else {
+        let set = std::iter::once(miembro.clone()).collect();
+        bdd.guardar_valor(destino.clone(), TipoRedis::Set(set));
+        true
+    };
+    if agregado {
+        bdd.notificar(ClaseEvento::Set, "sadd", destino);
+    }
+    ResultadoRedis::Int(1)
+}
+
+/// Devuelve un numero pseudoaleatorio entre 0 y el limite, sin incluirlo. Cada RandomState se crea con claves
+/// distintas, por lo que alcanza con tomar el hash de un valor fijo
+fn aleatorio(limite: usize) -> usize {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(limite);
+    (hasher.finish() % limite as u64) as usize
+}
+
+/// Elige la cantidad indicada de miembros distintos del set, o todos si el set tiene menos
+fn elegir_distintos(set: &ConjuntoEscaneable, cantidad: usize) -> Vec<Vec<u8>> {
+    let mut miembros: Vec<Vec<u8>> = set.iter().cloned().collect();
+    let cantidad = cantidad.min(miembros.len());
+    for i in 0..cantidad {
+        let elegido = i + aleatorio(miembros.len() - i);
+        miembros.swap(i, elegido);
+    }
+    miembros.truncate(cantidad);
+    miembros
+}
+
+/// Interpreta la cantidad opcional de SPOP y SRANDMEMBER, que debe ser el ultimo argumento
+fn obtener_cantidad(argumentos: &[Vec<u8>], nombre: &str) -> Result<Option<i64>, ResultadoRedis> {
+    match argumentos.len() {
+        2 => Ok(None),
+        3 => match parsear_entero(&argumentos[2]) {
+            Some(cantidad) => Ok(Some(cantidad)),
+            None => Err(ResultadoRedis::Error(
+                "ERR value is not an integer or out of range".to_string(),
+            )),
+        },
+        0 | 1 => Err(ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            nombre
+        ))),
+        _ => Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    }
+}
+
+/// Elimina y retorna miembros al azar del set de la clave, eliminando la clave si queda vacio. Sin cantidad
+/// retorna un unico miembro, o nil si la clave no existe
+fn spop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    let cantidad = match obtener_cantidad(&argumentos, "spop") {
+        Ok(Some(cantidad)) if cantidad < 0 => {
+            return ResultadoRedis::Error("ERR value is out of range, must be positive".to_string())
+        }
+        Ok(cantidad) => cantidad,
+        Err(error) => return error,
+    };
+    let clave = &argumentos[1];
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let elegidos = match obtener_set(&bdd, clave) {
+        Ok(Some(set)) => elegir_distintos(set, cantidad.unwrap_or(1) as usize),
+        Ok(None) if cantidad.is_none() => return ResultadoRedis::Nil,
+        Ok(None) => return ResultadoRedis::Set(vec![]),
+        Err(error) => return error,
+    };
+    if elegidos.is_empty() {
+        return ResultadoRedis::Set(vec![]);
+    }
+
+    let vacio = bdd.modificar_set(clave, |set| {
+        for miembro in &elegidos {
+            set.remove(miembro);
+        }
+        set.is_empty()
+    });
+    bdd.notificar(ClaseEvento::Set, "spop", clave);
+    if vacio == Some(true) {
+        bdd.eliminar_clave(clave);
+        bdd.notificar(ClaseEvento::Generico, "del", clave);
+    }
+    match cantidad {
+        Some(_) => ResultadoRedis::Set(a_respuesta(&elegidos)),
+        None => ResultadoRedis::BulkStr(elegidos[0].clone()),
+    }
+}
+
+/// Retorna miembros al azar del set de la clave sin eliminarlos. Con una cantidad positiva los miembros son
+/// distintos y con una negativa se pueden repetir, devolviendo siempre esa cantidad
+fn srandmember(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    let cantidad = match obtener_cantidad(&argumentos, "srandmember") {
+        Ok(Some(i64::MIN)) => {
+            return ResultadoRedis::Error("ERR value is out of range".to_string());
+        }
+        Ok(cantidad) => cantidad,
+        Err(error) => return error,
+    };
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let set = match obtener_set(&bdd, &argumentos[1]) {
+        Ok(Some(set)) => set,
+        Ok(None) if cantidad.is_none() => return ResultadoRedis::Nil,
+        Ok(None) => return ResultadoRedis::Vector(vec![]),
+        Err(error) => return error,
+    };
+    match cantidad {
+        None => match elegir_distintos(set, 1).pop() {
+            Some(miembro) => ResultadoRedis::BulkStr(miembro),
+            None => ResultadoRedis::Nil,
+        },
+        Some(cantidad) if cantidad >= 0 => {
+            ResultadoRedis::Vector(a_respuesta(&elegir_distintos(set, cantidad as usize)))
+        }
+        Some(cantidad) => {
+            let miembros: Vec<&Vec<u8>> = set.iter().collect();
+            let elegidos =
+                (0..cantidad.unsigned_abs()).map(|_| miembros[aleatorio(miembros.len())]);
+            ResultadoRedis::Vector(a_respuesta(elegidos))
         }
     }
-    (set.clone(), cantidad_eliminada)
 }
 
 #[cfg(test)]
@@ -227,13 +698,10 @@ mod tests {
         assert_eq!(ResultadoRedis::Int(1), resultado,);
 
         let mut set = HashSet::new();
-        set.insert("miValor".to_string());
+        set.insert("miValor".into());
         assert_eq!(
-            h.lock()
-                .unwrap()
-                .obtener_valor(&"miClave".to_string())
-                .unwrap(),
-            &TipoRedis::Set(set),
+            h.lock().unwrap().obtener_valor("miClave").unwrap(),
+            &TipoRedis::Set(set.into()),
         );
     }
 
@@ -257,10 +725,7 @@ mod tests {
     #[test]
     fn sadd_cuando_se_envia_una_clave_invalida_se_envia_el_error_adecuado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor(
-            "miClave".to_string(),
-            TipoRedis::Str("unString".to_string()),
-        );
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("unString".into()));
         let vector = vec![
             "SADD".to_string(),
             "miClave".to_string(),
@@ -296,10 +761,10 @@ mod tests {
     fn scard_cuando_se_envia_una_clave_que_posee_dos_elementos_se_devuelve_2_de_cardinalidad() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
         let mut set = HashSet::new();
-        set.insert("miValor".to_string());
-        set.insert("otroValor".to_string());
+        set.insert("miValor".into());
+        set.insert("otroValor".into());
 
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set.into()));
         let vector = vec!["SCARD".to_string(), "miClave".to_string()];
 
         let h = Arc::new(Mutex::new(bdd));
@@ -312,10 +777,7 @@ mod tests {
     #[test]
     fn scard_cuando_se_envia_una_clave_invalida_se_envia_el_error_adecuado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor(
-            "miClave".to_string(),
-            TipoRedis::Str("unString".to_string()),
-        );
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("unString".into()));
         let vector = vec!["SCARD".to_string(), "miClave".to_string()];
 
         let h = Arc::new(Mutex::new(bdd));
@@ -352,10 +814,10 @@ mod tests {
     ) {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
         let mut set = HashSet::new();
-        set.insert("miValor".to_string());
-        set.insert("otroValor".to_string());
+        set.insert("miValor".into());
+        set.insert("otroValor".into());
 
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set.into()));
         let vector = vec![
             "SISMEMBER".to_string(),
             "miClave".to_string(),
@@ -372,10 +834,7 @@ mod tests {
     #[test]
     fn sismember_cuando_se_envia_una_clave_invalida_se_envia_el_error_adecuado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor(
-            "miClave".to_string(),
-            TipoRedis::Str("unString".to_string()),
-        );
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("unString".into()));
         let vector = vec![
             "SISMEMBER".to_string(),
             "miClave".to_string(),
@@ -403,16 +862,13 @@ mod tests {
         let mut comando = ComandoInfo::new(vector);
         let resultado = smembers(&mut comando, Arc::clone(&h));
 
-        assert_eq!(ResultadoRedis::Vector(vec![]), resultado);
+        assert_eq!(ResultadoRedis::Set(vec![]), resultado);
     }
 
     #[test]
     fn smembers_cuando_se_envia_una_clave_invalida_se_envia_el_error_adecuado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor(
-            "miClave".to_string(),
-            TipoRedis::Str("unString".to_string()),
-        );
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("unString".into()));
         let vector = vec!["SMEMBERS".to_string(), "miClave".to_string()];
 
         let h = Arc::new(Mutex::new(bdd));
@@ -450,10 +906,10 @@ mod tests {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
 
         let mut set = HashSet::new();
-        set.insert("miValor".to_string());
-        set.insert("otroValor".to_string());
+        set.insert("miValor".into());
+        set.insert("otroValor".into());
 
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set.into()));
         let vector = vec![
             "SREM".to_string(),
             "miClave".to_string(),
@@ -467,13 +923,10 @@ mod tests {
         assert_eq!(ResultadoRedis::Int(1), resultado,);
 
         let mut set = HashSet::new();
-        set.insert("otroValor".to_string());
+        set.insert("otroValor".into());
         assert_eq!(
-            h.lock()
-                .unwrap()
-                .obtener_valor(&"miClave".to_string())
-                .unwrap(),
-            &TipoRedis::Set(set),
+            h.lock().unwrap().obtener_valor("miClave").unwrap(),
+            &TipoRedis::Set(set.into()),
         );
     }
 
@@ -483,10 +936,10 @@ mod tests {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
 
         let mut set = HashSet::new();
-        set.insert("miValor".to_string());
-        set.insert("otroValor".to_string());
+        set.insert("miValor".into());
+        set.insert("otroValor".into());
 
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Set(set.into()));
         let vector = vec![
             "SREM".to_string(),
             "miClave".to_string(),
@@ -501,23 +954,17 @@ mod tests {
         assert_eq!(ResultadoRedis::Int(1), resultado,);
 
         let mut set = HashSet::new();
-        set.insert("otroValor".to_string());
+        set.insert("otroValor".into());
         assert_eq!(
-            h.lock()
-                .unwrap()
-                .obtener_valor(&"miClave".to_string())
-                .unwrap(),
-            &TipoRedis::Set(set),
+            h.lock().unwrap().obtener_valor("miClave").unwrap(),
+            &TipoRedis::Set(set.into()),
         );
     }
 
     #[test]
     fn srem_cuando_se_envia_una_clave_invalida_se_envia_el_error_adecuado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor(
-            "miClave".to_string(),
-            TipoRedis::Str("unString".to_string()),
-        );
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("unString".into()));
         let vector = vec![
             "SREM".to_string(),
             "miClave".to_string(),
@@ -535,4 +982,264 @@ mod tests {
             resultado,
         );
     }
+
+    fn ejecutar(argumentos: &[&str], bdd: &Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        Box::new(ComandoSetHandler::new(comando)).ejecutar(Arc::clone(bdd))
+    }
+
+    fn miembros(resultado: ResultadoRedis) -> Vec<String> {
+        let elementos = match resultado {
+            ResultadoRedis::Set(elementos) | ResultadoRedis::Vector(elementos) => elementos,
+            otro => panic!("se esperaba un set y se obtuvo {:?}", otro),
+        };
+        let mut miembros: Vec<String> = elementos
+            .into_iter()
+            .map(|elemento| match elemento {
+                ResultadoRedis::BulkStr(miembro) => String::from_utf8(miembro).unwrap(),
+                otro => panic!("se esperaba un bulk string y se obtuvo {:?}", otro),
+            })
+            .collect();
+        miembros.sort();
+        miembros
+    }
+
+    //sinter, sunion, sdiff
+    #[test]
+    fn las_operaciones_entre_sets_consideran_vacias_a_las_claves_inexistentes() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2", "3"], &bdd);
+        ejecutar(&["SADD", "b", "2", "3", "4"], &bdd);
+
+        assert_eq!(
+            vec!["2", "3"],
+            miembros(ejecutar(&["SINTER", "a", "b"], &bdd))
+        );
+        assert_eq!(
+            vec!["1", "2", "3", "4"],
+            miembros(ejecutar(&["SUNION", "a", "b", "nada"], &bdd))
+        );
+        assert_eq!(
+            vec!["1"],
+            miembros(ejecutar(&["SDIFF", "a", "b", "nada"], &bdd))
+        );
+        assert!(miembros(ejecutar(&["SINTER", "a", "nada"], &bdd)).is_empty());
+        assert!(miembros(ejecutar(&["SDIFF", "nada", "a"], &bdd)).is_empty());
+    }
+
+    #[test]
+    fn las_operaciones_entre_sets_devuelven_wrongtype_aunque_otra_clave_no_exista() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("texto", TipoRedis::Str("hola".into()));
+        let wrongtype = ResultadoRedis::Error(
+            "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+        );
+
+        assert_eq!(wrongtype, ejecutar(&["SINTER", "nada", "texto"], &bdd));
+        assert_eq!(wrongtype, ejecutar(&["SDIFF", "nada", "texto"], &bdd));
+        assert_eq!(
+            wrongtype,
+            ejecutar(&["SUNIONSTORE", "destino", "texto"], &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("destino"));
+    }
+
+    #[test]
+    fn los_store_guardan_el_resultado_y_eliminan_el_destino_si_queda_vacio() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2"], &bdd);
+        ejecutar(&["SADD", "b", "2", "3"], &bdd);
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("destino", TipoRedis::Str("hola".into()));
+
+        assert_eq!(
+            ResultadoRedis::Int(3),
+            ejecutar(&["SUNIONSTORE", "destino", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            vec!["1", "2", "3"],
+            miembros(ejecutar(&["SMEMBERS", "destino"], &bdd))
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["SINTERSTORE", "destino", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            vec!["2"],
+            miembros(ejecutar(&["SMEMBERS", "destino"], &bdd))
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SDIFFSTORE", "destino", "a", "a"], &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("destino"));
+    }
+
+    //sintercard
+    #[test]
+    fn sintercard_cuenta_la_interseccion_hasta_el_limite() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2", "3", "4"], &bdd);
+        ejecutar(&["SADD", "b", "2", "3", "4", "5"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Int(3),
+            ejecutar(&["SINTERCARD", "2", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            ejecutar(&["SINTERCARD", "2", "a", "b", "LIMIT", "2"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(3),
+            ejecutar(&["SINTERCARD", "2", "a", "b", "LIMIT", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SINTERCARD", "2", "a", "nada"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR numkeys should be greater than 0".to_string()),
+            ejecutar(&["SINTERCARD", "0", "a"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR Number of keys can't be greater than number of args".to_string()
+            ),
+            ejecutar(&["SINTERCARD", "3", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR LIMIT can't be negative".to_string()),
+            ejecutar(&["SINTERCARD", "1", "a", "LIMIT", "-1"], &bdd)
+        );
+    }
+
+    //smismember, smove
+    #[test]
+    fn smismember_indica_la_pertenencia_de_cada_miembro() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::Int(1),
+                ResultadoRedis::Int(0),
+                ResultadoRedis::Int(1)
+            ]),
+            ejecutar(&["SMISMEMBER", "a", "1", "3", "2"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(0)]),
+            ejecutar(&["SMISMEMBER", "nada", "1"], &bdd)
+        );
+    }
+
+    #[test]
+    fn smove_mueve_el_miembro_y_elimina_el_origen_vacio() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "origen", "1"], &bdd);
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("texto", TipoRedis::Str("hola".into()));
+
+        assert_eq!(
+            ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string()
+            ),
+            ejecutar(&["SMOVE", "origen", "texto", "1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SMOVE", "origen", "destino", "2"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["SMOVE", "origen", "destino", "1"], &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("origen"));
+        assert_eq!(
+            vec!["1"],
+            miembros(ejecutar(&["SMEMBERS", "destino"], &bdd))
+        );
+    }
+
+    //spop, srandmember
+    #[test]
+    fn spop_con_cantidad_elimina_miembros_distintos_y_la_clave_al_vaciarse() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2", "3"], &bdd);
+
+        let sacados = miembros(ejecutar(&["SPOP", "a", "2"], &bdd));
+        assert_eq!(2, sacados.len());
+        assert_ne!(sacados[0], sacados[1]);
+        assert_eq!(ResultadoRedis::Int(1), ejecutar(&["SCARD", "a"], &bdd));
+        assert_eq!(1, miembros(ejecutar(&["SPOP", "a", "5"], &bdd)).len());
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("a"));
+        assert_eq!(ResultadoRedis::Nil, ejecutar(&["SPOP", "a"], &bdd));
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is out of range, must be positive".to_string()),
+            ejecutar(&["SPOP", "a", "-1"], &bdd)
+        );
+    }
+
+    #[test]
+    fn srandmember_con_cantidad_negativa_puede_repetir_miembros() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SADD", "a", "1", "2"], &bdd);
+
+        assert_eq!(
+            vec!["1", "2"],
+            miembros(ejecutar(&["SRANDMEMBER", "a", "10"], &bdd))
+        );
+        let repetidos = miembros(ejecutar(&["SRANDMEMBER", "a", "-10"], &bdd));
+        assert_eq!(10, repetidos.len());
+        assert!(repetidos.iter().all(|m| m == "1" || m == "2"));
+        assert_eq!(ResultadoRedis::Int(2), ejecutar(&["SCARD", "a"], &bdd));
+        assert_eq!(
+            ResultadoRedis::Nil,
+            ejecutar(&["SRANDMEMBER", "nada"], &bdd)
+        );
+    }
+
+    #[test]
+    fn sscan_recorre_todos_los_miembros_que_coinciden_con_el_patron() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut sadd = vec!["SADD".to_string(), "set".to_string()];
+        sadd.extend((0..30).map(|i| format!("a{}", i)));
+        sadd.extend((0..30).map(|i| format!("b{}", i)));
+        let sadd: Vec<&str> = sadd.iter().map(|a| a.as_str()).collect();
+        ejecutar(&sadd, &bdd);
+
+        let mut devueltos = vec![];
+        let mut cursor = "0".to_string();
+        loop {
+            let mut respuesta = match ejecutar(&["SSCAN", "set", &cursor, "MATCH", "a*"], &bdd) {
+                ResultadoRedis::Vector(respuesta) => respuesta,
+                otro => panic!("respuesta invalida: {:?}", otro),
+            };
+            devueltos.extend(miembros(respuesta.pop().unwrap()));
+            cursor = match respuesta.pop() {
+                Some(ResultadoRedis::BulkStr(c)) => String::from_utf8(c).unwrap(),
+                otro => panic!("cursor invalido: {:?}", otro),
+            };
+            if cursor == "0" {
+                break;
+            }
+        }
+        devueltos.sort();
+        let mut esperados: Vec<String> = (0..30).map(|i| format!("a{}", i)).collect();
+        esperados.sort();
+        assert_eq!(esperados, devueltos);
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("0".into()),
+                ResultadoRedis::Vector(vec![])
+            ]),
+            ejecutar(&["SSCAN", "nada", "0"], &bdd)
+        );
+    }
 }
@@ -1,6 +1,10 @@
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::{parsear, parsear_entero};
 use crate::comando::{Comando, ComandoHandler};
 use crate::comando_info::ComandoInfo;
+use crate::notificacion::ClaseEvento;
+use crate::parser::{formatear_flotante, MAXIMO_BULK};
+use crate::valor::ahora_ms;
 use std::sync::{Arc, Mutex};
 
 pub struct ComandoStringHandler {
@@ -17,9 +21,20 @@ impl ComandoStringHandler {
             "STRLEN" => strlen,
             "DECRBY" => decrby,
             "INCRBY" => incrby,
+            "INCR" => incr,
+            "DECR" => decr,
+            "INCRBYFLOAT" => incrbyfloat,
+            "GETRANGE" => getrange,
+            "SETRANGE" => setrange,
+            "LCS" => lcs,
             "MGET" => mget,
             "MSET" => mset,
             "GETSET" => getset,
+            "SETNX" => setnx,
+            "SETEX" => setex,
+            "PSETEX" => psetex,
+            "MSETNX" => msetnx,
+            "GETEX" => getex,
             _ => set,
         };
         ComandoStringHandler {
@@ -37,7 +52,27 @@ impl ComandoHandler for ComandoStringHandler {
 /// Se encarga de detectar si el comando corresponde a los implementados del tipo string
 pub fn es_comando_string(comando: &str) -> bool {
     let comandos = vec![
-        "GET", "SET", "APPEND", "STRLEN", "INCRBY", "DECRBY", "MGET", "MSET", "GETSET", "GETDEL",
+        "GET",
+        "SET",
+        "APPEND",
+        "STRLEN",
+        "INCRBY",
+        "DECRBY",
+        "MGET",
+        "MSET",
+        "GETSET",
+        "GETDEL",
+        "SETNX",
+        "SETEX",
+        "PSETEX",
+        "MSETNX",
+        "GETEX",
+        "INCR",
+        "DECR",
+        "INCRBYFLOAT",
+        "GETRANGE",
+        "SETRANGE",
+        "LCS",
     ];
     comandos.iter().any(|&c| c == comando)
 }
@@ -54,7 +89,7 @@ fn get(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedi
 
     match bdd.lock() {
         Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Str(valor)) => ResultadoRedis::BulkStr(valor.to_string()),
+            Some(TipoRedis::Str(valor)) => ResultadoRedis::BulkStr(valor.clone()),
             None => ResultadoRedis::Nil,
             _ => ResultadoRedis::Error(
                 "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
@@ -64,76 +99,287 @@ fn get(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedi
     }
 }
 
-fn obtener_tiempo_expiracion(parametros: Vec<String>, support: &str) -> Option<u64> {
-    match parametros.rsplit(|p| p == &support.to_string()).next() {
-        Some(c) => match c[0].clone().parse::<u64>() {
-            Ok(num) => Some(num),
-            Err(_) => None,
-        },
-        None => None,
+/// Condicion para escribir la clave segun si ya existe
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Condicion {
+    /// NX, solo se escribe si la clave no existe
+    SiNoExiste,
+    /// XX, solo se escribe si la clave ya existe
+    SiExiste,
+}
+
+/// Que hacer con la expiracion de la clave al escribirla
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expiracion {
+    /// Sin opcion: SET descarta la expiracion anterior y GETEX no la modifica
+    Ninguna,
+    /// KEEPTTL, se conserva la expiracion anterior
+    Mantener,
+    /// PERSIST, se quita la expiracion
+    Persistir,
+    /// EX, PX, EXAT o PXAT, ya convertidas al momento en milisegundos desde el epoch de Unix
+    En(u64),
+}
+
+/// Opciones de SET y de los comandos que se arman sobre el
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OpcionesSet {
+    condicion: Option<Condicion>,
+    expiracion: Expiracion,
+    devolver_anterior: bool,
+}
+
+impl OpcionesSet {
+    fn new() -> Self {
+        OpcionesSet {
+            condicion: None,
+            expiracion: Expiracion::Ninguna,
+            devolver_anterior: false,
+        }
     }
 }
-/// Setea que la clave especificada almacene el valor especificado de tipo string. Si la clave contiene un valor previo, la clave es sobreescrita, independientemente del tipo de dato contenido (descartando también el valor previo de TTL)
-fn set(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
-    let clave = match comando.get_clave() {
-        Some(c) => c,
+
+/// Convierte el tiempo de una opcion EX, PX, EXAT o PXAT en el momento en que vence la clave,
+/// en milisegundos desde el epoch de Unix
+fn calcular_vencimiento(opcion: &[u8], tiempo: &[u8], nombre: &str) -> Result<u64, ResultadoRedis> {
+    let tiempo = match parsear::<i64>(tiempo) {
+        Some(t) => t,
         None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'set' command".to_string(),
-            )
+            return Err(ResultadoRedis::Error(
+                "ERR value is not an integer or out of range".to_string(),
+            ))
+        }
+    };
+    let vencimiento = if tiempo <= 0 {
+        None
+    } else {
+        match opcion.to_ascii_uppercase().as_slice() {
+            b"EX" => tiempo
+                .checked_mul(1000)
+                .and_then(|ms| ms.checked_add(ahora_ms() as i64)),
+            b"PX" => tiempo.checked_add(ahora_ms() as i64),
+            b"EXAT" => tiempo.checked_mul(1000),
+            _ => Some(tiempo),
         }
     };
+    match vencimiento {
+        Some(v) => Ok(v as u64),
+        None => Err(ResultadoRedis::Error(format!(
+            "ERR invalid expire time in '{}' command",
+            nombre
+        ))),
+    }
+}
 
-    let parametros = match comando.get_parametros() {
-        Some(p) => p,
-        None => {
-            return ResultadoRedis::Error(
-                "ERR wrong number of arguments for 'set' command".to_string(),
-            )
+/// Interpreta las opciones que siguen al valor. Solo se aceptan las indicadas en `permitidas`,
+/// y dos opciones que se contradicen, como NX y XX o EX y KEEPTTL, son un error de sintaxis
+fn parsear_opciones(
+    opciones: &[Vec<u8>],
+    nombre: &str,
+    permitidas: &[&str],
+) -> Result<OpcionesSet, ResultadoRedis> {
+    let error_de_sintaxis = || ResultadoRedis::Error("ERR syntax error".to_string());
+    let mut resultado = OpcionesSet::new();
+    let mut i = 0;
+    while i < opciones.len() {
+        let opcion = String::from_utf8_lossy(&opciones[i]).to_uppercase();
+        if !permitidas.contains(&opcion.as_str()) {
+            return Err(error_de_sintaxis());
+        }
+        match opcion.as_str() {
+            "NX" | "XX" if resultado.condicion.is_some() => return Err(error_de_sintaxis()),
+            "NX" => resultado.condicion = Some(Condicion::SiNoExiste),
+            "XX" => resultado.condicion = Some(Condicion::SiExiste),
+            "GET" => resultado.devolver_anterior = true,
+            _ if resultado.expiracion != Expiracion::Ninguna => return Err(error_de_sintaxis()),
+            "KEEPTTL" => resultado.expiracion = Expiracion::Mantener,
+            "PERSIST" => resultado.expiracion = Expiracion::Persistir,
+            _ => {
+                let tiempo = opciones.get(i + 1).ok_or_else(error_de_sintaxis)?;
+                let vencimiento = calcular_vencimiento(&opciones[i], tiempo, nombre)?;
+                resultado.expiracion = Expiracion::En(vencimiento);
+                i += 1;
+            }
+        }
+        i += 1;
+    }
+    Ok(resultado)
+}
+
+/// Escribe el valor en la clave respetando la condicion y la expiracion de las opciones.
+/// Devuelve si se escribio junto con el valor anterior de la clave
+fn escribir_string(
+    bdd: &mut BaseDeDatos,
+    clave: &[u8],
+    valor: Vec<u8>,
+    opciones: &OpcionesSet,
+) -> Result<(bool, Option<Vec<u8>>), ResultadoRedis> {
+    let anterior = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Str(anterior)) => Some(anterior.clone()),
+        Some(_) if opciones.devolver_anterior => {
+            return Err(ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            ))
         }
+        Some(_) => Some(vec![]),
+        None => None,
+    };
+    let existe = anterior.is_some();
+    match opciones.condicion {
+        Some(Condicion::SiNoExiste) if existe => return Ok((false, anterior)),
+        Some(Condicion::SiExiste) if !existe => return Ok((false, anterior)),
+        _ => (),
+    }
+
+    let vencimiento = match opciones.expiracion {
+        Expiracion::En(vencimiento) => Some(vencimiento),
+        Expiracion::Mantener => bdd.obtener_vencimiento(clave).flatten(),
+        Expiracion::Ninguna | Expiracion::Persistir => None,
     };
-    if parametros.len() == 1 {
+    match vencimiento {
+        Some(v) => bdd.guardar_valor_con_vencimiento(clave, v, TipoRedis::Str(valor)),
+        None => bdd.guardar_valor(clave, TipoRedis::Str(valor)),
+    }
+    bdd.notificar(ClaseEvento::Cadena, "set", clave);
+    if let Expiracion::En(_) = opciones.expiracion {
+        bdd.notificar(ClaseEvento::Generico, "expire", clave);
+    }
+    Ok((true, anterior))
+}
+
+/// Setea que la clave especificada almacene el valor especificado de tipo string. Si la clave contiene un valor previo, la clave es sobreescrita, independientemente del tipo de dato contenido (descartando también el valor previo de TTL salvo que se indique KEEPTTL).
+/// Admite NX y XX para escribir solo si la clave no existe o si existe, EX, PX, EXAT y PXAT para indicar la expiracion y GET para devolver el valor anterior
+fn set(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
         return ResultadoRedis::Error(
             "ERR wrong number of arguments for 'set' command".to_string(),
         );
     }
+    let permitidas = ["NX", "XX", "GET", "KEEPTTL", "EX", "PX", "EXAT", "PXAT"];
+    let opciones = match parsear_opciones(&argumentos[3..], "set", &permitidas) {
+        Ok(o) => o,
+        Err(error) => return error,
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    match escribir_string(&mut bdd, &argumentos[1], argumentos[2].clone(), &opciones) {
+        Ok((_, anterior)) if opciones.devolver_anterior => match anterior {
+            Some(anterior) => ResultadoRedis::BulkStr(anterior),
+            None => ResultadoRedis::Nil,
+        },
+        Ok((true, _)) => ResultadoRedis::StrSimple("OK".to_string()),
+        Ok((false, _)) => ResultadoRedis::Nil,
+        Err(error) => error,
+    }
+}
+/// Setea el valor de la clave solo si no existe. Devuelve 1 si se seteo y 0 si la clave ya existia
+fn setnx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'setnx' command".to_string(),
+        );
+    }
+    let mut opciones = OpcionesSet::new();
+    opciones.condicion = Some(Condicion::SiNoExiste);
+
     match bdd.lock() {
         Ok(mut bdd) => {
-            if parametros.contains(&"EX".to_string()) {
-                let expiracion = match obtener_tiempo_expiracion(parametros.clone(), "EX") {
-                    Some(e) => e,
-                    None => {
-                        return ResultadoRedis::Error(
-                            "ERR value is not an integer or out of range".to_string(),
-                        )
-                    }
-                };
-                bdd.guardar_valor_con_expiracion(
-                    clave,
-                    expiracion,
-                    TipoRedis::Str(parametros[1].clone()),
-                )
-            } else if parametros.contains(&"PX".to_string()) {
-                let expiracion = match obtener_tiempo_expiracion(parametros.clone(), "PX") {
-                    Some(e) => e,
-                    None => {
-                        return ResultadoRedis::Error(
-                            "ERR value is not an integer or out of range".to_string(),
-                        )
-                    }
-                };
-                bdd.guardar_valor_con_expiracion(
-                    clave,
-                    expiracion / 1000,
-                    TipoRedis::Str(parametros[1].clone()),
-                )
-            } else {
-                bdd.guardar_valor(clave, TipoRedis::Str(parametros[1].clone()))
+            match escribir_string(&mut bdd, &argumentos[1], argumentos[2].clone(), &opciones) {
+                Ok((escrito, _)) => ResultadoRedis::Int(escrito as isize),
+                Err(error) => error,
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Setea el valor de la clave junto con su expiracion en segundos
+fn setex(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    setear_con_expiracion(comando, bdd, b"EX")
+}
+/// Setea el valor de la clave junto con su expiracion en milisegundos
+fn psetex(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    setear_con_expiracion(comando, bdd, b"PX")
+}
+
+fn setear_con_expiracion(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    unidad: &[u8],
+) -> ResultadoRedis {
+    let nombre = comando.get_nombre().to_lowercase();
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            nombre
+        ));
+    }
+    let mut opciones = OpcionesSet::new();
+    opciones.expiracion = match calcular_vencimiento(unidad, &argumentos[2], &nombre) {
+        Ok(vencimiento) => Expiracion::En(vencimiento),
+        Err(error) => return error,
+    };
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            match escribir_string(&mut bdd, &argumentos[1], argumentos[3].clone(), &opciones) {
+                Ok(_) => ResultadoRedis::StrSimple("OK".to_string()),
+                Err(error) => error,
             }
         }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Devuelve el valor de la clave y opcionalmente cambia su expiracion con EX, PX, EXAT, PXAT o PERSIST
+fn getex(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 2 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'getex' command".to_string(),
+        );
+    }
+    let permitidas = ["EX", "PX", "EXAT", "PXAT", "PERSIST"];
+    let opciones = match parsear_opciones(&argumentos[2..], "getex", &permitidas) {
+        Ok(o) => o,
+        Err(error) => return error,
+    };
+    let clave = &argumentos[1];
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let valor = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Str(valor)) => valor.clone(),
+        None => return ResultadoRedis::Nil,
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    match opciones.expiracion {
+        Expiracion::En(vencimiento) if vencimiento <= ahora_ms() => {
+            bdd.eliminar_clave(clave);
+            bdd.notificar(ClaseEvento::Generico, "del", clave);
+        }
+        Expiracion::En(vencimiento) => {
+            bdd.actualizar_vencimiento(clave, vencimiento);
+            bdd.notificar(ClaseEvento::Generico, "expire", clave);
+        }
+        Expiracion::Persistir => {
+            if bdd.actualizar_valor_sin_expiracion(clave) > 0 {
+                bdd.notificar(ClaseEvento::Generico, "persist", clave);
+            }
+        }
+        Expiracion::Ninguna | Expiracion::Mantener => (),
     }
-    ResultadoRedis::StrSimple("OK".to_string())
+    ResultadoRedis::BulkStr(valor)
 }
 /// Atómicamente setea el valor a la clave deseada, y retorna el valor anterior almacenado en la clave
 fn getset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -155,17 +401,24 @@ fn getset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
     };
 
     match bdd.lock() {
-        Ok(mut bdd) => match bdd.intercambiar_valor(clave, TipoRedis::Str(parametro)) {
-            Some(TipoRedis::Str(valor_enterior)) => ResultadoRedis::StrSimple(valor_enterior),
-            None => ResultadoRedis::Nil,
-            _ => ResultadoRedis::Error(
-                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-            ),
-        },
+        Ok(mut bdd) => {
+            let anterior = match bdd.intercambiar_valor(clave.clone(), TipoRedis::Str(parametro)) {
+                Some(TipoRedis::Str(valor_enterior)) => ResultadoRedis::BulkStr(valor_enterior),
+                None => ResultadoRedis::Nil,
+                _ => {
+                    return ResultadoRedis::Error(
+                        "WRONGTYPE Operation against a key holding the wrong kind of value"
+                            .to_string(),
+                    )
+                }
+            };
+            bdd.notificar(ClaseEvento::Cadena, "set", &clave);
+            anterior
+        }
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
-/// Si la clave ya existe y es un string, este comando agrega el valor al final del string. Si no existe, es creada con el string vacío y luego le agrega el valor deseado. En este caso es similar al comando SET
+/// Si la clave ya existe y es un string, este comando agrega el valor al final del string. Si no existe, es creada con el string vacío y luego le agrega el valor deseado. En este caso es similar al comando SET. Devuelve el largo en bytes del string resultante
 fn append(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let clave = match comando.get_clave() {
         Some(c) => c,
@@ -187,7 +440,7 @@ fn append(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
         Ok(mut bdd) => {
             if bdd.existe_clave(&clave) {
                 valor = match bdd.obtener_valor(&clave) {
-                    Some(TipoRedis::Str(v)) => v.to_string() + &valor,
+                    Some(TipoRedis::Str(v)) => [v.as_slice(), valor.as_slice()].concat(),
                     _ => {
                         return ResultadoRedis::Error(
                             "WRONGTYPE Operation against a key holding the wrong kind of value"
@@ -196,8 +449,10 @@ fn append(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
                     }
                 };
             };
-            bdd.guardar_valor(clave, TipoRedis::Str(valor.to_string()));
-            ResultadoRedis::Int(valor.len() as isize)
+            let largo = valor.len();
+            bdd.guardar_valor(clave.clone(), TipoRedis::Str(valor));
+            bdd.notificar(ClaseEvento::Cadena, "append", &clave);
+            ResultadoRedis::Int(largo as isize)
         }
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
@@ -218,6 +473,7 @@ fn getdel(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
         ResultadoRedis::BulkStr(valor) => match bdd.lock() {
             Ok(mut bdd) => {
                 bdd.eliminar_clave(&clave);
+                bdd.notificar(ClaseEvento::Generico, "del", &clave);
                 ResultadoRedis::BulkStr(valor)
             }
             Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
@@ -225,7 +481,7 @@ fn getdel(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
         _ => ResultadoRedis::Nil,
     }
 }
-/// Retorna el largo del valor de tipo string almacenado en una clave. Retorna error si la clave no almacena un string
+/// Retorna el largo en bytes del valor de tipo string almacenado en una clave. Retorna error si la clave no almacena un string
 fn strlen(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
     let clave = match comando.get_clave() {
         Some(c) => c,
@@ -241,63 +497,410 @@ fn strlen(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoR
         Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
     }
 }
-/// Dado un elemento almacenado en la base de datos que puede ser casteable a un int y una funcion que opere sobre el, se aplica la funcion devolviendo otro int
-fn operar_sobre_int(
-    comando: &mut ComandoInfo,
-    bdd: Arc<Mutex<BaseDeDatos>>,
-    f: fn(i32, i32) -> i32,
-) -> ResultadoRedis {
-    let clave = match comando.get_clave() {
-        Some(c) => c,
-        None => return ResultadoRedis::Error("ERR wrong number of arguments".to_string()),
+/// Tamaño maximo que puede alcanzar un string, igual al de Redis
+const LARGO_MAXIMO_STRING: usize = 512 * 1024 * 1024;
+
+/// Devuelve la porcion del string almacenado en la clave entre los indices indicados, ambos inclusive.
+/// Los indices negativos se cuentan desde el final, siendo -1 el ultimo byte. Si la clave no existe devuelve un string vacio
+fn getrange(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'getrange' command".to_string(),
+        );
+    }
+    let (inicio, fin) = match (
+        parsear::<i64>(&argumentos[2]),
+        parsear::<i64>(&argumentos[3]),
+    ) {
+        (Some(inicio), Some(fin)) => (inicio, fin),
+        _ => {
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+        }
     };
 
-    let valor = match bdd.lock() {
-        Ok(bdd) => match bdd.obtener_valor(&clave) {
-            Some(TipoRedis::Str(valor)) => valor.clone(),
-            None => "0".to_string(),
-            _ => {
-                return ResultadoRedis::Error(
-                    "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
-                )
-            }
-        },
+    let bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
-
-    let mut num = match valor.parse::<i32>() {
-        Ok(n) => n,
-        Err(_) => {
+    let valor = match bdd.obtener_valor(&argumentos[1]) {
+        Some(TipoRedis::Str(valor)) => valor,
+        None => return ResultadoRedis::BulkStr(vec![]),
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let largo = valor.len() as i64;
+    let normalizar = |indice: i64| {
+        if indice < 0 {
+            (largo + indice).max(0)
+        } else {
+            indice
+        }
+    };
+    let (inicio, fin) = (normalizar(inicio), normalizar(fin).min(largo - 1));
+    if largo == 0 || inicio > fin {
+        return ResultadoRedis::BulkStr(vec![]);
+    }
+    ResultadoRedis::BulkStr(valor[inicio as usize..=fin as usize].to_vec())
+}
+/// Sobreescribe el string almacenado en la clave a partir del offset indicado. Si el offset esta mas alla
+/// del final el string se completa con bytes en cero. Devuelve el largo del string resultante
+fn setrange(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 4 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'setrange' command".to_string(),
+        );
+    }
+    let (clave, parche) = (&argumentos[1], &argumentos[3]);
+    let offset = match parsear::<i64>(&argumentos[2]) {
+        Some(offset) if offset < 0 => {
+            return ResultadoRedis::Error("ERR offset is out of range".to_string())
+        }
+        Some(offset) => offset as usize,
+        None => {
             return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
         }
     };
 
-    let param = match comando.get_parametro() {
-        Some(p) => p,
-        None => 0.to_string(),
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
+    let mut valor = match bdd.obtener_valor(clave) {
+        Some(TipoRedis::Str(valor)) => valor.clone(),
+        None => vec![],
+        _ => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    // Un parche vacio no modifica el string ni crea la clave
+    if parche.is_empty() {
+        return ResultadoRedis::Int(valor.len() as isize);
+    }
+    if offset.saturating_add(parche.len()) > LARGO_MAXIMO_STRING {
+        return ResultadoRedis::Error(
+            "ERR string exceeds maximum allowed size (proto-max-bulk-len)".to_string(),
+        );
+    }
 
-    let param = match param.parse::<i32>() {
-        Ok(p) => p,
-        Err(_) => {
-            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+    if valor.len() < offset + parche.len() {
+        valor.resize(offset + parche.len(), 0);
+    }
+    valor[offset..offset + parche.len()].copy_from_slice(parche);
+    let largo = valor.len();
+    bdd.reemplazar_valor(clave.clone(), TipoRedis::Str(valor));
+    bdd.notificar(ClaseEvento::Cadena, "setrange", clave);
+    ResultadoRedis::Int(largo as isize)
+}
+
+/// Tramo contiguo en comun entre los dos strings de LCS, con sus posiciones en cada uno, ambas inclusive
+struct Coincidencia {
+    en_a: (usize, usize),
+    en_b: (usize, usize),
+}
+
+impl Coincidencia {
+    fn largo(&self) -> usize {
+        self.en_a.1 - self.en_a.0 + 1
+    }
+}
+
+/// Calcula solo el largo de la subsecuencia comun mas larga, guardando dos filas de la tabla en lugar de toda
+fn largo_subsecuencia_comun(a: &[u8], b: &[u8]) -> usize {
+    let mut anterior = vec![0u32; b.len() + 1];
+    let mut fila = vec![0u32; b.len() + 1];
+    for &byte_a in a {
+        for j in 1..=b.len() {
+            fila[j] = if byte_a == b[j - 1] {
+                anterior[j - 1] + 1
+            } else {
+                anterior[j].max(fila[j - 1])
+            };
         }
+        std::mem::swap(&mut anterior, &mut fila);
+    }
+    anterior[b.len()] as usize
+}
+
+/// Calcula la subsecuencia comun mas larga entre los dos strings junto con los tramos contiguos que la forman,
+/// ordenados desde el final de los strings hacia el principio como los devuelve Redis
+fn subsecuencia_comun(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<Coincidencia>) {
+    // largos[i * columnas + j] es el largo de la subsecuencia comun entre los primeros i bytes de a y los primeros j de b
+    let columnas = b.len() + 1;
+    let mut largos = vec![0u32; (a.len() + 1) * columnas];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            largos[i * columnas + j] = if a[i - 1] == b[j - 1] {
+                largos[(i - 1) * columnas + j - 1] + 1
+            } else {
+                largos[(i - 1) * columnas + j].max(largos[i * columnas + j - 1])
+            };
+        }
+    }
+
+    let mut subsecuencia = Vec::new();
+    let mut coincidencias = Vec::new();
+    let mut actual: Option<Coincidencia> = None;
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            subsecuencia.push(a[i - 1]);
+            match actual.as_mut() {
+                Some(c) if c.en_a.0 == i && c.en_b.0 == j => {
+                    c.en_a.0 -= 1;
+                    c.en_b.0 -= 1;
+                }
+                _ => {
+                    coincidencias.extend(actual.take());
+                    actual = Some(Coincidencia {
+                        en_a: (i - 1, i - 1),
+                        en_b: (j - 1, j - 1),
+                    });
+                }
+            }
+            i -= 1;
+            j -= 1;
+        } else {
+            coincidencias.extend(actual.take());
+            if largos[(i - 1) * columnas + j] > largos[i * columnas + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+        }
+    }
+    coincidencias.extend(actual);
+    subsecuencia.reverse();
+    (subsecuencia, coincidencias)
+}
+
+/// Devuelve la subsecuencia comun mas larga entre los strings de las dos claves. Con LEN devuelve solo su largo y con IDX
+/// los tramos que la forman, pudiendo filtrar los mas cortos que MINMATCHLEN e incluir el largo de cada uno con WITHMATCHLEN
+fn lcs(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'lcs' command".to_string(),
+        );
+    }
+    let (mut solo_largo, mut con_indices, mut con_largos) = (false, false, false);
+    let mut largo_minimo = 0;
+    let mut i = 3;
+    while i < argumentos.len() {
+        match argumentos[i].to_ascii_uppercase().as_slice() {
+            b"LEN" => solo_largo = true,
+            b"IDX" => con_indices = true,
+            b"WITHMATCHLEN" => con_largos = true,
+            b"MINMATCHLEN" if i + 1 < argumentos.len() => {
+                largo_minimo = match parsear::<i64>(&argumentos[i + 1]) {
+                    Some(n) => n.max(0) as usize,
+                    None => {
+                        return ResultadoRedis::Error(
+                            "ERR value is not an integer or out of range".to_string(),
+                        )
+                    }
+                };
+                i += 1;
+            }
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+        i += 1;
+    }
+    if solo_largo && con_indices {
+        return ResultadoRedis::Error(
+            "ERR If you want both the length and indexes, please just use IDX.".to_string(),
+        );
+    }
+
+    let (a, b) = match bdd.lock() {
+        Ok(bdd) => {
+            let como_string = |clave: &[u8]| match bdd.obtener_valor(clave) {
+                Some(TipoRedis::Str(valor)) => Ok(valor.clone()),
+                None => Ok(vec![]),
+                _ => Err(ResultadoRedis::Error(
+                    "ERR The specified keys must contain string values".to_string(),
+                )),
+            };
+            match (como_string(&argumentos[1]), como_string(&argumentos[2])) {
+                (Ok(a), Ok(b)) => (a, b),
+                (Err(error), _) | (_, Err(error)) => return error,
+            }
+        }
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
 
-    num = f(num, param);
-    match bdd.lock() {
-        Ok(mut bdd) => bdd.guardar_valor(clave, TipoRedis::Str(num.to_string())),
+    if solo_largo {
+        return ResultadoRedis::Int(largo_subsecuencia_comun(&a, &b) as isize);
+    }
+    // La tabla completa tiene un u32 por cada par de posiciones, igual que en Redis se limita su tamaño
+    let tabla = (a.len() + 1)
+        .checked_mul(b.len() + 1)
+        .and_then(|celdas| celdas.checked_mul(std::mem::size_of::<u32>()));
+    if tabla.is_none_or(|bytes| bytes > MAXIMO_BULK) {
+        return ResultadoRedis::Error(
+            "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                .to_string(),
+        );
+    }
+
+    let (subsecuencia, coincidencias) = subsecuencia_comun(&a, &b);
+    if !con_indices {
+        return ResultadoRedis::BulkStr(subsecuencia);
+    }
+    let rango = |(desde, hasta): (usize, usize)| {
+        ResultadoRedis::Vector(vec![
+            ResultadoRedis::Int(desde as isize),
+            ResultadoRedis::Int(hasta as isize),
+        ])
+    };
+    let tramos = coincidencias
+        .iter()
+        .filter(|c| c.largo() >= largo_minimo)
+        .map(|c| {
+            let mut tramo = vec![rango(c.en_a), rango(c.en_b)];
+            if con_largos {
+                tramo.push(ResultadoRedis::Int(c.largo() as isize));
+            }
+            ResultadoRedis::Vector(tramo)
+        })
+        .collect();
+    ResultadoRedis::mapa(vec![
+        ResultadoRedis::BulkStr("matches".into()),
+        ResultadoRedis::Vector(tramos),
+        ResultadoRedis::BulkStr("len".into()),
+        ResultadoRedis::Int(subsecuencia.len() as isize),
+    ])
+}
+/// Suma el incremento al entero almacenado en la clave, que se toma como 0 si no existe, y devuelve el resultado.
+/// La clave conserva su expiracion. Devuelve error si la clave no contiene un entero o si el resultado no entra en 64 bits
+fn incrementar(
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    clave: &[u8],
+    incremento: i64,
+    evento: &str,
+) -> ResultadoRedis {
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let actual = match bdd.obtener_valor(clave) {
+        None => 0,
+        Some(TipoRedis::Str(_)) => match bdd.obtener_entero(clave) {
+            Some(n) => n,
+            None => {
+                return ResultadoRedis::Error(
+                    "ERR value is not an integer or out of range".to_string(),
+                )
+            }
+        },
+        Some(_) => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let resultado = match actual.checked_add(incremento) {
+        Some(n) => n,
+        None => {
+            return ResultadoRedis::Error("ERR increment or decrement would overflow".to_string())
+        }
+    };
+    bdd.guardar_entero(clave, resultado);
+    bdd.notificar(ClaseEvento::Cadena, evento, clave);
+    ResultadoRedis::Int(resultado as isize)
+}
+/// Obtiene la clave y el incremento de INCRBY o DECRBY, validando la cantidad de argumentos
+fn clave_e_incremento(comando: &mut ComandoInfo) -> Result<(Vec<u8>, i64), ResultadoRedis> {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 3 {
+        return Err(ResultadoRedis::Error(format!(
+            "ERR wrong number of arguments for '{}' command",
+            comando.get_nombre().to_lowercase()
+        )));
+    }
+    match parsear_entero(&argumentos[2]) {
+        Some(incremento) => Ok((argumentos[1].clone(), incremento)),
+        None => Err(ResultadoRedis::Error(
+            "ERR value is not an integer or out of range".to_string(),
+        )),
+    }
+}
+/// Incrementa en uno el número almacenado en la clave. Si la clave no existe, se setea en 0 antes de realizar la operación
+fn incr(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    match comando.argumentos().as_slice() {
+        [_, clave] => incrementar(bdd, clave, 1, "incrby"),
+        _ => ResultadoRedis::Error("ERR wrong number of arguments for 'incr' command".to_string()),
+    }
+}
+/// Decrementa en uno el número almacenado en la clave. Si la clave no existe, se setea en 0 antes de realizar la operación
+fn decr(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    match comando.argumentos().as_slice() {
+        [_, clave] => incrementar(bdd, clave, -1, "decrby"),
+        _ => ResultadoRedis::Error("ERR wrong number of arguments for 'decr' command".to_string()),
     }
-    ResultadoRedis::BulkStr(num.to_string())
 }
 /// Decrementa el número almacenado en una clave por el valor deseado. Si la clave no existe, se setea en 0 antes de realizar la operación
 fn decrby(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
-    operar_sobre_int(comando, bdd, |a, b| a - b)
+    match clave_e_incremento(comando) {
+        Ok((clave, decremento)) => match decremento.checked_neg() {
+            Some(incremento) => incrementar(bdd, &clave, incremento, "decrby"),
+            None => ResultadoRedis::Error("ERR decrement would overflow".to_string()),
+        },
+        Err(error) => error,
+    }
 }
 /// Incrementa el número almacenado en la clave en un incremento. Si la clave no existe, es seteado a 0 antes de realizar la operación. Devuelve error si la clave contiene un valor de tipo erróneo o un string que no puede ser representado como entero
 fn incrby(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
-    operar_sobre_int(comando, bdd, |a, b| a + b)
+    match clave_e_incremento(comando) {
+        Ok((clave, incremento)) => incrementar(bdd, &clave, incremento, "incrby"),
+        Err(error) => error,
+    }
+}
+/// Incrementa el número de punto flotante almacenado en la clave en el valor indicado. Si la clave no existe, se setea en 0 antes de realizar la operación.
+/// El resultado se guarda como string con el mismo formato con el que se devuelve, conservando la expiracion de la clave
+fn incrbyfloat(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() != 3 {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'incrbyfloat' command".to_string(),
+        );
+    }
+    let clave = &argumentos[1];
+    let incremento = match parsear::<f64>(&argumentos[2]) {
+        Some(n) if n.is_finite() => n,
+        _ => return ResultadoRedis::Error("ERR value is not a valid float".to_string()),
+    };
+
+    let mut bdd = match bdd.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    let actual = match bdd.obtener_valor(clave) {
+        None => 0.0,
+        Some(TipoRedis::Str(valor)) => match parsear::<f64>(valor) {
+            Some(n) if n.is_finite() => n,
+            _ => return ResultadoRedis::Error("ERR value is not a valid float".to_string()),
+        },
+        Some(_) => {
+            return ResultadoRedis::Error(
+                "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+            )
+        }
+    };
+    let resultado = actual + incremento;
+    if !resultado.is_finite() {
+        return ResultadoRedis::Error("ERR increment would produce NaN or Infinity".to_string());
+    }
+    let resultado = formatear_flotante(resultado).into_bytes();
+    bdd.reemplazar_valor(clave.clone(), TipoRedis::Str(resultado.clone()));
+    bdd.notificar(ClaseEvento::Cadena, "incrbyfloat", clave);
+    ResultadoRedis::BulkStr(resultado)
 }
 /// Retorna el valor de todas las claves especificadas. Para las claves que no contienen valor o el valor no es un string, se retorna el tipo especial nil
 fn mget(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
@@ -310,7 +913,7 @@ fn mget(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
             Some(p) => match bdd.lock() {
                 Ok(bdd) => match bdd.obtener_valor(&p) {
                     Some(TipoRedis::Str(valor)) => {
-                        valores.push(ResultadoRedis::BulkStr(valor.to_string()))
+                        valores.push(ResultadoRedis::BulkStr(valor.clone()))
                     }
                     _ => valores.push(ResultadoRedis::Nil),
                 },
@@ -345,25 +948,58 @@ fn mset(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRed
     }
 
     match bdd.lock() {
-        Ok(mut bdd) => bdd.guardar_valores(parametros),
+        Ok(mut bdd) => {
+            let claves: Vec<_> = parametros.iter().step_by(2).cloned().collect();
+            bdd.guardar_valores(parametros);
+            for clave in claves {
+                bdd.notificar(ClaseEvento::Cadena, "set", &clave);
+            }
+        }
         Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
     };
     ResultadoRedis::StrSimple("OK".to_string())
 }
+/// Igual que MSET pero solo setea las claves si ninguna existe. Devuelve 1 si se setearon todas y 0 si no se seteo ninguna
+fn msetnx(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = comando.argumentos();
+    if argumentos.len() < 3 || argumentos.len().is_multiple_of(2) {
+        return ResultadoRedis::Error(
+            "ERR wrong number of arguments for 'msetnx' command".to_string(),
+        );
+    }
+    let mut opciones = OpcionesSet::new();
+    opciones.condicion = Some(Condicion::SiNoExiste);
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let pares: Vec<_> = argumentos[1..].chunks(2).collect();
+            if pares.iter().any(|par| bdd.existe_clave(&par[0])) {
+                return ResultadoRedis::Int(0);
+            }
+            for par in pares {
+                if let Err(error) = escribir_string(&mut bdd, &par[0], par[1].clone(), &opciones) {
+                    return error;
+                }
+            }
+            ResultadoRedis::Int(1)
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashSet;
+    use std::collections::VecDeque;
 
     #[test]
     fn get_devuelve_el_valor_almacenado_en_el_hash() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".into()));
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("miValor".to_string()),
+            ResultadoRedis::BulkStr("miValor".into()),
             get(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -371,7 +1007,7 @@ mod tests {
     #[test]
     fn get_devuelve_error_al_ser_llamado_con_una_clave_que_correspondia_a_una_lista() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
@@ -397,10 +1033,332 @@ mod tests {
         );
     }
 
+    fn ejecutar(argumentos: &[&str], bdd: &Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        let comando = ComandoInfo::new(argumentos.to_vec());
+        Box::new(ComandoStringHandler::new(comando)).ejecutar(Arc::clone(bdd))
+    }
+
+    #[test]
+    fn set_con_nx_y_xx_escribe_segun_si_la_clave_existe() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        assert_eq!(
+            ResultadoRedis::Nil,
+            ejecutar(&["SET", "clave", "a", "XX"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            ejecutar(&["SET", "clave", "a", "NX"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Nil,
+            ejecutar(&["SET", "clave", "b", "NX"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::BulkStr("a".into()),
+            ejecutar(&["SET", "clave", "c", "XX", "GET"], &bdd)
+        );
+        assert_eq!(
+            Some(&TipoRedis::Str("c".into())),
+            bdd.lock().unwrap().obtener_valor("clave")
+        );
+    }
+
+    #[test]
+    fn set_con_keepttl_conserva_la_expiracion_anterior() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        ejecutar(&["SET", "clave", "a", "PXAT", "4102444800000"], &bdd);
+        ejecutar(&["SET", "clave", "b", "KEEPTTL"], &bdd);
+        assert_eq!(
+            Some(Some(4102444800000)),
+            bdd.lock().unwrap().obtener_vencimiento("clave")
+        );
+
+        ejecutar(&["SET", "clave", "c"], &bdd);
+        assert_eq!(Some(None), bdd.lock().unwrap().obtener_vencimiento("clave"));
+    }
+
+    #[test]
+    fn set_con_opciones_invalidas_devuelve_error() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let error_de_sintaxis = ResultadoRedis::Error("ERR syntax error".to_string());
+
+        assert_eq!(
+            error_de_sintaxis,
+            ejecutar(&["SET", "clave", "a", "NX", "XX"], &bdd)
+        );
+        assert_eq!(
+            error_de_sintaxis,
+            ejecutar(&["SET", "clave", "a", "EX", "10", "KEEPTTL"], &bdd)
+        );
+        assert_eq!(
+            error_de_sintaxis,
+            ejecutar(&["SET", "clave", "a", "PX"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR invalid expire time in 'set' command".to_string()),
+            ejecutar(&["SET", "clave", "a", "EX", "0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is not an integer or out of range".to_string()),
+            ejecutar(&["SET", "clave", "a", "EX", "diez"], &bdd)
+        );
+        assert!(!bdd.lock().unwrap().existe_clave("clave"));
+    }
+
+    #[test]
+    fn setnx_y_msetnx_solo_escriben_claves_nuevas() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+
+        assert_eq!(ResultadoRedis::Int(1), ejecutar(&["SETNX", "a", "1"], &bdd));
+        assert_eq!(ResultadoRedis::Int(0), ejecutar(&["SETNX", "a", "2"], &bdd));
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["MSETNX", "b", "1", "a", "3"], &bdd)
+        );
+        assert!(!bdd.lock().unwrap().existe_clave("b"));
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            ejecutar(&["MSETNX", "b", "1", "c", "2"], &bdd)
+        );
+        assert_eq!(
+            Some(&TipoRedis::Str("2".into())),
+            bdd.lock().unwrap().obtener_valor("c")
+        );
+    }
+
+    #[test]
+    fn getex_devuelve_el_valor_y_cambia_su_expiracion() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SETEX", "clave", "100", "valor"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::BulkStr("valor".into()),
+            ejecutar(&["GETEX", "clave", "PERSIST"], &bdd)
+        );
+        assert_eq!(Some(None), bdd.lock().unwrap().obtener_vencimiento("clave"));
+
+        ejecutar(&["GETEX", "clave", "EXAT", "4102444800"], &bdd);
+        assert_eq!(
+            Some(Some(4102444800000)),
+            bdd.lock().unwrap().obtener_vencimiento("clave")
+        );
+
+        assert_eq!(
+            ResultadoRedis::BulkStr("valor".into()),
+            ejecutar(&["GETEX", "clave", "PXAT", "1"], &bdd)
+        );
+        assert!(!bdd.lock().unwrap().existe_clave("clave"));
+        assert_eq!(ResultadoRedis::Nil, ejecutar(&["GETEX", "clave"], &bdd));
+    }
+
+    #[test]
+    fn incr_y_decr_conservan_la_expiracion_y_detectan_overflow() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SET", "contador", "9", "PXAT", "4102444800000"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Int(10),
+            ejecutar(&["INCR", "contador"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(9),
+            ejecutar(&["DECR", "contador"], &bdd)
+        );
+        assert_eq!(
+            Some(Some(4102444800000)),
+            bdd.lock().unwrap().obtener_vencimiento("contador")
+        );
+        assert_eq!(Some(9), bdd.lock().unwrap().obtener_entero("contador"));
+
+        ejecutar(&["SET", "contador", "9223372036854775807"], &bdd);
+        assert_eq!(
+            ResultadoRedis::Error("ERR increment or decrement would overflow".to_string()),
+            ejecutar(&["INCR", "contador"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR decrement would overflow".to_string()),
+            ejecutar(&["DECRBY", "contador", "-9223372036854775808"], &bdd)
+        );
+        ejecutar(&["SET", "contador", "010"], &bdd);
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is not an integer or out of range".to_string()),
+            ejecutar(&["INCR", "contador"], &bdd)
+        );
+    }
+
+    #[test]
+    fn incrbyfloat_suma_y_guarda_el_resultado_formateado() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SET", "clave", "10.50"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::BulkStr("10.6".into()),
+            ejecutar(&["INCRBYFLOAT", "clave", "0.1"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::BulkStr("5.6".into()),
+            ejecutar(&["INCRBYFLOAT", "clave", "-5"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::BulkStr("3".into()),
+            ejecutar(&["INCRBYFLOAT", "nueva", "3.0"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is not a valid float".to_string()),
+            ejecutar(&["INCRBYFLOAT", "clave", "uno"], &bdd)
+        );
+        ejecutar(&["SET", "clave", "1.7e308"], &bdd);
+        assert_eq!(
+            ResultadoRedis::Error("ERR increment would produce NaN or Infinity".to_string()),
+            ejecutar(&["INCRBYFLOAT", "clave", "1.7e308"], &bdd)
+        );
+    }
+
+    #[test]
+    fn getrange_admite_indices_negativos_y_fuera_de_rango() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["SET", "clave", "This is a string"], &bdd);
+
+        let rango = |inicio: &str, fin: &str| ejecutar(&["GETRANGE", "clave", inicio, fin], &bdd);
+        assert_eq!(ResultadoRedis::BulkStr("This".into()), rango("0", "3"));
+        assert_eq!(ResultadoRedis::BulkStr("ing".into()), rango("-3", "-1"));
+        assert_eq!(
+            ResultadoRedis::BulkStr("This is a string".into()),
+            rango("0", "-1")
+        );
+        assert_eq!(ResultadoRedis::BulkStr("string".into()), rango("10", "100"));
+        assert_eq!(ResultadoRedis::BulkStr("".into()), rango("5", "2"));
+        assert_eq!(
+            ResultadoRedis::BulkStr("".into()),
+            ejecutar(&["GETRANGE", "otra", "0", "-1"], &bdd)
+        );
+    }
+
+    #[test]
+    fn setrange_completa_con_ceros_si_el_offset_esta_despues_del_final() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(
+            &["SET", "clave", "Hello World", "PXAT", "4102444800000"],
+            &bdd,
+        );
+
+        assert_eq!(
+            ResultadoRedis::Int(11),
+            ejecutar(&["SETRANGE", "clave", "6", "Redis"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::BulkStr("Hello Redis".into()),
+            ejecutar(&["GET", "clave"], &bdd)
+        );
+        assert_eq!(
+            Some(Some(4102444800000)),
+            bdd.lock().unwrap().obtener_vencimiento("clave")
+        );
+
+        assert_eq!(
+            ResultadoRedis::Int(8),
+            ejecutar(&["SETRANGE", "nueva", "3", "abcde"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::BulkStr(b"\0\0\0abcde".to_vec()),
+            ejecutar(&["GET", "nueva"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            ejecutar(&["SETRANGE", "vacia", "3", ""], &bdd)
+        );
+        assert!(!bdd.lock().unwrap().existe_clave("vacia"));
+        assert_eq!(
+            ResultadoRedis::Error("ERR offset is out of range".to_string()),
+            ejecutar(&["SETRANGE", "clave", "-1", "x"], &bdd)
+        );
+    }
+
+    #[test]
+    fn lcs_devuelve_la_subsecuencia_su_largo_o_los_tramos_que_la_forman() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        ejecutar(&["MSET", "a", "ohmytext", "b", "mynewtext"], &bdd);
+
+        assert_eq!(
+            ResultadoRedis::BulkStr("mytext".into()),
+            ejecutar(&["LCS", "a", "b"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Int(6),
+            ejecutar(&["LCS", "a", "b", "LEN"], &bdd)
+        );
+
+        let rango = |desde, hasta| {
+            ResultadoRedis::Vector(vec![ResultadoRedis::Int(desde), ResultadoRedis::Int(hasta)])
+        };
+        assert_eq!(
+            ResultadoRedis::Map(vec![
+                (
+                    ResultadoRedis::BulkStr("matches".into()),
+                    ResultadoRedis::Vector(vec![
+                        ResultadoRedis::Vector(vec![rango(4, 7), rango(5, 8)]),
+                        ResultadoRedis::Vector(vec![rango(2, 3), rango(0, 1)]),
+                    ])
+                ),
+                (
+                    ResultadoRedis::BulkStr("len".into()),
+                    ResultadoRedis::Int(6)
+                ),
+            ]),
+            ejecutar(&["LCS", "a", "b", "IDX"], &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Map(vec![
+                (
+                    ResultadoRedis::BulkStr("matches".into()),
+                    ResultadoRedis::Vector(vec![ResultadoRedis::Vector(vec![
+                        rango(4, 7),
+                        rango(5, 8),
+                        ResultadoRedis::Int(4)
+                    ])])
+                ),
+                (
+                    ResultadoRedis::BulkStr("len".into()),
+                    ResultadoRedis::Int(6)
+                ),
+            ]),
+            ejecutar(
+                &["LCS", "a", "b", "IDX", "MINMATCHLEN", "4", "WITHMATCHLEN"],
+                &bdd
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR If you want both the length and indexes, please just use IDX.".to_string()
+            ),
+            ejecutar(&["LCS", "a", "b", "LEN", "IDX"], &bdd)
+        );
+    }
+
+    #[test]
+    fn lcs_rechaza_strings_cuya_tabla_supera_el_limite_de_memoria() {
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        {
+            let mut bdd = bdd.lock().unwrap();
+            bdd.guardar_valor("a", TipoRedis::Str(vec![b'a'; 12_000]));
+            bdd.guardar_valor("b", TipoRedis::Str(vec![b'b'; 12_000]));
+        }
+
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                    .to_string()
+            ),
+            ejecutar(&["LCS", "a", "b"], &bdd)
+        );
+    }
+
     #[test]
     fn append_agrega_el_string_enviado_al_final_del_string_guardado_con_la_misma_clave() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".into()));
         let ptr_hash = Arc::new(Mutex::new(bdd));
         let ptr_hash1 = Arc::clone(&ptr_hash);
 
@@ -412,7 +1370,7 @@ mod tests {
 
         assert_eq!(ResultadoRedis::Int(24), append(&mut comando, ptr_hash1));
         assert_eq!(
-            ResultadoRedis::BulkStr("miValorconAlgoAppendeado".to_string()),
+            ResultadoRedis::BulkStr("miValorconAlgoAppendeado".into()),
             get(&mut comando, ptr_hash)
         );
     }
@@ -431,7 +1389,7 @@ mod tests {
 
         assert_eq!(ResultadoRedis::Int(17), append(&mut comando, ptr_hash1));
         assert_eq!(
-            ResultadoRedis::BulkStr("conAlgoAppendeado".to_string()),
+            ResultadoRedis::BulkStr("conAlgoAppendeado".into()),
             get(&mut comando, ptr_hash)
         );
     }
@@ -439,7 +1397,7 @@ mod tests {
     #[test]
     fn append_devuelve_error_al_ser_llamado_con_una_clave_que_correspondia_a_una_lista() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec![
             "APPEND".to_owned(),
             "miClave".to_string(),
@@ -457,7 +1415,7 @@ mod tests {
     #[test]
     fn getdel_devuelve_el_valor_almacenado_en_el_hash() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".into()));
 
         let ptr_hash = Arc::new(Mutex::new(bdd));
         let ptr_hash_clone = Arc::clone(&ptr_hash);
@@ -465,7 +1423,7 @@ mod tests {
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("miValor".to_string()),
+            ResultadoRedis::BulkStr("miValor".into()),
             getdel(&mut comando, ptr_hash_clone)
         );
         assert_eq!(ResultadoRedis::Nil, getdel(&mut comando, ptr_hash));
@@ -474,7 +1432,7 @@ mod tests {
     #[test]
     fn getdel_devuelve_error_al_ser_llamado_con_una_clave_que_correspondia_a_una_lista() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
@@ -488,7 +1446,7 @@ mod tests {
     #[test]
     fn strlen_devuelve_el_valor_almacenado_en_el_hash() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("miValor".into()));
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
@@ -497,10 +1455,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn strlen_cuenta_bytes_y_no_caracteres() {
+        let mut bdd: BaseDeDatos = BaseDeDatos::new();
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("año".into()));
+        let mut comando = ComandoInfo::new(vec!["strlen".to_string(), "miClave".to_string()]);
+
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            strlen(&mut comando, Arc::new(Mutex::new(bdd)))
+        );
+    }
+
+    #[test]
+    fn append_concatena_valores_binarios_y_devuelve_la_cantidad_de_bytes() {
+        let mut bdd: BaseDeDatos = BaseDeDatos::new();
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str(vec![0xff, 0x00]));
+        let ptr_hash = Arc::new(Mutex::new(bdd));
+        let ptr_hash1 = Arc::clone(&ptr_hash);
+
+        let mut comando = ComandoInfo::new(vec![
+            b"APPEND".to_vec(),
+            b"miClave".to_vec(),
+            vec![b'\r', b'\n', 0xfe],
+        ]);
+
+        assert_eq!(ResultadoRedis::Int(5), append(&mut comando, ptr_hash1));
+        assert_eq!(
+            ResultadoRedis::BulkStr(vec![0xff, 0x00, b'\r', b'\n', 0xfe]),
+            get(&mut comando, ptr_hash)
+        );
+    }
+
     #[test]
     fn strlen_devuelve_error_al_ser_llamado_con_una_clave_que_correspondia_a_una_lista() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec!["get".to_string(), "miClave".to_string()]);
 
         assert_eq!(
@@ -514,7 +1504,7 @@ mod tests {
     #[test]
     fn decrby_resta_correcatemente_un_valor_entero_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".into()));
         let mut comando = ComandoInfo::new(vec![
             "decrby".to_string(),
             "miClave".to_string(),
@@ -522,7 +1512,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("0".to_string()),
+            ResultadoRedis::Int(0),
             decrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -530,7 +1520,7 @@ mod tests {
     #[test]
     fn decrby_resta_correcatemente_un_valor_entero_a_una_clave_negativa_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("-10".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("-10".into()));
         let mut comando = ComandoInfo::new(vec![
             "decrby".to_string(),
             "miClave".to_string(),
@@ -538,7 +1528,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("-11".to_string()),
+            ResultadoRedis::Int(-11),
             decrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -546,7 +1536,7 @@ mod tests {
     #[test]
     fn decrby_resta_correcatemente_un_valor_negativo_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("10".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("10".into()));
         let mut comando = ComandoInfo::new(vec![
             "decrby".to_string(),
             "miClave".to_string(),
@@ -554,7 +1544,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("11".to_string()),
+            ResultadoRedis::Int(11),
             decrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -569,7 +1559,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("1".to_string()),
+            ResultadoRedis::Int(1),
             decrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -577,7 +1567,7 @@ mod tests {
     #[test]
     fn decrby_devuelve_error_un_valor_entero_a_una_clave_inparseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec![
             "decrby".to_string(),
             "miClave".to_string(),
@@ -595,7 +1585,7 @@ mod tests {
     #[test]
     fn decrby_devuelve_error_un_valor_erroneo_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".into()));
         let mut comando = ComandoInfo::new(vec![
             "decrby".to_string(),
             "miClave".to_string(),
@@ -611,7 +1601,7 @@ mod tests {
     #[test]
     fn incrby_resta_correcatemente_un_valor_entero_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".into()));
         let mut comando = ComandoInfo::new(vec![
             "incrby".to_string(),
             "miClave".to_string(),
@@ -619,7 +1609,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("2".to_string()),
+            ResultadoRedis::Int(2),
             incrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -627,7 +1617,7 @@ mod tests {
     #[test]
     fn incrby_resta_correcatemente_un_valor_entero_a_una_clave_negativa_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("-10".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("-10".into()));
         let mut comando = ComandoInfo::new(vec![
             "incrby".to_string(),
             "miClave".to_string(),
@@ -635,7 +1625,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("-9".to_string()),
+            ResultadoRedis::Int(-9),
             incrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -643,7 +1633,7 @@ mod tests {
     #[test]
     fn incrby_resta_correcatemente_un_valor_negativo_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("10".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("10".into()));
         let mut comando = ComandoInfo::new(vec![
             "incrby".to_string(),
             "miClave".to_string(),
@@ -651,7 +1641,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("9".to_string()),
+            ResultadoRedis::Int(9),
             incrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -666,7 +1656,7 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::BulkStr("1".to_string()),
+            ResultadoRedis::Int(1),
             incrby(&mut comando, Arc::new(Mutex::new(bdd)))
         );
     }
@@ -674,7 +1664,7 @@ mod tests {
     #[test]
     fn incrby_devuelve_error_un_valor_entero_a_una_clave_inparseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(Vec::new()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Lista(VecDeque::new()));
         let mut comando = ComandoInfo::new(vec![
             "incrby".to_string(),
             "miClave".to_string(),
@@ -692,7 +1682,7 @@ mod tests {
     #[test]
     fn incrby_devuelve_error_un_valor_erroneo_a_una_clave_parseable() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".to_string()));
+        bdd.guardar_valor("miClave".to_string(), TipoRedis::Str("1".into()));
         let mut comando = ComandoInfo::new(vec![
             "incrby".to_string(),
             "miClave".to_string(),
@@ -708,10 +1698,10 @@ mod tests {
     #[test]
     fn mget_devuelve_una_lista_con_todos_los_valores_de_las_claves() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".to_string()));
-        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".to_string()));
-        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".to_string()));
-        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".to_string()));
+        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".into()));
+        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".into()));
+        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".into()));
+        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".into()));
 
         let mut comando = ComandoInfo::new(vec![
             "mget".to_string(),
@@ -723,10 +1713,10 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("1".to_string()),
-                ResultadoRedis::BulkStr("2".to_string()),
-                ResultadoRedis::BulkStr("3".to_string()),
-                ResultadoRedis::BulkStr("4".to_string())
+                ResultadoRedis::BulkStr("1".into()),
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
+                ResultadoRedis::BulkStr("4".into())
             ]),
             mget(&mut comando, Arc::new(Mutex::new(bdd)))
         );
@@ -736,10 +1726,10 @@ mod tests {
     fn mget_devuelve_una_lista_con_todos_los_valores_de_las_claves_y_si_la_clave_no_existe_devuelve_nil(
     ) {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".to_string()));
-        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".to_string()));
-        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".to_string()));
-        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".to_string()));
+        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".into()));
+        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".into()));
+        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".into()));
+        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".into()));
 
         let mut comando = ComandoInfo::new(vec![
             "mget".to_string(),
@@ -751,9 +1741,9 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("1".to_string()),
-                ResultadoRedis::BulkStr("2".to_string()),
-                ResultadoRedis::BulkStr("3".to_string()),
+                ResultadoRedis::BulkStr("1".into()),
+                ResultadoRedis::BulkStr("2".into()),
+                ResultadoRedis::BulkStr("3".into()),
                 ResultadoRedis::Nil
             ]),
             mget(&mut comando, Arc::new(Mutex::new(bdd)))
@@ -763,10 +1753,10 @@ mod tests {
     #[test]
     fn mget_devuelve_una_lista_con_todos_nil_si_la_clave_no_existen() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".to_string()));
-        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".to_string()));
-        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".to_string()));
-        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".to_string()));
+        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".into()));
+        bdd.guardar_valor("clave2".to_string(), TipoRedis::Str("2".into()));
+        bdd.guardar_valor("clave3".to_string(), TipoRedis::Str("3".into()));
+        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".into()));
 
         let mut comando = ComandoInfo::new(vec![
             "mget".to_string(),
@@ -790,10 +1780,10 @@ mod tests {
     #[test]
     fn mget_devuelve_una_lista_con_todos_nil_si_la_clave_no_es_de_tipo_str() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".to_string()));
-        bdd.guardar_valor("clave2".to_string(), TipoRedis::Lista(Vec::new()));
-        bdd.guardar_valor("clave3".to_string(), TipoRedis::Set(HashSet::new()));
-        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".to_string()));
+        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".into()));
+        bdd.guardar_valor("clave2".to_string(), TipoRedis::Lista(VecDeque::new()));
+        bdd.guardar_valor("clave3".to_string(), TipoRedis::Set(Default::default()));
+        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".into()));
 
         let mut comando = ComandoInfo::new(vec![
             "mget".to_string(),
@@ -805,10 +1795,10 @@ mod tests {
 
         assert_eq!(
             ResultadoRedis::Vector(vec![
-                ResultadoRedis::BulkStr("1".to_string()),
+                ResultadoRedis::BulkStr("1".into()),
                 ResultadoRedis::Nil,
                 ResultadoRedis::Nil,
-                ResultadoRedis::BulkStr("4".to_string())
+                ResultadoRedis::BulkStr("4".into())
             ]),
             mget(&mut comando, Arc::new(Mutex::new(bdd)))
         );
@@ -817,10 +1807,10 @@ mod tests {
     #[test]
     fn mget_devuelve_una_error_con_si_no_hay_parametro() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
-        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".to_string()));
-        bdd.guardar_valor("clave2".to_string(), TipoRedis::Lista(Vec::new()));
-        bdd.guardar_valor("clave3".to_string(), TipoRedis::Set(HashSet::new()));
-        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".to_string()));
+        bdd.guardar_valor("clave1".to_string(), TipoRedis::Str("1".into()));
+        bdd.guardar_valor("clave2".to_string(), TipoRedis::Lista(VecDeque::new()));
+        bdd.guardar_valor("clave3".to_string(), TipoRedis::Set(Default::default()));
+        bdd.guardar_valor("clave4".to_string(), TipoRedis::Str("4".into()));
 
         let mut comando = ComandoInfo::new(vec!["mget".to_string()]);
 
@@ -852,15 +1842,15 @@ mod tests {
         );
 
         assert_eq!(
-            Some(&TipoRedis::Str("1".to_string())),
+            Some(&TipoRedis::Str("1".into())),
             ptr_hash.lock().unwrap().obtener_valor("clave1")
         );
         assert_eq!(
-            Some(&TipoRedis::Str("2".to_string())),
+            Some(&TipoRedis::Str("2".into())),
             ptr_hash.lock().unwrap().obtener_valor("clave2")
         );
         assert_eq!(
-            Some(&TipoRedis::Str("3".to_string())),
+            Some(&TipoRedis::Str("3".into())),
             ptr_hash.lock().unwrap().obtener_valor("clave3")
         );
     }
@@ -894,7 +1884,7 @@ mod tests {
     fn getset_devuelve_el_antiguo_valor_almacenado() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
 
-        bdd.guardar_valor("clave".to_string(), TipoRedis::Str("clave".to_string()));
+        bdd.guardar_valor("clave".to_string(), TipoRedis::Str("clave".into()));
         let ptr_hash = Arc::new(Mutex::new(bdd));
         let ptr_hash1 = Arc::clone(&ptr_hash);
 
@@ -905,12 +1895,12 @@ mod tests {
         ]);
 
         assert_eq!(
-            ResultadoRedis::StrSimple("clave".to_string()),
+            ResultadoRedis::BulkStr("clave".into()),
             getset(&mut comando, ptr_hash1)
         );
 
         assert_eq!(
-            Some(&TipoRedis::Str("nueva_clave".to_string())),
+            Some(&TipoRedis::Str("nueva_clave".into())),
             ptr_hash.lock().unwrap().obtener_valor("clave")
         );
     }
@@ -931,7 +1921,7 @@ mod tests {
         assert_eq!(ResultadoRedis::Nil, getset(&mut comando, ptr_hash1));
 
         assert_eq!(
-            Some(&TipoRedis::Str("nueva_clave".to_string())),
+            Some(&TipoRedis::Str("nueva_clave".into())),
             ptr_hash.lock().unwrap().obtener_valor("clave")
         );
     }
@@ -940,7 +1930,7 @@ mod tests {
     fn getset_devuelve_error_porque_la_clave_no_corresponde_a_un_string() {
         let mut bdd: BaseDeDatos = BaseDeDatos::new();
 
-        bdd.guardar_valor("clave".to_string(), TipoRedis::Lista(vec![]));
+        bdd.guardar_valor("clave".to_string(), TipoRedis::Lista(vec![].into()));
         let ptr_hash = Arc::new(Mutex::new(bdd));
         let ptr_hash1 = Arc::clone(&ptr_hash);
 
@@ -958,7 +1948,7 @@ mod tests {
         );
 
         assert_eq!(
-            Some(&TipoRedis::Lista(vec![])),
+            Some(&TipoRedis::Lista(vec![].into())),
             ptr_hash.lock().unwrap().obtener_valor("clave")
         );
     }
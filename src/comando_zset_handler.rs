@@ -0,0 +1,1587 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis, TipoRedis};
+use crate::binario::parsear;
+use crate::comando::{Comando, ComandoHandler};
+use crate::comando_info::ComandoInfo;
+use crate::conjunto_ordenado::ConjuntoOrdenado;
+use crate::cursor::{respuesta, Escaneo};
+use crate::notificacion::ClaseEvento;
+use crate::parser::formatear_double;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Funcion que elige, dentro de un sorted set, los miembros con sus puntajes sobre los que operar
+type Seleccion<'a> = &'a dyn Fn(&ConjuntoOrdenado) -> Vec<(Vec<u8>, f64)>;
+
+/// Manejador de comandos del tipo sorted set
+pub struct ComandoZSetHandler {
+    comando: ComandoInfo,
+    a_ejecutar: Comando,
+}
+
+impl ComandoZSetHandler {
+    pub fn new(comando: ComandoInfo) -> Self {
+        let a_ejecutar = match comando.get_nombre().as_str() {
+            "ZSCORE" => zscore,
+            "ZINCRBY" => zincrby,
+            "ZRANK" => zrank,
+            "ZREVRANK" => zrevrank,
+            "ZRANGE" => zrange,
+            "ZRANGEBYSCORE" => zrangebyscore,
+            "ZREM" => zrem,
+            "ZREMRANGEBYSCORE" => zremrangebyscore,
+            "ZREMRANGEBYRANK" => zremrangebyrank,
+            "ZCARD" => zcard,
+            "ZSCAN" => zscan,
+            "ZCOUNT" => zcount,
+            "ZPOPMIN" => zpopmin,
+            "ZPOPMAX" => zpopmax,
+            "ZUNIONSTORE" => zunionstore,
+            "ZINTERSTORE" => zinterstore,
+            _ => zadd,
+        };
+        ComandoZSetHandler {
+            comando,
+            a_ejecutar: Box::new(a_ejecutar),
+        }
+    }
+}
+
+impl ComandoHandler for ComandoZSetHandler {
+    fn ejecutar(mut self: Box<Self>, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+        (self.a_ejecutar)(&mut self.comando, bdd)
+    }
+}
+/// Se encarga de detectar si el comando corresponde a los implementados del tipo sorted set
+pub fn es_comando_zset(comando: &str) -> bool {
+    let comandos = vec![
+        "ZADD",
+        "ZSCORE",
+        "ZINCRBY",
+        "ZRANK",
+        "ZREVRANK",
+        "ZRANGE",
+        "ZRANGEBYSCORE",
+        "ZREM",
+        "ZREMRANGEBYSCORE",
+        "ZREMRANGEBYRANK",
+        "ZCARD",
+        "ZSCAN",
+        "ZCOUNT",
+        "ZPOPMIN",
+        "ZPOPMAX",
+        "ZUNIONSTORE",
+        "ZINTERSTORE",
+    ];
+    comandos.contains(&comando)
+}
+
+/// Limite de un rango por puntaje, puede ser inclusivo o exclusivo
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct LimitePuntaje {
+    valor: f64,
+    exclusivo: bool,
+}
+
+/// Limite de un rango lexicografico
+#[derive(Debug, Clone, PartialEq)]
+enum LimiteLex {
+    Menos,
+    Mas,
+    Incluido(Vec<u8>),
+    Excluido(Vec<u8>),
+}
+
+/// Forma en la que se interpretan los limites de ZRANGE
+#[derive(Debug, Clone, PartialEq)]
+enum TipoRango {
+    Posicion,
+    Puntaje,
+    Lex,
+}
+
+fn error_tipo() -> ResultadoRedis {
+    ResultadoRedis::Error(
+        "WRONGTYPE Operation against a key holding the wrong kind of value".to_string(),
+    )
+}
+
+fn error_argumentos(comando: &ComandoInfo) -> ResultadoRedis {
+    ResultadoRedis::Error(format!(
+        "ERR wrong number of arguments for '{}' command",
+        comando.get_nombre().to_lowercase()
+    ))
+}
+
+/// Interpreta un puntaje, aceptando inf, +inf y -inf
+pub fn parsear_puntaje(puntaje: &[u8]) -> Option<f64> {
+    match parsear::<f64>(puntaje) {
+        Some(p) if !p.is_nan() => Some(p),
+        _ => None,
+    }
+}
+
+fn parsear_limite_puntaje(limite: &[u8]) -> Option<LimitePuntaje> {
+    match limite.strip_prefix(b"(") {
+        Some(valor) => parsear_puntaje(valor).map(|valor| LimitePuntaje {
+            valor,
+            exclusivo: true,
+        }),
+        None => parsear_puntaje(limite).map(|valor| LimitePuntaje {
+            valor,
+            exclusivo: false,
+        }),
+    }
+}
+
+fn parsear_limite_lex(limite: &[u8]) -> Option<LimiteLex> {
+    if limite == b"-" {
+        Some(LimiteLex::Menos)
+    } else if limite == b"+" {
+        Some(LimiteLex::Mas)
+    } else if let Some(valor) = limite.strip_prefix(b"[") {
+        Some(LimiteLex::Incluido(valor.to_vec()))
+    } else {
+        limite
+            .strip_prefix(b"(")
+            .map(|valor| LimiteLex::Excluido(valor.to_vec()))
+    }
+}
+
+/// Devuelve el intervalo de posiciones [inicio, fin) que ocupan los elementos con puntaje entre min y max
+fn posiciones_por_puntaje(
+    conjunto: &ConjuntoOrdenado,
+    min: LimitePuntaje,
+    max: LimitePuntaje,
+) -> (usize, usize) {
+    let inicio = conjunto.contar_mientras(|p, _| {
+        if min.exclusivo {
+            p <= min.valor
+        } else {
+            p < min.valor
+        }
+    });
+    let fin = conjunto.contar_mientras(|p, _| {
+        if max.exclusivo {
+            p < max.valor
+        } else {
+            p <= max.valor
+        }
+    });
+    (inicio, fin.max(inicio))
+}
+
+/// Devuelve el intervalo de posiciones [inicio, fin) que ocupan los miembros entre min y max lexicograficamente
+fn posiciones_por_lex(
+    conjunto: &ConjuntoOrdenado,
+    min: &LimiteLex,
+    max: &LimiteLex,
+) -> (usize, usize) {
+    let inicio = conjunto.contar_mientras(|_, m| match min {
+        LimiteLex::Menos => false,
+        LimiteLex::Mas => true,
+        LimiteLex::Incluido(v) => m < v.as_slice(),
+        LimiteLex::Excluido(v) => m <= v.as_slice(),
+    });
+    let fin = conjunto.contar_mientras(|_, m| match max {
+        LimiteLex::Menos => false,
+        LimiteLex::Mas => true,
+        LimiteLex::Incluido(v) => m <= v.as_slice(),
+        LimiteLex::Excluido(v) => m < v.as_slice(),
+    });
+    (inicio, fin.max(inicio))
+}
+
+/// Normaliza los indices recibidos (que pueden ser negativos) a un intervalo [inicio, fin) valido
+fn posiciones_por_indice(inicio: i64, fin: i64, largo: usize) -> (usize, usize) {
+    let largo = largo as i64;
+    let inicio = if inicio < 0 { inicio + largo } else { inicio }.max(0);
+    let fin = if fin < 0 { fin + largo } else { fin }.min(largo - 1);
+    if inicio > fin || inicio >= largo {
+        (0, 0)
+    } else {
+        (inicio as usize, fin as usize + 1)
+    }
+}
+
+/// Selecciona los elementos del intervalo [inicio, fin) aplicando el orden, el desplazamiento y la cantidad pedidos
+fn seleccionar(
+    conjunto: &ConjuntoOrdenado,
+    (inicio, fin): (usize, usize),
+    reverso: bool,
+    desplazamiento: usize,
+    cantidad: Option<usize>,
+) -> Vec<(Vec<u8>, f64)> {
+    let disponibles = (fin - inicio).saturating_sub(desplazamiento);
+    let cantidad = cantidad.map_or(disponibles, |c| c.min(disponibles));
+    if reverso {
+        let ultimo = fin - desplazamiento;
+        let mut elementos = conjunto.rango(ultimo - cantidad, ultimo);
+        elementos.reverse();
+        elementos
+    } else {
+        let primero = inicio + desplazamiento;
+        conjunto.rango(primero, primero + cantidad)
+    }
+}
+
+fn respuesta_elementos(elementos: Vec<(Vec<u8>, f64)>, con_puntajes: bool) -> ResultadoRedis {
+    let mut respuesta = vec![];
+    for (miembro, puntaje) in elementos {
+        respuesta.push(ResultadoRedis::BulkStr(miembro));
+        if con_puntajes {
+            respuesta.push(ResultadoRedis::BulkStr(
+                formatear_double(puntaje).into_bytes(),
+            ));
+        }
+    }
+    ResultadoRedis::Vector(respuesta)
+}
+
+/// Opciones que modifican el comportamiento de ZADD
+#[derive(Default)]
+struct OpcionesZAdd {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool,
+    ch: bool,
+    incr: bool,
+}
+
+/// Agrega los miembros con sus puntajes al sorted set almacenado en la clave, o actualiza sus puntajes si ya pertenecian.
+/// Acepta las opciones NX, XX, GT, LT, CH e INCR
+fn zadd(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => return error_argumentos(comando),
+    };
+
+    let mut opciones = OpcionesZAdd::default();
+    let mut resto = vec![];
+    while let Some(parametro) = comando.get_parametro() {
+        if !resto.is_empty() {
+            resto.push(parametro);
+            continue;
+        }
+        match parametro.to_ascii_uppercase().as_slice() {
+            b"NX" => opciones.nx = true,
+            b"XX" => opciones.xx = true,
+            b"GT" => opciones.gt = true,
+            b"LT" => opciones.lt = true,
+            b"CH" => opciones.ch = true,
+            b"INCR" => opciones.incr = true,
+            _ => resto.push(parametro),
+        }
+    }
+
+    if resto.is_empty() || resto.len() % 2 != 0 {
+        return ResultadoRedis::Error("ERR syntax error".to_string());
+    }
+    if opciones.nx && opciones.xx {
+        return ResultadoRedis::Error(
+            "ERR XX and NX options at the same time are not compatible".to_string(),
+        );
+    }
+    if (opciones.gt && opciones.lt) || (opciones.nx && (opciones.gt || opciones.lt)) {
+        return ResultadoRedis::Error(
+            "ERR GT, LT, and/or NX options at the same time are not compatible".to_string(),
+        );
+    }
+    if opciones.incr && resto.len() > 2 {
+        return ResultadoRedis::Error(
+            "ERR INCR option supports a single increment-element pair".to_string(),
+        );
+    }
+
+    let mut pares = vec![];
+    for par in resto.chunks(2) {
+        match parsear_puntaje(&par[0]) {
+            Some(puntaje) => pares.push((puntaje, par[1].clone())),
+            None => return ResultadoRedis::Error("ERR value is not a valid float".to_string()),
+        }
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let conjunto = match bdd.obtener_valor(&clave) {
+                Some(TipoRedis::ZSet(conjunto)) => Some(conjunto),
+                None => None,
+                _ => return error_tipo(),
+            };
+
+            // Primero se calculan los puntajes nuevos sin tocar el conjunto, para que un error no
+            // deje aplicada la mitad de los cambios
+            let mut cambios: HashMap<Vec<u8>, f64> = HashMap::new();
+            let mut agregados = 0;
+            let mut modificados = 0;
+            let mut ultimo_puntaje = None;
+            for (puntaje, miembro) in pares {
+                let anterior = cambios
+                    .get(&miembro)
+                    .copied()
+                    .or_else(|| conjunto.and_then(|c| c.puntaje(&miembro)));
+                let puntaje = match (opciones.incr, anterior) {
+                    (true, Some(a)) => a + puntaje,
+                    _ => puntaje,
+                };
+                if puntaje.is_nan() {
+                    return ResultadoRedis::Error(
+                        "ERR resulting score is not a number (NaN)".to_string(),
+                    );
+                }
+                let omitir = match anterior {
+                    Some(a) => {
+                        opciones.nx
+                            || (opciones.gt && puntaje <= a)
+                            || (opciones.lt && puntaje >= a)
+                    }
+                    None => opciones.xx,
+                };
+                if omitir {
+                    continue;
+                }
+                match anterior {
+                    Some(a) if a != puntaje => modificados += 1,
+                    None => agregados += 1,
+                    _ => (),
+                }
+                cambios.insert(miembro, puntaje);
+                ultimo_puntaje = Some(puntaje);
+            }
+
+            // Un conjunto nuevo se arma completo antes de guardarlo, para que nunca quede vacio
+            if agregados + modificados > 0 {
+                let insertar = |conjunto: &mut ConjuntoOrdenado| {
+                    for (miembro, puntaje) in cambios {
+                        conjunto.insertar(miembro, puntaje);
+                    }
+                };
+                if conjunto.is_some() {
+                    bdd.modificar_zset(&clave, insertar);
+                } else {
+                    let mut conjunto = ConjuntoOrdenado::new();
+                    insertar(&mut conjunto);
+                    bdd.guardar_valor(clave.clone(), TipoRedis::ZSet(conjunto));
+                }
+                let evento = if opciones.incr { "zincr" } else { "zadd" };
+                bdd.notificar(ClaseEvento::ZSet, evento, &clave);
+            }
+
+            if opciones.incr {
+                match ultimo_puntaje {
+                    Some(p) => ResultadoRedis::Double(p),
+                    None => ResultadoRedis::Nil,
+                }
+            } else if opciones.ch {
+                ResultadoRedis::Int(agregados + modificados)
+            } else {
+                ResultadoRedis::Int(agregados)
+            }
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna el puntaje del miembro en el sorted set almacenado en la clave
+fn zscore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, miembro) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(m)) => (c, m),
+        _ => return error_argumentos(comando),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::ZSet(conjunto)) => match conjunto.puntaje(&miembro) {
+                Some(p) => ResultadoRedis::Double(p),
+                None => ResultadoRedis::Nil,
+            },
+            None => ResultadoRedis::Nil,
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Incrementa el puntaje del miembro en el sorted set almacenado en la clave. Si el miembro no existe se agrega con el incremento como puntaje
+fn zincrby(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, incremento, miembro) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(i), Some(m)) => (c, i, m),
+        _ => return error_argumentos(comando),
+    };
+    let mut zadd = ComandoInfo::new(vec![
+        b"ZADD".to_vec(),
+        clave,
+        b"INCR".to_vec(),
+        incremento,
+        miembro,
+    ]);
+    self::zadd(&mut zadd, bdd)
+}
+
+fn posicion(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    reverso: bool,
+) -> ResultadoRedis {
+    let (clave, miembro) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(c), Some(m)) => (c, m),
+        _ => return error_argumentos(comando),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::ZSet(conjunto)) => match conjunto.posicion(&miembro) {
+                Some(p) if reverso => ResultadoRedis::Int((conjunto.len() - 1 - p) as isize),
+                Some(p) => ResultadoRedis::Int(p as isize),
+                None => ResultadoRedis::Nil,
+            },
+            None => ResultadoRedis::Nil,
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna la posicion del miembro en el sorted set ordenado de menor a mayor puntaje
+fn zrank(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    posicion(comando, bdd, false)
+}
+/// Retorna la posicion del miembro en el sorted set ordenado de mayor a menor puntaje
+fn zrevrank(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    posicion(comando, bdd, true)
+}
+
+/// Obtiene los elementos del rango pedido segun su tipo, orden, desplazamiento y cantidad
+fn obtener_rango(
+    conjunto: &ConjuntoOrdenado,
+    tipo: &TipoRango,
+    (min, max): (&[u8], &[u8]),
+    reverso: bool,
+    limite: Option<(usize, Option<usize>)>,
+) -> Result<Vec<(Vec<u8>, f64)>, ResultadoRedis> {
+    let (desplazamiento, cantidad) = limite.unwrap_or((0, None));
+    let posiciones = match tipo {
+        TipoRango::Posicion => {
+            let (inicio, fin) = match (parsear::<i64>(min), parsear::<i64>(max)) {
+                (Some(i), Some(f)) => (i, f),
+                _ => {
+                    return Err(ResultadoRedis::Error(
+                        "ERR value is not an integer or out of range".to_string(),
+                    ))
+                }
+            };
+            let (inicio, fin) = posiciones_por_indice(inicio, fin, conjunto.len());
+            if reverso {
+                (conjunto.len() - fin, conjunto.len() - inicio)
+            } else {
+                (inicio, fin)
+            }
+        }
+        TipoRango::Puntaje => match (parsear_limite_puntaje(min), parsear_limite_puntaje(max)) {
+            (Some(min), Some(max)) => posiciones_por_puntaje(conjunto, min, max),
+            _ => {
+                return Err(ResultadoRedis::Error(
+                    "ERR min or max is not a float".to_string(),
+                ))
+            }
+        },
+        TipoRango::Lex => match (parsear_limite_lex(min), parsear_limite_lex(max)) {
+            (Some(min), Some(max)) => posiciones_por_lex(conjunto, &min, &max),
+            _ => {
+                return Err(ResultadoRedis::Error(
+                    "ERR min or max not valid string range item".to_string(),
+                ))
+            }
+        },
+    };
+    Ok(seleccionar(
+        conjunto,
+        posiciones,
+        reverso,
+        desplazamiento,
+        cantidad,
+    ))
+}
+
+/// Interpreta los argumentos LIMIT offset count. Una cantidad negativa indica todos los elementos restantes
+fn parsear_limite(
+    comando: &mut ComandoInfo,
+) -> Result<Option<(usize, Option<usize>)>, ResultadoRedis> {
+    let (desplazamiento, cantidad) = match (comando.get_parametro(), comando.get_parametro()) {
+        (Some(d), Some(c)) => match (parsear::<i64>(&d), parsear::<i64>(&c)) {
+            (Some(d), Some(c)) => (d, c),
+            _ => {
+                return Err(ResultadoRedis::Error(
+                    "ERR value is not an integer or out of range".to_string(),
+                ))
+            }
+        },
+        _ => return Err(ResultadoRedis::Error("ERR syntax error".to_string())),
+    };
+    if desplazamiento < 0 {
+        return Ok(Some((usize::MAX, Some(0))));
+    }
+    let cantidad = if cantidad < 0 {
+        None
+    } else {
+        Some(cantidad as usize)
+    };
+    Ok(Some((desplazamiento as usize, cantidad)))
+}
+
+fn ejecutar_rango(
+    clave: Vec<u8>,
+    limites: (Vec<u8>, Vec<u8>),
+    tipo: TipoRango,
+    reverso: bool,
+    limite: Option<(usize, Option<usize>)>,
+    con_puntajes: bool,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+) -> ResultadoRedis {
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::ZSet(conjunto)) => {
+                match obtener_rango(conjunto, &tipo, (&limites.0, &limites.1), reverso, limite) {
+                    Ok(elementos) => respuesta_elementos(elementos, con_puntajes),
+                    Err(e) => e,
+                }
+            }
+            None => ResultadoRedis::Vector(vec![]),
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna el rango de elementos del sorted set almacenado en la clave. Por defecto el rango es por posicion,
+/// con BYSCORE o BYLEX se interpreta por puntaje o lexicograficamente. REV invierte el orden y LIMIT permite paginar
+fn zrange(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, min, max) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(min), Some(max)) => (c, min, max),
+        _ => return error_argumentos(comando),
+    };
+
+    let mut tipo = TipoRango::Posicion;
+    let mut reverso = false;
+    let mut con_puntajes = false;
+    let mut limite = None;
+    while let Some(parametro) = comando.get_parametro() {
+        match parametro.to_ascii_uppercase().as_slice() {
+            b"BYSCORE" => tipo = TipoRango::Puntaje,
+            b"BYLEX" => tipo = TipoRango::Lex,
+            b"REV" => reverso = true,
+            b"WITHSCORES" => con_puntajes = true,
+            b"LIMIT" => match parsear_limite(comando) {
+                Ok(l) => limite = l,
+                Err(e) => return e,
+            },
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+
+    if limite.is_some() && tipo == TipoRango::Posicion {
+        return ResultadoRedis::Error(
+            "ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                .to_string(),
+        );
+    }
+    if con_puntajes && tipo == TipoRango::Lex {
+        return ResultadoRedis::Error(
+            "ERR syntax error, WITHSCORES not supported in combination with BYLEX".to_string(),
+        );
+    }
+    // Con REV los rangos por puntaje o lexicograficos reciben primero el maximo y luego el minimo
+    let limites = if reverso && tipo != TipoRango::Posicion {
+        (max, min)
+    } else {
+        (min, max)
+    };
+    ejecutar_rango(clave, limites, tipo, reverso, limite, con_puntajes, bdd)
+}
+/// Retorna los elementos del sorted set almacenado en la clave con puntaje entre min y max
+fn zrangebyscore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, min, max) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(min), Some(max)) => (c, min, max),
+        _ => return error_argumentos(comando),
+    };
+
+    let mut con_puntajes = false;
+    let mut limite = None;
+    while let Some(parametro) = comando.get_parametro() {
+        match parametro.to_ascii_uppercase().as_slice() {
+            b"WITHSCORES" => con_puntajes = true,
+            b"LIMIT" => match parsear_limite(comando) {
+                Ok(l) => limite = l,
+                Err(e) => return e,
+            },
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+    ejecutar_rango(
+        clave,
+        (min, max),
+        TipoRango::Puntaje,
+        false,
+        limite,
+        con_puntajes,
+        bdd,
+    )
+}
+/// Retorna la cantidad de elementos del sorted set almacenado en la clave
+/// Devuelve de a partes los miembros de un set ordenado seguidos de su puntaje a partir de un
+/// cursor, filtrandolos con MATCH
+fn zscan(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let argumentos = match comando.get_parametros() {
+        Some(a) if a.len() >= 2 => a,
+        _ => return error_argumentos(comando),
+    };
+    let escaneo = match Escaneo::parsear(&argumentos[1..], &[]) {
+        Ok(e) => e,
+        Err(e) => return ResultadoRedis::Error(e),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&argumentos[0]) {
+            Some(TipoRedis::ZSet(zset)) => {
+                let (cursor, miembros) = zset.escanear(escaneo.cursor, escaneo.cantidad);
+                let elementos = miembros
+                    .into_iter()
+                    .filter(|(miembro, _)| escaneo.coincide(miembro))
+                    .flat_map(|(miembro, puntaje)| {
+                        vec![
+                            ResultadoRedis::BulkStr(miembro.to_vec()),
+                            ResultadoRedis::BulkStr(formatear_double(puntaje).into_bytes()),
+                        ]
+                    })
+                    .collect();
+                respuesta(cursor, elementos)
+            }
+            None => respuesta(0, vec![]),
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+fn zcard(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => return error_argumentos(comando),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::ZSet(conjunto)) => ResultadoRedis::Int(conjunto.len() as isize),
+            None => ResultadoRedis::Int(0),
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+/// Retorna la cantidad de elementos del sorted set almacenado en la clave con puntaje entre min y max
+fn zcount(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, min, max) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(min), Some(max)) => (c, min, max),
+        _ => return error_argumentos(comando),
+    };
+    let (min, max) = match (parsear_limite_puntaje(&min), parsear_limite_puntaje(&max)) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return ResultadoRedis::Error("ERR min or max is not a float".to_string()),
+    };
+
+    match bdd.lock() {
+        Ok(bdd) => match bdd.obtener_valor(&clave) {
+            Some(TipoRedis::ZSet(conjunto)) => {
+                let (inicio, fin) = posiciones_por_puntaje(conjunto, min, max);
+                ResultadoRedis::Int((fin - inicio) as isize)
+            }
+            None => ResultadoRedis::Int(0),
+            _ => error_tipo(),
+        },
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+/// Elimina del sorted set almacenado en la clave los miembros seleccionados por la funcion recibida
+/// y notifica el evento indicado. Si el sorted set queda vacio se elimina la clave. Retorna los
+/// elementos eliminados
+fn eliminar_seleccionados(
+    clave: Vec<u8>,
+    evento: &str,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    seleccion: Seleccion,
+) -> Result<Vec<(Vec<u8>, f64)>, ResultadoRedis> {
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let eliminados = match bdd.obtener_valor(&clave) {
+                Some(TipoRedis::ZSet(conjunto)) => seleccion(conjunto),
+                None => return Ok(vec![]),
+                _ => return Err(error_tipo()),
+            };
+            if eliminados.is_empty() {
+                return Ok(eliminados);
+            }
+            let vacio = bdd.modificar_zset(&clave, |conjunto| {
+                for (miembro, _) in &eliminados {
+                    conjunto.eliminar(miembro);
+                }
+                conjunto.is_empty()
+            });
+            bdd.notificar(ClaseEvento::ZSet, evento, &clave);
+            if vacio == Some(true) {
+                bdd.eliminar_clave(&clave);
+                bdd.notificar(ClaseEvento::Generico, "del", &clave);
+            }
+            Ok(eliminados)
+        }
+        Err(_) => Err(ResultadoRedis::Error(
+            "ERR when accessing the database".to_string(),
+        )),
+    }
+}
+/// Elimina los miembros indicados del sorted set almacenado en la clave. Retorna la cantidad de miembros eliminados
+fn zrem(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => return error_argumentos(comando),
+    };
+    let mut miembros = vec![];
+    while let Some(miembro) = comando.get_parametro() {
+        miembros.push(miembro);
+    }
+    if miembros.is_empty() {
+        return error_argumentos(comando);
+    }
+
+    let seleccion = |conjunto: &ConjuntoOrdenado| {
+        let mut seleccionados: Vec<(Vec<u8>, f64)> = vec![];
+        for miembro in &miembros {
+            if let Some(p) = conjunto.puntaje(miembro) {
+                if !seleccionados.iter().any(|(m, _)| m == miembro) {
+                    seleccionados.push((miembro.clone(), p));
+                }
+            }
+        }
+        seleccionados
+    };
+    let evento = comando.get_nombre().to_lowercase();
+    match eliminar_seleccionados(clave, &evento, bdd, &seleccion) {
+        Ok(eliminados) => ResultadoRedis::Int(eliminados.len() as isize),
+        Err(e) => e,
+    }
+}
+/// Elimina los miembros del sorted set almacenado en la clave con puntaje entre min y max
+fn zremrangebyscore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, min, max) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(min), Some(max)) => (c, min, max),
+        _ => return error_argumentos(comando),
+    };
+    let (min, max) = match (parsear_limite_puntaje(&min), parsear_limite_puntaje(&max)) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return ResultadoRedis::Error("ERR min or max is not a float".to_string()),
+    };
+
+    let seleccion = |conjunto: &ConjuntoOrdenado| {
+        let (inicio, fin) = posiciones_por_puntaje(conjunto, min, max);
+        conjunto.rango(inicio, fin)
+    };
+    let evento = comando.get_nombre().to_lowercase();
+    match eliminar_seleccionados(clave, &evento, bdd, &seleccion) {
+        Ok(eliminados) => ResultadoRedis::Int(eliminados.len() as isize),
+        Err(e) => e,
+    }
+}
+/// Elimina los miembros del sorted set almacenado en la clave ubicados entre las posiciones indicadas
+fn zremrangebyrank(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    let (clave, inicio, fin) = match (
+        comando.get_clave(),
+        comando.get_parametro(),
+        comando.get_parametro(),
+    ) {
+        (Some(c), Some(i), Some(f)) => (c, i, f),
+        _ => return error_argumentos(comando),
+    };
+    let (inicio, fin) = match (parsear::<i64>(&inicio), parsear::<i64>(&fin)) {
+        (Some(i), Some(f)) => (i, f),
+        _ => {
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+        }
+    };
+
+    let seleccion = |conjunto: &ConjuntoOrdenado| {
+        let (inicio, fin) = posiciones_por_indice(inicio, fin, conjunto.len());
+        conjunto.rango(inicio, fin)
+    };
+    let evento = comando.get_nombre().to_lowercase();
+    match eliminar_seleccionados(clave, &evento, bdd, &seleccion) {
+        Ok(eliminados) => ResultadoRedis::Int(eliminados.len() as isize),
+        Err(e) => e,
+    }
+}
+
+fn pop(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>, maximos: bool) -> ResultadoRedis {
+    let clave = match comando.get_clave() {
+        Some(c) => c,
+        None => return error_argumentos(comando),
+    };
+    let cantidad = match comando.get_parametro() {
+        Some(c) => match parsear::<i64>(&c) {
+            Some(c) if c >= 0 => c as usize,
+            _ => {
+                return ResultadoRedis::Error(
+                    "ERR value is out of range, must be positive".to_string(),
+                )
+            }
+        },
+        None => 1,
+    };
+
+    let seleccion = |conjunto: &ConjuntoOrdenado| {
+        let cantidad = cantidad.min(conjunto.len());
+        if maximos {
+            let mut elementos = conjunto.rango(conjunto.len() - cantidad, conjunto.len());
+            elementos.reverse();
+            elementos
+        } else {
+            conjunto.rango(0, cantidad)
+        }
+    };
+    let evento = comando.get_nombre().to_lowercase();
+    match eliminar_seleccionados(clave, &evento, bdd, &seleccion) {
+        Ok(eliminados) => respuesta_elementos(eliminados, true),
+        Err(e) => e,
+    }
+}
+/// Elimina y retorna los miembros con menor puntaje del sorted set almacenado en la clave
+fn zpopmin(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    pop(comando, bdd, false)
+}
+/// Elimina y retorna los miembros con mayor puntaje del sorted set almacenado en la clave
+fn zpopmax(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    pop(comando, bdd, true)
+}
+
+/// Forma en la que se combinan los puntajes de un mismo miembro presente en varios conjuntos
+#[derive(Clone, Copy)]
+enum Agregacion {
+    Suma,
+    Minimo,
+    Maximo,
+}
+
+impl Agregacion {
+    fn aplicar(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Agregacion::Suma => {
+                let suma = a + b;
+                // inf + -inf se considera 0, al igual que en redis
+                if suma.is_nan() {
+                    0.0
+                } else {
+                    suma
+                }
+            }
+            Agregacion::Minimo => a.min(b),
+            Agregacion::Maximo => a.max(b),
+        }
+    }
+}
+
+/// Combina los sorted sets (o sets) almacenados en las claves indicadas y guarda el resultado en la clave destino.
+/// Si interseccion es verdadero solo se conservan los miembros presentes en todos los conjuntos
+fn combinar_y_guardar(
+    comando: &mut ComandoInfo,
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    interseccion: bool,
+) -> ResultadoRedis {
+    let nombre = comando.get_nombre().to_lowercase();
+    let (destino, cantidad) = match (comando.get_clave(), comando.get_parametro()) {
+        (Some(d), Some(c)) => (d, c),
+        _ => return error_argumentos(comando),
+    };
+    let cantidad = match parsear::<i64>(&cantidad) {
+        Some(c) if c > 0 => c as usize,
+        Some(_) => {
+            return ResultadoRedis::Error(format!(
+                "ERR at least 1 input key is needed for '{}' command",
+                nombre
+            ))
+        }
+        None => {
+            return ResultadoRedis::Error("ERR value is not an integer or out of range".to_string())
+        }
+    };
+
+    let mut claves = vec![];
+    for _ in 0..cantidad {
+        match comando.get_parametro() {
+            Some(c) => claves.push(c),
+            None => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+
+    let mut pesos = vec![1.0; cantidad];
+    let mut agregacion = Agregacion::Suma;
+    while let Some(parametro) = comando.get_parametro() {
+        match parametro.to_ascii_uppercase().as_slice() {
+            b"WEIGHTS" => {
+                for peso in pesos.iter_mut() {
+                    match comando.get_parametro().as_deref().and_then(parsear_puntaje) {
+                        Some(p) => *peso = p,
+                        None => {
+                            return ResultadoRedis::Error(
+                                "ERR weight value is not a float".to_string(),
+                            )
+                        }
+                    }
+                }
+            }
+            b"AGGREGATE" => {
+                agregacion = match comando
+                    .get_parametro()
+                    .map(|a| a.to_ascii_uppercase())
+                    .as_deref()
+                {
+                    Some(b"SUM") => Agregacion::Suma,
+                    Some(b"MIN") => Agregacion::Minimo,
+                    Some(b"MAX") => Agregacion::Maximo,
+                    _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+                }
+            }
+            _ => return ResultadoRedis::Error("ERR syntax error".to_string()),
+        }
+    }
+
+    match bdd.lock() {
+        Ok(mut bdd) => {
+            let mut conjuntos = vec![];
+            for clave in &claves {
+                let elementos = match bdd.obtener_valor(clave) {
+                    Some(TipoRedis::ZSet(conjunto)) => conjunto.elementos(),
+                    Some(TipoRedis::Set(set)) => set.iter().map(|m| (m.clone(), 1.0)).collect(),
+                    None => vec![],
+                    _ => return error_tipo(),
+                };
+                conjuntos.push(elementos);
+            }
+
+            let mut resultado = ConjuntoOrdenado::new();
+            for (i, elementos) in conjuntos.iter().enumerate() {
+                for (miembro, puntaje) in elementos {
+                    let puntaje = ponderar(*puntaje, pesos[i]);
+                    let combinado = match resultado.puntaje(miembro) {
+                        Some(actual) => agregacion.aplicar(actual, puntaje),
+                        None if interseccion && i > 0 => continue,
+                        None => puntaje,
+                    };
+                    resultado.insertar(miembro.clone(), combinado);
+                }
+                if interseccion && i > 0 {
+                    let ausentes: Vec<Vec<u8>> = resultado
+                        .elementos()
+                        .into_iter()
+                        .map(|(m, _)| m)
+                        .filter(|m| !elementos.iter().any(|(e, _)| e == m))
+                        .collect();
+                    for miembro in ausentes {
+                        resultado.eliminar(&miembro);
+                    }
+                }
+            }
+
+            let tamanio = resultado.len();
+            if resultado.is_empty() {
+                if bdd.eliminar_clave(&destino) > 0 {
+                    bdd.notificar(ClaseEvento::Generico, "del", &destino);
+                }
+            } else {
+                bdd.guardar_valor(destino.clone(), TipoRedis::ZSet(resultado));
+                bdd.notificar(ClaseEvento::ZSet, &nombre, &destino);
+            }
+            ResultadoRedis::Int(tamanio as isize)
+        }
+        Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    }
+}
+
+fn ponderar(puntaje: f64, peso: f64) -> f64 {
+    let ponderado = puntaje * peso;
+    // 0 * inf se considera 0
+    if ponderado.is_nan() {
+        0.0
+    } else {
+        ponderado
+    }
+}
+/// Guarda en la clave destino la union de los sorted sets indicados
+fn zunionstore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    combinar_y_guardar(comando, bdd, false)
+}
+/// Guarda en la clave destino la interseccion de los sorted sets indicados
+fn zinterstore(comando: &mut ComandoInfo, bdd: Arc<Mutex<BaseDeDatos>>) -> ResultadoRedis {
+    combinar_y_guardar(comando, bdd, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn comando(argumentos: &[&str]) -> ComandoInfo {
+        ComandoInfo::new(argumentos.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn bdd_con_puntajes() -> Arc<Mutex<BaseDeDatos>> {
+        let mut conjunto = ConjuntoOrdenado::new();
+        conjunto.insertar("uno".into(), 1.0);
+        conjunto.insertar("dos".into(), 2.0);
+        conjunto.insertar("tres".into(), 3.0);
+        conjunto.insertar("cuatro".into(), 4.0);
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("miZSet".to_string(), TipoRedis::ZSet(conjunto));
+        Arc::new(Mutex::new(bdd))
+    }
+
+    fn bulks(elementos: &[&str]) -> ResultadoRedis {
+        ResultadoRedis::Vector(
+            elementos
+                .iter()
+                .map(|e| ResultadoRedis::BulkStr(e.to_string().into_bytes()))
+                .collect(),
+        )
+    }
+
+    struct VigilanteDeConjuntosVacios(Arc<Mutex<bool>>);
+
+    impl crate::observer::Observer for VigilanteDeConjuntosVacios {
+        fn actualizar(&self, bdd: &crate::cursor::TablaEscaneable<crate::valor::Valor>) {
+            let hay_vacios = bdd
+                .values()
+                .any(|v| matches!(v.get(), Some(TipoRedis::ZSet(conjunto)) if conjunto.is_empty()));
+            if hay_vacios {
+                *self.0.lock().unwrap() = true;
+            }
+        }
+    }
+
+    #[test]
+    fn zadd_sobre_una_clave_nueva_nunca_deja_un_conjunto_vacio_en_la_base_de_datos() {
+        use crate::observer::Observable;
+        let vio_un_conjunto_vacio = Arc::new(Mutex::new(false));
+        let mut bdd = BaseDeDatos::new();
+        bdd.agregar_observador(Box::new(VigilanteDeConjuntosVacios(Arc::clone(
+            &vio_un_conjunto_vacio,
+        ))));
+        let h = Arc::new(Mutex::new(bdd));
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zadd(
+                &mut comando(&["ZADD", "z", "1", "a", "2", "b"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            zadd(
+                &mut comando(&["ZADD", "vacio", "XX", "1", "a"]),
+                Arc::clone(&h)
+            )
+        );
+
+        assert!(!*vio_un_conjunto_vacio.lock().unwrap());
+        assert_eq!(None, h.lock().unwrap().obtener_valor("vacio"));
+    }
+
+    #[test]
+    fn zadd_agrega_miembros_nuevos_y_devuelve_la_cantidad_agregada() {
+        let h = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut zadd_1 = comando(&["ZADD", "miZSet", "1", "a", "2", "b"]);
+        let mut zadd_2 = comando(&["ZADD", "miZSet", "5", "a", "3", "c"]);
+
+        assert_eq!(ResultadoRedis::Int(2), zadd(&mut zadd_1, Arc::clone(&h)));
+        assert_eq!(ResultadoRedis::Int(1), zadd(&mut zadd_2, Arc::clone(&h)));
+        assert_eq!(
+            bulks(&["b", "c", "a"]),
+            zrange(&mut comando(&["ZRANGE", "miZSet", "0", "-1"]), h)
+        );
+    }
+
+    #[test]
+    fn zadd_con_ch_cuenta_tambien_los_miembros_modificados() {
+        let h = bdd_con_puntajes();
+        let mut zadd = comando(&[
+            "ZADD", "miZSet", "CH", "10", "uno", "2", "dos", "5", "cinco",
+        ]);
+
+        assert_eq!(ResultadoRedis::Int(2), self::zadd(&mut zadd, h));
+    }
+
+    #[test]
+    fn zadd_con_nx_xx_gt_y_lt_respeta_las_condiciones() {
+        let h = bdd_con_puntajes();
+        let mut nx = comando(&["ZADD", "miZSet", "NX", "10", "uno", "5", "cinco"]);
+        let mut xx = comando(&["ZADD", "miZSet", "XX", "10", "dos", "6", "seis"]);
+        let mut gt = comando(&["ZADD", "miZSet", "GT", "0", "tres"]);
+        let mut lt = comando(&["ZADD", "miZSet", "LT", "0", "cuatro"]);
+
+        assert_eq!(ResultadoRedis::Int(1), zadd(&mut nx, Arc::clone(&h)));
+        assert_eq!(ResultadoRedis::Int(0), zadd(&mut xx, Arc::clone(&h)));
+        assert_eq!(ResultadoRedis::Int(0), zadd(&mut gt, Arc::clone(&h)));
+        assert_eq!(ResultadoRedis::Int(0), zadd(&mut lt, Arc::clone(&h)));
+
+        let bdd = h.lock().unwrap();
+        let conjunto = match bdd.obtener_valor("miZSet") {
+            Some(TipoRedis::ZSet(c)) => c,
+            _ => panic!(),
+        };
+        assert_eq!(Some(1.0), conjunto.puntaje(b"uno"));
+        assert_eq!(Some(10.0), conjunto.puntaje(b"dos"));
+        assert_eq!(None, conjunto.puntaje(b"seis"));
+        assert_eq!(Some(3.0), conjunto.puntaje(b"tres"));
+        assert_eq!(Some(0.0), conjunto.puntaje(b"cuatro"));
+    }
+
+    #[test]
+    fn zadd_con_opciones_incompatibles_devuelve_error() {
+        let h = bdd_con_puntajes();
+        let mut nx_xx = comando(&["ZADD", "miZSet", "NX", "XX", "1", "a"]);
+        let mut gt_lt = comando(&["ZADD", "miZSet", "GT", "LT", "1", "a"]);
+        let mut incr = comando(&["ZADD", "miZSet", "INCR", "1", "a", "2", "b"]);
+        let mut puntaje = comando(&["ZADD", "miZSet", "uno", "a"]);
+
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR XX and NX options at the same time are not compatible".to_string()
+            ),
+            zadd(&mut nx_xx, Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR GT, LT, and/or NX options at the same time are not compatible".to_string()
+            ),
+            zadd(&mut gt_lt, Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "ERR INCR option supports a single increment-element pair".to_string()
+            ),
+            zadd(&mut incr, Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is not a valid float".to_string()),
+            zadd(&mut puntaje, h)
+        );
+    }
+
+    #[test]
+    fn zadd_con_incr_devuelve_el_nuevo_puntaje_o_nil_si_no_se_aplica() {
+        let h = bdd_con_puntajes();
+        let mut incr = comando(&["ZADD", "miZSet", "INCR", "2.5", "uno"]);
+        let mut incr_nx = comando(&["ZADD", "miZSet", "NX", "INCR", "1", "uno"]);
+
+        assert_eq!(ResultadoRedis::Double(3.5), zadd(&mut incr, Arc::clone(&h)));
+        assert_eq!(ResultadoRedis::Nil, zadd(&mut incr_nx, h));
+    }
+
+    #[test]
+    fn zscore_y_zincrby_devuelven_el_puntaje_del_miembro() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            ResultadoRedis::Double(2.0),
+            zscore(&mut comando(&["ZSCORE", "miZSet", "dos"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Nil,
+            zscore(&mut comando(&["ZSCORE", "miZSet", "cinco"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Double(-1.0),
+            zincrby(
+                &mut comando(&["ZINCRBY", "miZSet", "-3", "dos"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            zrank(&mut comando(&["ZRANK", "miZSet", "dos"]), h)
+        );
+    }
+
+    #[test]
+    fn zrank_y_zrevrank_devuelven_la_posicion_del_miembro() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zrank(&mut comando(&["ZRANK", "miZSet", "tres"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            zrevrank(
+                &mut comando(&["ZREVRANK", "miZSet", "tres"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Nil,
+            zrank(&mut comando(&["ZRANK", "miZSet", "cinco"]), h)
+        );
+    }
+
+    #[test]
+    fn zrange_por_posicion_acepta_indices_negativos_rev_y_withscores() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            bulks(&["dos", "tres"]),
+            zrange(
+                &mut comando(&["ZRANGE", "miZSet", "1", "-2"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["cuatro", "4", "tres", "3"]),
+            zrange(
+                &mut comando(&["ZRANGE", "miZSet", "0", "1", "REV", "WITHSCORES"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&[]),
+            zrange(&mut comando(&["ZRANGE", "miZSet", "5", "10"]), h)
+        );
+    }
+
+    #[test]
+    fn zrange_por_puntaje_respeta_limites_exclusivos_rev_y_limit() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            bulks(&["dos", "tres", "cuatro"]),
+            zrange(
+                &mut comando(&["ZRANGE", "miZSet", "(1", "+inf", "BYSCORE"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["tres", "dos"]),
+            zrange(
+                &mut comando(&[
+                    "ZRANGE", "miZSet", "(4", "-inf", "BYSCORE", "REV", "LIMIT", "0", "2"
+                ]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["dos", "2", "tres", "3"]),
+            zrangebyscore(
+                &mut comando(&[
+                    "ZRANGEBYSCORE",
+                    "miZSet",
+                    "-inf",
+                    "3",
+                    "WITHSCORES",
+                    "LIMIT",
+                    "1",
+                    "-1"
+                ]),
+                h
+            )
+        );
+    }
+
+    #[test]
+    fn zrange_por_lex_devuelve_los_miembros_en_el_rango() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        for miembro in ["a", "b", "c", "d", "e"].iter() {
+            conjunto.insertar(miembro.as_bytes().to_vec(), 0.0);
+        }
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("letras".to_string(), TipoRedis::ZSet(conjunto));
+        let h = Arc::new(Mutex::new(bdd));
+
+        assert_eq!(
+            bulks(&["b", "c"]),
+            zrange(
+                &mut comando(&["ZRANGE", "letras", "[b", "(d", "BYLEX"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["e", "d"]),
+            zrange(
+                &mut comando(&["ZRANGE", "letras", "+", "-", "BYLEX", "REV", "LIMIT", "0", "2"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR min or max not valid string range item".to_string()),
+            zrange(&mut comando(&["ZRANGE", "letras", "a", "c", "BYLEX"]), h)
+        );
+    }
+
+    #[test]
+    fn zcard_y_zcount_cuentan_los_miembros() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            zcard(&mut comando(&["ZCARD", "miZSet"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zcount(
+                &mut comando(&["ZCOUNT", "miZSet", "(1", "3"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(0),
+            zcard(&mut comando(&["ZCARD", "otro"]), h)
+        );
+    }
+
+    #[test]
+    fn zrem_elimina_los_miembros_y_la_clave_si_queda_vacia() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zrem(
+                &mut comando(&["ZREM", "miZSet", "uno", "dos", "cinco"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zrem(
+                &mut comando(&["ZREM", "miZSet", "tres", "cuatro"]),
+                Arc::clone(&h)
+            )
+        );
+        assert!(!h.lock().unwrap().existe_clave("miZSet"));
+    }
+
+    #[test]
+    fn zremrangebyscore_y_zremrangebyrank_eliminan_el_rango() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            ResultadoRedis::Int(2),
+            zremrangebyscore(
+                &mut comando(&["ZREMRANGEBYSCORE", "miZSet", "-inf", "(3"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            zremrangebyrank(
+                &mut comando(&["ZREMRANGEBYRANK", "miZSet", "-1", "-1"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["tres"]),
+            zrange(&mut comando(&["ZRANGE", "miZSet", "0", "-1"]), h)
+        );
+    }
+
+    #[test]
+    fn zpopmin_y_zpopmax_extraen_los_extremos() {
+        let h = bdd_con_puntajes();
+
+        assert_eq!(
+            bulks(&["uno", "1"]),
+            zpopmin(&mut comando(&["ZPOPMIN", "miZSet"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            bulks(&["cuatro", "4", "tres", "3"]),
+            zpopmax(&mut comando(&["ZPOPMAX", "miZSet", "2"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR value is out of range, must be positive".to_string()),
+            zpopmax(&mut comando(&["ZPOPMAX", "miZSet", "-2"]), h)
+        );
+    }
+
+    #[test]
+    fn zunionstore_y_zinterstore_combinan_con_pesos_y_agregacion() {
+        let h = bdd_con_puntajes();
+        {
+            let mut bdd = h.lock().unwrap();
+            let mut set = HashSet::new();
+            set.insert("uno".into());
+            set.insert("cinco".into());
+            bdd.guardar_valor("miSet".to_string(), TipoRedis::Set(set.into()));
+        }
+
+        assert_eq!(
+            ResultadoRedis::Int(5),
+            zunionstore(
+                &mut comando(&[
+                    "ZUNIONSTORE",
+                    "union",
+                    "2",
+                    "miZSet",
+                    "miSet",
+                    "WEIGHTS",
+                    "1",
+                    "10"
+                ]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Double(11.0),
+            zscore(&mut comando(&["ZSCORE", "union", "uno"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            zinterstore(
+                &mut comando(&[
+                    "ZINTERSTORE",
+                    "inter",
+                    "2",
+                    "miZSet",
+                    "miSet",
+                    "AGGREGATE",
+                    "MAX"
+                ]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            bulks(&["uno", "1"]),
+            zrange(
+                &mut comando(&["ZRANGE", "inter", "0", "-1", "WITHSCORES"]),
+                h
+            )
+        );
+    }
+
+    #[test]
+    fn un_comando_sobre_una_clave_de_otro_tipo_devuelve_wrongtype() {
+        let mut bdd = BaseDeDatos::new();
+        bdd.guardar_valor("clave".to_string(), TipoRedis::Str("valor".into()));
+        let h = Arc::new(Mutex::new(bdd));
+
+        assert_eq!(
+            error_tipo(),
+            zadd(&mut comando(&["ZADD", "clave", "1", "a"]), h)
+        );
+    }
+
+    #[test]
+    fn zscan_devuelve_los_miembros_con_su_puntaje() {
+        let h = bdd_con_puntajes();
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::BulkStr("0".into()),
+                bulks(&["tres", "3"]),
+            ]),
+            zscan(
+                &mut comando(&["ZSCAN", "miZSet", "0", "MATCH", "t*"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            error_argumentos(&comando(&["ZSCAN"])),
+            zscan(&mut comando(&["ZSCAN", "miZSet"]), h)
+        );
+    }
+
+    #[test]
+    fn las_escrituras_modifican_el_conjunto_en_el_lugar_y_conservan_la_expiracion() {
+        let h = bdd_con_puntajes();
+        let vencimiento = crate::valor::ahora_ms() + 100_000;
+        h.lock()
+            .unwrap()
+            .actualizar_vencimiento("miZSet", vencimiento);
+
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            zadd(
+                &mut comando(&["ZADD", "miZSet", "5", "cinco"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            ResultadoRedis::Int(1),
+            zrem(&mut comando(&["ZREM", "miZSet", "uno"]), Arc::clone(&h))
+        );
+        assert_eq!(
+            ResultadoRedis::Double(3.0),
+            zincrby(
+                &mut comando(&["ZINCRBY", "miZSet", "1", "dos"]),
+                Arc::clone(&h)
+            )
+        );
+        assert_eq!(
+            Some(Some(vencimiento)),
+            h.lock().unwrap().obtener_vencimiento("miZSet")
+        );
+        assert_eq!(
+            ResultadoRedis::Int(4),
+            zcard(&mut comando(&["ZCARD", "miZSet"]), h)
+        );
+    }
+
+    #[test]
+    fn las_escrituras_sobre_el_sorted_set_notifican_sus_eventos() {
+        use crate::notificacion::Notificaciones;
+        use crate::prueba::cliente_de_prueba;
+        use std::io::Read;
+
+        let (cliente, mut conexion) = cliente_de_prueba(1);
+        let mut bdd = BaseDeDatos::new();
+        bdd.set_notificaciones(Notificaciones::desde("Kzg").unwrap());
+        bdd.pubsub()
+            .suscribir(b"__keyspace@0__:miZSet".to_vec(), cliente);
+        let h = Arc::new(Mutex::new(bdd));
+
+        zadd(
+            &mut comando(&["ZADD", "miZSet", "1", "uno"]),
+            Arc::clone(&h),
+        );
+        zincrby(
+            &mut comando(&["ZINCRBY", "miZSet", "2", "uno"]),
+            Arc::clone(&h),
+        );
+        zrem(&mut comando(&["ZREM", "miZSet", "uno"]), Arc::clone(&h));
+
+        let esperado: String = ["zadd", "zincr", "zrem", "del"]
+            .iter()
+            .map(|evento| {
+                format!(
+                    "*3\r\n$7\r\nmessage\r\n$21\r\n__keyspace@0__:miZSet\r\n${}\r\n{}\r\n",
+                    evento.len(),
+                    evento
+                )
+            })
+            .collect();
+        let mut recibido = String::new();
+        let mut buffer = [0; 512];
+        while !recibido.ends_with(&esperado) {
+            let leidos = conexion.read(&mut buffer).unwrap();
+            recibido.push_str(&String::from_utf8_lossy(&buffer[..leidos]));
+        }
+    }
+}
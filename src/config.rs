@@ -1,12 +1,14 @@
+use crate::aof::{ArchivoAof, PoliticaFsync};
 use crate::cliente::Cliente;
 use crate::log_handler::Logger;
+use crate::notificacion::Notificaciones;
+use crate::patron::coincide;
 use crate::persistencia::Persistidor;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
-
-use regex::Regex;
+use std::sync::{Arc, Mutex};
 
 /// Representa un error al leer el archivo de configuracion
 pub enum ArchivoError {
@@ -18,6 +20,7 @@ pub enum ArchivoError {
 pub struct Config {
     mapa_config: HashMap<String, String>,
     persistidor: Option<Persistidor>,
+    aof: Option<Arc<Mutex<ArchivoAof>>>,
     monitorear_ultimo_cliente: bool,
 }
 
@@ -31,9 +34,15 @@ impl Config {
         mapa_config.insert("timeout".to_string(), "0".to_string());
         mapa_config.insert("dbfilename".to_string(), "dump.rb".to_string());
         mapa_config.insert("logfile".to_string(), "redis.log".to_string());
+        mapa_config.insert("appendonly".to_string(), "no".to_string());
+        mapa_config.insert("appendfilename".to_string(), "appendonly.aof".to_string());
+        mapa_config.insert("appendfsync".to_string(), "everysec".to_string());
+        mapa_config.insert("notify-keyspace-events".to_string(), "".to_string());
+        mapa_config.insert("hz".to_string(), "10".to_string());
         Config {
             mapa_config,
             persistidor: None,
+            aof: None,
             monitorear_ultimo_cliente: false,
         }
     }
@@ -73,6 +82,50 @@ impl Config {
         }
     }
 
+    /// Indica si la base de datos se persiste registrando los comandos en un archivo AOF
+    pub fn appendonly(&self) -> bool {
+        match self.mapa_config.get("appendonly") {
+            Some(a) => a == "yes",
+            None => false,
+        }
+    }
+
+    pub fn appendfilename(&self) -> String {
+        match self.mapa_config.get("appendfilename") {
+            Some(a) => a.to_string(),
+            None => "appendonly.aof".to_string(),
+        }
+    }
+
+    /// Politica de sincronizacion del archivo AOF, por defecto everysec
+    pub fn appendfsync(&self) -> PoliticaFsync {
+        match self.mapa_config.get("appendfsync") {
+            Some(a) => PoliticaFsync::desde(a).unwrap_or(PoliticaFsync::CadaSegundo),
+            None => PoliticaFsync::CadaSegundo,
+        }
+    }
+
+    /// Notificaciones de eventos sobre claves habilitadas, por defecto ninguna
+    pub fn notify_keyspace_events(&self) -> Notificaciones {
+        self.mapa_config
+            .get("notify-keyspace-events")
+            .and_then(|n| Notificaciones::desde(n))
+            .unwrap_or_default()
+    }
+
+    /// Cantidad de veces por segundo que se buscan claves expiradas para eliminarlas, entre 1 y 500.
+    /// Por defecto 10
+    pub fn hz(&self) -> u64 {
+        match self
+            .mapa_config
+            .get("hz")
+            .and_then(|h| h.parse::<u64>().ok())
+        {
+            Some(hz) => hz.clamp(1, 500),
+            None => 10,
+        }
+    }
+
     pub fn verbose(&self) -> bool {
         match self.mapa_config.get("verbose") {
             Some(t) => match t.parse::<u32>() {
@@ -83,16 +136,13 @@ impl Config {
         }
     }
 
-    /// Obtiene los items de la configuracion que matchean la expresion regular
-    pub fn get(&self, re: &str) -> Vec<String> {
-        let regex = match Regex::new(re) {
-            Ok(r) => r,
-            Err(_) => return Vec::new(),
-        };
-
+    /// Obtiene los items de la configuracion cuyo nombre coincide con el patron glob, sin
+    /// distinguir mayusculas de minusculas
+    pub fn get(&self, patron: &[u8]) -> Vec<String> {
+        let patron = patron.to_ascii_lowercase();
         let mut vec = Vec::new();
         for (clave, valor) in &self.mapa_config {
-            if regex.is_match(clave) {
+            if coincide(&patron, clave.to_ascii_lowercase().as_bytes()) {
                 vec.push(clave.clone());
                 vec.push(valor.clone());
             }
@@ -139,11 +189,20 @@ impl Config {
             Some(p) => p.cambiar_archivo(self.dbfilename()),
             None => (),
         }
+        if let Some(aof) = &self.aof {
+            if let Ok(mut aof) = aof.lock() {
+                aof.cambiar_politica(self.appendfsync());
+            }
+        }
     }
 
     pub fn set_persistidor(&mut self, p: Persistidor) {
         self.persistidor = Some(p);
     }
+
+    pub fn set_aof(&mut self, aof: Arc<Mutex<ArchivoAof>>) {
+        self.aof = Some(aof);
+    }
 }
 
 /// Lee un archivo de configuracion y devuelve la configuracion leida
@@ -168,6 +227,7 @@ pub fn obtener_configuracion(ruta_archivo: String) -> Result<Config, ArchivoErro
         Ok(Config {
             mapa_config: mapa,
             persistidor: None,
+            aof: None,
             monitorear_ultimo_cliente: false,
         })
     }
@@ -0,0 +1,353 @@
+use crate::cursor::TablaEscaneable;
+use std::cmp::Ordering;
+
+/// Conjunto de miembros unicos ordenados por su puntaje, y ante igual puntaje lexicograficamente.
+/// Mantiene un indice de miembro a puntaje y un arbol AVL aumentado con el tamaño de cada subarbol,
+/// de modo que las busquedas por rango y por posicion son logaritmicas. El indice tambien guarda el
+/// orden en que ZSCAN recorre los miembros
+#[derive(Debug, Clone, Default)]
+pub struct ConjuntoOrdenado {
+    puntajes: TablaEscaneable<f64>,
+    arbol: Option<Box<Nodo>>,
+}
+
+#[derive(Debug, Clone)]
+struct Nodo {
+    puntaje: f64,
+    miembro: Vec<u8>,
+    altura: i32,
+    tamanio: usize,
+    izq: Option<Box<Nodo>>,
+    der: Option<Box<Nodo>>,
+}
+
+impl ConjuntoOrdenado {
+    pub fn new() -> Self {
+        ConjuntoOrdenado {
+            puntajes: TablaEscaneable::default(),
+            arbol: None,
+        }
+    }
+
+    /// Cantidad de miembros del conjunto
+    pub fn len(&self) -> usize {
+        self.puntajes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.puntajes.is_empty()
+    }
+
+    /// Devuelve el puntaje del miembro, o ninguno si no pertenece al conjunto
+    pub fn puntaje(&self, miembro: &[u8]) -> Option<f64> {
+        self.puntajes.get(miembro).copied()
+    }
+
+    /// Inserta el miembro con el puntaje indicado, reemplazando su puntaje anterior si ya existia.
+    /// Devuelve true si el miembro es nuevo
+    pub fn insertar(&mut self, miembro: Vec<u8>, puntaje: f64) -> bool {
+        // -0.0 y 0.0 se consideran el mismo puntaje
+        let puntaje = if puntaje == 0.0 { 0.0 } else { puntaje };
+        let nuevo = match self.puntajes.insert(miembro.clone(), puntaje) {
+            Some(anterior) => {
+                self.arbol = eliminar_nodo(self.arbol.take(), anterior, &miembro);
+                false
+            }
+            None => true,
+        };
+        self.arbol = Some(insertar_nodo(self.arbol.take(), puntaje, miembro));
+        nuevo
+    }
+
+    /// Elimina el miembro del conjunto, devuelve true si pertenecia al mismo
+    pub fn eliminar(&mut self, miembro: &[u8]) -> bool {
+        match self.puntajes.remove(miembro) {
+            Some(puntaje) => {
+                self.arbol = eliminar_nodo(self.arbol.take(), puntaje, miembro);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Devuelve la posicion (comenzando en 0) del miembro en el orden ascendente del conjunto
+    pub fn posicion(&self, miembro: &[u8]) -> Option<usize> {
+        let puntaje = self.puntaje(miembro)?;
+        Some(self.contar_mientras(|p, m| comparar(p, m, puntaje, miembro) == Ordering::Less))
+    }
+
+    /// Devuelve el miembro y su puntaje ubicados en la posicion indicada del orden ascendente
+    pub fn en_posicion(&self, mut posicion: usize) -> Option<(&[u8], f64)> {
+        let mut actual = self.arbol.as_ref();
+        while let Some(nodo) = actual {
+            let izquierda = tamanio(&nodo.izq);
+            match posicion.cmp(&izquierda) {
+                Ordering::Less => actual = nodo.izq.as_ref(),
+                Ordering::Equal => return Some((&nodo.miembro, nodo.puntaje)),
+                Ordering::Greater => {
+                    posicion -= izquierda + 1;
+                    actual = nodo.der.as_ref();
+                }
+            }
+        }
+        None
+    }
+
+    /// Cuenta la cantidad de elementos, en orden ascendente, que cumplen con el predicado.
+    /// El predicado debe ser monotono: verdadero para un prefijo del conjunto y falso para el resto
+    pub fn contar_mientras<F>(&self, predicado: F) -> usize
+    where
+        F: Fn(f64, &[u8]) -> bool,
+    {
+        let mut cantidad = 0;
+        let mut actual = self.arbol.as_ref();
+        while let Some(nodo) = actual {
+            if predicado(nodo.puntaje, &nodo.miembro) {
+                cantidad += tamanio(&nodo.izq) + 1;
+                actual = nodo.der.as_ref();
+            } else {
+                actual = nodo.izq.as_ref();
+            }
+        }
+        cantidad
+    }
+
+    /// Devuelve los elementos comprendidos entre las posiciones [inicio, fin) en orden ascendente
+    pub fn rango(&self, inicio: usize, fin: usize) -> Vec<(Vec<u8>, f64)> {
+        (inicio..fin.min(self.len()))
+            .filter_map(|i| self.en_posicion(i))
+            .map(|(miembro, puntaje)| (miembro.to_vec(), puntaje))
+            .collect()
+    }
+
+    /// Recorre los miembros con su puntaje, sin ningun orden en particular
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], f64)> + Clone {
+        self.puntajes
+            .iter()
+            .map(|(miembro, puntaje)| (miembro.as_slice(), *puntaje))
+    }
+
+    /// Recorre los miembros desde el cursor con su puntaje, ver `TablaEscaneable::escanear`
+    pub fn escanear(&self, cursor: u64, cantidad: usize) -> (u64, Vec<(&[u8], f64)>) {
+        let (cursor, miembros) = self.puntajes.escanear(cursor, cantidad);
+        let miembros = miembros
+            .into_iter()
+            .map(|(miembro, puntaje)| (miembro.as_slice(), *puntaje))
+            .collect();
+        (cursor, miembros)
+    }
+
+    /// Devuelve todos los elementos en orden ascendente
+    pub fn elementos(&self) -> Vec<(Vec<u8>, f64)> {
+        let mut elementos = Vec::with_capacity(self.len());
+        recorrer(&self.arbol, &mut elementos);
+        elementos
+    }
+}
+
+impl PartialEq for ConjuntoOrdenado {
+    fn eq(&self, other: &Self) -> bool {
+        self.puntajes == other.puntajes
+    }
+}
+
+/// Compara dos elementos del conjunto, primero por puntaje y luego por miembro byte a byte
+pub fn comparar(puntaje_a: f64, miembro_a: &[u8], puntaje_b: f64, miembro_b: &[u8]) -> Ordering {
+    match puntaje_a.partial_cmp(&puntaje_b) {
+        Some(Ordering::Equal) | None => miembro_a.cmp(miembro_b),
+        Some(orden) => orden,
+    }
+}
+
+fn tamanio(nodo: &Option<Box<Nodo>>) -> usize {
+    nodo.as_ref().map_or(0, |n| n.tamanio)
+}
+
+fn altura(nodo: &Option<Box<Nodo>>) -> i32 {
+    nodo.as_ref().map_or(0, |n| n.altura)
+}
+
+fn actualizar(nodo: &mut Box<Nodo>) {
+    nodo.altura = 1 + altura(&nodo.izq).max(altura(&nodo.der));
+    nodo.tamanio = 1 + tamanio(&nodo.izq) + tamanio(&nodo.der);
+}
+
+fn rotar_derecha(mut nodo: Box<Nodo>) -> Box<Nodo> {
+    let mut izq = match nodo.izq.take() {
+        Some(izq) => izq,
+        None => return nodo,
+    };
+    nodo.izq = izq.der.take();
+    actualizar(&mut nodo);
+    izq.der = Some(nodo);
+    actualizar(&mut izq);
+    izq
+}
+
+fn rotar_izquierda(mut nodo: Box<Nodo>) -> Box<Nodo> {
+    let mut der = match nodo.der.take() {
+        Some(der) => der,
+        None => return nodo,
+    };
+    nodo.der = der.izq.take();
+    actualizar(&mut nodo);
+    der.izq = Some(nodo);
+    actualizar(&mut der);
+    der
+}
+
+fn balancear(mut nodo: Box<Nodo>) -> Box<Nodo> {
+    actualizar(&mut nodo);
+    let factor = altura(&nodo.izq) - altura(&nodo.der);
+    if factor > 1 {
+        if let Some(izq) = nodo.izq.take() {
+            nodo.izq = Some(if altura(&izq.izq) < altura(&izq.der) {
+                rotar_izquierda(izq)
+            } else {
+                izq
+            });
+        }
+        rotar_derecha(nodo)
+    } else if factor < -1 {
+        if let Some(der) = nodo.der.take() {
+            nodo.der = Some(if altura(&der.der) < altura(&der.izq) {
+                rotar_derecha(der)
+            } else {
+                der
+            });
+        }
+        rotar_izquierda(nodo)
+    } else {
+        nodo
+    }
+}
+
+fn insertar_nodo(nodo: Option<Box<Nodo>>, puntaje: f64, miembro: Vec<u8>) -> Box<Nodo> {
+    let mut nodo = match nodo {
+        Some(n) => n,
+        None => {
+            return Box::new(Nodo {
+                puntaje,
+                miembro,
+                altura: 1,
+                tamanio: 1,
+                izq: None,
+                der: None,
+            })
+        }
+    };
+    match comparar(puntaje, &miembro, nodo.puntaje, &nodo.miembro) {
+        Ordering::Less => nodo.izq = Some(insertar_nodo(nodo.izq.take(), puntaje, miembro)),
+        Ordering::Greater => nodo.der = Some(insertar_nodo(nodo.der.take(), puntaje, miembro)),
+        Ordering::Equal => return nodo,
+    }
+    balancear(nodo)
+}
+
+fn eliminar_nodo(nodo: Option<Box<Nodo>>, puntaje: f64, miembro: &[u8]) -> Option<Box<Nodo>> {
+    let mut nodo = nodo?;
+    match comparar(puntaje, miembro, nodo.puntaje, &nodo.miembro) {
+        Ordering::Less => nodo.izq = eliminar_nodo(nodo.izq.take(), puntaje, miembro),
+        Ordering::Greater => nodo.der = eliminar_nodo(nodo.der.take(), puntaje, miembro),
+        Ordering::Equal => match (nodo.izq.take(), nodo.der.take()) {
+            (None, None) => return None,
+            (Some(izq), None) => return Some(izq),
+            (None, Some(der)) => return Some(der),
+            (Some(izq), Some(der)) => {
+                let (resto, mut minimo) = extraer_minimo(der);
+                minimo.izq = Some(izq);
+                minimo.der = resto;
+                return Some(balancear(minimo));
+            }
+        },
+    }
+    Some(balancear(nodo))
+}
+
+/// Extrae el menor nodo del subarbol, devolviendo el subarbol restante y el nodo extraido
+fn extraer_minimo(mut nodo: Box<Nodo>) -> (Option<Box<Nodo>>, Box<Nodo>) {
+    match nodo.izq.take() {
+        None => (nodo.der.take(), nodo),
+        Some(izq) => {
+            let (resto, minimo) = extraer_minimo(izq);
+            nodo.izq = resto;
+            (Some(balancear(nodo)), minimo)
+        }
+    }
+}
+
+fn recorrer(nodo: &Option<Box<Nodo>>, elementos: &mut Vec<(Vec<u8>, f64)>) {
+    if let Some(n) = nodo {
+        recorrer(&n.izq, elementos);
+        elementos.push((n.miembro.clone(), n.puntaje));
+        recorrer(&n.der, elementos);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn los_elementos_se_recorren_ordenados_por_puntaje_y_luego_por_miembro() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        conjunto.insertar(b"c".to_vec(), 2.0);
+        conjunto.insertar(b"b".to_vec(), 1.0);
+        conjunto.insertar(b"a".to_vec(), 2.0);
+
+        assert_eq!(
+            vec![
+                (b"b".to_vec(), 1.0),
+                (b"a".to_vec(), 2.0),
+                (b"c".to_vec(), 2.0)
+            ],
+            conjunto.elementos()
+        );
+    }
+
+    #[test]
+    fn reinsertar_un_miembro_actualiza_su_puntaje_y_su_posicion() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        assert!(conjunto.insertar(b"a".to_vec(), 1.0));
+        assert!(conjunto.insertar(b"b".to_vec(), 2.0));
+        assert!(!conjunto.insertar(b"a".to_vec(), 3.0));
+
+        assert_eq!(2, conjunto.len());
+        assert_eq!(Some(1), conjunto.posicion(b"a"));
+        assert_eq!(Some((&b"b"[..], 2.0)), conjunto.en_posicion(0));
+    }
+
+    #[test]
+    fn las_posiciones_se_mantienen_consistentes_con_muchas_inserciones_y_eliminaciones() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        for i in 0..1000 {
+            conjunto.insertar(format!("m{}", i).into_bytes(), ((i * 7919) % 1000) as f64);
+        }
+        for i in (0..1000).step_by(3) {
+            assert!(conjunto.eliminar(format!("m{}", i).as_bytes()));
+        }
+
+        let elementos = conjunto.elementos();
+        assert_eq!(elementos.len(), conjunto.len());
+        for (i, (miembro, puntaje)) in elementos.iter().enumerate() {
+            assert_eq!(Some(i), conjunto.posicion(miembro));
+            assert_eq!(
+                Some((miembro.as_slice(), *puntaje)),
+                conjunto.en_posicion(i)
+            );
+        }
+        assert!(elementos.windows(2).all(|par| par[0].1 <= par[1].1));
+    }
+
+    #[test]
+    fn contar_mientras_cuenta_los_elementos_previos_a_un_limite() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        for (i, miembro) in ["a", "b", "c", "d"].iter().enumerate() {
+            conjunto.insertar(miembro.as_bytes().to_vec(), i as f64);
+        }
+
+        assert_eq!(2, conjunto.contar_mientras(|p, _| p < 2.0));
+        assert_eq!(0, conjunto.contar_mientras(|p, _| p < -1.0));
+        assert_eq!(4, conjunto.contar_mientras(|p, _| p <= 3.0));
+    }
+}
@@ -0,0 +1,607 @@
+use crate::base_de_datos::ResultadoRedis;
+use crate::binario::{parsear, parsear_entero};
+use crate::patron::coincide;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::hash::Hasher;
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
+
+/// Opciones de un recorrido con cursor, comunes a SCAN, SSCAN, HSCAN y ZSCAN
+#[derive(Debug, PartialEq)]
+pub struct Escaneo {
+    pub cursor: u64,
+    pub cantidad: usize,
+    patron: Option<Vec<u8>>,
+    tipo: Option<Vec<u8>>,
+    pub sin_valores: bool,
+}
+
+impl Escaneo {
+    /// Parsea el cursor seguido de las opciones MATCH y COUNT, y de las opciones propias del
+    /// comando que se indiquen en `extras` (TYPE o NOVALUES)
+    pub fn parsear(argumentos: &[Vec<u8>], extras: &[&str]) -> Result<Escaneo, String> {
+        let cursor = match argumentos.first().and_then(|c| parsear::<u64>(c)) {
+            Some(c) => c,
+            None => return Err("ERR invalid cursor".to_string()),
+        };
+        let mut escaneo = Escaneo {
+            cursor,
+            cantidad: 10,
+            patron: None,
+            tipo: None,
+            sin_valores: false,
+        };
+
+        let mut opciones = argumentos[1..].iter();
+        while let Some(opcion) = opciones.next() {
+            let opcion = String::from_utf8_lossy(opcion).to_uppercase();
+            let permitida =
+                matches!(opcion.as_str(), "MATCH" | "COUNT") || extras.contains(&&*opcion);
+            if !permitida {
+                return Err("ERR syntax error".to_string());
+            }
+            if opcion == "NOVALUES" {
+                escaneo.sin_valores = true;
+                continue;
+            }
+            let valor = match opciones.next() {
+                Some(v) => v,
+                None => return Err("ERR syntax error".to_string()),
+            };
+            match opcion.as_str() {
+                "MATCH" => escaneo.patron = Some(valor.clone()),
+                "TYPE" => escaneo.tipo = Some(valor.clone()),
+                _ => match parsear_entero(valor) {
+                    Some(c) if c >= 1 => escaneo.cantidad = c as usize,
+                    Some(_) => return Err("ERR syntax error".to_string()),
+                    None => return Err("ERR value is not an integer or out of range".to_string()),
+                },
+            }
+        }
+        Ok(escaneo)
+    }
+
+    /// Indica si el elemento coincide con el patron de MATCH, si es que se indico uno
+    pub fn coincide(&self, elemento: &[u8]) -> bool {
+        self.patron
+            .as_ref()
+            .is_none_or(|patron| coincide(patron, elemento))
+    }
+
+    /// Indica si el nombre del tipo es el pedido con TYPE, si es que se indico uno
+    pub fn es_del_tipo(&self, nombre: &str) -> bool {
+        self.tipo
+            .as_ref()
+            .is_none_or(|tipo| tipo.eq_ignore_ascii_case(nombre.as_bytes()))
+    }
+}
+
+/// Posicion de un elemento en el orden del recorrido. El hasher usa claves fijas, asi que la
+/// posicion no depende de como esten distribuidos los elementos en la tabla y no cambia cuando
+/// esta se redimensiona
+fn posicion(elemento: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(elemento);
+    hasher.finish()
+}
+
+/// Toma en orden las posiciones, junto con la cantidad de elementos en cada una, hasta juntar
+/// `cantidad` elementos. Devuelve las posiciones tomadas y el cursor desde el que sigue el
+/// recorrido, que es 0 si no quedan mas. Los elementos de una misma posicion se devuelven juntos,
+/// por lo que puede haber mas de `cantidad`
+fn tramo(posiciones: impl Iterator<Item = (u64, usize)>, cantidad: usize) -> (u64, Vec<u64>) {
+    let mut tomadas = vec![];
+    let mut elementos = 0;
+    let mut posiciones = posiciones.peekable();
+    while let Some((actual, en_posicion)) = posiciones.next() {
+        tomadas.push(actual);
+        elementos += en_posicion;
+        if elementos >= cantidad && posiciones.peek().is_some() {
+            return (actual + 1, tomadas);
+        }
+    }
+    (0, tomadas)
+}
+
+/// Indice de las posiciones de algunas claves de una tabla, ordenadas para recorrerlas con un
+/// cursor. Solo guarda cuantas claves hay en cada posicion, las claves se buscan en la tabla
+#[derive(Debug, Clone, Default)]
+pub struct IndiceEscaneo {
+    posiciones: BTreeMap<u64, usize>,
+}
+
+impl IndiceEscaneo {
+    pub fn insertar(&mut self, elemento: &[u8]) {
+        *self.posiciones.entry(posicion(elemento)).or_insert(0) += 1;
+    }
+
+    pub fn eliminar(&mut self, elemento: &[u8]) {
+        let posicion = posicion(elemento);
+        if let Some(cantidad) = self.posiciones.get_mut(&posicion) {
+            *cantidad -= 1;
+            if *cantidad == 0 {
+                self.posiciones.remove(&posicion);
+            }
+        }
+    }
+
+    pub fn limpiar(&mut self) {
+        self.posiciones.clear();
+    }
+
+    /// Devuelve las posiciones desde el cursor que tienen los siguientes `cantidad` elementos, y el
+    /// cursor desde el que sigue el recorrido, ver `TablaEscaneable::escanear`
+    pub fn escanear(&self, cursor: u64, cantidad: usize) -> (u64, Vec<u64>) {
+        let posiciones = self.posiciones.range(cursor..).map(|(p, c)| (*p, *c));
+        tramo(posiciones, cantidad)
+    }
+}
+
+/// Entradas de una tabla que comparten posicion. Casi siempre hay una sola, que se guarda sin
+/// reservar un vector
+#[derive(Clone)]
+enum Cubeta<V> {
+    Una((Vec<u8>, V)),
+    Varias(Vec<(Vec<u8>, V)>),
+}
+
+impl<V> Cubeta<V> {
+    fn entradas(&self) -> &[(Vec<u8>, V)] {
+        match self {
+            Cubeta::Una(entrada) => slice::from_ref(entrada),
+            Cubeta::Varias(entradas) => entradas,
+        }
+    }
+
+    fn entradas_mut(&mut self) -> &mut [(Vec<u8>, V)] {
+        match self {
+            Cubeta::Una(entrada) => slice::from_mut(entrada),
+            Cubeta::Varias(entradas) => entradas,
+        }
+    }
+
+    fn agregar(self, entrada: (Vec<u8>, V)) -> Self {
+        let mut entradas = match self {
+            Cubeta::Una(otra) => vec![otra],
+            Cubeta::Varias(entradas) => entradas,
+        };
+        entradas.push(entrada);
+        Cubeta::Varias(entradas)
+    }
+
+    /// Quita la entrada indicada y devuelve su valor, junto con lo que queda de la cubeta
+    fn quitar(self, indice: usize) -> (Option<Self>, V) {
+        match self {
+            Cubeta::Una((_, valor)) => (None, valor),
+            Cubeta::Varias(mut entradas) => {
+                let (_, valor) = entradas.swap_remove(indice);
+                let resto = match entradas.len() {
+                    0 => None,
+                    1 => entradas.pop().map(Cubeta::Una),
+                    _ => Some(Cubeta::Varias(entradas)),
+                };
+                (resto, valor)
+            }
+        }
+    }
+}
+
+/// Tabla de claves binarias agrupadas por su posicion en el recorrido, junto con las posiciones
+/// ordenadas. Cada clave se guarda una sola vez, y el orden permite continuar desde un cursor con
+/// una busqueda logaritmica, de modo que cada llamada cuesta en proporcion a la cantidad pedida y
+/// no al tamaño de la tabla. Tiene los mismos metodos que un `HashMap` que la usan
+#[derive(Clone)]
+pub struct TablaEscaneable<V> {
+    cubetas: HashMap<u64, Cubeta<V>>,
+    orden: BTreeSet<u64>,
+    cantidad: usize,
+}
+
+impl<V> Default for TablaEscaneable<V> {
+    fn default() -> Self {
+        TablaEscaneable {
+            cubetas: HashMap::new(),
+            orden: BTreeSet::new(),
+            cantidad: 0,
+        }
+    }
+}
+
+impl<V: PartialEq> PartialEq for TablaEscaneable<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(c, v)| other.get(c) == Some(v))
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for TablaEscaneable<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V> TablaEscaneable<V> {
+    pub fn len(&self) -> usize {
+        self.cantidad
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cantidad == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cubetas.capacity()
+    }
+
+    pub fn get(&self, clave: &[u8]) -> Option<&V> {
+        self.cubetas
+            .get(&posicion(clave))?
+            .entradas()
+            .iter()
+            .find(|(c, _)| c == clave)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, clave: &[u8]) -> Option<&mut V> {
+        self.cubetas
+            .get_mut(&posicion(clave))?
+            .entradas_mut()
+            .iter_mut()
+            .find(|(c, _)| c == clave)
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, clave: &[u8]) -> bool {
+        self.get(clave).is_some()
+    }
+
+    pub fn insert(&mut self, clave: Vec<u8>, valor: V) -> Option<V> {
+        let posicion = posicion(&clave);
+        if let Some(cubeta) = self.cubetas.get_mut(&posicion) {
+            let actual = cubeta.entradas_mut().iter_mut().find(|(c, _)| *c == clave);
+            if let Some((_, actual)) = actual {
+                return Some(mem::replace(actual, valor));
+            }
+        }
+        let cubeta = match self.cubetas.remove(&posicion) {
+            Some(cubeta) => cubeta.agregar((clave, valor)),
+            None => {
+                self.orden.insert(posicion);
+                Cubeta::Una((clave, valor))
+            }
+        };
+        self.cubetas.insert(posicion, cubeta);
+        self.cantidad += 1;
+        None
+    }
+
+    pub fn remove(&mut self, clave: &[u8]) -> Option<V> {
+        let posicion = posicion(clave);
+        let cubeta = self.cubetas.get(&posicion)?;
+        let indice = cubeta.entradas().iter().position(|(c, _)| c == clave)?;
+        let (resto, valor) = self.cubetas.remove(&posicion)?.quitar(indice);
+        match resto {
+            Some(resto) => {
+                self.cubetas.insert(posicion, resto);
+            }
+            None => {
+                self.orden.remove(&posicion);
+            }
+        }
+        self.cantidad -= 1;
+        Some(valor)
+    }
+
+    pub fn retain(&mut self, mut conservar: impl FnMut(&Vec<u8>, &mut V) -> bool) {
+        let (orden, cantidad) = (&mut self.orden, &mut self.cantidad);
+        self.cubetas.retain(|posicion, cubeta| {
+            let antes = cubeta.entradas().len();
+            let quedan = match cubeta {
+                Cubeta::Una((clave, valor)) => conservar(clave, valor) as usize,
+                Cubeta::Varias(entradas) => {
+                    entradas.retain_mut(|(clave, valor)| conservar(clave, valor));
+                    entradas.len()
+                }
+            };
+            *cantidad -= antes - quedan;
+            if quedan == 0 {
+                orden.remove(posicion);
+            }
+            quedan > 0
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.cubetas.clear();
+        self.orden.clear();
+        self.cantidad = 0;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Vec<u8>, &V)> + Clone {
+        self.cubetas
+            .values()
+            .flat_map(|cubeta| cubeta.entradas().iter().map(|(c, v)| (c, v)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Vec<u8>> + Clone {
+        self.iter().map(|(c, _)| c)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + Clone {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Entradas cuyas claves estan en la posicion indicada, que se obtiene de un `IndiceEscaneo`
+    pub fn en_posicion(&self, posicion: u64) -> impl Iterator<Item = (&Vec<u8>, &V)> {
+        self.cubetas
+            .get(&posicion)
+            .into_iter()
+            .flat_map(|cubeta| cubeta.entradas().iter().map(|(c, v)| (c, v)))
+    }
+
+    /// Devuelve las siguientes `cantidad` entradas desde el cursor, junto con el cursor desde el que
+    /// sigue el recorrido, que es 0 si no quedan mas. El servidor no guarda ningun estado: toda
+    /// entrada presente durante el recorrido completo se devuelve al menos una vez sin importar que
+    /// se agreguen o eliminen otras. Las entradas que comparten posicion se devuelven juntas, por
+    /// lo que puede haber mas de `cantidad`
+    pub fn escanear(&self, cursor: u64, cantidad: usize) -> (u64, Vec<(&Vec<u8>, &V)>) {
+        let posiciones = self
+            .orden
+            .range(cursor..)
+            .map(|p| (*p, self.cubetas.get(p).map_or(0, |c| c.entradas().len())));
+        let (siguiente, posiciones) = tramo(posiciones, cantidad);
+        let entradas = posiciones
+            .into_iter()
+            .flat_map(|p| self.en_posicion(p))
+            .collect();
+        (siguiente, entradas)
+    }
+}
+
+impl<V> FromIterator<(Vec<u8>, V)> for TablaEscaneable<V> {
+    fn from_iter<I: IntoIterator<Item = (Vec<u8>, V)>>(pares: I) -> Self {
+        let mut tabla = Self::default();
+        pares.into_iter().for_each(|(clave, valor)| {
+            tabla.insert(clave, valor);
+        });
+        tabla
+    }
+}
+
+impl<V> From<HashMap<Vec<u8>, V>> for TablaEscaneable<V> {
+    fn from(tabla: HashMap<Vec<u8>, V>) -> Self {
+        tabla.into_iter().collect()
+    }
+}
+
+/// Conjunto de cadenas binarias que se puede recorrer con un cursor, ver `TablaEscaneable`.
+/// Tiene los mismos metodos que un `HashSet` que lo usan
+#[derive(Clone, Default, PartialEq)]
+pub struct ConjuntoEscaneable {
+    tabla: TablaEscaneable<()>,
+}
+
+impl fmt::Debug for ConjuntoEscaneable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl ConjuntoEscaneable {
+    pub fn len(&self) -> usize {
+        self.tabla.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabla.is_empty()
+    }
+
+    pub fn contains(&self, elemento: &[u8]) -> bool {
+        self.tabla.contains_key(elemento)
+    }
+
+    pub fn insert(&mut self, elemento: Vec<u8>) -> bool {
+        self.tabla.insert(elemento, ()).is_none()
+    }
+
+    pub fn remove(&mut self, elemento: &[u8]) -> bool {
+        self.tabla.remove(elemento).is_some()
+    }
+
+    pub fn retain(&mut self, mut conservar: impl FnMut(&Vec<u8>) -> bool) {
+        self.tabla.retain(|elemento, _| conservar(elemento));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Vec<u8>> + Clone {
+        self.tabla.keys()
+    }
+
+    /// Recorre el conjunto desde el cursor, ver `TablaEscaneable::escanear`
+    pub fn escanear(&self, cursor: u64, cantidad: usize) -> (u64, Vec<&Vec<u8>>) {
+        let (siguiente, entradas) = self.tabla.escanear(cursor, cantidad);
+        (siguiente, entradas.into_iter().map(|(e, _)| e).collect())
+    }
+}
+
+impl FromIterator<Vec<u8>> for ConjuntoEscaneable {
+    fn from_iter<I: IntoIterator<Item = Vec<u8>>>(elementos: I) -> Self {
+        ConjuntoEscaneable {
+            tabla: elementos.into_iter().map(|e| (e, ())).collect(),
+        }
+    }
+}
+
+impl From<HashSet<Vec<u8>>> for ConjuntoEscaneable {
+    fn from(conjunto: HashSet<Vec<u8>>) -> Self {
+        conjunto.into_iter().collect()
+    }
+}
+
+/// Respuesta de un recorrido: el cursor siguiente y los elementos devueltos en esta llamada
+pub fn respuesta(cursor: u64, elementos: Vec<ResultadoRedis>) -> ResultadoRedis {
+    ResultadoRedis::Vector(vec![
+        ResultadoRedis::BulkStr(cursor.to_string().into_bytes()),
+        ResultadoRedis::Vector(elementos),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argumentos(argumentos: &[&str]) -> Vec<Vec<u8>> {
+        argumentos.iter().map(|a| a.as_bytes().to_vec()).collect()
+    }
+
+    fn conjunto(prefijo: &str, cantidad: usize) -> ConjuntoEscaneable {
+        (0..cantidad)
+            .map(|i| format!("{}{}", prefijo, i).into_bytes())
+            .collect()
+    }
+
+    /// Recorre el conjunto completo y devuelve los elementos junto con la cantidad de llamadas
+    fn recorrer_todo(set: &ConjuntoEscaneable, cantidad: usize) -> (Vec<Vec<u8>>, usize) {
+        let mut devueltos = vec![];
+        let mut cursor = 0;
+        let mut llamadas = 0;
+        loop {
+            let (siguiente, elementos) = set.escanear(cursor, cantidad);
+            llamadas += 1;
+            devueltos.extend(elementos.into_iter().cloned());
+            if siguiente == 0 {
+                return (devueltos, llamadas);
+            }
+            assert!(siguiente > cursor, "el cursor debe avanzar");
+            cursor = siguiente;
+        }
+    }
+
+    #[test]
+    fn parsear_lee_el_cursor_y_las_opciones() {
+        let escaneo = Escaneo::parsear(
+            &argumentos(&["17", "match", "a*", "COUNT", "5", "TYPE", "set"]),
+            &["TYPE"],
+        )
+        .unwrap();
+        assert_eq!(17, escaneo.cursor);
+        assert_eq!(5, escaneo.cantidad);
+        assert!(escaneo.coincide(b"abc"));
+        assert!(!escaneo.coincide(b"bc"));
+        assert!(escaneo.es_del_tipo("SET"));
+        assert!(!escaneo.es_del_tipo("hash"));
+
+        assert_eq!(
+            Err("ERR invalid cursor".to_string()),
+            Escaneo::parsear(&argumentos(&["-1"]), &[])
+        );
+        assert_eq!(
+            Err("ERR syntax error".to_string()),
+            Escaneo::parsear(&argumentos(&["0", "COUNT", "0"]), &[])
+        );
+        assert_eq!(
+            Err("ERR syntax error".to_string()),
+            Escaneo::parsear(&argumentos(&["0", "TYPE", "set"]), &[])
+        );
+        assert_eq!(
+            Err("ERR syntax error".to_string()),
+            Escaneo::parsear(&argumentos(&["0", "MATCH"]), &[])
+        );
+    }
+
+    #[test]
+    fn escanear_devuelve_todos_los_elementos_una_vez() {
+        let set = conjunto("e", 1000);
+
+        let (devueltos, _) = recorrer_todo(&set, 7);
+        assert_eq!(set.len(), devueltos.len());
+        assert_eq!(set, devueltos.into_iter().collect());
+        assert_eq!((0, vec![]), ConjuntoEscaneable::default().escanear(0, 10));
+    }
+
+    #[test]
+    fn el_recorrido_termina_en_tantas_llamadas_como_indica_la_cantidad_pedida() {
+        let set = conjunto("e", 1000);
+        for cantidad in [1, 10, 999, 1000, 5000] {
+            let (devueltos, llamadas) = recorrer_todo(&set, cantidad);
+            assert_eq!(1000, devueltos.len());
+            assert_eq!(1000_usize.div_ceil(cantidad), llamadas);
+        }
+    }
+
+    #[test]
+    fn escanear_no_pierde_elementos_si_la_coleccion_cambia_durante_el_recorrido() {
+        let mut set = conjunto("fijo", 100);
+        let mut devueltos = HashSet::new();
+        let mut cursor = 0;
+        let mut vuelta = 0;
+        loop {
+            let (siguiente, elementos) = set.escanear(cursor, 5);
+            devueltos.extend(elementos.into_iter().cloned());
+            if siguiente == 0 {
+                break;
+            }
+            cursor = siguiente;
+            // La coleccion crece y despues se achica mientras se recorre
+            if vuelta < 5 {
+                for i in 0..1000 {
+                    set.insert(format!("nuevo{}-{}", vuelta, i).into_bytes());
+                }
+            } else {
+                set.retain(|e| e.starts_with(b"fijo"));
+            }
+            vuelta += 1;
+        }
+        assert!((0..100).all(|i| devueltos.contains(format!("fijo{}", i).as_bytes())));
+    }
+
+    #[test]
+    fn la_tabla_se_mantiene_al_eliminar_elementos() {
+        let mut tabla: TablaEscaneable<u8> = (0..10u8).map(|i| (vec![i], i)).collect();
+        assert_eq!(Some(3), tabla.remove(&[3][..]));
+        assert_eq!(None, tabla.remove(&[3][..]));
+        tabla.retain(|_, valor| *valor % 2 == 0);
+        assert_eq!(5, tabla.len());
+
+        let (cursor, entradas) = tabla.escanear(0, 100);
+        assert_eq!(0, cursor);
+        let mut valores: Vec<u8> = entradas.into_iter().map(|(_, v)| *v).collect();
+        valores.sort_unstable();
+        assert_eq!(vec![0, 2, 4, 6, 8], valores);
+    }
+
+    #[test]
+    fn las_entradas_que_comparten_posicion_se_agregan_y_se_quitan_por_separado() {
+        let cubeta = Cubeta::Una((b"a".to_vec(), 1))
+            .agregar((b"b".to_vec(), 2))
+            .agregar((b"c".to_vec(), 3));
+        assert_eq!(3, cubeta.entradas().len());
+
+        let (resto, valor) = cubeta.quitar(0);
+        assert_eq!(1, valor);
+        let (resto, valor) = resto.unwrap().quitar(0);
+        assert_eq!(3, valor);
+        let resto = resto.unwrap();
+        assert!(matches!(resto, Cubeta::Una((ref clave, 2)) if clave == b"b"));
+        assert!(resto.quitar(0).0.is_none());
+    }
+
+    #[test]
+    fn el_indice_devuelve_las_posiciones_mientras_quede_alguna_clave_en_ellas() {
+        let mut indice = IndiceEscaneo::default();
+        indice.insertar(b"a");
+        indice.insertar(b"a");
+        indice.insertar(b"b");
+        indice.eliminar(b"a");
+
+        let (cursor, posiciones) = indice.escanear(0, 10);
+        assert_eq!(0, cursor);
+        let mut esperadas = vec![posicion(b"a"), posicion(b"b")];
+        esperadas.sort_unstable();
+        assert_eq!(esperadas, posiciones);
+
+        indice.eliminar(b"a");
+        assert_eq!((0, vec![posicion(b"b")]), indice.escanear(0, 10));
+    }
+}
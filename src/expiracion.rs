@@ -0,0 +1,101 @@
+use crate::base_de_datos::BaseDeDatos;
+use crate::config::Config;
+
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Cantidad de claves con expiracion que se revisan en cada muestra
+const CLAVES_POR_MUESTRA: usize = 20;
+/// Si en una muestra expiro mas de este porcentaje de las claves se toma otra en el mismo ciclo
+const PORCENTAJE_PARA_REPETIR: usize = 25;
+
+/// Entidad que corre en su propio hilo y, `hz` veces por segundo, elimina claves expiradas que
+/// nadie volvio a leer. Sin este ciclo esas claves solo se eliminarian al accederlas
+pub struct ExpiradorHandler {
+    bdd: Arc<Mutex<BaseDeDatos>>,
+    config: Arc<Mutex<Config>>,
+    receptor: Receiver<()>,
+}
+
+impl ExpiradorHandler {
+    pub fn new(
+        bdd: Arc<Mutex<BaseDeDatos>>,
+        config: Arc<Mutex<Config>>,
+        receptor: Receiver<()>,
+    ) -> Self {
+        ExpiradorHandler {
+            bdd,
+            config,
+            receptor,
+        }
+    }
+
+    /// Ejecuta ciclos de expiracion hasta recibir un mensaje o que se cierre el canal
+    pub fn expirar(&mut self) {
+        loop {
+            let (hz, notificaciones) = match self.config.lock() {
+                Ok(config) => (config.hz(), config.notify_keyspace_events()),
+                Err(_) => return,
+            };
+            let periodo = Duration::from_millis(1000 / hz);
+            match self.receptor.recv_timeout(periodo) {
+                Err(RecvTimeoutError::Timeout) => (),
+                _ => return,
+            }
+            match self.bdd.lock() {
+                Ok(mut bdd) => {
+                    bdd.set_notificaciones(notificaciones);
+                    ciclo_de_expiracion(&mut bdd, periodo / 4);
+                }
+                Err(_) => return,
+            }
+        }
+    }
+}
+
+/// Toma muestras de claves con expiracion y elimina las expiradas. Mientras en cada muestra
+/// expire mas de un cuarto de las claves sigue con otra, sin pasarse del tiempo indicado.
+/// Devuelve cuantas claves elimino
+pub fn ciclo_de_expiracion(bdd: &mut BaseDeDatos, limite: Duration) -> usize {
+    let inicio = Instant::now();
+    let mut total = 0;
+    loop {
+        let (revisadas, eliminadas) = bdd.expirar_muestra(CLAVES_POR_MUESTRA);
+        total += eliminadas;
+        if eliminadas * 100 <= revisadas * PORCENTAJE_PARA_REPETIR || inicio.elapsed() >= limite {
+            return total;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_de_datos::TipoRedis;
+    use crate::valor::ahora_ms;
+
+    #[test]
+    fn el_ciclo_sigue_tomando_muestras_mientras_expiren_muchas_claves() {
+        let mut bdd = BaseDeDatos::new();
+        for i in 0..100 {
+            bdd.guardar_valor_con_vencimiento(
+                format!("vencida{}", i),
+                0,
+                TipoRedis::Str("v".into()),
+            );
+        }
+        for i in 0..5 {
+            bdd.guardar_valor_con_vencimiento(
+                format!("vigente{}", i),
+                ahora_ms() + 100_000,
+                TipoRedis::Str("v".into()),
+            );
+        }
+        bdd.guardar_valor("persistente", TipoRedis::Str("v".into()));
+
+        assert_eq!(100, ciclo_de_expiracion(&mut bdd, Duration::from_secs(1)));
+        assert_eq!(6, bdd.cantidad_claves());
+        assert_eq!(6, bdd.claves(b"*").len());
+    }
+}
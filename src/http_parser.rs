@@ -1,6 +1,6 @@
 use crate::base_de_datos::ResultadoRedis;
 use crate::comando_http::ComandoHttp;
-use crate::parser::ParserError;
+use crate::parser::{formatear_double, ParserError};
 use std::io::{BufReader, Read};
 
 /// Entidad encargada de parsear stream que cumplen con la sintaxis del protocolo HTTP/1.1
@@ -50,21 +50,41 @@ impl<R: Read + std::fmt::Debug> HttpParser<R> {
 pub fn parsear_respuesta(res: &ResultadoRedis) -> String {
     match res {
         ResultadoRedis::StrSimple(cad) => cad.to_string(),
-        ResultadoRedis::BulkStr(cad) => format!("'{}'", cad),
+        ResultadoRedis::BulkStr(cad) => format!("'{}'", String::from_utf8_lossy(cad)),
         ResultadoRedis::Int(ent) => format!("(integer) {}", ent),
-        ResultadoRedis::Vector(vec) => format!(
-            "(vector) {}",
-            vec.iter()
-                .map(|r| format!(" {}", parsear_respuesta(r)))
+        ResultadoRedis::Vector(vec) => parsear_elementos("(vector)", vec),
+        ResultadoRedis::Nil | ResultadoRedis::Null => "(nil)".to_string(),
+        ResultadoRedis::Error(e) => format!("(error) {}", e),
+        ResultadoRedis::Vacio => String::new(),
+        ResultadoRedis::Map(pares) => format!(
+            "(map) {}",
+            pares
+                .iter()
+                .map(|(c, v)| format!(" {} => {}", parsear_respuesta(c), parsear_respuesta(v)))
                 .collect::<Vec<String>>()
                 .join("")
         ),
-        ResultadoRedis::Nil => "(nil)".to_string(),
-        ResultadoRedis::Error(e) => format!("(error) {}", e),
-        ResultadoRedis::Vacio => String::new(),
+        ResultadoRedis::Set(vec) => parsear_elementos("(set)", vec),
+        ResultadoRedis::Push(vec) => parsear_elementos("(push)", vec),
+        ResultadoRedis::Double(d) => format!("(double) {}", formatear_double(*d)),
+        ResultadoRedis::Boolean(b) => format!("(boolean) {}", b),
+        ResultadoRedis::BigNumber(n) => format!("(big number) {}", n),
+        ResultadoRedis::Verbatim(_, texto) => texto.to_string(),
     }
 }
 
+fn parsear_elementos(tipo: &str, elementos: &[ResultadoRedis]) -> String {
+    format!(
+        "{} {}",
+        tipo,
+        elementos
+            .iter()
+            .map(|r| format!(" {}", parsear_respuesta(r)))
+            .collect::<Vec<String>>()
+            .join("")
+    )
+}
+
 fn obtener_metodo(linea: &str) -> Result<Vec<String>, ParserError> {
     let metodo: Vec<&str> = linea.split(' ').collect();
     if metodo.len() != 3 {
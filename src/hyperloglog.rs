@@ -0,0 +1,330 @@
+/// Bits del hash que eligen el registro. Con 2^14 registros el error estandar es de 0.81%
+const P: u32 = 14;
+const REGISTROS: usize = 1 << P;
+/// Bits restantes del hash, en los que se busca la cantidad de ceros al final
+const Q: usize = 64 - P as usize;
+const BITS_REGISTRO: usize = 6;
+const MAXIMO_REGISTRO: u8 = (1 << BITS_REGISTRO) - 1;
+/// Cabecera de Redis: la marca `HYLL`, la codificacion, tres bytes sin uso y la cardinalidad cacheada
+const LARGO_CABECERA: usize = 16;
+const LARGO_DENSO: usize = LARGO_CABECERA + (REGISTROS * BITS_REGISTRO).div_ceil(8);
+const MARCA: &[u8] = b"HYLL";
+const DENSO: u8 = 0;
+const DISPERSO: u8 = 1;
+/// Mayor tamaño que puede tener la representacion dispersa, con la cabecera, antes de pasar a la densa
+const MAXIMO_DISPERSO: usize = 3000;
+/// Mayor valor que puede guardar un registro en la representacion dispersa
+const MAXIMO_VALOR_DISPERSO: u8 = 32;
+const SEMILLA: u64 = 0xadc83b19;
+const ALFA_INFINITO: f64 = 0.721_347_520_444_481_7;
+
+/// Estimador de cardinalidad HyperLogLog con el mismo formato que Redis, por lo que se guarda como un string comun.
+/// Mientras tiene pocos registros distintos de cero se codifica en forma dispersa, como tramos de registros iguales,
+/// y cuando crece pasa a la forma densa de 6 bits por registro, de la que ya no vuelve
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperLogLog {
+    registros: Vec<u8>,
+    denso: bool,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog::new()
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        HyperLogLog {
+            registros: vec![0; REGISTROS],
+            denso: false,
+        }
+    }
+
+    /// Interpreta un string como HyperLogLog. Devuelve ninguno si no tiene el formato esperado
+    pub fn desde_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < LARGO_CABECERA || &bytes[..MARCA.len()] != MARCA {
+            return None;
+        }
+        let cuerpo = &bytes[LARGO_CABECERA..];
+        let registros = match bytes[MARCA.len()] {
+            DENSO if bytes.len() == LARGO_DENSO => {
+                (0..REGISTROS).map(|i| leer_denso(cuerpo, i)).collect()
+            }
+            DISPERSO => leer_disperso(cuerpo)?,
+            _ => return None,
+        };
+        Some(HyperLogLog {
+            registros,
+            denso: bytes[MARCA.len()] == DENSO,
+        })
+    }
+
+    /// Codifica el HyperLogLog en forma dispersa si entra, o en forma densa si no
+    pub fn a_bytes(&self) -> Vec<u8> {
+        let disperso = if self.denso {
+            None
+        } else {
+            escribir_disperso(&self.registros)
+                .filter(|d| LARGO_CABECERA + d.len() <= MAXIMO_DISPERSO)
+        };
+        let (codificacion, cuerpo) = match disperso {
+            Some(cuerpo) => (DISPERSO, cuerpo),
+            None => (DENSO, escribir_denso(&self.registros)),
+        };
+        let mut bytes = MARCA.to_vec();
+        bytes.extend_from_slice(&[codificacion, 0, 0, 0]);
+        // La cardinalidad no se cachea, el bit mas alto del ultimo byte la marca como invalida
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0x80]);
+        bytes.extend(cuerpo);
+        bytes
+    }
+
+    /// Agrega el elemento al estimador. Devuelve si cambio algun registro
+    pub fn agregar(&mut self, elemento: &[u8]) -> bool {
+        let hash = murmur_hash_64a(elemento, SEMILLA);
+        let indice = (hash & (REGISTROS as u64 - 1)) as usize;
+        // El bit Q asegura que la cuenta termine aunque el resto del hash sea cero
+        let cuenta = (((hash >> P) | (1 << Q)).trailing_zeros() + 1) as u8;
+        if cuenta > self.registros[indice] {
+            self.registros[indice] = cuenta;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Combina los registros de otro estimador, quedando uno que estima la union de ambos
+    pub fn fusionar(&mut self, otro: &HyperLogLog) {
+        for (registro, otro) in self.registros.iter_mut().zip(&otro.registros) {
+            *registro = (*registro).max(*otro);
+        }
+    }
+
+    /// Fuerza la representacion densa, como la que deja PFMERGE en Redis
+    pub fn densificar(&mut self) {
+        self.denso = true;
+    }
+
+    /// Estima la cantidad de elementos distintos agregados con el estimador mejorado de Ertl que usa Redis
+    pub fn contar(&self) -> u64 {
+        // Un string denso puede traer cualquier registro de 6 bits, aunque los mayores a Q + 1 no se usen
+        let mut histograma = [0u32; MAXIMO_REGISTRO as usize + 1];
+        for &registro in &self.registros {
+            histograma[registro as usize] += 1;
+        }
+        let m = REGISTROS as f64;
+        let mut z = m * tau((m - histograma[Q + 1] as f64) / m);
+        for j in (1..=Q).rev() {
+            z += histograma[j] as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(histograma[0] as f64 / m);
+        (ALFA_INFINITO * m * m / z).round() as u64
+    }
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let (mut y, mut z) = (1.0, x);
+    loop {
+        x *= x;
+        let anterior = z;
+        z += x * y;
+        y += y;
+        if anterior == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let (mut y, mut z) = (1.0, 1.0 - x);
+    loop {
+        x = x.sqrt();
+        let anterior = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if anterior == z {
+            return z / 3.0;
+        }
+    }
+}
+
+/// Lee el registro de 6 bits de la forma densa, que empiezan en el bit menos significativo de cada byte
+fn leer_denso(cuerpo: &[u8], indice: usize) -> u8 {
+    let bit = indice * BITS_REGISTRO;
+    let (byte, desplazamiento) = (bit / 8, bit % 8);
+    let bajo = cuerpo[byte] as u16 >> desplazamiento;
+    let alto = (cuerpo.get(byte + 1).copied().unwrap_or(0) as u16) << (8 - desplazamiento);
+    ((bajo | alto) as u8) & MAXIMO_REGISTRO
+}
+
+fn escribir_denso(registros: &[u8]) -> Vec<u8> {
+    let mut cuerpo = vec![0u8; LARGO_DENSO - LARGO_CABECERA];
+    for (indice, &valor) in registros.iter().enumerate() {
+        let bit = indice * BITS_REGISTRO;
+        let (byte, desplazamiento) = (bit / 8, bit % 8);
+        let valor = (valor.min(MAXIMO_REGISTRO) as u16) << desplazamiento;
+        cuerpo[byte] |= valor as u8;
+        if let Some(siguiente) = cuerpo.get_mut(byte + 1) {
+            *siguiente |= (valor >> 8) as u8;
+        }
+    }
+    cuerpo
+}
+
+/// Lee la forma dispersa, formada por los codigos ZERO (`00xxxxxx`, hasta 64 registros en cero), XZERO
+/// (`01xxxxxx xxxxxxxx`, hasta 16384 registros en cero) y VAL (`1vvvvvxx`, hasta 4 registros con el valor v + 1)
+fn leer_disperso(cuerpo: &[u8]) -> Option<Vec<u8>> {
+    let mut registros = Vec::with_capacity(REGISTROS);
+    let mut i = 0;
+    while i < cuerpo.len() {
+        let codigo = cuerpo[i];
+        let (valor, largo) = if codigo & 0x80 != 0 {
+            (((codigo >> 2) & 0x1f) + 1, (codigo & 0x03) as usize + 1)
+        } else if codigo & 0x40 != 0 {
+            let segundo = *cuerpo.get(i + 1)? as usize;
+            i += 1;
+            (0, (((codigo & 0x3f) as usize) << 8 | segundo) + 1)
+        } else {
+            (0, (codigo & 0x3f) as usize + 1)
+        };
+        registros.extend(std::iter::repeat_n(valor, largo));
+        if registros.len() > REGISTROS {
+            return None;
+        }
+        i += 1;
+    }
+    (registros.len() == REGISTROS).then_some(registros)
+}
+
+/// Escribe la forma dispersa. Devuelve ninguno si algun registro supera el mayor valor que admite
+fn escribir_disperso(registros: &[u8]) -> Option<Vec<u8>> {
+    let mut cuerpo = Vec::new();
+    let mut i = 0;
+    while i < registros.len() {
+        let valor = registros[i];
+        let tramo = registros[i..].iter().take_while(|&&r| r == valor).count();
+        if valor > MAXIMO_VALOR_DISPERSO {
+            return None;
+        }
+        if valor == 0 && tramo > 64 {
+            let largo = tramo - 1;
+            cuerpo.extend_from_slice(&[0x40 | (largo >> 8) as u8, largo as u8]);
+        } else if valor == 0 {
+            cuerpo.push((tramo - 1) as u8);
+        } else {
+            for parte in (0..tramo).step_by(4) {
+                let largo = (tramo - parte).min(4);
+                cuerpo.push(0x80 | (valor - 1) << 2 | (largo - 1) as u8);
+            }
+        }
+        i += tramo;
+    }
+    Some(cuerpo)
+}
+
+/// Hash MurmurHash64A, el mismo que usa Redis para elegir el registro de cada elemento
+fn murmur_hash_64a(datos: &[u8], semilla: u64) -> u64 {
+    const M: u64 = 0xc6a4a7935bd1e995;
+    const R: u32 = 47;
+    let mut h = semilla ^ (datos.len() as u64).wrapping_mul(M);
+
+    let mut bloques = datos.chunks_exact(8);
+    for bloque in &mut bloques {
+        let mut k = bloque
+            .iter()
+            .rev()
+            .fold(0u64, |k, &byte| k << 8 | byte as u64);
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let resto = bloques.remainder();
+    if !resto.is_empty() {
+        for (i, &byte) in resto.iter().enumerate() {
+            h ^= (byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn con_elementos(desde: usize, hasta: usize) -> HyperLogLog {
+        let mut hll = HyperLogLog::new();
+        for i in desde..hasta {
+            hll.agregar(format!("elemento:{}", i).as_bytes());
+        }
+        hll
+    }
+
+    #[test]
+    fn la_estimacion_queda_dentro_del_error_esperado() {
+        assert_eq!(0, HyperLogLog::new().contar());
+        assert_eq!(3, con_elementos(0, 3).contar());
+        for cantidad in [1_000, 20_000, 200_000] {
+            let estimado = con_elementos(0, cantidad).contar() as f64;
+            let error = (estimado - cantidad as f64).abs() / cantidad as f64;
+            assert!(error < 0.03, "{} estimado como {}", cantidad, estimado);
+        }
+    }
+
+    #[test]
+    fn agregar_un_elemento_repetido_no_cambia_los_registros() {
+        let mut hll = con_elementos(0, 10);
+        assert!(!hll.agregar(b"elemento:3"));
+        assert_eq!(10, hll.contar());
+    }
+
+    #[test]
+    fn pasa_de_la_forma_dispersa_a_la_densa_al_crecer() {
+        let poco = con_elementos(0, 100);
+        let bytes = poco.a_bytes();
+        assert_eq!(DISPERSO, bytes[4]);
+        assert!(bytes.len() < 1000);
+        assert_eq!(Some(poco), HyperLogLog::desde_bytes(&bytes));
+
+        let mucho = con_elementos(0, 10_000);
+        let bytes = mucho.a_bytes();
+        assert_eq!(DENSO, bytes[4]);
+        assert_eq!(LARGO_DENSO, bytes.len());
+        let leido = HyperLogLog::desde_bytes(&bytes).unwrap();
+        assert_eq!(mucho.registros, leido.registros);
+    }
+
+    #[test]
+    fn fusionar_estima_la_union() {
+        let mut hll = con_elementos(0, 5_000);
+        hll.fusionar(&con_elementos(2_500, 7_500));
+        let estimado = hll.contar() as f64;
+        assert!((estimado - 7_500.0).abs() / 7_500.0 < 0.03);
+    }
+
+    #[test]
+    fn los_strings_que_no_son_hyperloglog_se_rechazan() {
+        assert_eq!(None, HyperLogLog::desde_bytes(b"hola"));
+        assert_eq!(
+            None,
+            HyperLogLog::desde_bytes(b"HYLL\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x80")
+        );
+        let mut truncado = HyperLogLog::new().a_bytes();
+        truncado.pop();
+        assert_eq!(None, HyperLogLog::desde_bytes(&truncado));
+    }
+}
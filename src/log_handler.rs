@@ -45,7 +45,7 @@ impl LogHandler {
         LogHandler {
             ruta,
             receptor,
-            canal: Canal::new("monitor".to_string()),
+            canal: Canal::new(b"monitor".to_vec()),
             tipo: set_verbose(verbose),
         }
     }
@@ -91,7 +91,7 @@ impl LogHandler {
                 Mensaje::Cerrar => break,
             };
 
-            self.canal.publicar(a_logear.clone());
+            self.canal.publicar(a_logear.clone().into_bytes());
 
             match self.tipo.logear(self.ruta.clone(), a_logear) {
                 Ok(_) => (),
@@ -1,9 +1,16 @@
+mod aof;
 mod base_de_datos;
+mod binario;
+mod bloqueo;
 mod canal;
 mod cliente;
 mod cliente_http;
 mod cliente_redis;
 mod comando;
+mod comando_bit_handler;
+mod comando_conexion_handler;
+mod comando_hash_handler;
+mod comando_hll_handler;
 mod comando_http;
 mod comando_info;
 mod comando_key_handler;
@@ -13,14 +20,25 @@ mod comando_pubsub_handler;
 mod comando_server_handler;
 mod comando_set_handler;
 mod comando_string_handler;
+mod comando_zset_handler;
 mod config;
+mod conjunto_ordenado;
+mod cursor;
+mod expiracion;
 mod http_parser;
+mod hyperloglog;
 mod log_handler;
+mod notificacion;
 mod observer;
 mod parser;
+mod patron;
 mod persistencia;
+#[cfg(test)]
+mod prueba;
 mod redis;
 mod redis_error;
+mod registro_pubsub;
+mod transaccion;
 mod valor;
 
 use std::env;
@@ -0,0 +1,120 @@
+/// Clases de eventos sobre las claves que se pueden habilitar con notify-keyspace-events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaseEvento {
+    /// Comandos genericos sobre claves como DEL, EXPIRE o RENAME, con la letra `g`
+    Generico,
+    /// Comandos sobre strings, con la letra `$`
+    Cadena,
+    /// Comandos sobre listas, con la letra `l`
+    Lista,
+    /// Comandos sobre sets, con la letra `s`
+    Set,
+    /// Comandos sobre hashes, con la letra `h`
+    Hash,
+    /// Comandos sobre sets ordenados, con la letra `z`
+    ZSet,
+    /// Claves eliminadas al expirar, con la letra `x`
+    Expirado,
+}
+
+impl ClaseEvento {
+    fn letra(&self) -> char {
+        match self {
+            ClaseEvento::Generico => 'g',
+            ClaseEvento::Cadena => '$',
+            ClaseEvento::Lista => 'l',
+            ClaseEvento::Set => 's',
+            ClaseEvento::Hash => 'h',
+            ClaseEvento::ZSet => 'z',
+            ClaseEvento::Expirado => 'x',
+        }
+    }
+}
+
+/// Letras de clases que se aceptan en la configuracion. `A` equivale a todas las de `TODAS`
+const CLASES: &str = "g$lshzxetmnd";
+const TODAS: &str = "g$lshzxetd";
+
+/// Notificaciones habilitadas segun el valor de notify-keyspace-events. Por cada evento de una
+/// clase habilitada se publica en `__keyspace@0__:<clave>` el nombre del evento (letra `K`) y en
+/// `__keyevent@0__:<evento>` la clave (letra `E`)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Notificaciones {
+    keyspace: bool,
+    keyevent: bool,
+    clases: String,
+}
+
+impl Notificaciones {
+    /// Interpreta las letras de notify-keyspace-events. Devuelve ninguna si alguna no es valida
+    pub fn desde(letras: &str) -> Option<Self> {
+        let mut notificaciones = Notificaciones::default();
+        for letra in letras.chars() {
+            match letra {
+                'K' => notificaciones.keyspace = true,
+                'E' => notificaciones.keyevent = true,
+                'A' => notificaciones.clases.push_str(TODAS),
+                c if CLASES.contains(c) => notificaciones.clases.push(c),
+                _ => return None,
+            }
+        }
+        Some(notificaciones)
+    }
+
+    /// Devuelve los canales y mensajes a publicar por el evento, vacio si su clase no esta habilitada
+    pub fn mensajes(
+        &self,
+        clase: ClaseEvento,
+        evento: &str,
+        clave: &[u8],
+    ) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut mensajes = Vec::new();
+        if !self.clases.contains(clase.letra()) {
+            return mensajes;
+        }
+        if self.keyspace {
+            let mut canal = b"__keyspace@0__:".to_vec();
+            canal.extend_from_slice(clave);
+            mensajes.push((canal, evento.as_bytes().to_vec()));
+        }
+        if self.keyevent {
+            let canal = format!("__keyevent@0__:{}", evento).into_bytes();
+            mensajes.push((canal, clave.to_vec()));
+        }
+        mensajes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sin_k_ni_e_no_se_publica_nada() {
+        let notificaciones = Notificaciones::desde("A").unwrap();
+        assert!(notificaciones
+            .mensajes(ClaseEvento::Cadena, "set", b"clave")
+            .is_empty());
+    }
+
+    #[test]
+    fn se_publica_en_keyspace_y_keyevent_solo_para_las_clases_habilitadas() {
+        let notificaciones = Notificaciones::desde("KE$").unwrap();
+        assert_eq!(
+            vec![
+                (b"__keyspace@0__:clave".to_vec(), b"set".to_vec()),
+                (b"__keyevent@0__:set".to_vec(), b"clave".to_vec()),
+            ],
+            notificaciones.mensajes(ClaseEvento::Cadena, "set", b"clave")
+        );
+        assert!(notificaciones
+            .mensajes(ClaseEvento::Lista, "lpush", b"clave")
+            .is_empty());
+    }
+
+    #[test]
+    fn las_letras_invalidas_se_rechazan() {
+        assert_eq!(None, Notificaciones::desde("KQ"));
+        assert_eq!(Some(Notificaciones::default()), Notificaciones::desde(""));
+    }
+}
@@ -2,6 +2,15 @@ use crate::base_de_datos::ResultadoRedis;
 use crate::comando_info::ComandoInfo;
 use std::io::{BufRead, BufReader, Read};
 
+/// Mayor longitud que puede tener un argumento, como `proto-max-bulk-len` en Redis
+pub const MAXIMO_BULK: usize = 512 * 1024 * 1024;
+/// Mayor cantidad de argumentos que puede tener un comando
+const MAXIMO_ARGUMENTOS: usize = i32::MAX as usize;
+/// Cuanto se reserva por adelantado, para no confiar en las longitudes que envia el cliente
+const MAXIMO_RESERVA: usize = 64 * 1024;
+/// Mayor longitud de un comando inline o de una linea de encabezado, como en Redis
+const MAXIMO_LINEA: usize = 64 * 1024;
+
 /// Errores que pueden ocurrir en la ejecucion del Parser
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParserError {
@@ -9,9 +18,13 @@ pub enum ParserError {
     RedisSyntaxError,
     /// Se esperaba una cadena pero estaba vacia
     MensajeVacioError,
+    /// Una linea supera `MAXIMO_LINEA` bytes, la conexion debe cerrarse
+    LineaDemasiadoLarga,
 }
 
-/// Entidad encargada de parsear stream que cumplen con la sintaxis de Redis
+/// Entidad encargada de parsear stream que cumplen con la sintaxis de Redis.
+/// Conserva lo leido de mas entre comandos, por lo que debe vivir tanto como la conexion
+/// para no perder los comandos que el cliente envia encadenados (pipelining)
 pub struct Parser<R> {
     lector: BufReader<R>,
 }
@@ -25,69 +38,260 @@ impl<R: Read> Parser<R> {
         }
     }
 
-    /// Parsea el stream obteniendo un Comando o un Error
-    pub fn parsear_stream(self) -> Result<ComandoInfo, ParserError> {
-        let mut lineas = self.lector.lines();
-
-        let primer_valor = match lineas.next() {
-            Some(Ok(valor)) => valor,
-            _ => return Err(ParserError::MensajeVacioError),
+    /// Parsea el stream obteniendo un Comando o un Error. Cada argumento se lee como
+    /// una cadena binaria de exactamente la longitud indicada, por lo que puede contener
+    /// cualquier byte, incluso `\r\n`. Si el comando llego incompleto espera el resto.
+    /// Una linea que no comienza con `*` se interpreta como un comando inline, como los
+    /// que se escriben a mano desde telnet o netcat
+    pub fn parsear_stream(&mut self) -> Result<ComandoInfo, ParserError> {
+        let primer_valor = loop {
+            match self.leer_linea()? {
+                Some(valor) if valor.iter().all(u8::is_ascii_whitespace) => continue,
+                Some(valor) => break valor,
+                None => return Err(ParserError::MensajeVacioError),
+            }
         };
 
-        let capacidad = match parsear_int(primer_valor) {
-            Some(valor) => valor,
-            None => return Err(ParserError::RedisSyntaxError),
+        if !primer_valor.starts_with(b"*") {
+            return Ok(ComandoInfo::new(separar_argumentos(&primer_valor)?));
+        }
+
+        let capacidad = match parsear_int(&primer_valor, b'*') {
+            Some(valor) if valor as usize <= MAXIMO_ARGUMENTOS => valor as usize,
+            _ => return Err(ParserError::RedisSyntaxError),
         };
 
-        let mut comando = Vec::with_capacity(capacidad as usize);
+        let mut comando = Vec::with_capacity(capacidad.min(MAXIMO_RESERVA));
+
+        while comando.len() < capacidad {
+            let longitud = match self.leer_linea()? {
+                Some(linea) => match parsear_int(&linea, b'$') {
+                    Some(valor) if valor as usize <= MAXIMO_BULK => valor as usize,
+                    _ => return Err(ParserError::RedisSyntaxError),
+                },
+                None => return Err(ParserError::RedisSyntaxError),
+            };
+
+            // Se lee a medida que llegan los bytes, asi la memoria crece con lo recibido y no
+            // con la longitud anunciada
+            let mut argumento = Vec::with_capacity((longitud + 2).min(MAXIMO_RESERVA));
+            let leidos = (&mut self.lector)
+                .take(longitud as u64 + 2)
+                .read_to_end(&mut argumento);
+            if leidos.ok() != Some(longitud + 2) || !argumento.ends_with(b"\r\n") {
+                return Err(ParserError::RedisSyntaxError);
+            }
+            argumento.truncate(longitud);
+            comando.push(argumento);
+        }
+
+        if comando.is_empty() {
+            return Err(ParserError::MensajeVacioError);
+        }
+        Ok(ComandoInfo::new(comando))
+    }
 
-        while let Some(Ok(longitud_str)) = lineas.next() {
-            if let Some(Ok(argumento)) = lineas.next() {
-                let longitud = match parsear_int(longitud_str) {
-                    Some(valor) => valor as usize,
-                    None => return Err(ParserError::RedisSyntaxError),
-                };
+    /// Predicado que indica si quedaron bytes leidos del stream que todavia no se parsearon,
+    /// como los siguientes comandos de un pipeline
+    pub fn tiene_pendientes(&self) -> bool {
+        !self.lector.buffer().is_empty()
+    }
 
-                if longitud != argumento.len() {
-                    return Err(ParserError::RedisSyntaxError);
+    /// Lee una linea terminada en `\r\n` o `\n` sin incluir el final de linea. Deja de leer
+    /// pasados los `MAXIMO_LINEA` bytes, para que un cliente no pueda llenar la memoria con una
+    /// linea que nunca termina
+    fn leer_linea(&mut self) -> Result<Option<Vec<u8>>, ParserError> {
+        let mut linea = Vec::new();
+        let leidos = (&mut self.lector)
+            .take(MAXIMO_LINEA as u64 + 2)
+            .read_until(b'\n', &mut linea);
+        match leidos {
+            Ok(0) | Err(_) => Ok(None),
+            Ok(_) => {
+                if linea.ends_with(b"\n") {
+                    linea.pop();
                 }
-                comando.push(argumento);
-                if comando.len() == capacidad as usize {
-                    return Ok(ComandoInfo::new(comando));
+                if linea.ends_with(b"\r") {
+                    linea.pop();
                 }
+                if linea.len() > MAXIMO_LINEA {
+                    return Err(ParserError::LineaDemasiadoLarga);
+                }
+                Ok(Some(linea))
             }
         }
-        println!("{:?}", comando);
-        Ok(ComandoInfo::new(comando))
     }
 }
 
-/// Parsea la respuesta para que cumpla con el protocolo Redis
-pub fn parsear_respuesta(res: &ResultadoRedis) -> String {
+/// Separa los argumentos de un comando inline. Se separan por espacios y pueden ir entre
+/// comillas dobles, donde se admiten los escapes `\n`, `\r`, `\t`, `\b`, `\a` y `\xHH`, o entre
+/// comillas simples, donde solo se admite `\'`. Las comillas sin cerrar o seguidas de algo
+/// que no sea un espacio son un error de sintaxis
+pub fn separar_argumentos(linea: &[u8]) -> Result<Vec<Vec<u8>>, ParserError> {
+    let mut argumentos = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < linea.len() && linea[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let comilla = match linea.get(i) {
+            None => return Ok(argumentos),
+            Some(c) if *c == b'"' || *c == b'\'' => {
+                i += 1;
+                Some(*c)
+            }
+            Some(_) => None,
+        };
+
+        let mut argumento = Vec::new();
+        loop {
+            let actual = linea.get(i).copied();
+            i += 1;
+            match (comilla, actual) {
+                (None, None) => break,
+                (None, Some(c)) if c.is_ascii_whitespace() => break,
+                (Some(_), None) => return Err(ParserError::RedisSyntaxError),
+                (Some(q), Some(c)) if c == q => {
+                    if linea.get(i).is_some_and(|c| !c.is_ascii_whitespace()) {
+                        return Err(ParserError::RedisSyntaxError);
+                    }
+                    break;
+                }
+                (Some(b'"'), Some(b'\\')) if i < linea.len() => {
+                    let (escapado, largo) = resolver_escape(&linea[i..]);
+                    argumento.push(escapado);
+                    i += largo;
+                }
+                (Some(b'\''), Some(b'\\')) if linea.get(i) == Some(&b'\'') => {
+                    argumento.push(b'\'');
+                    i += 1;
+                }
+                (_, Some(c)) => argumento.push(c),
+            }
+        }
+        argumentos.push(argumento);
+    }
+}
+
+/// Resuelve la secuencia de escape que sigue a una `\` dentro de comillas dobles,
+/// devolviendo el byte resultante y cuantos bytes de la entrada consume
+fn resolver_escape(resto: &[u8]) -> (u8, usize) {
+    let digito_hexa = |c: Option<&u8>| c.and_then(|c| (*c as char).to_digit(16));
+    match resto[0] {
+        b'x' => match (digito_hexa(resto.get(1)), digito_hexa(resto.get(2))) {
+            (Some(alto), Some(bajo)) => ((alto * 16 + bajo) as u8, 3),
+            _ => (b'x', 1),
+        },
+        b'n' => (b'\n', 1),
+        b'r' => (b'\r', 1),
+        b't' => (b'\t', 1),
+        b'b' => (0x08, 1),
+        b'a' => (0x07, 1),
+        c => (c, 1),
+    }
+}
+
+/// Version del protocolo que habla una conexion, por defecto RESP2. Se cambia con HELLO
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Protocolo {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+/// Parsea la respuesta para que cumpla con la version del protocolo Redis indicada.
+/// En RESP2 los tipos que solo existen en RESP3 se envian con su equivalente mas cercano
+pub fn parsear_respuesta(res: &ResultadoRedis, protocolo: Protocolo) -> Vec<u8> {
+    let resp3 = protocolo == Protocolo::Resp3;
     match res {
-        ResultadoRedis::StrSimple(cad) => format!("+{}\r\n", cad),
-        ResultadoRedis::BulkStr(cad) => format!("${}\r\n{}\r\n", cad.len(), cad),
-        ResultadoRedis::Int(ent) => format!(":{}\r\n", ent),
-        ResultadoRedis::Vector(vec) => format!(
-            "*{}\r\n{}",
-            vec.len(),
-            vec.iter()
-                .map(|r| parsear_respuesta(r))
-                .collect::<Vec<String>>()
-                .join("")
-        ),
-        ResultadoRedis::Nil => "$-1\r\n".to_string(),
-        ResultadoRedis::Error(e) => format!("-{}\r\n", e),
-        ResultadoRedis::Vacio => String::new(),
+        ResultadoRedis::StrSimple(cad) => format!("+{}\r\n", cad).into_bytes(),
+        ResultadoRedis::BulkStr(cad) => parsear_bulk(cad),
+        ResultadoRedis::Int(ent) => format!(":{}\r\n", ent).into_bytes(),
+        ResultadoRedis::Vector(vec) => parsear_agregado('*', vec, protocolo),
+        ResultadoRedis::Nil if resp3 => b"_\r\n".to_vec(),
+        ResultadoRedis::Nil => b"$-1\r\n".to_vec(),
+        ResultadoRedis::Error(e) => format!("-{}\r\n", e).into_bytes(),
+        ResultadoRedis::Vacio => Vec::new(),
+        ResultadoRedis::Map(pares) => {
+            let (prefijo, cantidad) = if resp3 {
+                ('%', pares.len())
+            } else {
+                ('*', pares.len() * 2)
+            };
+            let mut respuesta = format!("{}{}\r\n", prefijo, cantidad).into_bytes();
+            for (clave, valor) in pares {
+                respuesta.extend(parsear_respuesta(clave, protocolo));
+                respuesta.extend(parsear_respuesta(valor, protocolo));
+            }
+            respuesta
+        }
+        ResultadoRedis::Set(vec) if resp3 => parsear_agregado('~', vec, protocolo),
+        ResultadoRedis::Set(vec) => parsear_agregado('*', vec, protocolo),
+        ResultadoRedis::Double(d) if resp3 => format!(",{}\r\n", formatear_double(*d)).into_bytes(),
+        ResultadoRedis::Double(d) => parsear_bulk(formatear_double(*d).as_bytes()),
+        ResultadoRedis::Boolean(b) if resp3 => {
+            format!("#{}\r\n", if *b { 't' } else { 'f' }).into_bytes()
+        }
+        ResultadoRedis::Boolean(b) => format!(":{}\r\n", *b as u8).into_bytes(),
+        ResultadoRedis::BigNumber(n) if resp3 => format!("({}\r\n", n).into_bytes(),
+        ResultadoRedis::BigNumber(n) => parsear_bulk(n.as_bytes()),
+        ResultadoRedis::Verbatim(formato, texto) if resp3 => format!(
+            "={}\r\n{}:{}\r\n",
+            formato.len() + 1 + texto.len(),
+            formato,
+            texto
+        )
+        .into_bytes(),
+        ResultadoRedis::Verbatim(_, texto) => parsear_bulk(texto.as_bytes()),
+        ResultadoRedis::Push(vec) if resp3 => parsear_agregado('>', vec, protocolo),
+        ResultadoRedis::Push(vec) => parsear_agregado('*', vec, protocolo),
+        ResultadoRedis::Null if resp3 => b"_\r\n".to_vec(),
+        ResultadoRedis::Null => b"*-1\r\n".to_vec(),
+    }
+}
+
+/// Formatea un numero de punto flotante de la misma manera en que lo devuelve redis
+pub fn formatear_double(valor: f64) -> String {
+    if valor.is_nan() {
+        "nan".to_string()
+    } else {
+        valor.to_string()
     }
 }
 
-pub fn parsear_int(cadena: String) -> Option<u32> {
-    let mut chars = cadena.chars();
-    chars.next();
-    match chars.as_str().parse::<u32>() {
-        Ok(int) => Some(int),
-        Err(_) => None,
+/// Formatea el resultado de INCRBYFLOAT y HINCRBYFLOAT como lo hace Redis: sin exponente y sin
+/// ceros decimales de mas. Se redondea a 15 digitos significativos para no mostrar los errores de
+/// redondeo de la aritmetica binaria, de modo que 0.1 + 0.2 da "0.3"
+pub fn formatear_flotante(valor: f64) -> String {
+    let redondeado = format!("{:.14e}", valor).parse::<f64>().unwrap_or(valor);
+    if redondeado == 0.0 {
+        return "0".to_string();
+    }
+    redondeado.to_string()
+}
+
+fn parsear_bulk(cad: &[u8]) -> Vec<u8> {
+    let mut respuesta = format!("${}\r\n", cad.len()).into_bytes();
+    respuesta.extend_from_slice(cad);
+    respuesta.extend_from_slice(b"\r\n");
+    respuesta
+}
+
+/// Parsea un tipo agregado con el prefijo indicado seguido de cada uno de sus elementos
+fn parsear_agregado(prefijo: char, elementos: &[ResultadoRedis], protocolo: Protocolo) -> Vec<u8> {
+    let mut respuesta = format!("{}{}\r\n", prefijo, elementos.len()).into_bytes();
+    for elemento in elementos {
+        respuesta.extend(parsear_respuesta(elemento, protocolo));
+    }
+    respuesta
+}
+
+/// Parsea el entero de una linea que comienza con el prefijo indicado, como `*3` o `$5`
+pub fn parsear_int(linea: &[u8], prefijo: u8) -> Option<u32> {
+    match linea.split_first() {
+        Some((primero, resto)) if *primero == prefijo => crate::binario::parsear(resto),
+        _ => None,
     }
 }
 
@@ -95,10 +299,19 @@ pub fn parsear_int(cadena: String) -> Option<u32> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn los_flotantes_se_formatean_sin_exponente_ni_errores_de_redondeo() {
+        assert_eq!("0.3", formatear_flotante(0.1 + 0.2));
+        assert_eq!("5.6", formatear_flotante(10.6 - 5.0));
+        assert_eq!("5200", formatear_flotante(5.0e3 + 2.0e2));
+        assert_eq!("0.0001", formatear_flotante(1e-4));
+        assert_eq!("0", formatear_flotante(-0.0));
+    }
+
     #[test]
     fn cuando_se_recibe_un_mensaje_de_ping_este_se_parsea_y_se_devuelve_el_comando_correcto() {
         let stream = "*1\r\n$4\r\nPING\r\n".as_bytes();
-        let parser = Parser::new(stream);
+        let mut parser = Parser::new(stream);
         let mut resultado = parser.parsear_stream().unwrap();
         assert_eq!(resultado.get_nombre(), "PING".to_string());
         assert_eq!(resultado.get_clave(), None);
@@ -108,67 +321,95 @@ mod tests {
     #[test]
     fn cuando_se_recibe_un_mensaje_de_llen_este_se_parsea_y_se_devuelve_el_comando_correcto() {
         let stream = "*2\r\n$4\r\nLLEN\r\n$6\r\nmylist\r\n".as_bytes();
-        let parser = Parser::new(stream);
+        let mut parser = Parser::new(stream);
         let mut resultado = parser.parsear_stream().unwrap();
         assert_eq!(resultado.get_nombre(), "LLEN".to_string());
-        assert_eq!(resultado.get_clave(), Some("mylist".to_string()));
+        assert_eq!(resultado.get_clave(), Some(b"mylist".to_vec()));
         assert_eq!(resultado.get_parametro(), None);
     }
 
     #[test]
     fn cuando_se_recibe_un_mensaje_de_sort_este_se_parsea_y_se_devuelve_el_comando_correcto() {
         let stream = "*7\r\n$4\r\nSORT\r\n$6\r\nmylist\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n5\r\n$5\r\nALPHA\r\n$4\r\nDESC\r\n".as_bytes();
-        let parser = Parser::new(stream);
+        let mut parser = Parser::new(stream);
         let mut resultado = parser.parsear_stream().unwrap();
 
         assert_eq!(resultado.get_nombre(), "SORT".to_string());
-        assert_eq!(resultado.get_clave(), Some("mylist".to_string()));
-        assert_eq!(resultado.get_parametro(), Some("LIMIT".to_string()));
-        assert_eq!(resultado.get_parametro(), Some("0".to_string()));
-        assert_eq!(resultado.get_parametro(), Some("5".to_string()));
-        assert_eq!(resultado.get_parametro(), Some("ALPHA".to_string()));
-        assert_eq!(resultado.get_parametro(), Some("DESC".to_string()));
+        assert_eq!(resultado.get_clave(), Some(b"mylist".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(b"LIMIT".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(b"0".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(b"5".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(b"ALPHA".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(b"DESC".to_vec()));
     }
 
     #[test]
     fn cuando_se_manda_un_mensaje_vacio_se_lanza_un_parser_error_de_tipo_mensaje_vacio() {
         let stream = "".as_bytes();
-        let parser = Parser::new(stream);
+        let mut parser = Parser::new(stream);
         let error = parser.parsear_stream().unwrap_err();
         assert_eq!(error, ParserError::MensajeVacioError);
     }
 
     #[test]
     fn cuando_se_manda_un_mensaje_con_un_error_de_sintaxis_se_lanza_un_redis_syntax_error() {
-        let stream = "++\r\n$4\r\n".as_bytes();
-        let parser = Parser::new(stream);
+        let stream = "*2\r\n++\r\n$4\r\n".as_bytes();
+        let mut parser = Parser::new(stream);
         let error = parser.parsear_stream().unwrap_err();
         assert_eq!(error, ParserError::RedisSyntaxError);
     }
 
+    #[test]
+    fn las_longitudes_demasiado_grandes_se_rechazan_sin_reservar_memoria() {
+        let mut parser = Parser::new("*1\r\n$4000000000\r\nabc\r\n".as_bytes());
+        assert_eq!(
+            ParserError::RedisSyntaxError,
+            parser.parsear_stream().unwrap_err()
+        );
+
+        let mut parser = Parser::new("*4000000000\r\n$3\r\nabc\r\n".as_bytes());
+        assert_eq!(
+            ParserError::RedisSyntaxError,
+            parser.parsear_stream().unwrap_err()
+        );
+
+        // Una longitud valida pero mayor a lo que llega no reserva toda la memoria anunciada
+        let mut parser = Parser::new("*1\r\n$500000000\r\nabc\r\n".as_bytes());
+        assert_eq!(
+            ParserError::RedisSyntaxError,
+            parser.parsear_stream().unwrap_err()
+        );
+    }
+
     #[test]
     fn cuando_se_envia_un_resultado_redis_simple_string_envia_un_string_correcto() {
         let resultado = ResultadoRedis::StrSimple("Ok".to_string());
-        assert_eq!(parsear_respuesta(&resultado), "+Ok\r\n");
+        assert_eq!(parsear_respuesta(&resultado, Protocolo::Resp2), b"+Ok\r\n");
     }
 
     #[test]
     fn cuando_se_envia_un_resultado_redis_bulk_strings_se_parsea_correctamente() {
-        let resultado = ResultadoRedis::BulkStr("foo".to_string());
-        assert_eq!(parsear_respuesta(&resultado), "$3\r\nfoo\r\n");
+        let resultado = ResultadoRedis::BulkStr("foo".into());
+        assert_eq!(
+            parsear_respuesta(&resultado, Protocolo::Resp2),
+            b"$3\r\nfoo\r\n"
+        );
     }
 
     #[test]
     fn cuando_se_envia_un_resultado_redis_int_se_parsea_correctamente() {
         let resultado = ResultadoRedis::Int(55);
-        assert_eq!(parsear_respuesta(&resultado), ":55\r\n");
+        assert_eq!(parsear_respuesta(&resultado, Protocolo::Resp2), b":55\r\n");
     }
 
     #[test]
     fn cuando_se_envia_un_resultado_redis_vector_de_ints_se_parsea_correctamente() {
         let resultado =
             ResultadoRedis::Vector(vec![ResultadoRedis::Int(1), ResultadoRedis::Int(2)]);
-        assert_eq!(parsear_respuesta(&resultado), "*2\r\n:1\r\n:2\r\n");
+        assert_eq!(
+            parsear_respuesta(&resultado, Protocolo::Resp2),
+            b"*2\r\n:1\r\n:2\r\n"
+        );
     }
 
     #[test]
@@ -178,24 +419,242 @@ mod tests {
             ResultadoRedis::Int(2),
             ResultadoRedis::Int(3),
             ResultadoRedis::Int(4),
-            ResultadoRedis::BulkStr("foobar".to_string()),
+            ResultadoRedis::BulkStr("foobar".into()),
         ]);
         assert_eq!(
-            parsear_respuesta(&resultado),
-            "*5\r\n:1\r\n:2\r\n:3\r\n:4\r\n$6\r\nfoobar\r\n"
+            parsear_respuesta(&resultado, Protocolo::Resp2),
+            b"*5\r\n:1\r\n:2\r\n:3\r\n:4\r\n$6\r\nfoobar\r\n"
         );
     }
 
     #[test]
     fn a() {
         let stream = "*3\r\n$3\r\nSET\r\n$7\r\ncatedra\r\n$18\r\nTallerProgramacion\r\n".as_bytes();
-        let parser = Parser::new(stream);
+        let mut parser = Parser::new(stream);
         let mut resultado = parser.parsear_stream().unwrap();
         assert_eq!(resultado.get_nombre(), "SET".to_string());
-        assert_eq!(resultado.get_clave(), Some("catedra".to_string()));
+        assert_eq!(resultado.get_clave(), Some(b"catedra".to_vec()));
         assert_eq!(
             resultado.get_parametro(),
-            Some("TallerProgramacion".to_string())
+            Some(b"TallerProgramacion".to_vec())
+        );
+    }
+
+    #[test]
+    fn los_argumentos_se_leen_por_longitud_y_pueden_contener_cualquier_byte() {
+        let stream: &[u8] = b"*3\r\n$3\r\nSET\r\n$4\r\nc\r\nv\r\n$3\r\n\xff\x00\xfe\r\n";
+        let mut parser = Parser::new(stream);
+        let mut resultado = parser.parsear_stream().unwrap();
+        assert_eq!(resultado.get_nombre(), "SET".to_string());
+        assert_eq!(resultado.get_clave(), Some(b"c\r\nv".to_vec()));
+        assert_eq!(resultado.get_parametro(), Some(vec![0xff, 0x00, 0xfe]));
+    }
+
+    #[test]
+    fn un_bulk_string_binario_se_responde_con_su_longitud_en_bytes() {
+        let resultado = ResultadoRedis::BulkStr(vec![0xff, b'\r', b'\n']);
+        assert_eq!(
+            parsear_respuesta(&resultado, Protocolo::Resp2),
+            b"$3\r\n\xff\r\n\r\n"
         );
     }
+
+    #[test]
+    fn los_comandos_encadenados_se_parsean_en_orden_sin_perder_ninguno() {
+        let stream = "*1\r\n$4\r\nPING\r\n*2\r\n$3\r\nGET\r\n$1\r\na\r\n".as_bytes();
+        let mut parser = Parser::new(stream);
+
+        assert_eq!(parser.parsear_stream().unwrap().get_nombre(), "PING");
+        assert!(parser.tiene_pendientes());
+        let mut segundo = parser.parsear_stream().unwrap();
+        assert_eq!(segundo.get_nombre(), "GET");
+        assert_eq!(segundo.get_clave(), Some(b"a".to_vec()));
+        assert!(!parser.tiene_pendientes());
+    }
+
+    /// Lector que entrega el contenido de a partes, como un socket que recibe un pipeline en varios paquetes
+    struct LectorPorPartes {
+        partes: Vec<&'static [u8]>,
+    }
+
+    impl Read for LectorPorPartes {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.partes.is_empty() {
+                return Ok(0);
+            }
+            let parte = self.partes.remove(0);
+            buf[..parte.len()].copy_from_slice(parte);
+            Ok(parte.len())
+        }
+    }
+
+    #[test]
+    fn un_comando_incompleto_al_final_del_buffer_se_completa_con_la_siguiente_lectura() {
+        let lector = LectorPorPartes {
+            partes: vec![
+                b"*1\r\n$4\r\nPING\r\n*2\r\n$4\r\nEC",
+                b"HO\r\n$4\r\nhola\r\n",
+            ],
+        };
+        let mut parser = Parser::new(lector);
+
+        assert_eq!(parser.parsear_stream().unwrap().get_nombre(), "PING");
+        let mut segundo = parser.parsear_stream().unwrap();
+        assert_eq!(segundo.get_nombre(), "ECHO");
+        assert_eq!(segundo.get_clave(), Some(b"hola".to_vec()));
+        assert_eq!(
+            parser.parsear_stream().unwrap_err(),
+            ParserError::MensajeVacioError
+        );
+    }
+
+    #[test]
+    fn un_comando_inline_se_separa_por_espacios() {
+        let stream = "\r\nSET  clave\tvalor\nPING\r\n".as_bytes();
+        let mut parser = Parser::new(stream);
+
+        let mut set = parser.parsear_stream().unwrap();
+        assert_eq!(set.get_nombre(), "SET");
+        assert_eq!(set.get_clave(), Some(b"clave".to_vec()));
+        assert_eq!(set.get_parametro(), Some(b"valor".to_vec()));
+        assert_eq!(parser.parsear_stream().unwrap().get_nombre(), "PING");
+    }
+
+    #[test]
+    fn un_comando_inline_admite_argumentos_entre_comillas_con_escapes() {
+        let argumentos =
+            separar_argumentos(br#"set "hola mundo\r\n\x41\"" 'it\'s' "" "\xZZ""#).unwrap();
+        assert_eq!(
+            argumentos,
+            vec![
+                b"set".to_vec(),
+                b"hola mundo\r\nA\"".to_vec(),
+                b"it's".to_vec(),
+                b"".to_vec(),
+                b"xZZ".to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn un_comando_inline_con_comillas_sin_cerrar_es_un_error_de_sintaxis() {
+        assert_eq!(
+            separar_argumentos(b"get \"clave"),
+            Err(ParserError::RedisSyntaxError)
+        );
+        assert_eq!(
+            separar_argumentos(b"get \"cla\"ve"),
+            Err(ParserError::RedisSyntaxError)
+        );
+    }
+
+    #[test]
+    fn comandos_inline_y_multibulk_pueden_mezclarse_en_la_misma_conexion() {
+        let stream = "PING\r\n*2\r\n$3\r\nGET\r\n$1\r\na\r\nGET b\r\n".as_bytes();
+        let mut parser = Parser::new(stream);
+
+        assert_eq!(parser.parsear_stream().unwrap().get_nombre(), "PING");
+        assert_eq!(
+            parser.parsear_stream().unwrap().get_clave(),
+            Some(b"a".to_vec())
+        );
+        assert_eq!(
+            parser.parsear_stream().unwrap().get_clave(),
+            Some(b"b".to_vec())
+        );
+    }
+
+    #[test]
+    fn las_lineas_de_mas_de_64kb_son_un_error_aunque_no_terminen() {
+        let mut inline = vec![b'a'; MAXIMO_LINEA];
+        inline.extend_from_slice(b"\r\n");
+        let mut parser = Parser::new(inline.as_slice());
+        assert_eq!(
+            parser.parsear_stream().unwrap().get_nombre().len(),
+            MAXIMO_LINEA
+        );
+
+        let inline = vec![b'a'; MAXIMO_LINEA + 1];
+        let mut parser = Parser::new(inline.as_slice());
+        assert_eq!(
+            parser.parsear_stream().err(),
+            Some(ParserError::LineaDemasiadoLarga)
+        );
+
+        let mut encabezado = b"*1\r\n$".to_vec();
+        encabezado.extend(vec![b'1'; MAXIMO_LINEA * 2]);
+        let mut parser = Parser::new(encabezado.as_slice());
+        assert_eq!(
+            parser.parsear_stream().err(),
+            Some(ParserError::LineaDemasiadoLarga)
+        );
+    }
+
+    #[test]
+    fn los_tipos_de_resp3_se_envian_con_su_propio_prefijo_en_resp3() {
+        let mapa = ResultadoRedis::Map(vec![(
+            ResultadoRedis::BulkStr("a".into()),
+            ResultadoRedis::Int(1),
+        )]);
+        let casos: Vec<(ResultadoRedis, &[u8])> = vec![
+            (mapa, b"%1\r\n$1\r\na\r\n:1\r\n"),
+            (
+                ResultadoRedis::Set(vec![ResultadoRedis::Int(1)]),
+                b"~1\r\n:1\r\n",
+            ),
+            (ResultadoRedis::Double(1.5), b",1.5\r\n"),
+            (ResultadoRedis::Double(f64::NEG_INFINITY), b",-inf\r\n"),
+            (ResultadoRedis::Boolean(true), b"#t\r\n"),
+            (
+                ResultadoRedis::BigNumber("12345678901234567890".to_string()),
+                b"(12345678901234567890\r\n",
+            ),
+            (
+                ResultadoRedis::Verbatim("txt".to_string(), "hola".to_string()),
+                b"=8\r\ntxt:hola\r\n",
+            ),
+            (
+                ResultadoRedis::Push(vec![ResultadoRedis::Int(1)]),
+                b">1\r\n:1\r\n",
+            ),
+            (ResultadoRedis::Null, b"_\r\n"),
+            (ResultadoRedis::Nil, b"_\r\n"),
+        ];
+        for (resultado, esperado) in casos {
+            assert_eq!(parsear_respuesta(&resultado, Protocolo::Resp3), esperado);
+        }
+    }
+
+    #[test]
+    fn los_tipos_de_resp3_se_envian_con_su_equivalente_en_resp2() {
+        let mapa = ResultadoRedis::Map(vec![(
+            ResultadoRedis::BulkStr("a".into()),
+            ResultadoRedis::Int(1),
+        )]);
+        let casos: Vec<(ResultadoRedis, &[u8])> = vec![
+            (mapa, b"*2\r\n$1\r\na\r\n:1\r\n"),
+            (
+                ResultadoRedis::Set(vec![ResultadoRedis::Int(1)]),
+                b"*1\r\n:1\r\n",
+            ),
+            (ResultadoRedis::Double(1.5), b"$3\r\n1.5\r\n"),
+            (ResultadoRedis::Boolean(false), b":0\r\n"),
+            (
+                ResultadoRedis::BigNumber("123".to_string()),
+                b"$3\r\n123\r\n",
+            ),
+            (
+                ResultadoRedis::Verbatim("txt".to_string(), "hola".to_string()),
+                b"$4\r\nhola\r\n",
+            ),
+            (
+                ResultadoRedis::Push(vec![ResultadoRedis::Int(1)]),
+                b"*1\r\n:1\r\n",
+            ),
+            (ResultadoRedis::Null, b"*-1\r\n"),
+        ];
+        for (resultado, esperado) in casos {
+            assert_eq!(parsear_respuesta(&resultado, Protocolo::Resp2), esperado);
+        }
+    }
 }
@@ -0,0 +1,106 @@
+/// Indica si el texto coincide con un patron glob al estilo de Redis. El patron admite `*` para
+/// cualquier secuencia de bytes, `?` para un byte cualquiera, clases como `[abc]`, `[^a]` o
+/// `[a-z]` y `\` para tomar literalmente el caracter siguiente
+pub fn coincide(patron: &[u8], texto: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Posicion de la ultima estrella y del texto desde donde se la intenta extender
+    let mut estrella: Option<(usize, usize)> = None;
+
+    while t < texto.len() {
+        if patron.get(p) == Some(&b'*') {
+            estrella = Some((p, t));
+            p += 1;
+            continue;
+        }
+        if p < patron.len() {
+            if let Some(largo) = coincide_elemento(&patron[p..], texto[t]) {
+                p += largo;
+                t += 1;
+                continue;
+            }
+        }
+        match estrella {
+            Some((pe, te)) => {
+                estrella = Some((pe, te + 1));
+                p = pe + 1;
+                t = te + 1;
+            }
+            None => return false,
+        }
+    }
+    patron[p..].iter().all(|&c| c == b'*')
+}
+
+/// Si el primer elemento del patron coincide con el byte devuelve cuantos bytes del patron ocupa
+fn coincide_elemento(patron: &[u8], byte: u8) -> Option<usize> {
+    match patron[0] {
+        b'?' => Some(1),
+        b'[' => {
+            let (coincide, largo) = coincide_clase(patron, byte);
+            coincide.then_some(largo)
+        }
+        b'\\' if patron.len() > 1 => (patron[1] == byte).then_some(2),
+        c => (c == byte).then_some(1),
+    }
+}
+
+/// Evalua una clase de caracteres que empieza en el primer byte del patron. Devuelve si el byte
+/// pertenece a ella y cuantos bytes del patron ocupa. Una clase sin cerrar llega hasta el final
+fn coincide_clase(patron: &[u8], byte: u8) -> (bool, usize) {
+    let mut i = 1;
+    let negada = patron.get(i) == Some(&b'^');
+    if negada {
+        i += 1;
+    }
+    let mut pertenece = false;
+    while i < patron.len() && patron[i] != b']' {
+        if patron[i] == b'\\' && i + 1 < patron.len() {
+            pertenece |= patron[i + 1] == byte;
+            i += 2;
+        } else if i + 2 < patron.len() && patron[i + 1] == b'-' {
+            let (desde, hasta) = (patron[i].min(patron[i + 2]), patron[i].max(patron[i + 2]));
+            pertenece |= (desde..=hasta).contains(&byte);
+            i += 3;
+        } else {
+            pertenece |= patron[i] == byte;
+            i += 1;
+        }
+    }
+    (pertenece != negada, (i + 1).min(patron.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn la_estrella_y_el_signo_de_pregunta_coinciden_con_cualquier_caracter() {
+        assert!(coincide(b"*", b""));
+        assert!(coincide(b"*", b"cualquier:cosa"));
+        assert!(coincide(b"orders.*", b"orders.nuevo"));
+        assert!(coincide(b"user:*:perfil", b"user:42:perfil"));
+        assert!(!coincide(b"user:*:perfil", b"user:42:datos"));
+        assert!(coincide(b"h?llo", b"hallo"));
+        assert!(!coincide(b"h?llo", b"hllo"));
+        assert!(coincide(b"*a*b", b"xaxxab"));
+    }
+
+    #[test]
+    fn las_clases_admiten_rangos_negacion_y_escapes() {
+        assert!(coincide(b"h[ae]llo", b"hello"));
+        assert!(!coincide(b"h[ae]llo", b"hillo"));
+        assert!(coincide(b"h[^e]llo", b"hallo"));
+        assert!(!coincide(b"h[^e]llo", b"hello"));
+        assert!(coincide(b"h[a-b]llo", b"hbllo"));
+        assert!(coincide(b"h[z-a]llo", b"hqllo"));
+        assert!(coincide(b"h[\\]]llo", b"h]llo"));
+    }
+
+    #[test]
+    fn la_barra_toma_literalmente_el_caracter_siguiente() {
+        assert!(coincide(b"pregunta\\?", b"pregunta?"));
+        assert!(!coincide(b"pregunta\\?", b"preguntas"));
+        assert!(coincide(b"\\*", b"*"));
+        assert!(!coincide(b"\\*", b"a"));
+    }
+}
@@ -1,25 +1,32 @@
 use crate::observer::Observer;
-use std::collections::{HashMap, HashSet};
+use std::cell::Cell;
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Result, Write};
-use std::iter::FromIterator;
 
 use std::sync::mpsc::{Receiver, Sender};
 use std::time::{Duration, Instant};
 
 use crate::base_de_datos::TipoRedis;
-use crate::valor::Valor;
+use crate::conjunto_ordenado::ConjuntoOrdenado;
+use crate::cursor::TablaEscaneable;
+use crate::valor::{ahora_ms, Valor};
 
 const STRING: &str = "STRING";
 const LIST: &str = "LIST";
 const SET: &str = "SET";
+const HASH: &str = "HASH";
+const ZSET: &str = "ZSET";
 const EX: &str = "EX";
+const PXAT: &str = "PXAT";
 const SEPARADOR: &str = ":";
+/// Primera linea de los archivos en los que cada clave guarda su vencimiento despues del nombre
+const ENCABEZADO: &str = "PERSISTENCIA:2";
 
 /// Representa un mensaje que puede enviar el Persistidor al PersistidorHandler
 pub enum MensajePersistencia {
     /// Encapsula la tabla a persistir
-    Info(HashMap<String, Valor>),
+    Info(TablaEscaneable<Valor>),
     /// Encapsula el Archivo donde se debe persistir la base de datos
     ArchivoAPersistir(String),
     /// Cierra el hilo donde se esta ejecutando el PersistidorHandler
@@ -29,8 +36,6 @@ pub enum MensajePersistencia {
 /// Entidad que se encarga de correr en un hilo y persistir la base de datos a traves de mensajes con el Persistidor
 pub struct PersistidorHandler {
     archivo: String,
-    intervalo: Duration,
-    instante: Instant,
     receptor: Receiver<MensajePersistencia>,
 }
 
@@ -40,22 +45,16 @@ impl PersistidorHandler {
     /// # Argumentos
     ///
     /// * `archivo` - string donde se va a persistir la base de datos
-    /// * `intervalo` - intervalo de tiempo a traves del cual se va a persistir
     /// * `receptor` - Receiver de mensajes asociado al channel del Persistidor
-    pub fn new(archivo: String, intervalo: u64, receptor: Receiver<MensajePersistencia>) -> Self {
-        PersistidorHandler {
-            archivo,
-            receptor,
-            instante: Instant::now(),
-            intervalo: Duration::from_secs(intervalo),
-        }
+    pub fn new(archivo: String, receptor: Receiver<MensajePersistencia>) -> Self {
+        PersistidorHandler { archivo, receptor }
     }
 
     /// Ejecuta al manejador esperando mensajes
     ///
     /// ```no_run
     /// let (tx_pers, rx_pers) = channel();
-    /// let mut pers_handler = PersistidorHandler::new(config.dbfilename(), 1, rx_pers);
+    /// let mut pers_handler = PersistidorHandler::new(config.dbfilename(), rx_pers);
     ///
     /// let hilo_pers = thread::spawn(move || {
     ///     pers_handler.persistir();
@@ -65,22 +64,14 @@ impl PersistidorHandler {
         while let Ok(mensaje) = self.receptor.recv() {
             match mensaje {
                 MensajePersistencia::Info(a_persistir) => {
-                    if self.instante.elapsed() >= self.intervalo {
-                        //persisto
-                        let mut vector: Vec<String> = vec![];
-                        for (key, val) in a_persistir.iter() {
-                            vector.push(guardar_clave_valor(
-                                key.to_string(),
-                                val.get(),
-                                val.get_tiempo(),
-                            ));
-                        }
-                        match guardar_en_archivo(&self.archivo, vector) {
-                            Ok(_) => (),
-                            Err(_) => break,
-                        };
-                        self.instante = Instant::now();
+                    let mut vector: Vec<String> = vec![];
+                    for (key, val) in a_persistir.iter().filter(|(_, v)| !v.expiro()) {
+                        vector.push(guardar_clave_valor(key, val.get(), val.vencimiento()));
                     }
+                    match guardar_en_archivo(&self.archivo, vector) {
+                        Ok(_) => (),
+                        Err(_) => break,
+                    };
                 }
 
                 MensajePersistencia::ArchivoAPersistir(a) => self.archivo = a,
@@ -95,6 +86,8 @@ impl PersistidorHandler {
 #[derive(Debug, Clone)]
 pub struct Persistidor {
     persistidor: Sender<MensajePersistencia>,
+    intervalo: Duration,
+    ultimo_envio: Cell<Instant>,
 }
 
 impl Persistidor {
@@ -103,11 +96,16 @@ impl Persistidor {
     /// # Argumentos
     ///
     /// * `persistidor` - Sender de MensajePersistencia asociado la channel de PersistidorHandler
-    pub fn new(persistidor: Sender<MensajePersistencia>) -> Self {
-        Persistidor { persistidor }
+    /// * `intervalo` - segundos que deben pasar entre dos persistencias de la base de datos
+    pub fn new(persistidor: Sender<MensajePersistencia>, intervalo: u64) -> Self {
+        Persistidor {
+            persistidor,
+            intervalo: Duration::from_secs(intervalo),
+            ultimo_envio: Cell::new(Instant::now()),
+        }
     }
 
-    pub fn persistir(&self, base_de_datos: HashMap<String, Valor>) {
+    pub fn persistir(&self, base_de_datos: TablaEscaneable<Valor>) {
         if self
             .persistidor
             .send(MensajePersistencia::Info(base_de_datos))
@@ -127,75 +125,80 @@ impl Persistidor {
 
 /// El persistidor es un observador que espera a que la base de datos notifique cuando se produjo un cambio importante
 impl Observer for Persistidor {
-    /// Al actualizarse envia la nueva base de datos a persistir
-    fn actualizar(&self, bdd: HashMap<String, Valor>) {
-        self.persistir(bdd);
+    /// Al actualizarse envia la nueva base de datos a persistir, si ya paso el intervalo desde el ultimo envio.
+    /// Solo en ese caso se copia la base de datos, para que las escrituras no dependan de su tamaño
+    fn actualizar(&self, bdd: &TablaEscaneable<Valor>) {
+        if self.ultimo_envio.get().elapsed() >= self.intervalo {
+            self.persistir(bdd.clone());
+            self.ultimo_envio.set(Instant::now());
+        }
     }
 }
 
-/// Crea una cadena con una codificacion especifica para persistir a partir de una clave y un valor
-fn guardar_clave_valor(clave: String, valor: Option<&TipoRedis>, time: Option<Duration>) -> String {
-    match (valor, time) {
-        (Some(TipoRedis::Str(valor)), Some(duration)) => {
-            STRING.to_string()
-                + SEPARADOR
-                + &clave
-                + SEPARADOR
-                + valor
-                + SEPARADOR
-                + EX
-                + SEPARADOR
-                + &(duration.as_secs().to_string())
+/// Crea una cadena con una codificacion especifica para persistir a partir de una clave y un valor.
+/// Las claves y valores binarios se codifican con `codificar` para que no rompan el formato. Despues
+/// de la clave siempre va el momento en que vence, en milisegundos desde el epoch de Unix, o un campo
+/// vacio si no expira, asi ningun elemento del valor se puede confundir con el vencimiento
+fn guardar_clave_valor(
+    clave: &[u8],
+    valor: Option<&TipoRedis>,
+    vencimiento: Option<u64>,
+) -> String {
+    let clave =
+        codificar(clave) + SEPARADOR + &vencimiento.map(|v| v.to_string()).unwrap_or_default();
+    let persistencia = match valor {
+        Some(TipoRedis::Str(valor)) => {
+            STRING.to_string() + SEPARADOR + &clave + SEPARADOR + &codificar(valor)
         }
-
-        (Some(TipoRedis::Str(valor)), None) => {
-            STRING.to_string() + SEPARADOR + &clave + SEPARADOR + valor
-        }
-
-        (Some(TipoRedis::Lista(lista)), Some(duration)) => {
+        Some(TipoRedis::Lista(lista)) => {
             let mut persistencia_lista = LIST.to_string() + SEPARADOR + &clave;
             for valor in lista.iter() {
-                persistencia_lista += &(SEPARADOR.to_string() + valor);
+                persistencia_lista += &(SEPARADOR.to_string() + &codificar(valor));
             }
-            persistencia_lista +=
-                &(SEPARADOR.to_string() + EX + SEPARADOR + &(duration.as_secs().to_string()));
             persistencia_lista
         }
-
-        (Some(TipoRedis::Lista(lista)), None) => {
-            let mut persistencia_lista = LIST.to_string() + SEPARADOR + &clave;
-            for valor in lista.iter() {
-                persistencia_lista += &(SEPARADOR.to_string() + valor);
-            }
-            persistencia_lista
-        }
-
-        (Some(TipoRedis::Set(set)), Some(duration)) => {
+        Some(TipoRedis::Set(set)) => {
             let mut persistencia_set = SET.to_string() + SEPARADOR + &clave;
             for valor in set.iter() {
-                persistencia_set += &(SEPARADOR.to_string() + valor);
+                persistencia_set += &(SEPARADOR.to_string() + &codificar(valor));
             }
-            persistencia_set +=
-                &(SEPARADOR.to_string() + EX + SEPARADOR + &(duration.as_secs().to_string()));
             persistencia_set
         }
-        (Some(TipoRedis::Set(set)), None) => {
-            let mut persistencia_set = SET.to_string() + SEPARADOR + &clave;
-            for valor in set.iter() {
-                persistencia_set += &(SEPARADOR.to_string() + valor);
+        Some(TipoRedis::Hash(hash)) => {
+            let mut persistencia_hash = HASH.to_string() + SEPARADOR + &clave;
+            for (campo, valor) in hash.iter() {
+                persistencia_hash +=
+                    &(SEPARADOR.to_string() + &codificar(campo) + SEPARADOR + &codificar(valor));
             }
-            persistencia_set
+            persistencia_hash
         }
-        _ => String::new(),
-    }
+        Some(TipoRedis::ZSet(conjunto)) => {
+            let mut persistencia_zset = ZSET.to_string() + SEPARADOR + &clave;
+            for (miembro, puntaje) in conjunto.elementos() {
+                persistencia_zset += &(SEPARADOR.to_string()
+                    + &codificar(&miembro)
+                    + SEPARADOR
+                    + &puntaje.to_string());
+            }
+            persistencia_zset
+        }
+        None => return String::new(),
+    };
+    persistencia
 }
 
 fn guardar_en_archivo(archivo: &str, instrucciones: Vec<String>) -> Result<()> {
-    let mut archivo = match OpenOptions::new().write(true).create(true).open(archivo) {
+    let mut archivo = match OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(archivo)
+    {
         Ok(a) => a,
         Err(e) => return Err(e),
     };
 
+    writeln!(archivo, "{}", ENCABEZADO)?;
     for instruccion in instrucciones.iter() {
         if let Err(e) = writeln!(archivo, "{}", instruccion) {
             println!("{:?}", e);
@@ -204,9 +207,11 @@ fn guardar_en_archivo(archivo: &str, instrucciones: Vec<String>) -> Result<()> {
     Ok(())
 }
 
-/// Lee el archivo de persistencia y crea una nuevo hashmap a partir de el
-pub fn levantar_tabla(archivo_persistencia: String) -> HashMap<String, Valor> {
-    let mut hashmap = HashMap::<String, Valor>::new();
+/// Lee el archivo de persistencia y crea una nuevo hashmap a partir de el. Las claves cuyo
+/// vencimiento paso mientras el servidor estaba detenido no se cargan. Los archivos sin encabezado
+/// son de versiones anteriores, que agregaban el vencimiento al final de la linea
+pub fn levantar_tabla(archivo_persistencia: String) -> HashMap<Vec<u8>, Valor> {
+    let mut hashmap = HashMap::<Vec<u8>, Valor>::new();
 
     let archivo = match File::open(archivo_persistencia) {
         Ok(archivo) => archivo,
@@ -214,85 +219,128 @@ pub fn levantar_tabla(archivo_persistencia: String) -> HashMap<String, Valor> {
     };
 
     let reader = BufReader::new(archivo);
-    let mut lineas = reader.lines();
+    let mut lineas = reader.lines().peekable();
+    let con_encabezado = matches!(lineas.peek(), Some(Ok(linea)) if linea == ENCABEZADO);
+    if con_encabezado {
+        lineas.next();
+    }
     while let Some(Ok(line)) = lineas.next() {
-        if line.is_empty() {
+        let mut elemento: Vec<&str> = line.split(':').collect();
+        if elemento.len() < 2 {
             continue;
         }
-        let mut elemento: Vec<&str> = line.split(':').collect();
-
-        if elemento.contains(&"STRING") {
-            let mut valor = Valor::no_expirable(TipoRedis::Str(elemento[2].to_string()));
-
-            if es_expirable(elemento.clone()) {
-                let tiempo = obtener_tiempo_expiracion(elemento.clone(), "EX").unwrap_or(0);
-                valor = Valor::expirable(TipoRedis::Str(elemento[2].to_string()), tiempo);
+        let tipo = elemento.remove(0);
+        let clave = decodificar(elemento.remove(0));
+        let vencimiento = if !con_encabezado {
+            separar_vencimiento_anterior(&mut elemento)
+        } else if elemento.is_empty() {
+            continue;
+        } else {
+            match elemento.remove(0) {
+                "" => None,
+                ms => match ms.parse::<u64>() {
+                    Ok(ms) => Some(ms),
+                    Err(_) => continue,
+                },
             }
-            hashmap.insert(elemento[1].to_string(), valor);
-        } else if elemento.contains(&"LIST") {
-            elemento.remove(0);
-            let clave = elemento.remove(0).to_string();
-            let mut valor = Valor::no_expirable(TipoRedis::Lista(
-                elemento.iter().map(|x| x.to_string()).collect(),
-            ));
-
-            if es_expirable(elemento.clone()) {
-                let tiempo = obtener_tiempo_expiracion(elemento.clone(), "EX").unwrap_or(0);
+        };
+        if vencimiento.is_some_and(|v| v <= ahora_ms()) {
+            continue;
+        }
 
-                valor = Valor::expirable(
-                    TipoRedis::Lista(elemento.iter().map(|x| x.to_string()).collect()),
-                    tiempo,
-                );
-            }
-            hashmap.insert(clave, valor);
-        } else if elemento.contains(&"SET") {
-            elemento.remove(0);
-            let clave = elemento.remove(0).to_string();
-            let mut valor = Valor::no_expirable(TipoRedis::Set(HashSet::from_iter(
+        let valor = match tipo {
+            STRING => match elemento.first() {
+                Some(valor) => TipoRedis::Str(decodificar(valor)),
+                None => continue,
+            },
+            LIST => TipoRedis::Lista(elemento.iter().map(|x| decodificar(x)).collect()),
+            SET => TipoRedis::Set(elemento.iter().map(|x| decodificar(x)).collect()),
+            HASH => TipoRedis::Hash(
                 elemento
-                    .iter()
-                    .map(|x| x.to_string())
-                    .collect::<Vec<String>>(),
-            )));
-            if es_expirable(elemento.clone()) {
-                let tiempo = obtener_tiempo_expiracion(elemento.clone(), "EX").unwrap_or(0);
-
-                valor = Valor::expirable(
-                    TipoRedis::Set(HashSet::from_iter(
-                        elemento
-                            .iter()
-                            .map(|x| x.to_string())
-                            .collect::<Vec<String>>(),
-                    )),
-                    tiempo,
-                );
+                    .chunks(2)
+                    .filter(|par| par.len() == 2)
+                    .map(|par| (decodificar(par[0]), decodificar(par[1])))
+                    .collect(),
+            ),
+            ZSET => {
+                let mut conjunto = ConjuntoOrdenado::new();
+                for par in elemento.chunks(2).filter(|par| par.len() == 2) {
+                    if let Ok(puntaje) = par[1].parse::<f64>() {
+                        conjunto.insertar(decodificar(par[0]), puntaje);
+                    }
+                }
+                TipoRedis::ZSet(conjunto)
             }
-            hashmap.insert(clave, valor);
-        } else {
-            continue;
-        }
+            _ => continue,
+        };
+        let valor = match vencimiento {
+            Some(v) => Valor::con_vencimiento(valor, v),
+            None => Valor::no_expirable(valor),
+        };
+        hashmap.insert(clave, valor);
     }
     hashmap
 }
 
-fn es_expirable(parametros: Vec<&str>) -> bool {
-    parametros.contains(&"EX")
+/// Codifica una cadena binaria para poder escribirla en una linea del archivo. Los bytes que no
+/// son ASCII imprimible, el separador y el `%` se escriben como `%XX` en hexadecimal
+fn codificar(bytes: &[u8]) -> String {
+    let mut codificado = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'%' | b':' => codificado += &format!("%{:02X}", byte),
+            0x20..=0x7e => codificado.push(*byte as char),
+            _ => codificado += &format!("%{:02X}", byte),
+        }
+    }
+    codificado
+}
+
+/// Decodifica una cadena escrita con `codificar`
+fn decodificar(cadena: &str) -> Vec<u8> {
+    let bytes = cadena.as_bytes();
+    let mut decodificado = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = cadena
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decodificado.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decodificado.push(bytes[i]);
+        i += 1;
+    }
+    decodificado
 }
 
-fn obtener_tiempo_expiracion(parametros: Vec<&str>, support: &str) -> Option<u64> {
-    match parametros.rsplit(|p| p == &support.to_string()).next() {
-        Some(c) => match c[0].parse::<u64>() {
-            Ok(num) => Some(num),
-            Err(_) => None,
-        },
-        None => None,
+/// Quita del final de una linea de un archivo sin encabezado el vencimiento de la clave y lo
+/// devuelve en milisegundos desde el epoch de Unix. Los archivos escritos antes de guardar
+/// vencimientos absolutos tienen `EX` con los segundos de vida, que se cuentan desde la carga
+fn separar_vencimiento_anterior(elemento: &mut Vec<&str>) -> Option<u64> {
+    if elemento.len() < 2 {
+        return None;
     }
+    let (marca, tiempo) = (elemento[elemento.len() - 2], elemento[elemento.len() - 1]);
+    let vencimiento = match (marca, tiempo.parse::<u64>()) {
+        (PXAT, Ok(ms)) => ms,
+        (EX, Ok(segundos)) => ahora_ms().saturating_add(segundos.saturating_mul(1000)),
+        _ => return None,
+    };
+    elemento.truncate(elemento.len() - 2);
+    Some(vencimiento)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hyperloglog::HyperLogLog;
     use std::collections::HashMap;
+    use std::collections::VecDeque;
 
     #[test]
     fn inserto_varios_strings_en_hash_map_y_guardar_clave_valor_devuelve_el_mensaje_para_volver_a_cargarlos(
@@ -300,28 +348,28 @@ mod tests {
         let mut map = HashMap::new();
         map.insert(
             "UnaClave1",
-            Valor::no_expirable(TipoRedis::Str("UnValor".to_string())),
+            Valor::no_expirable(TipoRedis::Str("UnValor".into())),
         );
         map.insert(
             "UnaClave2",
-            Valor::no_expirable(TipoRedis::Str("UnValor".to_string())),
+            Valor::no_expirable(TipoRedis::Str("UnValor".into())),
         );
         map.insert(
             "UnaClave3",
-            Valor::no_expirable(TipoRedis::Str("UnValor".to_string())),
+            Valor::no_expirable(TipoRedis::Str("UnValor".into())),
         );
 
         let mut vector: Vec<String> = vec![];
         for (key, val) in map.iter() {
             vector.push(guardar_clave_valor(
-                key.to_string(),
+                key.as_bytes(),
                 val.get(),
-                val.get_tiempo(),
+                val.vencimiento(),
             ));
         }
-        assert!(vector.contains(&"STRING:UnaClave1:UnValor".to_string()));
-        assert!(vector.contains(&"STRING:UnaClave2:UnValor".to_string()));
-        assert!(vector.contains(&"STRING:UnaClave3:UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave1::UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave2::UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave3::UnValor".to_string()));
     }
 
     #[test]
@@ -330,20 +378,20 @@ mod tests {
         let mut map = HashMap::new();
         map.insert(
             "UnaClave1",
-            Valor::no_expirable(TipoRedis::Str("UnValor".to_string())),
+            Valor::no_expirable(TipoRedis::Str("UnValor".into())),
         );
         map.insert(
             "UnaClave2",
-            Valor::no_expirable(TipoRedis::Str("UnValor".to_string())),
+            Valor::no_expirable(TipoRedis::Str("UnValor".into())),
         );
 
-        let mut lista = TipoRedis::Lista(Vec::new());
+        let mut lista = TipoRedis::Lista(VecDeque::new());
 
         match lista {
             TipoRedis::Lista(ref mut lista) => {
-                lista.push("PRIMER_VALOR".to_string());
-                lista.push("SEGUNDO_VALOR".to_string());
-                lista.push("TERCER_VALOR".to_string());
+                lista.push_back("PRIMER_VALOR".into());
+                lista.push_back("SEGUNDO_VALOR".into());
+                lista.push_back("TERCER_VALOR".into());
             }
             _ => {}
         }
@@ -353,15 +401,15 @@ mod tests {
         let mut vector: Vec<String> = vec![];
         for (key, val) in map.iter() {
             vector.push(guardar_clave_valor(
-                key.to_string(),
+                key.as_bytes(),
                 val.get(),
-                val.get_tiempo(),
+                val.vencimiento(),
             ));
         }
-        assert!(vector.contains(&"STRING:UnaClave1:UnValor".to_string()));
-        assert!(vector.contains(&"STRING:UnaClave2:UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave1::UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave2::UnValor".to_string()));
         assert!(
-            vector.contains(&"LIST:milista:PRIMER_VALOR:SEGUNDO_VALOR:TERCER_VALOR".to_string())
+            vector.contains(&"LIST:milista::PRIMER_VALOR:SEGUNDO_VALOR:TERCER_VALOR".to_string())
         );
     }
 
@@ -371,42 +419,195 @@ mod tests {
         let mut map = HashMap::new();
         map.insert(
             "UnaClave1",
-            Valor::expirable(TipoRedis::Str("UnValor".to_string()), 3000),
+            Valor::con_vencimiento(TipoRedis::Str("UnValor".into()), 4102444800000),
         );
         map.insert(
             "UnaClave2",
-            Valor::expirable(TipoRedis::Str("UnValor".to_string()), 3000),
+            Valor::con_vencimiento(TipoRedis::Str("UnValor".into()), 4102444800000),
         );
         map.insert(
             "UnaClave3",
-            Valor::expirable(TipoRedis::Str("UnValor".to_string()), 3000),
+            Valor::con_vencimiento(TipoRedis::Str("UnValor".into()), 4102444800000),
         );
 
-        let mut lista = TipoRedis::Lista(Vec::new());
+        let mut lista = TipoRedis::Lista(VecDeque::new());
 
         match lista {
             TipoRedis::Lista(ref mut lista) => {
-                lista.push("PRIMER_VALOR".to_string());
-                lista.push("SEGUNDO_VALOR".to_string());
-                lista.push("TERCER_VALOR".to_string());
+                lista.push_back("PRIMER_VALOR".into());
+                lista.push_back("SEGUNDO_VALOR".into());
+                lista.push_back("TERCER_VALOR".into());
             }
             _ => {}
         }
 
-        map.insert("milista", Valor::expirable(lista, 4500));
+        map.insert("milista", Valor::con_vencimiento(lista, 4102444800000));
 
         let mut vector: Vec<String> = vec![];
         for (key, val) in map.iter() {
             vector.push(guardar_clave_valor(
-                key.to_string(),
+                key.as_bytes(),
                 val.get(),
-                val.get_tiempo(),
+                val.vencimiento(),
             ));
         }
-        assert!(vector.contains(&"STRING:UnaClave1:UnValor:EX:3000".to_string()));
-        assert!(vector.contains(&"STRING:UnaClave2:UnValor:EX:3000".to_string()));
-        assert!(vector.contains(&"STRING:UnaClave3:UnValor:EX:3000".to_string()));
-        assert!(vector
-            .contains(&"LIST:milista:PRIMER_VALOR:SEGUNDO_VALOR:TERCER_VALOR:EX:4500".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave1:4102444800000:UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave2:4102444800000:UnValor".to_string()));
+        assert!(vector.contains(&"STRING:UnaClave3:4102444800000:UnValor".to_string()));
+        assert!(vector.contains(
+            &"LIST:milista:4102444800000:PRIMER_VALOR:SEGUNDO_VALOR:TERCER_VALOR".to_string()
+        ));
+    }
+
+    #[test]
+    fn un_hash_persistido_se_vuelve_a_levantar_con_sus_campos_y_expiracion() {
+        let mut hash = HashMap::new();
+        hash.insert(b"nombre".to_vec(), b"Juan".to_vec());
+        hash.insert(b"edad".to_vec(), b"20".to_vec());
+        let valor = Valor::con_vencimiento(TipoRedis::Hash(hash.clone().into()), 4102444800000);
+
+        let linea = guardar_clave_valor(b"miHash", valor.get(), valor.vencimiento());
+        let archivo = "test_persistencia_hash.rb";
+        guardar_en_archivo(archivo, vec![linea]).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let levantado = tabla.get(&b"miHash"[..]).unwrap();
+        assert_eq!(Some(&TipoRedis::Hash(hash.into())), levantado.get());
+        assert_eq!(Some(4102444800000), levantado.vencimiento());
+    }
+
+    #[test]
+    fn un_sorted_set_persistido_se_vuelve_a_levantar_con_sus_puntajes() {
+        let mut conjunto = ConjuntoOrdenado::new();
+        conjunto.insertar(b"uno".to_vec(), 1.0);
+        conjunto.insertar(b"medio".to_vec(), 0.5);
+        conjunto.insertar(b"infinito".to_vec(), f64::INFINITY);
+        let valor = Valor::no_expirable(TipoRedis::ZSet(conjunto.clone()));
+
+        let linea = guardar_clave_valor(b"miZSet", valor.get(), valor.vencimiento());
+        let archivo = "test_persistencia_zset.rb";
+        guardar_en_archivo(archivo, vec![linea]).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let levantado = tabla.get(&b"miZSet"[..]).unwrap();
+        assert_eq!(Some(&TipoRedis::ZSet(conjunto)), levantado.get());
+        assert_eq!(None, levantado.vencimiento());
+    }
+
+    #[test]
+    fn una_clave_y_un_valor_binarios_se_persisten_y_se_vuelven_a_levantar_sin_cambios() {
+        let clave = b"clave:con\r\nseparadores".to_vec();
+        let contenido = vec![0xff, 0x00, b':', b'%', b'\n', b'a'];
+        let valor = Valor::no_expirable(TipoRedis::Str(contenido.clone()));
+
+        let linea = guardar_clave_valor(&clave, valor.get(), valor.vencimiento());
+        assert!(!linea.contains('\n'));
+        let archivo = "test_persistencia_binaria.rb";
+        guardar_en_archivo(archivo, vec![linea]).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let levantado = tabla.get(&clave).unwrap();
+        assert_eq!(Some(&TipoRedis::Str(contenido)), levantado.get());
+    }
+
+    #[test]
+    fn un_hyperloglog_denso_se_persiste_como_string_y_sigue_estimando_lo_mismo() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..5_000 {
+            hll.agregar(format!("visitante:{}", i).as_bytes());
+        }
+        let valor = Valor::no_expirable(TipoRedis::Str(hll.a_bytes()));
+
+        let linea = guardar_clave_valor(b"visitas", valor.get(), valor.vencimiento());
+        let archivo = "test_persistencia_hll.rb";
+        guardar_en_archivo(archivo, vec![linea]).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let levantado = match tabla.get(&b"visitas"[..]).and_then(|v| v.get()) {
+            Some(TipoRedis::Str(bytes)) => HyperLogLog::desde_bytes(bytes).unwrap(),
+            otro => panic!("se esperaba un string y se obtuvo {:?}", otro),
+        };
+        assert_eq!(hll.contar(), levantado.contar());
+    }
+
+    #[test]
+    fn las_claves_vencidas_mientras_el_servidor_estaba_detenido_no_se_levantan() {
+        let vencida = Valor::con_vencimiento(TipoRedis::Str("valor".into()), ahora_ms() - 1000);
+        let vigente = Valor::con_vencimiento(TipoRedis::Str("valor".into()), ahora_ms() + 60_000);
+        let lineas = vec![
+            guardar_clave_valor(
+                b"vencida",
+                Some(&TipoRedis::Str("valor".into())),
+                vencida.vencimiento(),
+            ),
+            guardar_clave_valor(b"vigente", vigente.get(), vigente.vencimiento()),
+        ];
+        let archivo = "test_persistencia_vencimientos.rb";
+        guardar_en_archivo(archivo, lineas).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        assert!(!tabla.contains_key(&b"vencida"[..]));
+        assert_eq!(
+            vigente.vencimiento(),
+            tabla.get(&b"vigente"[..]).unwrap().vencimiento()
+        );
+    }
+
+    #[test]
+    fn los_elementos_que_parecen_un_vencimiento_se_levantan_como_datos() {
+        let lista = TipoRedis::Lista(VecDeque::from(vec![b"PXAT".to_vec(), b"5".to_vec()]));
+        let mut hash = HashMap::new();
+        hash.insert(b"EX".to_vec(), b"100".to_vec());
+        let hash = TipoRedis::Hash(hash.into());
+        let lineas = vec![
+            guardar_clave_valor(b"victim", Some(&lista), None),
+            guardar_clave_valor(b"hv", Some(&hash), None),
+        ];
+        let archivo = "test_persistencia_marcas_en_los_datos.rb";
+        guardar_en_archivo(archivo, lineas).unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let victim = tabla.get(&b"victim"[..]).unwrap();
+        assert_eq!(Some(&lista), victim.get());
+        assert_eq!(None, victim.vencimiento());
+        let hv = tabla.get(&b"hv"[..]).unwrap();
+        assert_eq!(Some(&hash), hv.get());
+        assert_eq!(None, hv.vencimiento());
+    }
+
+    #[test]
+    fn un_archivo_sin_encabezado_se_levanta_con_el_vencimiento_al_final() {
+        let archivo = "test_persistencia_formato_anterior.rb";
+        std::fs::write(
+            archivo,
+            "STRING:clave:valor:PXAT:4102444800000\nLIST:lista:a:b\n",
+        )
+        .unwrap();
+
+        let tabla = levantar_tabla(archivo.to_string());
+        std::fs::remove_file(archivo).unwrap();
+
+        let clave = tabla.get(&b"clave"[..]).unwrap();
+        assert_eq!(Some(&TipoRedis::Str(b"valor".to_vec())), clave.get());
+        assert_eq!(Some(4102444800000), clave.vencimiento());
+        assert_eq!(
+            Some(&TipoRedis::Lista(VecDeque::from(vec![
+                b"a".to_vec(),
+                b"b".to_vec()
+            ]))),
+            tabla.get(&b"lista"[..]).unwrap().get()
+        );
     }
 }
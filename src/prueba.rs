@@ -0,0 +1,18 @@
+//! Utilidades compartidas por los tests de los distintos modulos.
+
+use crate::cliente::{Cliente, Token};
+use crate::cliente_redis::ClienteRedis;
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// Crea un cliente conectado a un socket local y devuelve tambien el otro
+/// extremo de la conexion, desde donde se leen las respuestas que recibe.
+pub fn cliente_de_prueba(token: Token) -> (Cliente, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let conexion = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    conexion
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    (Box::new(ClienteRedis::new(token, 0, stream)), conexion)
+}
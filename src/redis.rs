@@ -1,11 +1,16 @@
+use crate::aof::{reproducir, ArchivoAof, MensajeAof, SincronizadorAof};
 use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::bloqueo::{claves_y_timeout, es_comando_bloqueante, esperar_desbloqueo};
 use crate::cliente::{crear_cliente, Cliente, Token};
-use crate::comando::crear_comando_handler;
+use crate::comando::ejecutar_comando;
 use crate::comando_info::ComandoInfo;
+use crate::comando_pubsub_handler::responder_en_modo_suscripcion;
+use crate::expiracion::ExpiradorHandler;
 use crate::log_handler::{LogHandler, Logger, Mensaje};
 use crate::observer::Observable;
 use crate::persistencia::{levantar_tabla, MensajePersistencia, Persistidor, PersistidorHandler};
 use crate::redis_error::RedisError;
+use crate::transaccion::es_comando_transaccion;
 use crate::Config;
 
 use std::net::TcpListener;
@@ -24,6 +29,10 @@ pub struct Redis {
     hilo_log: Option<JoinHandle<()>>,
     tx_pers: Sender<MensajePersistencia>,
     hilo_pers: Option<JoinHandle<()>>,
+    tx_aof: Sender<MensajeAof>,
+    hilo_aof: Option<JoinHandle<()>>,
+    tx_expiracion: Sender<()>,
+    hilo_expiracion: Option<JoinHandle<()>>,
     hilos_clientes: Vec<Option<JoinHandle<()>>>,
 }
 
@@ -53,24 +62,73 @@ impl Redis {
         });
 
         let (tx_pers, rx_pers) = channel();
-        let mut pers_handler = PersistidorHandler::new(config.dbfilename(), 1, rx_pers);
+        let ruta_dump = config.dbfilename();
+        let mut pers_handler = PersistidorHandler::new(config.dbfilename(), rx_pers);
 
         let hilo_pers = thread::spawn(move || {
             pers_handler.persistir();
         });
 
-        let mut bdd = BaseDeDatos::new_con(levantar_tabla(config.dbfilename()));
-        bdd.agregar_observador(Box::new(Persistidor::new(tx_pers.clone())));
-        config.set_persistidor(Persistidor::new(tx_pers.clone()));
+        config.set_persistidor(Persistidor::new(tx_pers.clone(), 1));
+
+        let aof = if config.appendonly() {
+            match ArchivoAof::abrir(config.appendfilename(), config.appendfsync()) {
+                Ok(aof) => {
+                    let aof = Arc::new(Mutex::new(aof));
+                    config.set_aof(Arc::clone(&aof));
+                    Some(aof)
+                }
+                Err(_) => None,
+            }
+        } else {
+            None
+        };
+        let ruta_aof = config.appendfilename();
+        let config = Arc::new(Mutex::new(config));
+
+        // Con el modo AOF activo la base de datos se reconstruye reproduciendo los comandos registrados
+        let bdd = match &aof {
+            Some(_) => {
+                let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+                reproducir(&ruta_aof, Arc::clone(&bdd), Arc::clone(&config));
+                bdd
+            }
+            None => Arc::new(Mutex::new(BaseDeDatos::new_con(levantar_tabla(ruta_dump)))),
+        };
+        if let Ok(mut bdd) = bdd.lock() {
+            bdd.agregar_observador(Box::new(Persistidor::new(tx_pers.clone(), 1)));
+            if let Some(aof) = &aof {
+                bdd.set_aof(Arc::clone(aof));
+            }
+        }
+
+        let (tx_aof, rx_aof) = channel();
+        let hilo_aof = aof.map(|aof| {
+            let mut sincronizador = SincronizadorAof::new(aof, rx_aof);
+            thread::spawn(move || {
+                sincronizador.sincronizar();
+            })
+        });
+
+        let (tx_expiracion, rx_expiracion) = channel();
+        let mut expirador =
+            ExpiradorHandler::new(Arc::clone(&bdd), Arc::clone(&config), rx_expiracion);
+        let hilo_expiracion = thread::spawn(move || {
+            expirador.expirar();
+        });
 
         Redis {
-            config: Arc::new(Mutex::new(config)),
-            bdd: Arc::new(Mutex::new(bdd)),
+            config,
+            bdd,
             siguiente_id: 0,
             tx_log,
             hilo_log: Some(hilo_log),
             tx_pers,
             hilo_pers: Some(hilo_pers),
+            tx_aof,
+            hilo_aof,
+            tx_expiracion,
+            hilo_expiracion: Some(hilo_expiracion),
             hilos_clientes: Vec::new(),
         }
     }
@@ -102,10 +160,14 @@ impl Redis {
 
             let handle = thread::spawn(move || {
                 logger.log_coneccion(cliente.obtener_addr(), "Se conecto usario".to_string());
-                match manejar_cliente(&mut cliente, clon_tabla, clon_config, &logger) {
+                match manejar_cliente(&mut cliente, Arc::clone(&clon_tabla), clon_config, &logger) {
                     Ok(()) => (),
                     Err(e) => manejar_error(&logger, e, cliente.obtener_addr()),
                 };
+                // Se quita de los canales para no seguir contandolo como suscriptor
+                if let Ok(mut bdd) = clon_tabla.lock() {
+                    bdd.pubsub().quitar_cliente(cliente.clone());
+                }
 
                 logger.log_coneccion(cliente.obtener_addr(), "se desconecto usuario".to_string());
             });
@@ -116,7 +178,7 @@ impl Redis {
 }
 
 /// Elimina recursos tomados por el servidor siendo estos
-/// los hilos de los clientes, y los hilos de log y persistencia
+/// los hilos de los clientes, y los hilos de log, persistencia y expiracion
 impl Drop for Redis {
     fn drop(&mut self) {
         for cliente in &mut self.hilos_clientes {
@@ -136,6 +198,18 @@ impl Drop for Redis {
         if let Some(hilo) = self.hilo_pers.take() {
             if hilo.join().is_ok() {}
         }
+
+        self.tx_expiracion.send(()).ok();
+
+        if let Some(hilo) = self.hilo_expiracion.take() {
+            hilo.join().ok();
+        }
+
+        self.tx_aof.send(MensajeAof::Cerrar).ok();
+
+        if let Some(hilo) = self.hilo_aof.take() {
+            hilo.join().ok();
+        }
     }
 }
 
@@ -163,12 +237,8 @@ fn manejar_cliente(
             };
             logger.log_comando(cliente.obtener_addr(), comando.clone());
 
-            let resultado = manejar_comando(
-                comando,
-                cliente.clone(),
-                Arc::clone(&tabla),
-                Arc::clone(&config),
-            );
+            let resultado =
+                manejar_comando(comando, cliente, Arc::clone(&tabla), Arc::clone(&config));
 
             match config.lock() {
                 Ok(mut c) => c.actualizar(logger, cliente.clone()),
@@ -186,15 +256,46 @@ fn manejar_cliente(
     Ok(())
 }
 
-/// Ejecuta el comando ya procesado, para ello instancia al manejador correcto
+/// Ejecuta el comando ya procesado, para ello instancia al manejador correcto.
+/// Si el cliente esta suscrito a canales solo puede enviar los comandos admitidos en ese modo.
+/// Si el cliente tiene una transaccion en curso o el comando es de transaccion, lo maneja la transaccion del cliente.
+/// Si es un comando bloqueante sin elementos para sacar, el cliente queda bloqueado hasta ser atendido o
+/// hasta que venza el timeout, sin retener la base de datos mientras espera
 fn manejar_comando(
     entrada: ComandoInfo,
-    cliente: Cliente,
+    cliente: &mut Cliente,
     tabla: Arc<Mutex<BaseDeDatos>>,
     config: Arc<Mutex<Config>>,
 ) -> ResultadoRedis {
-    let handler = crear_comando_handler(entrada, cliente, config);
-    handler.ejecutar(tabla)
+    if let Some(respuesta) = responder_en_modo_suscripcion(&entrada, cliente) {
+        return respuesta;
+    }
+    let clon_cliente = cliente.clone();
+    if let Some(transaccion) = cliente.obtener_transaccion() {
+        // RESET no se encola, descarta la transaccion en curso y las claves observadas
+        if entrada.get_nombre() == "RESET" {
+            transaccion.reiniciar();
+        } else if transaccion.en_curso() || es_comando_transaccion(entrada.get_nombre().as_str()) {
+            return transaccion.manejar(entrada, clon_cliente, tabla, config);
+        }
+    }
+    let mut bdd = match tabla.lock() {
+        Ok(bdd) => bdd,
+        Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+    };
+    if !es_comando_bloqueante(entrada.get_nombre().as_str()) {
+        return ejecutar_comando(entrada, clon_cliente, &mut bdd, config);
+    }
+
+    let resultado = ejecutar_comando(entrada.clone(), clon_cliente.clone(), &mut bdd, config);
+    if resultado != ResultadoRedis::Null {
+        return resultado;
+    }
+    // Se bloquea con la base de datos todavia tomada para no perder elementos agregados mientras tanto
+    let (claves, timeout) = claves_y_timeout(&entrada);
+    let (id, rx) = bdd.bloqueos().bloquear(entrada, claves, clon_cliente);
+    drop(bdd);
+    esperar_desbloqueo(id, rx, timeout, cliente, &tabla)
 }
 
 /// Loggea el error obtenido en la ejecucion de un cliente en particular
@@ -0,0 +1,204 @@
+use crate::base_de_datos::ResultadoRedis;
+use crate::canal::{Canal, PatronCanal};
+use crate::cliente::Cliente;
+use crate::patron::coincide;
+use std::collections::HashMap;
+
+/// Registro de los canales y patrones con clientes suscritos. Se guarda aparte de las claves,
+/// por lo que los comandos sobre claves no pueden ver ni pisar los canales
+#[derive(Default)]
+pub struct RegistroPubSub {
+    canales: HashMap<Vec<u8>, Canal>,
+    patrones: HashMap<Vec<u8>, PatronCanal>,
+}
+
+impl RegistroPubSub {
+    pub fn new() -> Self {
+        RegistroPubSub::default()
+    }
+
+    /// Suscribe al cliente al canal, creandolo si no existia, y le confirma la suscripcion
+    /// junto con la cantidad total de canales y patrones a los que esta suscrito
+    pub fn suscribir(&mut self, canal: Vec<u8>, cliente: Cliente) {
+        self.canales
+            .entry(canal.clone())
+            .or_insert_with(|| Canal::new(canal.clone()))
+            .agregar(cliente.clone());
+        self.notificar(cliente, "subscribe", ResultadoRedis::BulkStr(canal));
+    }
+
+    /// Desuscribe al cliente del canal y se lo confirma. El canal se elimina cuando se queda sin suscriptores
+    pub fn desuscribir(&mut self, canal: Vec<u8>, cliente: Cliente) {
+        if let Some(suscripcion) = self.canales.get_mut(&canal) {
+            suscripcion.quitar(&cliente);
+            if suscripcion.len() == 0 {
+                self.canales.remove(&canal);
+            }
+        }
+        self.notificar(cliente, "unsubscribe", ResultadoRedis::BulkStr(canal));
+    }
+
+    /// Suscribe al cliente al patron, creandolo si no existia, y le confirma la suscripcion
+    pub fn suscribir_patron(&mut self, patron: Vec<u8>, cliente: Cliente) {
+        self.patrones
+            .entry(patron.clone())
+            .or_insert_with(|| PatronCanal::new(patron.clone()))
+            .agregar(cliente.clone());
+        self.notificar(cliente, "psubscribe", ResultadoRedis::BulkStr(patron));
+    }
+
+    /// Desuscribe al cliente del patron y se lo confirma. El patron se elimina cuando se queda sin suscriptores
+    pub fn desuscribir_patron(&mut self, patron: Vec<u8>, cliente: Cliente) {
+        if let Some(suscripcion) = self.patrones.get_mut(&patron) {
+            suscripcion.quitar(&cliente);
+            if suscripcion.is_empty() {
+                self.patrones.remove(&patron);
+            }
+        }
+        self.notificar(cliente, "punsubscribe", ResultadoRedis::BulkStr(patron));
+    }
+
+    /// Desuscribe al cliente de todos sus canales, notificando cada uno. Si no tenia ninguno
+    /// igual le informa la cantidad de suscripciones que le quedan
+    pub fn desuscribir_todos(&mut self, cliente: Cliente) {
+        let canales = self.canales_de(&cliente);
+        if canales.is_empty() {
+            self.notificar(cliente.clone(), "unsubscribe", ResultadoRedis::Nil);
+        }
+        for canal in canales {
+            self.desuscribir(canal, cliente.clone());
+        }
+    }
+
+    /// Desuscribe al cliente de todos sus patrones, notificando cada uno
+    pub fn desuscribir_todos_los_patrones(&mut self, cliente: Cliente) {
+        let patrones = self.patrones_de(&cliente);
+        if patrones.is_empty() {
+            self.notificar(cliente.clone(), "punsubscribe", ResultadoRedis::Nil);
+        }
+        for patron in patrones {
+            self.desuscribir_patron(patron, cliente.clone());
+        }
+    }
+
+    /// Quita al cliente de todos los canales y patrones sin notificarlo, como cuando se desconecta
+    pub fn quitar_cliente(&mut self, mut cliente: Cliente) {
+        for suscripcion in self.canales.values_mut() {
+            suscripcion.quitar(&cliente);
+        }
+        for suscripcion in self.patrones.values_mut() {
+            suscripcion.quitar(&cliente);
+        }
+        self.canales.retain(|_, c| c.len() > 0);
+        self.patrones.retain(|_, p| !p.is_empty());
+        cliente.cambiar_suscripciones(0);
+    }
+
+    /// Devuelve los canales a los que esta suscrito el cliente
+    pub fn canales_de(&self, cliente: &Cliente) -> Vec<Vec<u8>> {
+        self.canales
+            .iter()
+            .filter(|(_, c)| c.es_suscriptor(cliente))
+            .map(|(canal, _)| canal.clone())
+            .collect()
+    }
+
+    /// Devuelve los patrones a los que esta suscrito el cliente
+    pub fn patrones_de(&self, cliente: &Cliente) -> Vec<Vec<u8>> {
+        self.patrones
+            .iter()
+            .filter(|(_, p)| p.es_suscriptor(cliente))
+            .map(|(patron, _)| patron.clone())
+            .collect()
+    }
+
+    /// Cantidad de canales y patrones a los que esta suscrito el cliente
+    pub fn cantidad_suscripciones(&self, cliente: &Cliente) -> usize {
+        let canales = self.canales.values().filter(|c| c.es_suscriptor(cliente));
+        let patrones = self.patrones.values().filter(|p| p.es_suscriptor(cliente));
+        canales.count() + patrones.count()
+    }
+
+    /// Publica el mensaje en el canal y en los patrones que coinciden con el. Devuelve a cuantos
+    /// suscriptores se envio
+    pub fn publicar(&mut self, canal: &[u8], mensaje: &[u8]) -> usize {
+        let mut publicados = match self.canales.get_mut(canal) {
+            Some(suscripcion) => suscripcion.publicar(mensaje.to_vec()),
+            None => 0,
+        };
+        for patron in self.patrones.values_mut() {
+            publicados += patron.publicar(canal, mensaje);
+        }
+        publicados
+    }
+
+    /// Devuelve los canales con al menos un suscriptor cuyo nombre coincide con el patron glob
+    pub fn canales_activos(&self, patron: &[u8]) -> Vec<Vec<u8>> {
+        self.canales
+            .iter()
+            .filter(|(nombre, canal)| canal.es_activo() && coincide(patron, nombre))
+            .map(|(nombre, _)| nombre.clone())
+            .collect()
+    }
+
+    /// Devuelve la cantidad de suscriptores del canal, sin contar los suscritos a patrones
+    pub fn cantidad_suscriptores(&self, canal: &[u8]) -> usize {
+        self.canales.get(canal).map_or(0, |c| c.len())
+    }
+
+    /// Devuelve la cantidad de patrones con clientes suscritos
+    pub fn cantidad_patrones(&self) -> usize {
+        self.patrones.len()
+    }
+
+    /// Actualiza la cantidad de suscripciones del cliente y le envia la confirmacion de la operacion
+    fn notificar(&self, mut cliente: Cliente, tipo: &str, nombre: ResultadoRedis) {
+        let cantidad = self.cantidad_suscripciones(&cliente);
+        cliente.cambiar_suscripciones(cantidad);
+        let resultado = ResultadoRedis::Push(vec![
+            ResultadoRedis::BulkStr(tipo.into()),
+            nombre,
+            ResultadoRedis::Int(cantidad as isize),
+        ]);
+        cliente.enviar_resultado(&resultado).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prueba::cliente_de_prueba;
+
+    #[test]
+    fn los_canales_sin_suscriptores_se_eliminan_del_registro() {
+        let mut registro = RegistroPubSub::new();
+        let (cliente, _conexion) = cliente_de_prueba(1);
+
+        registro.suscribir(b"noticias".to_vec(), cliente.clone());
+        assert_eq!(1, registro.cantidad_suscriptores(b"noticias"));
+        assert_eq!(vec![b"noticias".to_vec()], registro.canales_activos(b"*"));
+        assert_eq!(
+            vec![b"noticias".to_vec()],
+            registro.canales_activos(b"noti*")
+        );
+        assert!(registro.canales_activos(b"noti").is_empty());
+
+        registro.desuscribir(b"noticias".to_vec(), cliente);
+        assert_eq!(0, registro.cantidad_suscriptores(b"noticias"));
+        assert!(registro.canales_activos(b"*").is_empty());
+    }
+
+    #[test]
+    fn publicar_cuenta_los_suscriptores_del_canal_y_de_los_patrones() {
+        let mut registro = RegistroPubSub::new();
+        let (uno, _conexion_uno) = cliente_de_prueba(1);
+        let (otro, _conexion_otro) = cliente_de_prueba(2);
+
+        registro.suscribir(b"orders.1".to_vec(), uno.clone());
+        registro.suscribir_patron(b"orders.*".to_vec(), uno);
+        registro.suscribir_patron(b"*".to_vec(), otro);
+
+        assert_eq!(3, registro.publicar(b"orders.1", b"hola"));
+        assert_eq!(1, registro.publicar(b"users.1", b"hola"));
+    }
+}
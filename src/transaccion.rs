@@ -0,0 +1,320 @@
+use crate::base_de_datos::{BaseDeDatos, ResultadoRedis};
+use crate::cliente::Cliente;
+use crate::comando::{ejecutar_comando, es_comando_conocido};
+use crate::comando_info::ComandoInfo;
+use crate::config::Config;
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// Estado de la transaccion de un cliente. Mientras esta en curso los comandos se encolan
+/// y se ejecutan todos juntos al recibir EXEC. Las claves observadas con WATCH guardan la
+/// version que tenian, si alguna cambio antes de EXEC la transaccion no se ejecuta
+#[derive(Debug, Clone, Default)]
+pub struct Transaccion {
+    en_curso: bool,
+    abortada: bool,
+    comandos: Vec<ComandoInfo>,
+    observadas: HashMap<Vec<u8>, u64>,
+}
+
+/// Se encarga de detectar si el comando corresponde a los que manejan transacciones
+pub fn es_comando_transaccion(comando: &str) -> bool {
+    let comandos = ["MULTI", "EXEC", "DISCARD", "WATCH", "UNWATCH"];
+    comandos.contains(&comando)
+}
+
+impl Transaccion {
+    pub fn new() -> Self {
+        Transaccion::default()
+    }
+
+    /// Predicado que indica si se recibio MULTI y todavia no se recibio EXEC o DISCARD
+    pub fn en_curso(&self) -> bool {
+        self.en_curso
+    }
+
+    /// Procesa un comando recibido mientras la transaccion esta en curso o un comando de transaccion
+    ///
+    /// # Argumentos
+    ///
+    /// * `comando` - comando a procesar
+    /// * `cliente` - cliente que envio el comando, necesario para ejecutar los comandos encolados
+    /// * `bdd` - base de datos sobre la que se ejecuta la transaccion
+    /// * `config` - la configuracion del servidor
+    pub fn manejar(
+        &mut self,
+        comando: ComandoInfo,
+        cliente: Cliente,
+        bdd: Arc<Mutex<BaseDeDatos>>,
+        config: Arc<Mutex<Config>>,
+    ) -> ResultadoRedis {
+        match comando.get_nombre().as_str() {
+            "MULTI" => self.iniciar(),
+            "EXEC" => self.ejecutar(cliente, bdd, config),
+            "DISCARD" => self.descartar(),
+            "WATCH" => self.observar(comando, bdd),
+            "UNWATCH" => self.dejar_de_observar(),
+            _ => self.encolar(comando),
+        }
+    }
+
+    fn iniciar(&mut self) -> ResultadoRedis {
+        if self.en_curso {
+            return ResultadoRedis::Error("ERR MULTI calls can not be nested".to_string());
+        }
+        self.en_curso = true;
+        ResultadoRedis::StrSimple("OK".to_string())
+    }
+
+    fn encolar(&mut self, comando: ComandoInfo) -> ResultadoRedis {
+        if !es_comando_conocido(&comando.get_nombre()) {
+            self.abortada = true;
+            return ResultadoRedis::Error(format!(
+                "ERR unknown command '{}'",
+                comando.get_nombre().to_lowercase()
+            ));
+        }
+        self.comandos.push(comando);
+        ResultadoRedis::StrSimple("QUEUED".to_string())
+    }
+
+    fn descartar(&mut self) -> ResultadoRedis {
+        if !self.en_curso {
+            return ResultadoRedis::Error("ERR DISCARD without MULTI".to_string());
+        }
+        self.reiniciar();
+        ResultadoRedis::StrSimple("OK".to_string())
+    }
+
+    fn observar(
+        &mut self,
+        mut comando: ComandoInfo,
+        bdd: Arc<Mutex<BaseDeDatos>>,
+    ) -> ResultadoRedis {
+        if self.en_curso {
+            return ResultadoRedis::Error("ERR WATCH inside MULTI is not allowed".to_string());
+        }
+        let mut claves = vec![];
+        if let Some(clave) = comando.get_clave() {
+            claves.push(clave);
+        }
+        while let Some(clave) = comando.get_parametro() {
+            claves.push(clave);
+        }
+        if claves.is_empty() {
+            return ResultadoRedis::Error(
+                "ERR wrong number of arguments for 'watch' command".to_string(),
+            );
+        }
+
+        match bdd.lock() {
+            Ok(bdd) => {
+                for clave in claves {
+                    let version = bdd.version(&clave);
+                    self.observadas.entry(clave).or_insert(version);
+                }
+                ResultadoRedis::StrSimple("OK".to_string())
+            }
+            Err(_) => ResultadoRedis::Error("ERR when accessing the database".to_string()),
+        }
+    }
+
+    fn dejar_de_observar(&mut self) -> ResultadoRedis {
+        self.observadas.clear();
+        ResultadoRedis::StrSimple("OK".to_string())
+    }
+
+    /// Ejecuta los comandos encolados sin que otros clientes puedan acceder a la base de datos
+    /// mientras tanto. Devuelve un vector nulo si alguna de las claves observadas fue modificada
+    fn ejecutar(
+        &mut self,
+        cliente: Cliente,
+        bdd: Arc<Mutex<BaseDeDatos>>,
+        config: Arc<Mutex<Config>>,
+    ) -> ResultadoRedis {
+        if !self.en_curso {
+            return ResultadoRedis::Error("ERR EXEC without MULTI".to_string());
+        }
+        let transaccion = mem::take(self);
+        if transaccion.abortada {
+            return ResultadoRedis::Error(
+                "EXECABORT Transaction discarded because of previous errors.".to_string(),
+            );
+        }
+
+        let mut global = match bdd.lock() {
+            Ok(b) => b,
+            Err(_) => return ResultadoRedis::Error("ERR when accessing the database".to_string()),
+        };
+        if transaccion
+            .observadas
+            .iter()
+            .any(|(clave, version)| global.version(clave) != *version)
+        {
+            return ResultadoRedis::Null;
+        }
+
+        global.iniciar_transaccion_aof();
+        let resultados = transaccion
+            .comandos
+            .into_iter()
+            .map(|comando| {
+                ejecutar_comando(comando, cliente.clone(), &mut global, Arc::clone(&config))
+            })
+            .collect();
+        global.terminar_transaccion_aof();
+        match global.tomar_error_aof() {
+            Some(error) => error,
+            None => ResultadoRedis::Vector(resultados),
+        }
+    }
+
+    /// Descarta la transaccion en curso y deja de observar las claves
+    pub fn reiniciar(&mut self) {
+        *self = Transaccion::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base_de_datos::TipoRedis;
+    use crate::prueba::cliente_de_prueba;
+
+    fn comando(argumentos: &[&str]) -> ComandoInfo {
+        ComandoInfo::new(argumentos.iter().map(|a| a.to_string()).collect())
+    }
+
+    fn ejecutar(
+        transaccion: &mut Transaccion,
+        argumentos: &[&str],
+        cliente: &Cliente,
+        bdd: &Arc<Mutex<BaseDeDatos>>,
+    ) -> ResultadoRedis {
+        transaccion.manejar(
+            comando(argumentos),
+            cliente.clone(),
+            Arc::clone(bdd),
+            Arc::new(Mutex::new(Config::new())),
+        )
+    }
+
+    #[test]
+    fn exec_ejecuta_los_comandos_encolados_y_devuelve_sus_resultados() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut transaccion = Transaccion::new();
+
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            ejecutar(&mut transaccion, &["MULTI"], &cliente, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::StrSimple("QUEUED".to_string()),
+            ejecutar(&mut transaccion, &["SET", "clave", "valor"], &cliente, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::StrSimple("QUEUED".to_string()),
+            ejecutar(&mut transaccion, &["GET", "clave"], &cliente, &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("clave"));
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![
+                ResultadoRedis::StrSimple("OK".to_string()),
+                ResultadoRedis::BulkStr("valor".into()),
+            ]),
+            ejecutar(&mut transaccion, &["EXEC"], &cliente, &bdd)
+        );
+        assert!(!transaccion.en_curso());
+        assert_eq!(
+            Some(&TipoRedis::Str("valor".into())),
+            bdd.lock().unwrap().obtener_valor("clave")
+        );
+    }
+
+    #[test]
+    fn discard_descarta_los_comandos_encolados() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut transaccion = Transaccion::new();
+
+        ejecutar(&mut transaccion, &["MULTI"], &cliente, &bdd);
+        ejecutar(&mut transaccion, &["SET", "clave", "valor"], &cliente, &bdd);
+        assert_eq!(
+            ResultadoRedis::StrSimple("OK".to_string()),
+            ejecutar(&mut transaccion, &["DISCARD"], &cliente, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error("ERR EXEC without MULTI".to_string()),
+            ejecutar(&mut transaccion, &["EXEC"], &cliente, &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("clave"));
+    }
+
+    #[test]
+    fn exec_devuelve_un_vector_nulo_si_una_clave_observada_fue_modificada() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut transaccion = Transaccion::new();
+
+        ejecutar(&mut transaccion, &["WATCH", "clave"], &cliente, &bdd);
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("clave".to_string(), TipoRedis::Str("otro".into()));
+        ejecutar(&mut transaccion, &["MULTI"], &cliente, &bdd);
+        ejecutar(&mut transaccion, &["SET", "clave", "valor"], &cliente, &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Null,
+            ejecutar(&mut transaccion, &["EXEC"], &cliente, &bdd)
+        );
+        assert_eq!(
+            Some(&TipoRedis::Str("otro".into())),
+            bdd.lock().unwrap().obtener_valor("clave")
+        );
+    }
+
+    #[test]
+    fn unwatch_deja_de_observar_las_claves() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut transaccion = Transaccion::new();
+
+        ejecutar(&mut transaccion, &["WATCH", "clave"], &cliente, &bdd);
+        bdd.lock()
+            .unwrap()
+            .guardar_valor("clave".to_string(), TipoRedis::Str("otro".into()));
+        ejecutar(&mut transaccion, &["UNWATCH"], &cliente, &bdd);
+        ejecutar(&mut transaccion, &["MULTI"], &cliente, &bdd);
+        ejecutar(&mut transaccion, &["SET", "clave", "valor"], &cliente, &bdd);
+
+        assert_eq!(
+            ResultadoRedis::Vector(vec![ResultadoRedis::StrSimple("OK".to_string())]),
+            ejecutar(&mut transaccion, &["EXEC"], &cliente, &bdd)
+        );
+    }
+
+    #[test]
+    fn un_comando_desconocido_aborta_la_transaccion() {
+        let (cliente, _conexion) = cliente_de_prueba(0);
+        let bdd = Arc::new(Mutex::new(BaseDeDatos::new()));
+        let mut transaccion = Transaccion::new();
+
+        ejecutar(&mut transaccion, &["MULTI"], &cliente, &bdd);
+        ejecutar(&mut transaccion, &["SET", "clave", "valor"], &cliente, &bdd);
+        assert_eq!(
+            ResultadoRedis::Error("ERR unknown command 'noexiste'".to_string()),
+            ejecutar(&mut transaccion, &["NOEXISTE"], &cliente, &bdd)
+        );
+        assert_eq!(
+            ResultadoRedis::Error(
+                "EXECABORT Transaction discarded because of previous errors.".to_string()
+            ),
+            ejecutar(&mut transaccion, &["EXEC"], &cliente, &bdd)
+        );
+        assert_eq!(None, bdd.lock().unwrap().obtener_valor("clave"));
+    }
+}
@@ -1,42 +1,83 @@
 use crate::base_de_datos::TipoRedis;
-
-use std::time::{Duration, Instant};
+use crate::binario::parsear_entero;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Representa el valor que se almacena en la base de datos,
 /// este esta compuesto por un TipoRedis y su expiracion
 #[derive(Clone)]
 pub struct Valor {
     valor: TipoRedis,
-    momento_de_creacion: Instant,
     ultimo_acceso: Instant,
-    vida_util: Option<Duration>,
+    /// Momento en que expira, en milisegundos desde el epoch de Unix. Al ser un momento absoluto
+    /// del reloj se puede persistir y sigue valiendo luego de reiniciar el servidor
+    vencimiento: Option<u64>,
+    /// Si el valor es un string con forma de entero se guarda tambien ya parseado, para que los
+    /// contadores no tengan que volver a parsear el string en cada incremento
+    entero: Option<i64>,
+}
+
+/// Largo maximo de un string que puede representar un entero de 64 bits, incluyendo el signo
+const LARGO_MAXIMO_ENTERO: usize = 20;
+
+/// Devuelve el momento actual en milisegundos desde el epoch de Unix
+pub fn ahora_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Entero que representa el valor, si es un string con forma de entero
+fn entero_de(valor: &TipoRedis) -> Option<i64> {
+    match valor {
+        TipoRedis::Str(s) if s.len() <= LARGO_MAXIMO_ENTERO => parsear_entero(s),
+        _ => None,
+    }
 }
 
 impl Valor {
-    /// Instancia un Valor expirable con una determinada vida util
+    /// Instancia un Valor expirable con una determinada vida util en segundos
     pub fn expirable(valor: TipoRedis, vida_util: u64) -> Self {
-        Valor {
+        Valor::con_vencimiento(
             valor,
-            momento_de_creacion: Instant::now(),
-            ultimo_acceso: Instant::now(),
-            vida_util: Some(Duration::from_secs(vida_util)),
-        }
+            ahora_ms().saturating_add(vida_util.saturating_mul(1000)),
+        )
+    }
+
+    /// Instancia un Valor que expira en el momento indicado, en milisegundos desde el epoch de Unix
+    pub fn con_vencimiento(valor: TipoRedis, vencimiento: u64) -> Self {
+        Valor::nuevo(valor, Some(vencimiento))
     }
 
     /// Instancia un valor que no expira nunca
     pub fn no_expirable(valor: TipoRedis) -> Self {
+        Valor::nuevo(valor, None)
+    }
+
+    /// Instancia un valor con el vencimiento indicado, o que no expira si es ninguno
+    pub fn nuevo(valor: TipoRedis, vencimiento: Option<u64>) -> Self {
         Valor {
+            entero: entero_de(&valor),
             valor,
-            momento_de_creacion: Instant::now(),
             ultimo_acceso: Instant::now(),
-            vida_util: None,
+            vencimiento,
+        }
+    }
+
+    /// Instancia un string que representa al entero, sin tener que parsearlo
+    pub fn desde_entero(entero: i64, vencimiento: Option<u64>) -> Self {
+        Valor {
+            valor: TipoRedis::Str(entero.to_string().into_bytes()),
+            ultimo_acceso: Instant::now(),
+            vencimiento,
+            entero: Some(entero),
         }
     }
 
     /// Predicado que responde si un valor expiro o no
     pub fn expiro(&self) -> bool {
-        match self.vida_util {
-            Some(vida) => self.momento_de_creacion.elapsed() >= vida,
+        match self.vencimiento {
+            Some(vencimiento) => vencimiento <= ahora_ms(),
             None => false,
         }
     }
@@ -51,27 +92,38 @@ impl Valor {
         }
     }
 
-    /// Devuelve la duracion de la expiracion del valor en segundos,
-    /// puede ser alguna expiracion o ninguna en caso de que no expire
-    pub fn get_tiempo(&self) -> Option<Duration> {
-        self.vida_util
+    /// Aplica la modificacion al valor en el lugar si no expiro. La modificacion devuelve ninguno si
+    /// el valor no es del tipo que espera. Si es un string se vuelve a calcular el entero que representa
+    pub fn modificar<R>(
+        &mut self,
+        modificacion: impl FnOnce(&mut TipoRedis) -> Option<R>,
+    ) -> Option<R> {
+        if self.expiro() {
+            return None;
+        }
+        let resultado = modificacion(&mut self.valor)?;
+        self.entero = entero_de(&self.valor);
+        Some(resultado)
     }
 
-    pub fn obtener_expiracion(&self) -> isize {
-        match self.vida_util {
-            Some(d) => d.as_secs() as isize,
-            None => -1,
-        }
+    /// Devuelve el entero que representa el valor si es un string con forma de entero y no expiro
+    pub fn entero(&self) -> Option<i64> {
+        self.entero.filter(|_| !self.expiro())
+    }
+
+    /// Devuelve el momento en que expira el valor en milisegundos desde el epoch de Unix,
+    /// o ninguno si no expira
+    pub fn vencimiento(&self) -> Option<u64> {
+        self.vencimiento
     }
 
-    /// Resetea la expiracion con la nueva a partir de llamar a este mensaje
-    pub fn actualizar_expiracion(&mut self, nueva_expiracion: u64) {
-        self.momento_de_creacion = Instant::now();
-        self.vida_util = Some(Duration::from_secs(nueva_expiracion));
+    /// Cambia el momento en que expira el valor, en milisegundos desde el epoch de Unix
+    pub fn cambiar_vencimiento(&mut self, vencimiento: u64) {
+        self.vencimiento = Some(vencimiento);
     }
 
     pub fn hacer_persistente(&mut self) {
-        self.vida_util = None;
+        self.vencimiento = None;
     }
 
     pub fn actualizar_ultimo_acceso(&mut self) {
@@ -83,10 +135,11 @@ impl Valor {
 mod tests {
     use super::*;
     use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn cuando_se_crea_una_valor_no_expirable_este_no_expira_nunca() {
-        let valor = Valor::no_expirable(TipoRedis::Str("miClave".to_string()));
+        let valor = Valor::no_expirable(TipoRedis::Str("miClave".into()));
 
         assert!(!valor.expiro());
     }
@@ -94,10 +147,41 @@ mod tests {
     #[test]
     fn cuando_se_espera_mas_tiempo_del_que_se_dijo_que_una_clave_expiraba_la_clave_efectivamente_esta_espirada(
     ) {
-        let valor = Valor::expirable(TipoRedis::Str("miClave".to_string()), 1);
+        let valor = Valor::expirable(TipoRedis::Str("miClave".into()), 1);
 
         thread::sleep(Duration::from_secs(2));
 
         assert!(valor.expiro());
     }
+
+    #[test]
+    fn el_vencimiento_es_un_momento_absoluto_en_milisegundos() {
+        let vencido = Valor::con_vencimiento(TipoRedis::Str("valor".into()), ahora_ms() - 1);
+        let vigente = Valor::con_vencimiento(TipoRedis::Str("valor".into()), ahora_ms() + 60_000);
+
+        assert!(vencido.expiro());
+        assert_eq!(None, vencido.get());
+        assert!(!vigente.expiro());
+        assert_eq!(Some(&TipoRedis::Str("valor".into())), vigente.get());
+    }
+
+    #[test]
+    fn los_strings_con_forma_de_entero_se_guardan_parseados() {
+        assert_eq!(
+            Some(-15),
+            Valor::no_expirable(TipoRedis::Str("-15".into())).entero()
+        );
+        assert_eq!(
+            None,
+            Valor::no_expirable(TipoRedis::Str("015".into())).entero()
+        );
+        assert_eq!(
+            None,
+            Valor::no_expirable(TipoRedis::Str("1.5".into())).entero()
+        );
+
+        let contador = Valor::desde_entero(42, None);
+        assert_eq!(Some(&TipoRedis::Str("42".into())), contador.get());
+        assert_eq!(Some(42), contador.entero());
+    }
 }